@@ -24,6 +24,14 @@ impl TopLevelDeclaration {
     }
 }
 
+/// Imports are resolved when the program is loaded, before it is parsed as a whole, so they are
+/// not visited with the declarations
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportDeclaration {
+    pub path: std::string::String,
+    pub line_info: LineInfo,
+}
+
 impl Visitable for TopLevelDeclaration {
     fn visit(&mut self, v: &mut dyn Visitor, ctx: &mut Context) -> VResult {
         v.start_top_level_declaration(self, ctx)?;
@@ -62,7 +70,7 @@ impl ContractDeclaration {
                     Some(v)
                 }
             }
-            ContractMember::EventDeclaration(_) => None,
+            ContractMember::EventDeclaration(_) | ContractMember::Invariant(_) => None,
         })
     }
 }
@@ -95,6 +103,7 @@ impl Visitable for ContractDeclaration {
 pub enum ContractMember {
     VariableDeclaration(VariableDeclaration, Option<Modifier>),
     EventDeclaration(EventDeclaration),
+    Invariant(Specification),
 }
 
 impl Visitable for ContractMember {
@@ -103,6 +112,8 @@ impl Visitable for ContractMember {
         match self {
             ContractMember::VariableDeclaration(d, _) => d.visit(v, ctx),
             ContractMember::EventDeclaration(d) => d.visit(v, ctx),
+            // Invariants are visited in the functions which check them
+            ContractMember::Invariant(_) => Ok(()),
         }?;
         v.finish_contract_member(self, ctx)?;
         Ok(())
@@ -329,6 +340,7 @@ pub enum StructMember {
     VariableDeclaration(VariableDeclaration, Option<Modifier>),
     FunctionDeclaration(FunctionDeclaration),
     SpecialDeclaration(SpecialDeclaration),
+    Invariant(Specification),
 }
 
 impl Visitable for StructMember {
@@ -338,6 +350,8 @@ impl Visitable for StructMember {
             StructMember::FunctionDeclaration(f) => f.visit(v, ctx),
             StructMember::SpecialDeclaration(s) => s.visit(v, ctx),
             StructMember::VariableDeclaration(d, _) => d.visit(v, ctx),
+            // Invariants are visited in the functions which check them
+            StructMember::Invariant(_) => Ok(()),
         }?;
         v.finish_struct_member(self, ctx)?;
         Ok(())
@@ -468,6 +482,9 @@ impl Visitable for TraitMember {
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct FunctionDeclaration {
     pub head: FunctionSignatureDeclaration,
+    /// The preconditions and postconditions of the function, then the invariants of its type if
+    /// it is public
+    pub specifications: Vec<Specification>,
     pub body: Vec<Statement>,
     pub scope_context: Option<ScopeContext>,
     pub tags: Vec<String>,
@@ -563,6 +580,8 @@ impl Visitable for FunctionDeclaration {
                 .extend(self.head.parameters.iter().cloned());
         }
 
+        self.specifications.visit(v, ctx)?;
+
         let mut statements: Vec<Vec<Statement>> = vec![];
 
         for statement in &mut self.body {
@@ -596,6 +615,52 @@ impl Visitable for FunctionDeclaration {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecificationKind {
+    Pre,
+    Post,
+    Invariant,
+}
+
+impl SpecificationKind {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            SpecificationKind::Pre => "pre",
+            SpecificationKind::Post => "post",
+            SpecificationKind::Invariant => "invariant",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            SpecificationKind::Pre => "Precondition",
+            SpecificationKind::Post => "Postcondition",
+            SpecificationKind::Invariant => "Invariant",
+        }
+    }
+}
+
+/// A condition which must hold when a function is called, when it returns, or whenever a public
+/// function of a contract or struct is entered or left
+#[derive(Debug, Clone, PartialEq)]
+pub struct Specification {
+    pub kind: SpecificationKind,
+    pub expression: Expression,
+    pub line_info: LineInfo,
+}
+
+impl Visitable for Specification {
+    fn visit(&mut self, v: &mut dyn Visitor, ctx: &mut Context) -> VResult {
+        v.start_specification(self, ctx)?;
+
+        ctx.in_postcondition = self.kind == SpecificationKind::Post;
+        self.expression.visit(v, ctx)?;
+        ctx.in_postcondition = false;
+
+        v.finish_specification(self, ctx)
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct FunctionSignatureDeclaration {
     pub func_token: std::string::String,
@@ -716,6 +781,7 @@ impl SpecialDeclaration {
 
         FunctionDeclaration {
             head: function_sig,
+            specifications: vec![],
             body: self.body.clone(),
             scope_context: Option::from(self.scope_context.clone()),
             tags: vec![],
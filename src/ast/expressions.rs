@@ -22,6 +22,7 @@ pub enum Expression {
     RangeExpression(RangeExpression),
     RawAssembly(String, Option<Type>),
     CastExpression(CastExpression),
+    OldExpression(OldExpression),
     Sequence(Vec<Expression>),
 }
 
@@ -80,29 +81,43 @@ impl Expression {
         }
     }
 
+    /// Literals, `self` and generated expressions carry no position, so they have a default
+    /// `LineInfo` unless a subexpression has one
     pub fn get_line_info(&self) -> LineInfo {
         match self {
             Expression::Identifier(i) => i.line_info.clone(),
             Expression::BinaryExpression(b) => b.line_info.clone(),
             Expression::InoutExpression(i) => i.expression.get_line_info(),
-            Expression::ExternalCall(_) => unimplemented!(),
-            Expression::FunctionCall(_) => unimplemented!(),
-            Expression::VariableDeclaration(_) => unimplemented!(),
-            Expression::BracketedExpression(_) => unimplemented!(),
-            Expression::AttemptExpression(_) => unimplemented!(),
-            Expression::Literal(_) => unimplemented!(),
-            Expression::ArrayLiteral(_) => unimplemented!(),
-            Expression::DictionaryLiteral(_) => unimplemented!(),
-            Expression::SelfExpression => unimplemented!(),
-            Expression::SubscriptExpression(_) => unimplemented!(),
-            Expression::RangeExpression(_) => unimplemented!(),
-            Expression::RawAssembly(_, _) => unimplemented!(),
-            Expression::CastExpression(_) => unimplemented!(),
-            Expression::Sequence(_) => unimplemented!(),
+            Expression::ExternalCall(e) => e.function_call.line_info.clone(),
+            Expression::FunctionCall(f) => f.identifier.line_info.clone(),
+            Expression::VariableDeclaration(v) => v.identifier.line_info.clone(),
+            Expression::BracketedExpression(b) => b.expression.get_line_info(),
+            Expression::AttemptExpression(a) => a.function_call.identifier.line_info.clone(),
+            Expression::ArrayLiteral(a) => first_line_info(a.elements.iter()),
+            Expression::DictionaryLiteral(d) => {
+                first_line_info(d.elements.iter().flat_map(|(key, value)| vec![key, value]))
+            }
+            Expression::SubscriptExpression(s) => s.base_expression.line_info.clone(),
+            Expression::RangeExpression(r) => {
+                first_line_info(vec![&*r.start_expression, &*r.end_expression].into_iter())
+            }
+            Expression::CastExpression(c) => c.expression.get_line_info(),
+            Expression::OldExpression(o) => o.line_info.clone(),
+            Expression::Sequence(expressions) => first_line_info(expressions.iter()),
+            Expression::Literal(_) | Expression::SelfExpression | Expression::RawAssembly(_, _) => {
+                LineInfo::default()
+            }
         }
     }
 }
 
+fn first_line_info<'a, I: Iterator<Item = &'a Expression>>(expressions: I) -> LineInfo {
+    expressions
+        .map(Expression::get_line_info)
+        .find(|line_info| line_info.line != 0)
+        .unwrap_or_default()
+}
+
 impl Visitable for Expression {
     fn visit(&mut self, v: &mut dyn Visitor, ctx: &mut Context) -> VResult {
         v.start_expression(self, ctx)?;
@@ -124,6 +139,7 @@ impl Visitable for Expression {
             Expression::RangeExpression(r) => r.visit(v, ctx),
             Expression::RawAssembly(_, _) => return Ok(()),
             Expression::CastExpression(c) => c.visit(v, ctx),
+            Expression::OldExpression(o) => o.visit(v, ctx),
             Expression::Sequence(l) => {
                 for i in l {
                     i.visit(v, ctx)?;
@@ -154,6 +170,30 @@ impl Visitable for CastExpression {
     }
 }
 
+/// `old(expression)` in a postcondition is the value the expression had when the function was
+/// called
+#[derive(Clone, Debug, PartialEq)]
+pub struct OldExpression {
+    pub expression: Box<Expression>,
+    pub line_info: LineInfo,
+}
+
+impl Visitable for OldExpression {
+    fn visit(&mut self, v: &mut dyn Visitor, ctx: &mut Context) -> VResult {
+        v.start_old_expression(self, ctx)?;
+
+        // The value is taken on entry, when no value is old
+        let in_postcondition = ctx.in_postcondition;
+        ctx.in_postcondition = false;
+
+        self.expression.visit(v, ctx)?;
+
+        ctx.in_postcondition = in_postcondition;
+
+        v.finish_old_expression(self, ctx)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct RangeExpression {
     pub start_expression: Box<Expression>,
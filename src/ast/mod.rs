@@ -30,10 +30,17 @@ pub type VResult = Result<(), Box<dyn Error>>;
 
 pub type TypeIdentifier = String;
 
+/// Identifies a file of the program by the order in which it was added to the `SourceMap`
+pub type FileId = usize;
+
 #[derive(Clone, Default, Debug, PartialEq)]
 pub struct LineInfo {
     pub line: u32,
+    /// Counted in characters from one
+    pub column: u32,
+    /// From the start of the program, whose files follow each other in the order of their ids
     pub offset: usize,
+    pub file: FileId,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -46,8 +53,11 @@ pub struct TypeInfo {
     pub public_initializer: Option<SpecialDeclaration>,
     pub conformances: Vec<TypeInfo>,
     pub type_states: Vec<TypeState>,
-    pub current_state: Option<TypeState>,
+    pub reachable_states: Option<Vec<TypeState>>,
+    pub state_checked_functions: Vec<String>,
     pub modifiers: Vec<FunctionCall>,
+    /// Checked by each public function of the type when it is entered and left
+    pub invariants: Vec<Specification>,
 }
 
 impl TypeInfo {
@@ -61,8 +71,10 @@ impl TypeInfo {
             public_initializer: None,
             conformances: vec![],
             type_states: vec![],
-            current_state: None,
+            reachable_states: None,
+            state_checked_functions: vec![],
             modifiers: vec![],
+            invariants: vec![],
         }
     }
 
@@ -309,6 +321,16 @@ pub trait Visitable {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Module {
     pub declarations: Vec<TopLevelDeclaration>,
+    pub imports: Vec<ImportDeclaration>,
+    /// Kept for tools which write the source back out, the passes ignore them
+    pub comments: Vec<Comment>,
+}
+
+/// A comment, from the `//` to the end of the line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    pub line_info: LineInfo,
 }
 
 impl Visitable for Module {
@@ -379,10 +401,6 @@ impl CallerProtection {
         self.identifier.token.clone()
     }
 
-    pub fn is_sub_protection(&self, parent: &CallerProtection) -> bool {
-        parent.is_any() || self.name() == parent.name()
-    }
-
     pub fn any() -> CallerProtection {
         CallerProtection {
             identifier: Identifier::generated(Self::ANY),
@@ -426,31 +444,72 @@ pub fn is_literal(expression: &Expression) -> bool {
     }
 }
 
-#[allow(dead_code)]
-// TODO implement mangling
-pub fn mangle(string: &str) -> String {
-    string.to_string()
+/// Separates the name of a type from the name of one of its functions in mangled function names.
+/// Type names may not contain it or end with `_`, so a mangled name has only one reading
+pub const MANGLE_SEPARATOR: &str = "__";
+
+/// Prefix of the names of the values in which dictionaries are stored
+pub(crate) const DICTIONARY_PREFIX: &str = "_dictionary_";
+
+/// Prefix of the names of the functions which read an element of a dictionary
+pub(crate) const GETTER_PREFIX: &str = "_get_";
+
+/// Prefixes of the families of identifiers the compiler generates, taken from the generators
+const GENERATED_PREFIXES: &[&str] = &[
+    DICTIONARY_PREFIX,
+    GETTER_PREFIX,
+    crate::context::TEMPORARY_PREFIX,
+    crate::utils::specifications::OLD_VALUE_PREFIX,
+    crate::utils::specifications::RESULT_PREFIX,
+    crate::moveir::preprocessor::MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+    crate::ewasm::preprocessor::LLVMPreProcessor::TEMPORARY_PREFIX,
+];
+
+/// Single identifiers the compiler generates, taken from the generators
+const GENERATED_IDENTIFIERS: &[&str] = &[
+    Identifier::TYPESTATE_VAR_NAME,
+    crate::moveir::preprocessor::MovePreProcessor::CALLER_PROTECTIONS_PARAM,
+    crate::moveir::preprocessor::MovePreProcessor::PAYMENT_AMOUNT_PARAM,
+    crate::moveir::preprocessor::MovePreProcessor::CONTRACT_ADDRESS_PARAM,
+    crate::moveir::preprocessor::MovePreProcessor::RETURN_VARIABLE,
+    crate::moveir::preprocessor::MovePreProcessor::PUBLISHER_PARAM,
+    crate::moveir::preprocessor::MovePreProcessor::SELF_PARAM,
+    crate::moveir::preprocessor::MovePreProcessor::CONTRACT_VARIABLE,
+    crate::moveir::preprocessor::MovePreProcessor::PREASSIGN_VARIABLE,
+    crate::moveir::preprocessor::MovePreProcessor::EMPTY_STRUCT_FIELD,
+    crate::ewasm::preprocessor::LLVMPreProcessor::CALLER_PROTECTIONS_PARAM,
+    crate::ewasm::preprocessor::LLVMPreProcessor::STRUCT_INIT_TARGET,
+    crate::ewasm::preprocessor::LLVMPreProcessor::CALLER_WRAPPER_NAME,
+    crate::ewasm::preprocessor::LLVMPreProcessor::EXPONENTIATION_FUNCTION,
+];
+
+pub fn is_reserved_identifier(string: &str) -> bool {
+    GENERATED_IDENTIFIERS.contains(&string)
+        || GENERATED_PREFIXES
+            .iter()
+            .any(|prefix| string.starts_with(prefix))
+        || string.starts_with(crate::environment::FLINT_RUNTIME_PREFIX)
+        || crate::moveir::runtime_function::MoveRuntimeFunction::is_runtime_function(string)
 }
 
-pub fn mangle_dictionary(string: &str) -> String {
-    format!("_dictionary_{}", string)
+/// Whether the functions of a type of the name would have mangled names which are ambiguous or
+/// fall into the namespace of the runtime functions
+pub fn is_reserved_type_identifier(string: &str) -> bool {
+    is_reserved_identifier(string)
+        || string.contains(MANGLE_SEPARATOR)
+        || string.ends_with('_')
+        || mangle_function("", string, false).starts_with(crate::environment::FLINT_RUNTIME_PREFIX)
 }
 
-#[allow(dead_code)]
-pub fn mangle_function(string: &str, type_id: &str, is_contract: bool) -> String {
-    let func_type = if is_contract {
-        "".to_string()
-    } else {
-        format!("{}$", type_id)
-    };
-    format!("{func_type}{name}", name = string, func_type = func_type)
+pub fn mangle_dictionary(string: &str) -> String {
+    format!("{}{}", DICTIONARY_PREFIX, string)
 }
 
-pub fn mangle_function_move(string: &str, type_id: &str, is_contract: bool) -> String {
+pub fn mangle_function(string: &str, type_id: &str, is_contract: bool) -> String {
     let func_type = if is_contract {
         "".to_string()
     } else {
-        format!("{}_", type_id)
+        format!("{}{}", type_id, MANGLE_SEPARATOR)
     };
     format!("{func_type}{name}", name = string, func_type = func_type)
 }
@@ -1,5 +1,6 @@
 use crate::ast::*;
 use crate::context::{BlockContext, Context, ScopeContext};
+use crate::diagnostic::{code, Diagnostic};
 use crate::visitor::Visitor;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -276,16 +277,20 @@ impl Visitable for BecomeStatement {
                 .environment
                 .contains_type_state(contract_name, &self.state)
             {
-                ctx.environment
-                    .set_contract_state(contract_name, self.state.clone());
                 ctx.in_become = false;
                 return Ok(());
             }
         }
-        Err(Box::from(format!(
-            "Undeclared type state {} in become statement at line {}",
-            self.state.identifier.token, self.line_info.line
-        )))
+        Err(Box::from(
+            Diagnostic::error(
+                code::TYPE_STATE,
+                format!(
+                    "Undeclared type state {} in become statement at line {}",
+                    self.state.identifier.token, self.line_info.line
+                ),
+            )
+            .with_span(&self.state.identifier.line_info),
+        ))
     }
 }
 
@@ -53,7 +53,6 @@ impl Type {
         );
     }
 
-    #[allow(dead_code)]
     pub fn is_currency_original_type(&self) -> bool {
         return matches!(
             self,
@@ -1,27 +1,149 @@
 use super::ast::*;
 use super::context::*;
+use super::diagnostic::*;
 use super::environment::*;
 use super::semantic_analysis::*;
 use super::type_assigner::*;
 use super::type_checker::*;
+use super::visitor::Visitor;
+use crate::target::feature::FeatureCheck;
+use crate::target::output::{Artifact, Compilation, Emit, Options};
 use crate::target::Target;
 
-pub fn process_ast(mut module: Module, environment: Environment, mut target: Target) -> VResult {
-    let type_assigner = &mut TypeAssigner {};
-    let semantic_analysis = &mut SemanticAnalysis {};
-    let type_checker = &mut TypeChecker {};
-    let context = &mut Context {
+pub fn process_ast(
+    mut module: Module,
+    environment: Environment,
+    mut target: Target,
+    options: &Options,
+) -> Result<Compilation, Vec<Diagnostic>> {
+    let context = &mut new_context(environment, &target);
+
+    let mut artifacts = vec![];
+    if options.emits(Emit::Ast) {
+        artifacts.push(debug_artifact(Emit::Ast, options, &module));
+    }
+    if options.emits(Emit::Environment) {
+        artifacts.push(debug_artifact(
+            Emit::Environment,
+            options,
+            &context.environment,
+        ));
+    }
+
+    let warnings = check_program(&mut module, context, &target)?;
+    if options.emits(Emit::States) {
+        artifacts.extend(crate::target::states::generate(&module));
+    }
+    if options.emits(Emit::AccessReport) {
+        artifacts.extend(crate::target::access::generate(
+            &module,
+            &context.environment,
+        ));
+    }
+
+    context.check_specifications = options.check_specifications;
+    module
+        .visit(&mut *target.processor, context)
+        .map_err(|err| vec![Diagnostic::from(err)])?;
+    if options.emits(Emit::Preprocessed) {
+        artifacts.push(debug_artifact(Emit::Preprocessed, options, &module));
+    }
+
+    let generated = (target.generate)(&module, context, options).map_err(|err| {
+        vec![Diagnostic::error(
+            code::CODE_GENERATION,
+            format!("Could not generate code: {}", err),
+        )]
+    })?;
+    artifacts.extend(generated);
+    Ok(Compilation {
+        artifacts,
+        warnings,
+    })
+}
+
+/// Checks the program without generating code. Tools which inspect programs, such as the language
+/// server, use the module and environment as the passes left them even when there are errors
+pub fn analyse(
+    mut module: Module,
+    environment: Environment,
+    target: &Target,
+) -> (Module, Context, Vec<Diagnostic>) {
+    let mut context = new_context(environment, target);
+    let diagnostics = match check_program(&mut module, &mut context, target) {
+        Ok(warnings) => warnings,
+        Err(diagnostics) => diagnostics,
+    };
+    (module, context, diagnostics)
+}
+
+/// Checks the program as every target does, but not whether the target can generate code for its
+/// constructs, for tools which run programs rather than compile them
+pub fn check_semantics(
+    mut module: Module,
+    environment: Environment,
+    target: &Target,
+) -> Result<(Module, Context), Vec<Diagnostic>> {
+    let mut context = new_context(environment, target);
+    check_semantics_of(&mut module, &mut context)?;
+    Ok((module, context))
+}
+
+fn new_context(environment: Environment, target: &Target) -> Context {
+    Context {
         environment,
-        target: crate::context::Target::from(&target),
+        target: crate::context::Target::from(target),
         ..Default::default()
-    };
+    }
+}
+
+/// Returns the warnings about a program without errors
+fn check_program(
+    module: &mut Module,
+    context: &mut Context,
+    target: &Target,
+) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    let mut warnings = check_semantics_of(module, context)?;
+    warnings.extend(check(module, &mut FeatureCheck::new(target), context)?);
+    Ok(warnings)
+}
 
-    module.visit(type_assigner, context)?;
-    module.visit(semantic_analysis, context)?;
-    module.visit(type_checker, context)?;
+fn check_semantics_of(
+    module: &mut Module,
+    context: &mut Context,
+) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    check_conflicting_declarations(module, &context.environment)?;
+    module
+        .visit(&mut TypeAssigner {}, context)
+        .map_err(|err| vec![Diagnostic::from(err)])?;
+
+    // Later passes assume the program passed the earlier ones, so we only report errors from the
+    // first pass that finds any, along with its warnings
+    let mut warnings = check(module, &mut SemanticAnalysis {}, context)?;
+    warnings.extend(check_type_states(module, &mut context.environment)?);
+    warnings.extend(check(module, &mut TypeChecker {}, context)?);
+    Ok(warnings)
+}
+
+fn debug_artifact<T: std::fmt::Debug>(kind: Emit, options: &Options, value: &T) -> Artifact {
+    Artifact::new(kind, &options.name, format!("{:#?}\n", value))
+}
 
-    module.visit(&mut *target.processor, context)?;
-    (target.generate)(&module, context);
+fn check<V: Visitor>(
+    module: &mut Module,
+    pass: &mut V,
+    context: &mut Context,
+) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    let mut collector = Collector::new(pass);
+    let result = module.visit(&mut collector, context);
 
-    Ok(())
+    let mut diagnostics = collector.into_diagnostics();
+    if let Err(err) = result {
+        diagnostics.push(Diagnostic::from(err));
+    }
+    if has_errors(&diagnostics) {
+        Err(diagnostics)
+    } else {
+        Ok(diagnostics)
+    }
 }
@@ -0,0 +1,14 @@
+use std::io;
+use std::process::exit;
+
+fn main() {
+    let stdin = io::stdin();
+    match flint::lsp::run(stdin.lock(), io::stdout()) {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            exit(1)
+        }
+    }
+}
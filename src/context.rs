@@ -2,6 +2,9 @@ use crate::ast::*;
 use crate::target;
 use crate::target::currency::Currency;
 
+/// Prefix of the names of the temporaries introduced by the preprocessors
+pub(crate) const TEMPORARY_PREFIX: &str = "_temp__";
+
 #[derive(Debug, Default)]
 pub struct Context {
     pub environment: crate::environment::Environment,
@@ -28,9 +31,12 @@ pub struct Context {
     pub in_subscript: bool,
     pub is_enclosing: bool,
     pub in_emit: bool,
+    pub in_postcondition: bool,
     pub pre_statements: Vec<Statement>,
     pub post_statements: Vec<Statement>,
     pub target: Target,
+    /// Whether the preprocessors compile specifications into checks made when the code runs
+    pub check_specifications: bool,
 }
 
 impl Context {
@@ -209,7 +215,7 @@ impl ScopeContext {
     pub fn fresh_identifier(&mut self, line_info: LineInfo) -> Identifier {
         self.counter += 1;
         let count = self.local_variables.len() + self.parameters.len() + self.counter as usize;
-        let name = format!("temp__{}", count);
+        let name = format!("{}{}", TEMPORARY_PREFIX, count);
         Identifier {
             token: name,
             enclosing_type: None,
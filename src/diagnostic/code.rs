@@ -0,0 +1,29 @@
+//! Error codes identify the kind of a diagnostic independently of its message, so tools can match
+//! on them without parsing the text
+
+pub const SYNTAX_ERROR: &str = "E0001";
+pub const UNDECLARED_IDENTIFIER: &str = "E0002";
+pub const UNDECLARED_TYPE: &str = "E0003";
+pub const CONFLICTING_DECLARATION: &str = "E0004";
+pub const INVALID_DECLARATION: &str = "E0005";
+pub const RESERVED_IDENTIFIER: &str = "E0006";
+pub const TYPE_MISMATCH: &str = "E0007";
+pub const INVALID_CALL: &str = "E0008";
+pub const INACCESSIBLE_PROPERTY: &str = "E0009";
+pub const CONSTANT_REASSIGNMENT: &str = "E0010";
+pub const UNDECLARED_MUTATION: &str = "E0011";
+pub const INVALID_CONTROL_FLOW: &str = "E0012";
+pub const TYPE_STATE: &str = "E0013";
+pub const CALLER_PROTECTION: &str = "E0014";
+pub const ASSET_LINEARITY: &str = "E0015";
+pub const INVALID_IMPORT: &str = "E0016";
+pub const CODE_GENERATION: &str = "E0017";
+pub const UNSUPPORTED_FEATURE: &str = "E0018";
+pub const UNINITIALISED: &str = "E0019";
+pub const INVALID_SPECIFICATION: &str = "E0020";
+
+// Warnings
+
+pub const UNREACHABLE_TYPE_STATE: &str = "W0001";
+pub const UNREACHABLE_PROTECTION_BLOCK: &str = "W0002";
+pub const STUCK_TYPE_STATE: &str = "W0003";
@@ -0,0 +1,138 @@
+use super::Diagnostic;
+use crate::ast::*;
+use crate::context::Context;
+use crate::visitor::Visitor;
+
+/// Wraps a visitor so that an error raised by one of its hooks is recorded rather than aborting
+/// the traversal, which lets a pass report every error in a program in one run
+pub struct Collector<'a, V: Visitor> {
+    visitor: &'a mut V,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a, V: Visitor> Collector<'a, V> {
+    pub fn new(visitor: &'a mut V) -> Self {
+        Collector {
+            visitor,
+            diagnostics: vec![],
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    fn record(&mut self, result: VResult) -> VResult {
+        if let Err(error) = result {
+            let diagnostic = Diagnostic::from(error);
+            // A node may be visited more than once, for example a caller binding, so the same
+            // error would otherwise be reported twice
+            if !self.diagnostics.contains(&diagnostic) {
+                self.diagnostics.push(diagnostic);
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! collect_hooks {
+    ($($hook:ident: $node:ty,)*) => {
+        impl<'a, V: Visitor> Visitor for Collector<'a, V> {
+            $(
+                fn $hook(&mut self, t: &mut $node, ctx: &mut Context) -> VResult {
+                    let result = self.visitor.$hook(t, ctx);
+                    self.record(result)
+                }
+            )*
+        }
+    };
+}
+
+collect_hooks! {
+    start_module: Module,
+    finish_module: Module,
+    start_top_level_declaration: TopLevelDeclaration,
+    finish_top_level_declaration: TopLevelDeclaration,
+    start_contract_declaration: ContractDeclaration,
+    finish_contract_declaration: ContractDeclaration,
+    start_contract_member: ContractMember,
+    finish_contract_member: ContractMember,
+    start_contract_behaviour_declaration: ContractBehaviourDeclaration,
+    finish_contract_behaviour_declaration: ContractBehaviourDeclaration,
+    start_contract_behaviour_member: ContractBehaviourMember,
+    finish_contract_behaviour_member: ContractBehaviourMember,
+    start_struct_declaration: StructDeclaration,
+    finish_struct_declaration: StructDeclaration,
+    start_struct_member: StructMember,
+    finish_struct_member: StructMember,
+    start_asset_declaration: AssetDeclaration,
+    finish_asset_declaration: AssetDeclaration,
+    start_trait_declaration: TraitDeclaration,
+    finish_trait_declaration: TraitDeclaration,
+    start_enum_declaration: EnumDeclaration,
+    finish_enum_declaration: EnumDeclaration,
+    start_enum_member: EnumMember,
+    finish_enum_member: EnumMember,
+    start_variable_declaration: VariableDeclaration,
+    finish_variable_declaration: VariableDeclaration,
+    start_function_declaration: FunctionDeclaration,
+    finish_function_declaration: FunctionDeclaration,
+    start_specification: Specification,
+    finish_specification: Specification,
+    start_function_signature_declaration: FunctionSignatureDeclaration,
+    finish_function_signature_declaration: FunctionSignatureDeclaration,
+    start_special_declaration: SpecialDeclaration,
+    finish_special_declaration: SpecialDeclaration,
+    start_special_signature_declaration: SpecialSignatureDeclaration,
+    finish_special_signature_declaration: SpecialSignatureDeclaration,
+    start_statement: Statement,
+    finish_statement: Statement,
+    start_do_catch_statement: DoCatchStatement,
+    finish_do_catch_statement: DoCatchStatement,
+    start_if_statement: IfStatement,
+    finish_if_statement: IfStatement,
+    start_for_statement: ForStatement,
+    finish_for_statement: ForStatement,
+    start_emit_statement: EmitStatement,
+    finish_emit_statement: EmitStatement,
+    start_identifier: Identifier,
+    finish_identifier: Identifier,
+    start_range_expression: RangeExpression,
+    finish_range_expression: RangeExpression,
+    start_caller_protection: CallerProtection,
+    finish_caller_protection: CallerProtection,
+    start_conformance: Conformance,
+    finish_conformance: Conformance,
+    start_expression: Expression,
+    finish_expression: Expression,
+    start_subscript_expression: SubscriptExpression,
+    finish_subscript_expression: SubscriptExpression,
+    start_attempt_expression: AttemptExpression,
+    finish_attempt_expression: AttemptExpression,
+    start_binary_expression: BinaryExpression,
+    finish_binary_expression: BinaryExpression,
+    start_cast_expression: CastExpression,
+    finish_cast_expression: CastExpression,
+    start_old_expression: OldExpression,
+    finish_old_expression: OldExpression,
+    start_inout_expression: InoutExpression,
+    finish_inout_expression: InoutExpression,
+    start_function_call: FunctionCall,
+    finish_function_call: FunctionCall,
+    start_external_call: ExternalCall,
+    finish_external_call: ExternalCall,
+    start_return_statement: ReturnStatement,
+    finish_return_statement: ReturnStatement,
+    start_parameter: Parameter,
+    finish_parameter: Parameter,
+    start_function_argument: FunctionArgument,
+    finish_function_argument: FunctionArgument,
+    start_type: Type,
+    finish_type: Type,
+    start_array_literal: ArrayLiteral,
+    finish_array_literal: ArrayLiteral,
+    start_dictionary_literal: DictionaryLiteral,
+    finish_dictionary_literal: DictionaryLiteral,
+    start_assertion: Assertion,
+    finish_assertion: Assertion,
+}
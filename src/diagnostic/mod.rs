@@ -0,0 +1,131 @@
+pub mod code;
+mod collector;
+mod render;
+mod source_map;
+
+pub(crate) use self::collector::Collector;
+pub use self::render::ErrorFormat;
+pub use self::source_map::{Location, SourceMap};
+pub use crate::ast::LineInfo;
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A region of source code a diagnostic points at, with an optional message shown beneath it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub line_info: LineInfo,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error<S: Into<String>>(code: &'static str, message: S) -> Diagnostic {
+        Diagnostic::new(Severity::Error, Some(code), message.into())
+    }
+
+    pub fn warning<S: Into<String>>(code: &'static str, message: S) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, Some(code), message.into())
+    }
+
+    fn new(severity: Severity, code: Option<&'static str>, message: String) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code,
+            message,
+            primary: None,
+            secondary: vec![],
+            notes: vec![],
+            suggestions: vec![],
+        }
+    }
+
+    pub fn with_span(mut self, line_info: &LineInfo) -> Diagnostic {
+        self.primary = Some(Label {
+            line_info: line_info.clone(),
+            message: None,
+        });
+        self
+    }
+
+    pub fn with_label<S: Into<String>>(mut self, line_info: &LineInfo, message: S) -> Diagnostic {
+        self.primary = Some(Label {
+            line_info: line_info.clone(),
+            message: Some(message.into()),
+        });
+        self
+    }
+
+    pub fn with_secondary<S: Into<String>>(
+        mut self,
+        line_info: &LineInfo,
+        message: S,
+    ) -> Diagnostic {
+        self.secondary.push(Label {
+            line_info: line_info.clone(),
+            message: Some(message.into()),
+        });
+        self
+    }
+
+    pub fn with_note<S: Into<String>>(mut self, note: S) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_suggestion<S: Into<String>>(mut self, suggestion: S) -> Diagnostic {
+        self.suggestions.push(suggestion.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Diagnostic {}
+
+/// Errors raised as plain strings keep their message but have no code or location
+impl From<Box<dyn Error>> for Diagnostic {
+    fn from(error: Box<dyn Error>) -> Diagnostic {
+        match error.downcast::<Diagnostic>() {
+            Ok(diagnostic) => *diagnostic,
+            Err(error) => Diagnostic::new(Severity::Error, None, error.to_string()),
+        }
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(Diagnostic::is_error)
+}
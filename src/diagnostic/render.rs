@@ -0,0 +1,210 @@
+use super::{Diagnostic, Label, Location, SourceMap};
+use json::JsonValue;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(ErrorFormat::Human),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("Unknown error format `{}`", format)),
+        }
+    }
+}
+
+impl Default for ErrorFormat {
+    fn default() -> Self {
+        ErrorFormat::Human
+    }
+}
+
+impl Diagnostic {
+    pub fn render(&self, source_map: &SourceMap, format: ErrorFormat) -> String {
+        match format {
+            ErrorFormat::Human => self.render_human(source_map),
+            ErrorFormat::Json => self.render_json(source_map),
+        }
+    }
+
+    /// Renders the diagnostic in the style of rustc, quoting the source lines it points at
+    fn render_human(&self, source_map: &SourceMap) -> String {
+        let mut output = match self.code {
+            Some(code) => format!("{}[{}]: {}\n", self.severity, code, self.message),
+            None => format!("{}: {}\n", self.severity, self.message),
+        };
+
+        let labels = self
+            .primary
+            .iter()
+            .map(|label| (label, true))
+            .chain(self.secondary.iter().map(|label| (label, false)))
+            .filter_map(|(label, primary)| {
+                source_map
+                    .locate(&label.line_info)
+                    .map(|location| (label, location, primary))
+            })
+            .collect::<Vec<_>>();
+
+        let gutter = labels
+            .iter()
+            .map(|(_, location, _)| location.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        for (label, location, primary) in &labels {
+            output.push_str(&render_snippet(label, location, *primary, gutter));
+        }
+
+        if !labels.is_empty() && (!self.notes.is_empty() || !self.suggestions.is_empty()) {
+            output.push_str(&format!("{} |\n", " ".repeat(gutter)));
+        }
+        for note in &self.notes {
+            output.push_str(&format!("{} = note: {}\n", " ".repeat(gutter), note));
+        }
+        for suggestion in &self.suggestions {
+            output.push_str(&format!("{} = help: {}\n", " ".repeat(gutter), suggestion));
+        }
+
+        output
+    }
+
+    /// Renders the diagnostic as a single line of JSON
+    fn render_json(&self, source_map: &SourceMap) -> String {
+        let spans = self
+            .primary
+            .iter()
+            .map(|label| (label, true))
+            .chain(self.secondary.iter().map(|label| (label, false)))
+            .filter_map(|(label, primary)| {
+                let location = source_map.locate(&label.line_info)?;
+                Some(json::object! {
+                    file: location.file,
+                    line: location.line,
+                    column: location.column,
+                    primary: primary,
+                    label: label.message.as_deref(),
+                })
+            })
+            .collect::<json::Array>();
+
+        json::object! {
+            severity: self.severity.to_string(),
+            code: self.code,
+            message: &*self.message,
+            spans: spans,
+            notes: self.notes.iter().map(|note| JsonValue::from(&**note)).collect::<json::Array>(),
+            suggestions: self
+                .suggestions
+                .iter()
+                .map(|suggestion| JsonValue::from(&**suggestion))
+                .collect::<json::Array>(),
+        }
+        .dump()
+    }
+}
+
+fn render_snippet(label: &Label, location: &Location, primary: bool, gutter: usize) -> String {
+    let (arrow, marker) = if primary { ("-->", '^') } else { (":::", '-') };
+    let padding = " ".repeat(gutter);
+    let indent = location
+        .source_line
+        .chars()
+        .take(location.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let underline = marker
+        .to_string()
+        .repeat(token_length(location.source_line, location.column));
+    let message = label
+        .message
+        .as_ref()
+        .map(|message| format!(" {}", message))
+        .unwrap_or_default();
+
+    format!(
+        "{padding}{arrow} {file}:{line}:{column}\n\
+         {padding} |\n\
+         {line:>gutter$} | {source}\n\
+         {padding} | {indent}{underline}{message}\n",
+        padding = padding,
+        arrow = arrow,
+        file = location.file,
+        line = location.line,
+        column = location.column,
+        gutter = gutter,
+        source = location.source_line,
+        indent = indent,
+        underline = underline,
+        message = message,
+    )
+}
+
+/// Underlines the identifier or keyword starting at the column, or a single character otherwise
+fn token_length(source_line: &str, column: usize) -> usize {
+    source_line
+        .chars()
+        .skip(column - 1)
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .count()
+        .max(1)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::LineInfo;
+    use crate::diagnostic::{code, Diagnostic, ErrorFormat, SourceMap};
+
+    fn source_map() -> SourceMap {
+        let mut source_map = SourceMap::default();
+        source_map.add_file("counter.flint", "contract Counter {\n  var count: Nat\n}\n");
+        source_map
+    }
+
+    fn diagnostic() -> Diagnostic {
+        Diagnostic::error(code::UNDECLARED_TYPE, "Type `Nat` is not declared")
+            .with_label(
+                &LineInfo {
+                    line: 2,
+                    column: 14,
+                    offset: 32,
+                    file: 0,
+                },
+                "not declared",
+            )
+            .with_suggestion("did you mean `Int`?")
+    }
+
+    #[test]
+    fn test_render_human() {
+        assert_eq!(
+            diagnostic().render(&source_map(), ErrorFormat::Human),
+            "error[E0003]: Type `Nat` is not declared\n \
+             --> counter.flint:2:14\n  \
+             |\n\
+             2 |   var count: Nat\n  \
+             |              ^^^ not declared\n  \
+             |\n  \
+             = help: did you mean `Int`?\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let rendered = json::parse(&diagnostic().render(&source_map(), ErrorFormat::Json)).unwrap();
+        assert_eq!(rendered["severity"], "error");
+        assert_eq!(rendered["code"], "E0003");
+        assert_eq!(rendered["spans"][0]["file"], "counter.flint");
+        assert_eq!(rendered["spans"][0]["line"], 2);
+        assert_eq!(rendered["spans"][0]["column"], 14);
+        assert_eq!(rendered["spans"][0]["label"], "not declared");
+        assert_eq!(rendered["suggestions"][0], "did you mean `Int`?");
+    }
+}
@@ -0,0 +1,119 @@
+use crate::ast::{FileId, LineInfo};
+
+struct SourceFile {
+    name: String,
+    start: usize,
+    text: String,
+}
+
+/// The files making up a program, in the order they are concatenated. A `LineInfo` names the file
+/// it is in by its position here, and its offset is relative to the concatenated program
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+/// A position in a source file, with one-based line and column numbers
+pub struct Location<'a> {
+    pub file: &'a str,
+    pub line: usize,
+    pub column: usize,
+    pub source_line: &'a str,
+}
+
+impl SourceMap {
+    pub fn add_file<N: Into<String>, T: Into<String>>(&mut self, name: N, text: T) -> FileId {
+        let start = self
+            .files
+            .last()
+            .map(|file| file.start + file.text.len())
+            .unwrap_or(0);
+        self.files.push(SourceFile {
+            name: name.into(),
+            start,
+            text: text.into(),
+        });
+        self.files.len() - 1
+    }
+
+    /// Returns the offset of the file in the program
+    pub fn start(&self, file: FileId) -> usize {
+        self.files[file].start
+    }
+
+    /// Returns the offset in the program of the file with the name, if it was added
+    pub fn file_offset(&self, name: &str) -> Option<usize> {
+        self.files
+            .iter()
+            .find(|file| file.name == name)
+            .map(|file| file.start)
+    }
+
+    /// The id, offset in the program and text of each file
+    pub fn files(&self) -> impl Iterator<Item = (FileId, usize, &str)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(id, file)| (id, file.start, &*file.text))
+    }
+
+    /// Generated nodes carry a default `LineInfo`, which has no location in the source
+    pub fn locate(&self, line_info: &LineInfo) -> Option<Location<'_>> {
+        if line_info.line == 0 {
+            return None;
+        }
+
+        let file = self.files.get(line_info.file)?;
+        let source_line = file.text.split('\n').nth(line_info.line as usize - 1)?;
+
+        Some(Location {
+            file: &file.name,
+            line: line_info.line as usize,
+            column: line_info.column as usize,
+            source_line,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::LineInfo;
+    use crate::diagnostic::SourceMap;
+
+    #[test]
+    fn test_locate_across_files() {
+        let mut source_map = SourceMap::default();
+        source_map.add_file("main.flint", "contract C {}\n  var x: Int\n");
+        let global = source_map.add_file("global.flint", "struct Flint_Global {}\n");
+        assert_eq!(global, 1);
+        assert_eq!(source_map.start(global), 27);
+
+        let location = source_map
+            .locate(&LineInfo {
+                line: 2,
+                column: 7,
+                offset: 20,
+                file: 0,
+            })
+            .unwrap();
+        assert_eq!(location.file, "main.flint");
+        assert_eq!(location.line, 2);
+        assert_eq!(location.column, 7);
+        assert_eq!(location.source_line, "  var x: Int");
+
+        let location = source_map
+            .locate(&LineInfo {
+                line: 1,
+                column: 7,
+                offset: 33,
+                file: global,
+            })
+            .unwrap();
+        assert_eq!(location.file, "global.flint");
+        assert_eq!(location.line, 1);
+        assert_eq!(location.column, 7);
+        assert_eq!(location.source_line, "struct Flint_Global {}");
+
+        assert!(source_map.locate(&LineInfo::default()).is_none());
+    }
+}
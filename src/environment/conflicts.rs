@@ -2,20 +2,18 @@ use crate::ast::{is_redeclaration, FunctionDeclaration, FunctionInformation, Ide
 use crate::environment::*;
 
 impl Environment {
-    pub fn is_conflicting(&self, identifier: &Identifier) -> bool {
-        let list = vec![
-            &self.contract_declarations,
-            &self.struct_declarations,
-            &self.asset_declarations,
-        ];
-        let list: Vec<&Identifier> = list.iter().flat_map(|s| s.iter()).collect();
-
-        for i in list {
-            if is_redeclaration(i, identifier) {
-                return true;
-            }
-        }
-        false
+    /// The first declaration of a type with the same name, if it comes before this one. Only the
+    /// later declarations of a name are reported as conflicting, even when they are in different
+    /// files
+    pub fn conflicting_declaration(&self, identifier: &Identifier) -> Option<&Identifier> {
+        self.contract_declarations
+            .iter()
+            .chain(self.struct_declarations.iter())
+            .chain(self.asset_declarations.iter())
+            .chain(self.trait_declarations.iter())
+            .filter(|declaration| is_redeclaration(declaration, identifier))
+            .min_by_key(|declaration| declaration.line_info.offset)
+            .filter(|declaration| declaration.line_info.offset < identifier.line_info.offset)
     }
 
     pub fn conflicting<'a, T: IntoIterator<Item = &'a Identifier>>(
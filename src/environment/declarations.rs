@@ -37,6 +37,7 @@ impl Environment {
                     &v.identifier.token,
                     &c.identifier.token,
                 ),
+                ContractMember::Invariant(i) => self.add_invariant(i.clone(), &c.identifier.token),
             }
         }
     }
@@ -77,10 +78,27 @@ impl Environment {
                 StructMember::SpecialDeclaration(sd) => {
                     self.add_special(sd.clone(), &declaration.identifier.token, vec![], vec![])
                 }
+                StructMember::Invariant(i) => {
+                    self.add_invariant(i.clone(), &declaration.identifier.token)
+                }
             }
         }
     }
 
+    fn add_invariant(&mut self, invariant: Specification, type_id: &str) {
+        if let Some(type_info) = self.types.get_mut(type_id) {
+            type_info.invariants.push(invariant);
+        }
+    }
+
+    /// The invariants of a type, which its public functions check
+    pub fn invariants(&self, type_id: &str) -> &[Specification] {
+        self.types
+            .get(type_id)
+            .map(|type_info| &*type_info.invariants)
+            .unwrap_or_default()
+    }
+
     pub fn add_asset_declaration(&mut self, a: &AssetDeclaration) {
         let identifier = a.identifier.clone();
         self.asset_declarations.push(identifier);
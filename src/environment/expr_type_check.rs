@@ -110,6 +110,13 @@ impl ExpressionChecker for Environment {
             }
             Expression::RawAssembly(_, _) => unimplemented!(),
             Expression::CastExpression(c) => c.cast_type.clone(),
+            Expression::OldExpression(o) => self.get_expression_type(
+                &o.expression,
+                type_id,
+                type_states,
+                caller_protections,
+                scope,
+            ),
             Expression::Sequence(_) => unimplemented!(),
         }
     }
@@ -72,6 +72,7 @@ impl Environment {
         let name = signature.identifier.token.clone();
         let function_declaration = FunctionDeclaration {
             head: signature,
+            specifications: vec![],
             body: vec![],
             scope_context: None,
             tags: vec![],
@@ -115,7 +116,11 @@ impl Environment {
             if let Some(functions) = type_info.all_functions().get(&call.identifier.token) {
                 for function in functions {
                     if self.function_call_arguments_compatible(function, call, type_id, scope)
-                        && compatible_caller_protections(protections, &function.caller_protections)
+                        && self.compatible_caller_protections(
+                            protections,
+                            &function.caller_protections,
+                            type_id,
+                        )
                     {
                         return FunctionCallMatchResult::MatchedFunction(function.clone());
                     }
@@ -133,7 +138,11 @@ impl Environment {
             .into_iter()
             .filter(|c| {
                 c.get_parameter_types().eq(argument_types.iter())
-                    && compatible_caller_protections(protections, &c.caller_protections)
+                    && self.compatible_caller_protections(
+                        protections,
+                        &c.caller_protections,
+                        type_id,
+                    )
             })
             .collect();
 
@@ -173,7 +182,11 @@ impl Environment {
                 let equal_types = parameter_types == argument_types;
 
                 if equal_types
-                    && compatible_caller_protections(protections, &initialiser.caller_protections)
+                    && self.compatible_caller_protections(
+                        protections,
+                        &initialiser.caller_protections,
+                        &call.identifier.token,
+                    )
                 {
                     return FunctionCallMatchResult::MatchedInitializer(initialiser.clone());
                 } else {
@@ -207,7 +220,11 @@ impl Environment {
                         .iter()
                         .all(|argument_type| parameter_types.contains(&argument_type));
                     if equal_types
-                        && compatible_caller_protections(protections, &function.caller_protections)
+                        && self.compatible_caller_protections(
+                            protections,
+                            &function.caller_protections,
+                            crate::environment::FLINT_GLOBAL,
+                        )
                     {
                         return FunctionCallMatchResult::MatchedGlobalFunction(function.clone());
                     } else {
@@ -238,6 +255,46 @@ impl Environment {
             .map(move |a| self.get_expression_type(&a.expression, type_id, &[], &[], scope))
     }
 
+    /// The caller protections of the function a call within the type is to, which `try!` and
+    /// `try?` check when the call is made. The function is found by the types of the arguments, as
+    /// the type checker does. Functions anyone may call have none to check
+    pub fn callee_caller_protections(
+        &self,
+        call: &FunctionCall,
+        type_id: &str,
+        scope: &ScopeContext,
+    ) -> Vec<CallerProtection> {
+        let argument_types: Vec<_> = self.argument_types(call, type_id, scope).collect();
+        self.types
+            .get(type_id)
+            .and_then(|type_info| type_info.functions.get(&call.identifier.token))
+            .and_then(|functions| {
+                functions
+                    .iter()
+                    .find(|function| {
+                        self.function_call_arguments_compatible(function, call, type_id, scope)
+                    })
+                    .or_else(|| {
+                        functions.iter().find(|function| {
+                            function.get_parameter_types().eq(argument_types.iter())
+                        })
+                    })
+            })
+            .map(|function| function.caller_protections.clone())
+            .filter(|protections| !protections.iter().any(CallerProtection::is_any))
+            .unwrap_or_default()
+    }
+
+    /// Whether the type declares a public function of the name
+    pub fn is_public_function(&self, name: &str, type_id: &str) -> bool {
+        self.types
+            .get(type_id)
+            .and_then(|type_info| type_info.functions.get(name))
+            .into_iter()
+            .flatten()
+            .any(|function| function.declaration.is_public())
+    }
+
     pub fn is_runtime_function_call(function_call: &FunctionCall) -> bool {
         function_call
             .identifier
@@ -343,23 +400,48 @@ impl Environment {
     }
 }
 
-pub fn compatible_caller_protections(
-    source: &[CallerProtection],
-    target: &[CallerProtection],
-) -> bool {
-    // each caller protection in the source must match at least one caller protection in the target
-
-    for caller_protection in source {
-        let mut matched_any_parent = target.is_empty();
-
-        for parent in target {
-            matched_any_parent |= caller_protection.is_sub_protection(parent);
-        }
+impl Environment {
+    /// Whether a function of the type with the `source` caller protections may call one with the
+    /// `target` caller protections without checking them when it does, that is whether each
+    /// protection in the source implies one in the target
+    pub fn compatible_caller_protections(
+        &self,
+        source: &[CallerProtection],
+        target: &[CallerProtection],
+        type_id: &str,
+    ) -> bool {
+        target.is_empty()
+            || source.iter().all(|protection| {
+                target
+                    .iter()
+                    .any(|parent| self.implies_caller_protection(protection, parent, type_id))
+            })
+    }
 
-        if !matched_any_parent {
-            return false;
+    /// Whether a caller admitted by `protection` is always admitted by `parent`. Every protection
+    /// implies `any`, but otherwise only protections of the same kind and name imply each other:
+    /// whether an address is in an array or is accepted by a predicate is only known when the call
+    /// is made
+    fn implies_caller_protection(
+        &self,
+        protection: &CallerProtection,
+        parent: &CallerProtection,
+        type_id: &str,
+    ) -> bool {
+        use CallerProtectionKind::*;
+
+        match (
+            self.caller_protection_kind(protection, type_id),
+            self.caller_protection_kind(parent, type_id),
+        ) {
+            (_, Any) => true,
+            (Any, _) => false,
+            (Address, Address)
+            | (Addresses, Addresses)
+            | (Predicate, Predicate)
+            | (Function, Function)
+            | (Unknown, Unknown) => protection.name() == parent.name(),
+            _ => false,
         }
     }
-
-    true
 }
@@ -13,7 +13,7 @@ pub(crate) const FLINT_GLOBAL_TRANSFER: &str = "Flint_transfer";
 pub(crate) const FLINT_GLOBAL_ARRAY_REMOVE: &str = "Flint_array_remove";
 pub(crate) const FLINT_GLOBAL_ARRAY_INSERT: &str = "Flint_array_insert";
 pub(crate) const FLINT_GLOBAL_ARRAY_LENGTH: &str = "Flint_array_length";
-const FLINT_RUNTIME_PREFIX: &str = "Flint_";
+pub(crate) const FLINT_RUNTIME_PREFIX: &str = "Flint_";
 
 #[derive(Debug, Default, Clone)]
 pub struct Environment {
@@ -85,6 +85,21 @@ impl CallableInformation {
     }
 }
 
+/// What a caller protection refers to, which decides the protections it implies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallerProtectionKind {
+    Any,
+    /// A property holding a single address
+    Address,
+    /// An array or dictionary property holding addresses, which the caller must be one of
+    Addresses,
+    /// A function of an address returning whether it may call
+    Predicate,
+    /// A function without parameters returning the address which may call
+    Function,
+    Unknown,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Candidates {
     pub(crate) candidates: Vec<CallableInformation>,
@@ -167,22 +182,47 @@ impl Environment {
             .contains(type_state)
     }
 
-    pub fn get_contract_state(&self, contract_name: &str) -> Option<TypeState> {
-        if let Some(type_info) = self.types.get(contract_name) {
-            type_info.current_state.clone()
+    /// Records the states a stateful contract may be in when a transaction starts, and the
+    /// functions which must check its state when they are entered
+    pub fn set_type_state_analysis(
+        &mut self,
+        contract_name: &str,
+        reachable_states: Vec<TypeState>,
+        state_checked_functions: Vec<String>,
+    ) {
+        if let Some(type_info) = self.types.get_mut(contract_name) {
+            type_info.reachable_states = Some(reachable_states);
+            type_info.state_checked_functions = state_checked_functions;
         } else {
             panic!("Contract {} does not exist", contract_name)
         }
     }
 
-    pub fn set_contract_state(&mut self, contract_name: &str, type_state: TypeState) {
-        if let Some(type_info) = self.types.get_mut(contract_name) {
-            type_info.current_state = Some(type_state)
-        } else {
-            panic!("Contract {} does not exist", contract_name)
+    /// Whether a transaction could call a function restricted to the given states when the
+    /// contract is in another, so the call must check the state first
+    pub fn may_call_in_disallowed_state(&self, contract_name: &str, allowed: &[TypeState]) -> bool {
+        if allowed.is_empty() || allowed.iter().any(TypeState::is_any) {
+            return false;
+        }
+        match self.types.get(contract_name) {
+            Some(TypeInfo {
+                reachable_states: Some(reachable),
+                ..
+            }) => !reachable.iter().all(|state| allowed.contains(state)),
+            _ => true,
         }
     }
 
+    /// Whether the function may be called from within the contract in a state it does not allow
+    pub fn checks_type_state_on_entry(&self, contract_name: &str, function_name: &str) -> bool {
+        self.types.get(contract_name).map_or(false, |type_info| {
+            type_info
+                .state_checked_functions
+                .iter()
+                .any(|name| name == function_name)
+        })
+    }
+
     pub fn get_caller_protection(
         &self,
         protection: &CallerProtection,
@@ -262,6 +302,42 @@ impl Environment {
             .collect()
     }
 
+    /// What a caller protection of the type refers to
+    pub fn caller_protection_kind(
+        &self,
+        protection: &CallerProtection,
+        type_id: &str,
+    ) -> CallerProtectionKind {
+        if protection.is_any() {
+            return CallerProtectionKind::Any;
+        }
+        if let Some(property) = self.property(&protection.identifier.token, type_id) {
+            return match property.property.get_type() {
+                Type::Address => CallerProtectionKind::Address,
+                Type::ArrayType(_) | Type::FixedSizedArrayType(_) | Type::DictionaryType(_) => {
+                    CallerProtectionKind::Addresses
+                }
+                _ => CallerProtectionKind::Unknown,
+            };
+        }
+        self.types
+            .get(type_id)
+            .and_then(|type_info| type_info.functions.get(&protection.identifier.token))
+            .into_iter()
+            .flatten()
+            .find_map(|function| {
+                let head = &function.declaration.head;
+                if head.is_predicate() {
+                    Some(CallerProtectionKind::Predicate)
+                } else if head.is_0_ary_function() {
+                    Some(CallerProtectionKind::Function)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(CallerProtectionKind::Unknown)
+    }
+
     fn external_trait_init() -> SpecialSignatureDeclaration {
         SpecialSignatureDeclaration {
             special_token: "init".to_string(),
@@ -334,6 +410,16 @@ impl Environment {
         identifier.is_some()
     }
 
+    /// Assets (the target currencies and user `asset` declarations) must be treated linearly
+    pub fn is_asset_type(&self, asset_type: &Type) -> bool {
+        match asset_type {
+            Type::UserDefinedType(identifier) => {
+                asset_type.is_currency_original_type() || self.is_asset_declared(&identifier.token)
+            }
+            _ => false,
+        }
+    }
+
     pub fn is_enum_declared(&self, type_id: &str) -> bool {
         let enum_declaration = &self.enum_declarations.iter().find(|&x| x.token.eq(type_id));
         enum_declaration.is_some()
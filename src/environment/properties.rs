@@ -18,12 +18,18 @@ impl Environment {
             .cloned()
     }
 
+    /// The properties of the type in the order they were declared
     pub fn property_declarations(&self, type_id: &str) -> Vec<Property> {
         self.types
             .get(type_id)
             .into_iter()
-            .flat_map(|type_info| &type_info.properties)
-            .map(|(_, v)| v.property.clone())
+            .flat_map(|type_info| {
+                type_info
+                    .ordered_properties
+                    .iter()
+                    .filter_map(move |identifier| type_info.properties.get(identifier))
+            })
+            .map(|v| v.property.clone())
             .collect()
     }
 
@@ -1,6 +1,8 @@
 use crate::ast::{ContractBehaviourDeclaration, FunctionDeclaration, Parameter};
 use crate::ast::{ContractBehaviourMember, Type};
+use itertools::Itertools;
 use json::JsonValue;
+use sha3::{Digest, Keccak256};
 
 pub fn generate_abi(behaviour_declarations: &[&ContractBehaviourDeclaration]) -> String {
     let functions_and_specials = behaviour_declarations
@@ -71,6 +73,23 @@ fn generate_function_abi(declaration: &FunctionDeclaration) -> JsonValue {
     }
 }
 
+/// The first four bytes of the Keccak-256 hash of the signature of a function, with which call data
+/// selects it
+pub fn function_selector(declaration: &FunctionDeclaration) -> u32 {
+    let signature = format!(
+        "{}({})",
+        declaration.head.identifier.token,
+        declaration
+            .head
+            .parameters
+            .iter()
+            .map(|param| generate_ether_type(&param.type_assignment))
+            .join(",")
+    );
+    let hash = Keccak256::digest(signature.as_bytes());
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
 fn generate_parameter_abi(param: &Parameter) -> JsonValue {
     let abi_name = param.identifier.token.as_str();
     let ether_type = generate_ether_type(&param.type_assignment);
@@ -13,6 +13,9 @@ impl<'a, 'ctx> Codegen<'a, 'ctx> {
         self.import_get_external_balance();
         self.import_call();
         self.import_get_gas();
+        self.import_get_call_data_size();
+        self.import_call_data_copy();
+        self.import_finish();
     }
 
     #[allow(dead_code)]
@@ -110,6 +113,50 @@ impl<'a, 'ctx> Codegen<'a, 'ctx> {
         self.generate_import_and_extern("getGasLeft", func_type, Some(vec![]));
     }
 
+    fn import_get_call_data_size(&self) {
+        let func_type = self.context.i32_type().fn_type(&[], false);
+        self.generate_import_and_extern("getCallDataSize", func_type, Some(vec![]));
+    }
+
+    fn import_call_data_copy(&self) {
+        // Takes a memory pointer for where to copy the call data to, and ints saying where in the
+        // call data to start and how many bytes to copy
+        // Returns nothing
+        let result_ptr = self
+            .context
+            .i8_type()
+            .ptr_type(AddressSpace::Generic)
+            .as_basic_type_enum();
+
+        let int_type = self.context.i32_type().as_basic_type_enum();
+
+        let func_type = self
+            .context
+            .void_type()
+            .fn_type(&[result_ptr, int_type, int_type], false);
+        self.generate_import_and_extern(
+            "callDataCopy",
+            func_type,
+            Some(vec!["resultOffset", "dataOffset", "length"]),
+        );
+    }
+
+    fn import_finish(&self) {
+        // Takes a memory pointer for where the returned data is stored, and an int saying how long
+        // the data is
+        // Returns nothing
+        let data_ptr = self
+            .context
+            .i8_type()
+            .ptr_type(AddressSpace::Generic)
+            .as_basic_type_enum();
+
+        let length = self.context.i32_type().as_basic_type_enum();
+
+        let func_type = self.context.void_type().fn_type(&[data_ptr, length], false);
+        self.generate_import_and_extern("finish", func_type, Some(vec!["dataOffset", "length"]));
+    }
+
     fn import_revert(&self) {
         // Takes memory pointer for where output data is stored, and an int, saying how long the data is
         // Returns nothing
@@ -7,7 +7,7 @@ use inkwell::values::InstructionOpcode;
 use inkwell::{AddressSpace, IntPredicate};
 
 impl<'a, 'ctx> Codegen<'a, 'ctx> {
-    pub(crate) const EXPONENTIATION_NAME: &'ctx str = "_exp";
+    pub(crate) const EXPONENTIATION_NAME: &'ctx str = LLVMPreProcessor::EXPONENTIATION_FUNCTION;
     const INNER_BALANCE_OF_NAME: &'ctx str = "Flint_balanceOf_Inner";
     const INNER_TRANSFER_NAME: &'ctx str = "Flint_transfer_Inner";
 
@@ -0,0 +1,287 @@
+use crate::ast::{ContractBehaviourMember, FunctionDeclaration, Type};
+use crate::ewasm::abi::function_selector;
+use crate::ewasm::codegen::Codegen;
+use crate::ewasm::contract::LLVMContract;
+use inkwell::types::IntType;
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue};
+use inkwell::IntPredicate;
+use itertools::Itertools;
+
+/// The bytes at the start of the call data which select the function to call
+const SELECTOR_SIZE: u64 = 4;
+
+/// The bytes each argument and result takes up in the ABI encoding
+const WORD_SIZE: u64 = 32;
+
+/// Builds `main`, which eWASM runs for every message to the contract. The call data selects one of
+/// the public functions of the contract, whose arguments follow the selector, and the contract
+/// finishes with the encoding of its result. A message without call data, such as the one which
+/// deploys the contract, does nothing, and one which selects no function reverts
+pub fn generate_main(contract: &LLVMContract, codegen: &Codegen) {
+    let functions = contract
+        .contract_behaviour_declarations
+        .iter()
+        .flat_map(|declaration| declaration.members.iter())
+        .filter_map(|member| {
+            if let ContractBehaviourMember::FunctionDeclaration(function) = member {
+                if function.is_external && function.is_public() {
+                    return Some(function);
+                }
+            }
+            None
+        })
+        .collect::<Vec<&FunctionDeclaration>>();
+
+    let i32_type = codegen.context.i32_type();
+    let void_type = codegen.context.void_type().fn_type(&[], false);
+    let main = codegen.module.add_function("main", void_type, None);
+    let entry = codegen.context.append_basic_block(main, "entry");
+    let no_selector = codegen.context.append_basic_block(main, "no_selector");
+    let dispatch = codegen.context.append_basic_block(main, "dispatch");
+    let no_function = codegen.context.append_basic_block(main, "no_function");
+
+    // Large enough for the call data of any of the functions, and for a result
+    let buffer_size = functions
+        .iter()
+        .map(|function| call_data_size(function))
+        .max()
+        .unwrap_or(0)
+        .max(WORD_SIZE);
+
+    codegen.builder.position_at_end(entry);
+    let buffer = codegen.builder.build_alloca(
+        codegen.context.i8_type().array_type(buffer_size as u32),
+        "buffer",
+    );
+    let size = codegen
+        .builder
+        .build_call(get_function(codegen, "getCallDataSize"), &[], "size")
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_int_value();
+    let has_selector = codegen.builder.build_int_compare(
+        IntPredicate::UGE,
+        size,
+        i32_type.const_int(SELECTOR_SIZE, false),
+        "has_selector",
+    );
+    codegen
+        .builder
+        .build_conditional_branch(has_selector, dispatch, no_selector);
+
+    codegen.builder.position_at_end(no_selector);
+    codegen.builder.build_return(None);
+
+    codegen.builder.position_at_end(dispatch);
+    copy_call_data(codegen, buffer, SELECTOR_SIZE);
+    let selector = load_big_endian(codegen, buffer, 0, SELECTOR_SIZE, i32_type);
+    let cases = functions
+        .iter()
+        .map(|function| {
+            let block = codegen
+                .context
+                .append_basic_block(main, &function.head.identifier.token);
+            (
+                i32_type.const_int(function_selector(function) as u64, false),
+                block,
+            )
+        })
+        .collect::<Vec<_>>();
+    codegen.builder.build_switch(selector, no_function, &cases);
+
+    for (function, (_, block)) in functions.iter().zip(cases) {
+        codegen.builder.position_at_end(block);
+        let length = call_data_size(function);
+        let has_arguments = codegen.builder.build_int_compare(
+            IntPredicate::UGE,
+            size,
+            i32_type.const_int(length, false),
+            "has_arguments",
+        );
+        let call = codegen.context.append_basic_block(main, "call");
+        codegen
+            .builder
+            .build_conditional_branch(has_arguments, call, no_function);
+
+        codegen.builder.position_at_end(call);
+        copy_call_data(codegen, buffer, length);
+        let arguments = function
+            .head
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(index, parameter)| {
+                let offset = SELECTOR_SIZE + WORD_SIZE * index as u64;
+                decode(codegen, buffer, offset, &parameter.type_assignment)
+            })
+            .collect::<Vec<BasicValueEnum>>();
+
+        let function_name = function
+            .mangled_identifier
+            .as_ref()
+            .unwrap_or(&function.head.identifier.token);
+        let result = codegen
+            .builder
+            .build_call(get_function(codegen, function_name), &arguments, "result")
+            .try_as_basic_value()
+            .left();
+
+        if let (Some(result), Some(result_type)) = (result, function.get_result_type()) {
+            encode(codegen, buffer, result.into_int_value(), result_type);
+            codegen.builder.build_call(
+                get_function(codegen, "finish"),
+                &[
+                    byte_pointer(codegen, buffer, 0).as_basic_value_enum(),
+                    i32_type.const_int(WORD_SIZE, false).as_basic_value_enum(),
+                ],
+                "finish",
+            );
+            codegen.builder.build_unreachable();
+        } else {
+            codegen.builder.build_return(None);
+        }
+    }
+
+    codegen.builder.position_at_end(no_function);
+    let zero = i32_type.const_int(0, false);
+    let ptr = codegen.builder.build_alloca(zero.get_type(), "mem_ptr");
+    codegen.builder.build_store(ptr, zero);
+    codegen.builder.build_call(
+        get_function(codegen, "revert"),
+        &[ptr.as_basic_value_enum(), zero.as_basic_value_enum()],
+        "halt",
+    );
+    codegen.builder.build_unreachable();
+
+    codegen.verify_and_optimise(&main);
+}
+
+fn get_function<'ctx>(codegen: &Codegen<'_, 'ctx>, name: &str) -> FunctionValue<'ctx> {
+    codegen
+        .module
+        .get_function(name)
+        .unwrap_or_else(|| panic!("Could not find {} function", name))
+}
+
+fn call_data_size(function: &FunctionDeclaration) -> u64 {
+    SELECTOR_SIZE + WORD_SIZE * function.head.parameters.len() as u64
+}
+
+/// The bytes at the end of a word which hold a value of the type
+fn encoded_size(value_type: &Type) -> u64 {
+    match value_type {
+        Type::Bool => 1,
+        Type::Int => 8,
+        Type::Address => 20,
+        other => panic!("unimplemented type: {:?}", other),
+    }
+}
+
+fn byte_pointer<'ctx>(
+    codegen: &Codegen<'_, 'ctx>,
+    buffer: PointerValue<'ctx>,
+    offset: u64,
+) -> PointerValue<'ctx> {
+    let zero = codegen.context.i32_type().const_int(0, false);
+    let offset = codegen.context.i32_type().const_int(offset, false);
+    unsafe {
+        codegen
+            .builder
+            .build_in_bounds_gep(buffer, &[zero, offset], "byte_ptr")
+    }
+}
+
+/// Copies the first bytes of the call data to the start of the buffer
+fn copy_call_data<'ctx>(codegen: &Codegen<'_, 'ctx>, buffer: PointerValue<'ctx>, length: u64) {
+    let i32_type = codegen.context.i32_type();
+    codegen.builder.build_call(
+        get_function(codegen, "callDataCopy"),
+        &[
+            byte_pointer(codegen, buffer, 0).as_basic_value_enum(),
+            i32_type.const_int(0, false).as_basic_value_enum(),
+            i32_type.const_int(length, false).as_basic_value_enum(),
+        ],
+        "",
+    );
+}
+
+fn load_big_endian<'ctx>(
+    codegen: &Codegen<'_, 'ctx>,
+    buffer: PointerValue<'ctx>,
+    offset: u64,
+    length: u64,
+    int_type: IntType<'ctx>,
+) -> IntValue<'ctx> {
+    let eight = int_type.const_int(8, false);
+    (offset..offset + length)
+        .map(|index| {
+            let byte = codegen
+                .builder
+                .build_load(byte_pointer(codegen, buffer, index), "byte")
+                .into_int_value();
+            codegen.builder.build_int_z_extend(byte, int_type, "byte")
+        })
+        .fold1(|value, byte| {
+            let value = codegen.builder.build_left_shift(value, eight, "shifted");
+            codegen.builder.build_or(value, byte, "value")
+        })
+        .expect("Values take up at least one byte")
+}
+
+/// Reads the argument of the type from the word at the offset in the buffer
+fn decode<'ctx>(
+    codegen: &Codegen<'_, 'ctx>,
+    buffer: PointerValue<'ctx>,
+    offset: u64,
+    argument_type: &Type,
+) -> BasicValueEnum<'ctx> {
+    let length = encoded_size(argument_type);
+    let start = offset + WORD_SIZE - length;
+    match argument_type {
+        Type::Bool => {
+            let i8_type = codegen.context.i8_type();
+            let byte = load_big_endian(codegen, buffer, start, length, i8_type);
+            codegen
+                .builder
+                .build_int_compare(IntPredicate::NE, byte, i8_type.const_int(0, false), "bool")
+                .as_basic_value_enum()
+        }
+        _ => {
+            let int_type = codegen.context.custom_width_int_type(8 * length as u32);
+            load_big_endian(codegen, buffer, start, length, int_type).as_basic_value_enum()
+        }
+    }
+}
+
+/// Writes the result of the type as the first word of the buffer
+fn encode<'ctx>(
+    codegen: &Codegen<'_, 'ctx>,
+    buffer: PointerValue<'ctx>,
+    result: IntValue<'ctx>,
+    result_type: &Type,
+) {
+    let length = encoded_size(result_type);
+    let int_type = codegen.context.custom_width_int_type(8 * length as u32);
+    let i8_type = codegen.context.i8_type();
+    let result = codegen
+        .builder
+        .build_int_z_extend(result, int_type, "result");
+    for index in 0..WORD_SIZE {
+        let byte = if index < WORD_SIZE - length {
+            i8_type.const_int(0, false)
+        } else {
+            let shift = 8 * (WORD_SIZE - 1 - index);
+            let shifted = codegen.builder.build_right_shift(
+                result,
+                int_type.const_int(shift, false),
+                false,
+                "shifted",
+            );
+            codegen.builder.build_int_truncate(shifted, i8_type, "byte")
+        };
+        codegen
+            .builder
+            .build_store(byte_pointer(codegen, buffer, index), byte);
+    }
+}
@@ -2,6 +2,7 @@ use crate::ast::calls::{ExternalCall, FunctionCall};
 use crate::ewasm::codegen::Codegen;
 use crate::ewasm::expressions::LLVMExpression;
 use crate::ewasm::function_context::FunctionContext;
+use crate::ewasm::preprocessor::LLVMPreProcessor;
 use crate::ewasm::utils::get_num_pointer_layers;
 use inkwell::types::AnyType;
 use inkwell::values::BasicValueEnum;
@@ -49,7 +50,10 @@ impl<'a> LLVMFunctionCall<'a> {
             let struct_var = struct_type.const_zero();
 
             // Add local variable to function call arguments
-            function_context.add_local("tmp_var", BasicValueEnum::StructValue(struct_var));
+            function_context.add_local(
+                LLVMPreProcessor::STRUCT_INIT_TARGET,
+                BasicValueEnum::StructValue(struct_var),
+            );
         }
 
         let params = codegen
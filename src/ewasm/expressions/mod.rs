@@ -8,7 +8,7 @@ use crate::ast::expressions::{
     BinaryExpression, CastExpression, InoutExpression, SubscriptExpression,
 };
 use crate::ast::operators::BinOp;
-use crate::ast::{Assertion, Expression, Identifier, Literal};
+use crate::ast::{Assertion, Expression, Identifier, Literal, GETTER_PREFIX};
 use crate::ewasm::codegen::Codegen;
 use crate::ewasm::expressions::assignment::LLVMAssignment;
 use crate::ewasm::expressions::call::{LLVMExternalCall, LLVMFunctionCall};
@@ -16,6 +16,7 @@ use crate::ewasm::expressions::declaration::LLVMVariableDeclaration;
 use crate::ewasm::expressions::literal::LLVMLiteral;
 use crate::ewasm::expressions::struct_access::LLVMStructAccess;
 use crate::ewasm::function_context::FunctionContext;
+use crate::ewasm::preprocessor::LLVMPreProcessor;
 use crate::ewasm::statements::LLVMAssertion;
 use crate::ewasm::types::{get_type_as_string, LLVMType};
 use crate::ewasm::utils::*;
@@ -61,6 +62,7 @@ impl<'a> LLVMExpression<'a> {
             }
             .generate(codegen, function_context),
             Expression::AttemptExpression(_) => panic!("Should be removed in the preprocessor"),
+            Expression::OldExpression(_) => panic!("Should be removed in the preprocessor"),
             Expression::Literal(l) => {
                 LLVMLiteral { literal: l }.generate(codegen, function_context)
             }
@@ -291,7 +293,8 @@ impl<'a> LLVMBinaryExpression<'a> {
             let lhs_val = codegen
                 .builder
                 .build_load(lhs.into_pointer_value(), "tmp_l");
-            function_context.add_local("tmp_l", lhs_val);
+            function_context
+                .add_local(&format!("{}l", LLVMPreProcessor::TEMPORARY_PREFIX), lhs_val);
             lhs = lhs_val;
         }
 
@@ -299,7 +302,8 @@ impl<'a> LLVMBinaryExpression<'a> {
             let rhs_val = codegen
                 .builder
                 .build_load(rhs.into_pointer_value(), "tmp_r");
-            function_context.add_local("tmp_r", rhs_val);
+            function_context
+                .add_local(&format!("{}r", LLVMPreProcessor::TEMPORARY_PREFIX), rhs_val);
             rhs = rhs_val;
         }
 
@@ -1006,7 +1010,8 @@ impl<'a> LLVMSubscriptExpression<'a> {
             let value_type = field_types.get(1).unwrap();
 
             let get_func = if let Some(func) = codegen.module.get_function(&format!(
-                "_get_{}_{}_{}",
+                "{}{}_{}_{}",
+                GETTER_PREFIX,
                 array_len,
                 get_type_as_string(key_type),
                 get_type_as_string(value_type)
@@ -1021,7 +1026,8 @@ impl<'a> LLVMSubscriptExpression<'a> {
                 codegen
                     .module
                     .get_function(&format!(
-                        "_get_{}_{}_{}",
+                        "{}{}_{}_{}",
+                        GETTER_PREFIX,
                         array_len,
                         get_type_as_string(key_type),
                         get_type_as_string(value_type)
@@ -1162,7 +1168,8 @@ fn add_get_runtime_function<'ctx>(
     );
     let get_func = codegen.module.add_function(
         &format!(
-            "_get_{}_{}_{}",
+            "{}{}_{}_{}",
+            GETTER_PREFIX,
             array_len,
             get_type_as_string(key_type),
             get_type_as_string(value_type)
@@ -1,5 +1,5 @@
 use crate::ast::expressions::Identifier;
-use crate::ast::{CallerProtection, FunctionDeclaration};
+use crate::ast::{mangle_function, CallerProtection, FunctionDeclaration};
 use crate::ewasm::function_context::FunctionContext;
 use crate::ewasm::statements::LLVMStatement;
 use crate::ewasm::types::LLVMType;
@@ -141,11 +141,7 @@ fn contains_function(
     caller_protections.iter().any(|c| {
         codegen
             .module
-            .get_function(&mangle_ewasm_function(&c.identifier.token, enclosing))
+            .get_function(&mangle_function(&c.identifier.token, enclosing, false))
             .is_some()
     })
 }
-
-fn mangle_ewasm_function(function_name: &str, enclosing: &str) -> String {
-    format!("{}_{}", enclosing, function_name)
-}
@@ -0,0 +1,504 @@
+//! A mock of the Ethereum Environment Interface, the functions eWASM contracts import from the
+//! `ethereum` namespace, so that tests can run generated contracts without a testnet. Other
+//! accounts hold no code, so calls to them only transfer currency
+
+use std::collections::HashMap;
+use std::fmt;
+use wasmi::ValueType::{I32, I64};
+use wasmi::{
+    Error, Externals, FuncInstance, FuncRef, HostError, ImportsBuilder, MemoryRef, Module,
+    ModuleImportResolver, ModuleInstance, ModuleRef, RuntimeArgs, RuntimeValue, Signature, Trap,
+    TrapKind, ValueType,
+};
+
+pub type Address = [u8; 20];
+pub type Word = [u8; 32];
+
+/// The gas a message comes with unless it says otherwise
+const GAS: i64 = 10_000_000;
+
+/// The functions of the interface, with their parameters and results, in the order host
+/// functions are numbered
+const INTERFACE: &[(&str, &[ValueType], Option<ValueType>)] = &[
+    ("useGas", &[I64], None),
+    ("getGasLeft", &[], Some(I64)),
+    ("getAddress", &[I32], None),
+    ("getCaller", &[I32], None),
+    ("getCallValue", &[I32], None),
+    ("getCallDataSize", &[], Some(I32)),
+    ("callDataCopy", &[I32, I32, I32], None),
+    ("getExternalBalance", &[I32, I32], None),
+    ("storageStore", &[I32, I32], None),
+    ("storageLoad", &[I32, I32], None),
+    ("log", &[I32, I32, I32, I32, I32, I32, I32], None),
+    ("call", &[I64, I32, I32, I32, I32], Some(I32)),
+    ("finish", &[I32, I32], None),
+    ("revert", &[I32, I32], None),
+];
+
+/// A message to a contract: who sends it, the currency and gas it comes with, and its call data
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub caller: Address,
+    pub value: u128,
+    pub gas: i64,
+    pub data: Vec<u8>,
+}
+
+impl Message {
+    pub fn new(caller: Address) -> Message {
+        Message {
+            caller,
+            value: 0,
+            gas: GAS,
+            data: vec![],
+        }
+    }
+
+    pub fn with_value(self, value: u128) -> Message {
+        Message { value, ..self }
+    }
+
+    pub fn with_data(self, data: Vec<u8>) -> Message {
+        Message { data, ..self }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Log {
+    pub topics: Vec<Word>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub address: Address,
+    pub value: u128,
+    pub data: Vec<u8>,
+}
+
+/// How running a contract ended. Only a contract which finishes keeps its changes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Finish(Vec<u8>),
+    Revert(Vec<u8>),
+    Trap(String),
+}
+
+/// The chain as the contract at `address` sees it
+#[derive(Debug, Clone, Default)]
+pub struct Host {
+    pub address: Address,
+    pub balances: HashMap<Address, u128>,
+    pub storage: HashMap<Word, Word>,
+    pub logs: Vec<Log>,
+    pub calls: Vec<Call>,
+}
+
+impl Host {
+    pub fn new(address: Address) -> Host {
+        Host {
+            address,
+            ..Default::default()
+        }
+    }
+
+    /// Loads a contract, checking that it only imports functions of the interface, with their
+    /// signatures, and has no start function
+    pub fn instantiate(wasm: &[u8]) -> Result<ModuleRef, String> {
+        let module = Module::from_buffer(wasm).map_err(|err| err.to_string())?;
+        let imports = ImportsBuilder::new().with_resolver("ethereum", &Interface);
+        let instance = ModuleInstance::new(&module, &imports).map_err(|err| err.to_string())?;
+        if instance.has_start() {
+            return Err(String::from("eWASM contracts cannot have a start function"));
+        }
+        Ok(instance.assert_no_start())
+    }
+
+    /// Sends the message to the contract, which runs `main`
+    pub fn execute(&mut self, contract: &ModuleRef, message: &Message) -> Outcome {
+        self.invoke(contract, "main", &[], message)
+    }
+
+    /// Sends the message to the contract and runs one of its exports. The changes it makes are
+    /// undone unless it finishes
+    pub fn invoke(
+        &mut self,
+        contract: &ModuleRef,
+        function: &str,
+        arguments: &[RuntimeValue],
+        message: &Message,
+    ) -> Outcome {
+        let snapshot = self.clone();
+        let outcome = if self.transfer(message.caller, self.address, message.value) {
+            let mut execution = Execution {
+                host: self,
+                message,
+                memory: contract
+                    .export_by_name("memory")
+                    .and_then(|export| export.as_memory().cloned()),
+                gas_left: message.gas,
+            };
+            match contract.invoke_export(function, arguments, &mut execution) {
+                Ok(_) => Outcome::Finish(vec![]),
+                Err(err) => match err.as_host_error().and_then(|e| e.downcast_ref::<Stop>()) {
+                    Some(Stop::Finish(data)) => Outcome::Finish(data.clone()),
+                    Some(Stop::Revert(data)) => Outcome::Revert(data.clone()),
+                    Some(stop) => Outcome::Trap(stop.to_string()),
+                    None => Outcome::Trap(err.to_string()),
+                },
+            }
+        } else {
+            Outcome::Trap(String::from(
+                "The caller cannot pay the value of the message",
+            ))
+        };
+
+        if !matches!(outcome, Outcome::Finish(_)) {
+            *self = snapshot;
+        }
+        outcome
+    }
+
+    pub fn balance(&self, address: &Address) -> u128 {
+        self.balances.get(address).cloned().unwrap_or(0)
+    }
+
+    fn transfer(&mut self, from: Address, to: Address, value: u128) -> bool {
+        if value == 0 {
+            return true;
+        }
+        match self.balance(&from).checked_sub(value) {
+            Some(remaining) => {
+                self.balances.insert(from, remaining);
+                *self.balances.entry(to).or_insert(0) += value;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct Interface;
+
+impl ModuleImportResolver for Interface {
+    fn resolve_func(&self, field_name: &str, signature: &Signature) -> Result<FuncRef, Error> {
+        let index = INTERFACE
+            .iter()
+            .position(|(name, _, _)| *name == field_name)
+            .ok_or_else(|| {
+                Error::Instantiation(format!("`{}` is not part of the interface", field_name))
+            })?;
+        let (_, parameters, result) = INTERFACE[index];
+        if signature.params() != parameters || signature.return_type() != result {
+            return Err(Error::Instantiation(format!(
+                "`{}` should take {:?} and return {:?}",
+                field_name, parameters, result
+            )));
+        }
+        Ok(FuncInstance::alloc_host(signature.clone(), index))
+    }
+}
+
+/// Why a contract stopped before returning
+#[derive(Debug)]
+enum Stop {
+    Finish(Vec<u8>),
+    Revert(Vec<u8>),
+    OutOfGas,
+    InvalidArgument(&'static str),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Finish(_) => write!(f, "finished"),
+            Stop::Revert(_) => write!(f, "reverted"),
+            Stop::OutOfGas => write!(f, "Out of gas"),
+            Stop::InvalidArgument(function) => write!(f, "Invalid argument to `{}`", function),
+        }
+    }
+}
+
+impl HostError for Stop {}
+
+struct Execution<'a> {
+    host: &'a mut Host,
+    message: &'a Message,
+    memory: Option<MemoryRef>,
+    gas_left: i64,
+}
+
+impl Execution<'_> {
+    fn read(&self, offset: u32, length: usize) -> Result<Vec<u8>, Trap> {
+        self.memory
+            .as_ref()
+            .and_then(|memory| memory.get(offset, length).ok())
+            .ok_or_else(|| TrapKind::MemoryAccessOutOfBounds.into())
+    }
+
+    fn write(&self, offset: u32, bytes: &[u8]) -> Result<(), Trap> {
+        self.memory
+            .as_ref()
+            .and_then(|memory| memory.set(offset, bytes).ok())
+            .ok_or_else(|| TrapKind::MemoryAccessOutOfBounds.into())
+    }
+
+    fn address(&self, offset: u32) -> Result<Address, Trap> {
+        let mut address = Address::default();
+        address.copy_from_slice(&self.read(offset, 20)?);
+        Ok(address)
+    }
+
+    fn word(&self, offset: u32) -> Result<Word, Trap> {
+        let mut word = Word::default();
+        word.copy_from_slice(&self.read(offset, 32)?);
+        Ok(word)
+    }
+
+    fn value(&self, offset: u32) -> Result<u128, Trap> {
+        let mut value = [0; 16];
+        value.copy_from_slice(&self.read(offset, 16)?);
+        Ok(u128::from_le_bytes(value))
+    }
+}
+
+impl Externals for Execution<'_> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let offset = |n| args.nth_checked::<u32>(n);
+        match INTERFACE[index].0 {
+            "useGas" => {
+                self.gas_left -= args.nth_checked::<i64>(0)?;
+                if self.gas_left < 0 {
+                    return Err(Stop::OutOfGas.into());
+                }
+            }
+            "getGasLeft" => return Ok(Some(RuntimeValue::I64(self.gas_left))),
+            "getAddress" => self.write(offset(0)?, &self.host.address)?,
+            "getCaller" => self.write(offset(0)?, &self.message.caller)?,
+            "getCallValue" => self.write(offset(0)?, &self.message.value.to_le_bytes())?,
+            "getCallDataSize" => {
+                return Ok(Some(RuntimeValue::I32(self.message.data.len() as i32)));
+            }
+            "callDataCopy" => {
+                let (start, length) = (offset(1)? as usize, offset(2)? as usize);
+                let data = self
+                    .message
+                    .data
+                    .get(start..start + length)
+                    .ok_or(Stop::InvalidArgument("callDataCopy"))?;
+                self.write(offset(0)?, data)?;
+            }
+            "getExternalBalance" => {
+                let balance = self.host.balance(&self.address(offset(0)?)?);
+                self.write(offset(1)?, &balance.to_le_bytes())?;
+            }
+            "storageStore" => {
+                let (key, value) = (self.word(offset(0)?)?, self.word(offset(1)?)?);
+                self.host.storage.insert(key, value);
+            }
+            "storageLoad" => {
+                let key = self.word(offset(0)?)?;
+                let value = self.host.storage.get(&key).cloned().unwrap_or_default();
+                self.write(offset(1)?, &value)?;
+            }
+            "log" => {
+                let count = offset(2)? as usize;
+                if count > 4 {
+                    return Err(Stop::InvalidArgument("log").into());
+                }
+                let topics = (0..count)
+                    .map(|topic| self.word(offset(3 + topic)?))
+                    .collect::<Result<_, _>>()?;
+                let data = self.read(offset(0)?, offset(1)? as usize)?;
+                self.host.logs.push(Log { topics, data });
+            }
+            "call" => {
+                let address = self.address(offset(1)?)?;
+                let value = self.value(offset(2)?)?;
+                let data = self.read(offset(3)?, offset(4)? as usize)?;
+                if !self.host.transfer(self.host.address, address, value) {
+                    return Ok(Some(RuntimeValue::I32(1)));
+                }
+                self.host.calls.push(Call {
+                    address,
+                    value,
+                    data,
+                });
+                return Ok(Some(RuntimeValue::I32(0)));
+            }
+            "finish" => {
+                let data = self.read(offset(0)?, offset(1)? as usize)?;
+                return Err(Stop::Finish(data).into());
+            }
+            "revert" => {
+                let data = self.read(offset(0)?, offset(1)? as usize)?;
+                return Err(Stop::Revert(data).into());
+            }
+            _ => unreachable!(),
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ewasm::host::{Address, Call, Host, Log, Message, Outcome};
+    use wabt::wat2wasm;
+    use wasmi::ModuleRef;
+
+    const CONTRACT: Address = [0xc; 20];
+    const CALLER: Address = [0xa; 20];
+
+    fn contract(body: &str) -> ModuleRef {
+        let wat = format!(
+            r#"(module
+  (import "ethereum" "getCaller" (func $getCaller (param i32)))
+  (import "ethereum" "getCallValue" (func $getCallValue (param i32)))
+  (import "ethereum" "getCallDataSize" (func $getCallDataSize (result i32)))
+  (import "ethereum" "callDataCopy" (func $callDataCopy (param i32 i32 i32)))
+  (import "ethereum" "storageStore" (func $storageStore (param i32 i32)))
+  (import "ethereum" "storageLoad" (func $storageLoad (param i32 i32)))
+  (import "ethereum" "log" (func $log (param i32 i32 i32 i32 i32 i32 i32)))
+  (import "ethereum" "call" (func $call (param i64 i32 i32 i32 i32) (result i32)))
+  (import "ethereum" "useGas" (func $useGas (param i64)))
+  (import "ethereum" "finish" (func $finish (param i32 i32)))
+  (import "ethereum" "revert" (func $revert (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 256) "\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b")
+  (data (i32.const 288) "\05")
+  (data (i32.const 320) "no")
+  (func (export "main") {}))"#,
+            body
+        );
+        Host::instantiate(&wat2wasm(wat).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_storage() {
+        // Stores the caller at key 0, then finishes with what is loaded back
+        let contract = contract(
+            "(call $getCaller (i32.const 32))
+             (call $storageStore (i32.const 0) (i32.const 32))
+             (call $storageLoad (i32.const 0) (i32.const 64))
+             (call $finish (i32.const 64) (i32.const 20))",
+        );
+        let mut host = Host::new(CONTRACT);
+        let outcome = host.execute(&contract, &Message::new(CALLER));
+        assert_eq!(outcome, Outcome::Finish(CALLER.to_vec()));
+
+        let mut value = [0; 32];
+        value[..20].copy_from_slice(&CALLER);
+        assert_eq!(host.storage.get(&[0; 32]), Some(&value));
+    }
+
+    #[test]
+    fn test_call_data() {
+        // Finishes with the call data after its first byte
+        let contract = contract(
+            "(call $callDataCopy (i32.const 0) (i32.const 1) (i32.sub (call $getCallDataSize) (i32.const 1)))
+             (call $finish (i32.const 0) (i32.sub (call $getCallDataSize) (i32.const 1)))",
+        );
+        let mut host = Host::new(CONTRACT);
+        let message = Message::new(CALLER).with_data(vec![1, 2, 3]);
+        assert_eq!(
+            host.execute(&contract, &message),
+            Outcome::Finish(vec![2, 3])
+        );
+
+        let message = Message::new(CALLER);
+        assert!(matches!(
+            host.execute(&contract, &message),
+            Outcome::Trap(_)
+        ));
+    }
+
+    #[test]
+    fn test_revert() {
+        // Stores and logs the value of the message, then reverts if it is not zero
+        let contract = contract(
+            "(call $getCallValue (i32.const 32))
+             (call $storageStore (i32.const 0) (i32.const 32))
+             (call $log (i32.const 32) (i32.const 16) (i32.const 1) (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0))
+             (if (i64.ne (i64.load (i32.const 32)) (i64.const 0))
+               (then (call $revert (i32.const 320) (i32.const 2))))",
+        );
+        let mut host = Host::new(CONTRACT);
+        host.balances.insert(CALLER, 10);
+
+        let outcome = host.execute(&contract, &Message::new(CALLER).with_value(3));
+        assert_eq!(outcome, Outcome::Revert(b"no".to_vec()));
+        assert!(host.storage.is_empty() && host.logs.is_empty());
+        assert_eq!(host.balance(&CALLER), 10);
+
+        let outcome = host.execute(&contract, &Message::new(CALLER));
+        assert_eq!(outcome, Outcome::Finish(vec![]));
+        assert_eq!(
+            host.logs,
+            vec![Log {
+                topics: vec![[0; 32]],
+                data: vec![0; 16],
+            }]
+        );
+
+        let outcome = host.execute(&contract, &Message::new(CALLER).with_value(11));
+        assert!(matches!(outcome, Outcome::Trap(_)));
+    }
+
+    #[test]
+    fn test_call() {
+        // Sends 5 to the account at offset 256, and finishes with the result of the call
+        let contract = contract(
+            "(i32.store (i32.const 0)
+               (call $call (i64.const 0) (i32.const 256) (i32.const 288) (i32.const 320) (i32.const 2)))
+             (call $finish (i32.const 0) (i32.const 4))",
+        );
+        let mut host = Host::new(CONTRACT);
+        host.balances.insert(CALLER, 10);
+
+        let outcome = host.execute(&contract, &Message::new(CALLER).with_value(4));
+        assert_eq!(outcome, Outcome::Finish(vec![1, 0, 0, 0]));
+        assert!(host.calls.is_empty());
+
+        let outcome = host.execute(&contract, &Message::new(CALLER).with_value(2));
+        assert_eq!(outcome, Outcome::Finish(vec![0, 0, 0, 0]));
+        assert_eq!(
+            host.calls,
+            vec![Call {
+                address: [0xb; 20],
+                value: 5,
+                data: b"no".to_vec(),
+            }]
+        );
+        assert_eq!(host.balance(&CALLER), 4);
+        assert_eq!(host.balance(&CONTRACT), 1);
+        assert_eq!(host.balance(&[0xb; 20]), 5);
+    }
+
+    #[test]
+    fn test_gas() {
+        let contract = contract("(call $useGas (i64.const 100))");
+        let mut host = Host::new(CONTRACT);
+        let message = Message::new(CALLER);
+        assert_eq!(host.execute(&contract, &message), Outcome::Finish(vec![]));
+
+        let message = Message { gas: 99, ..message };
+        assert!(matches!(
+            host.execute(&contract, &message),
+            Outcome::Trap(_)
+        ));
+    }
+
+    #[test]
+    fn test_imports() {
+        let unknown = r#"(module (import "ethereum" "selfDestruct" (func (param i32))))"#;
+        assert!(Host::instantiate(&wat2wasm(unknown).unwrap()).is_err());
+
+        let mismatched = r#"(module (import "ethereum" "getCaller" (func (param i64))))"#;
+        assert!(Host::instantiate(&wat2wasm(mismatched).unwrap()).is_err());
+    }
+}
@@ -1,9 +1,12 @@
 mod abi;
 mod codegen;
 mod contract;
+mod dispatcher;
 mod expressions;
 mod function;
 mod function_context;
+#[cfg(test)]
+pub mod host;
 pub mod preprocessor;
 mod statements;
 mod structs;
@@ -21,22 +24,28 @@ use crate::context::Context;
 use crate::ewasm::abi::generate_abi;
 use crate::ewasm::codegen::Codegen;
 use crate::ewasm::contract::LLVMContract;
+use crate::ewasm::dispatcher::generate_main;
+use crate::io::config;
+use crate::target::output::{Artifact, Emit, Options};
+use crate::target::GenerationResult;
 use itertools::Itertools;
 use nom::lib::std::collections::HashMap;
 use process::Command;
 use regex::Regex;
 use std::error::Error;
 use std::io::Write;
-use std::{fs, path::Path, process};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, path::Path, process};
 use wabt::wasm2wat;
 use wabt::wat2wasm;
 
-const JSON_LCC_KEY: &str = "llcPath";
-const JSON_WASM_LD_KEY: &str = "wasm-ldPath";
-const TEMPORARY_DIRECTORY_NAME: &str = "tmp";
-const OUTPUT_DIRECTORY_NAME: &str = "output";
+const TEMPORARY_DIRECTORY_PREFIX: &str = "flint";
 
-pub fn generate(module: &Module, context: &mut Context) {
+/// Counts the temporary directories made by this process, so that each has its own name
+static TEMPORARY_DIRECTORY_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn generate(module: &Module, context: &mut Context, options: &Options) -> GenerationResult {
     let external_traits = module
         .declarations
         .iter()
@@ -110,127 +119,131 @@ pub fn generate(module: &Module, context: &mut Context) {
 
     assert!(!ewasm_contracts.is_empty());
 
-    // It seems from the move target that we are allowed to create multiple contracts, and each of these
-    // gets put into a separate file, so we will do the same here
-    let output_path = Path::new(OUTPUT_DIRECTORY_NAME);
-    if !output_path.exists() {
-        fs::create_dir(output_path).expect("Could not create output directory");
-    }
+    let mut artifacts = vec![];
+    for contract in ewasm_contracts.iter() {
+        let file_name = contract.contract_declaration.identifier.token.as_str();
+        let llvm = generate_llvm(contract);
+
+        if options.emits(Emit::Wat) || options.emits(Emit::Wasm) {
+            let wat = compile_to_wat(file_name, &llvm, options)?;
+            if options.emits(Emit::Wasm) {
+                let wasm = wat2wasm(wat.as_bytes())
+                    .map_err(|err| format!("Could not convert wat to wasm: {:?}", err))?;
+                artifacts.push(Artifact::new(Emit::Wasm, file_name, wasm));
+            }
+            if options.emits(Emit::Wat) {
+                artifacts.push(Artifact::new(Emit::Wat, file_name, wat));
+            }
+        }
 
-    let flint_config =
-        fs::read_to_string(Path::new("flint_config.json")).expect("Could not find flint config");
-    let flint_config = json::parse(flint_config.as_str()).expect("Could not parse flint config");
-    assert!(flint_config.is_object());
-    let llc_path = flint_config
-        .entries()
-        .find(|(key, _)| *key == JSON_LCC_KEY)
-        .expect("No llc in flint config")
-        .1
-        .as_str()
-        .expect("Could not find llc in flint config");
-    assert!(!llc_path.is_empty());
-
-    let wasm_ld_path = flint_config
-        .entries()
-        .find(|(key, _)| *key == JSON_WASM_LD_KEY)
-        .expect("No wasm-ld in flint config")
-        .1
-        .as_str()
-        .expect("Could not find wasm-ld path in flint config");
-    assert!(!wasm_ld_path.is_empty());
+        if options.emits(Emit::Llvm) {
+            artifacts.push(Artifact::new(Emit::Llvm, file_name, llvm));
+        }
 
-    for contract in ewasm_contracts.iter() {
-        let tmp_path = Path::new(TEMPORARY_DIRECTORY_NAME);
-        if !tmp_path.exists() {
-            fs::create_dir(tmp_path).expect("Could not create temporary directory");
+        if options.emits(Emit::Abi) {
+            artifacts.push(Artifact::new(
+                Emit::Abi,
+                file_name,
+                generate_abi(&contract.contract_behaviour_declarations),
+            ));
         }
+    }
 
-        let file_name = contract.contract_declaration.identifier.token.as_str();
-        let get_path = |folder: &str, ext: &str| format!("{}/{}.{}", folder, file_name, ext);
-
-        // Create LLVM file
-        create_and_write_to_file(
-            Path::new(get_path(TEMPORARY_DIRECTORY_NAME, "ll").as_str()),
-            &*generate_llvm(contract).as_bytes(),
-        )
-        .expect("Could not create file");
-
-        // Convert LLVM to wasm32:
-        Command::new(llc_path)
-            .arg("-O3")
-            .arg("-march=wasm32")
-            .arg("-filetype=obj")
-            .arg(get_path(TEMPORARY_DIRECTORY_NAME, "ll"))
-            .status()
-            .expect("Could not compile to WASM");
-
-        // Link externally defined functions
-        Command::new(wasm_ld_path)
-            .arg("--no-entry")
-            .arg("--export-all")
-            .arg("--allow-undefined")
-            .arg("-o")
-            .arg(get_path(TEMPORARY_DIRECTORY_NAME, "wasm"))
-            .arg(get_path(TEMPORARY_DIRECTORY_NAME, "o"))
-            .status()
-            .expect("Could not link externally defined methods");
-
-        // The following only exists so that we can inspect LLVM output and wasm files as wat files
-        // while developing, and should be removed. TODO
-        let wasm = fs::read(Path::new(
-            get_path(TEMPORARY_DIRECTORY_NAME, "wasm").as_str(),
-        ))
-        .expect("Could not read wasm");
-        let mut as_wat = wasm2wat(wasm).expect("Could not convert wasm to wat");
-
-        // Shift final module closing curly brace onto its own line so it is not removed when trimming exports
-        as_wat.insert(as_wat.len() - 2, '\n');
-        // Remove exports except memory and main
-        let export_regex = Regex::new("export \"((main)|(memory))\"").unwrap();
-
-        let as_wat = as_wat
-            .lines()
-            .filter(|line| !line.contains("export") || export_regex.is_match(line))
-            .intersperse("\n")
-            .collect::<String>();
-
-        create_and_write_to_file(
-            Path::new(get_path(TEMPORARY_DIRECTORY_NAME, "wat").as_str()),
-            &as_wat.as_bytes(),
-        )
-        .expect("Could not create temporary wat file");
-
-        let post_processed_wasm =
-            wat2wasm(as_wat.as_bytes()).expect("Could not convert wat to wasm");
-        create_and_write_to_file(
-            Path::new(get_path(OUTPUT_DIRECTORY_NAME, "wasm").as_str()),
-            &post_processed_wasm,
-        )
-        .expect("Could not write to output wasm file");
-
-        // TODO remove this for the final release, as we do not need to give wat and wasm files
-        fs::copy(
-            Path::new(get_path(TEMPORARY_DIRECTORY_NAME, "wat").as_str()),
-            Path::new(get_path(OUTPUT_DIRECTORY_NAME, "wat").as_str()),
-        )
-        .expect("Could not copy wat file from temporary dir to output dir");
-
-        // TODO remove this for the final release, but for testing the LLVM, we need to be able to read the LLVM files
-        fs::copy(
-            Path::new(get_path(TEMPORARY_DIRECTORY_NAME, "ll").as_str()),
-            Path::new(get_path(OUTPUT_DIRECTORY_NAME, "ll").as_str()),
-        )
-        .expect("Could not copy ll file from temporary dir to output dir");
-
-        // Delete all tmp files
-        fs::remove_dir_all(tmp_path).expect("Could not remove temporary directory");
-
-        // Generate the ABI
-        create_and_write_to_file(
-            Path::new(get_path(OUTPUT_DIRECTORY_NAME, "json").as_str()),
-            &*generate_abi(&contract.contract_behaviour_declarations).as_bytes(),
-        )
-        .expect("Could not generate abi file");
+    Ok(artifacts)
+}
+
+/// Compiles the LLVM of a contract to wasm with llc and wasm-ld, keeping only the exports
+/// eWASM allows
+fn compile_to_wat(
+    file_name: &str,
+    llvm: &str,
+    options: &Options,
+) -> Result<String, Box<dyn Error>> {
+    let tools = config::tools(options.config.as_deref())?;
+
+    let directory = TemporaryDirectory::new()?;
+    let get_path = |ext: &str| {
+        directory
+            .path
+            .join(format!("{}.{}", file_name, ext))
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    create_and_write_to_file(Path::new(get_path("ll").as_str()), llvm.as_bytes())?;
+
+    // Convert LLVM to wasm32:
+    run(
+        &tools.llc,
+        &[
+            format!("-O{}", options.optimisation),
+            "-march=wasm32".to_string(),
+            "-filetype=obj".to_string(),
+            get_path("ll"),
+        ],
+    )?;
+
+    // Link externally defined functions
+    run(
+        &tools.wasm_ld,
+        &[
+            "--no-entry".to_string(),
+            "--export-all".to_string(),
+            "--allow-undefined".to_string(),
+            "-o".to_string(),
+            get_path("wasm"),
+            get_path("o"),
+        ],
+    )?;
+
+    let wasm = fs::read(Path::new(get_path("wasm").as_str()))?;
+    drop(directory);
+
+    let mut as_wat =
+        wasm2wat(wasm).map_err(|err| format!("Could not convert wasm to wat: {:?}", err))?;
+
+    // Shift final module closing curly brace onto its own line so it is not removed when trimming exports
+    as_wat.insert(as_wat.len() - 2, '\n');
+    // Remove exports except memory and main
+    let export_regex = Regex::new("export \"((main)|(memory))\"").unwrap();
+
+    Ok(as_wat
+        .lines()
+        .filter(|line| !line.contains("export") || export_regex.is_match(line))
+        .intersperse("\n")
+        .collect::<String>())
+}
+
+/// A directory of its own for the files of one compilation, which is removed with everything in it
+/// when dropped, so that compilations running at the same time do not share files
+struct TemporaryDirectory {
+    path: PathBuf,
+}
+
+impl TemporaryDirectory {
+    fn new() -> Result<TemporaryDirectory, Box<dyn Error>> {
+        let path = env::temp_dir().join(format!(
+            "{}-{}-{}",
+            TEMPORARY_DIRECTORY_PREFIX,
+            process::id(),
+            TEMPORARY_DIRECTORY_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir(&path)?;
+        Ok(TemporaryDirectory { path })
+    }
+}
+
+impl Drop for TemporaryDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn run(program: &str, arguments: &[String]) -> Result<(), String> {
+    match Command::new(program).args(arguments).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("`{}` failed with {}", program, status)),
+        Err(err) => Err(format!("Could not run `{}`: {}", program, err)),
     }
 }
 
@@ -273,19 +286,7 @@ fn generate_llvm(contract: &LLVMContract) -> String {
         types: HashMap::new(),
     };
 
-    build_empty_main_function(&codegen);
-
     contract.generate(&mut codegen);
+    generate_main(contract, &codegen);
     llvm_module.print_to_string().to_string()
 }
-
-// This simply creates an empty main method, since eWASM requires a main that does not have
-// inputs or outputs
-fn build_empty_main_function(codegen: &Codegen) {
-    let void_type = codegen.context.void_type().fn_type(&[], false);
-    let main = codegen.module.add_function("main", void_type, None);
-    let block = codegen.context.append_basic_block(main, "entry");
-    codegen.builder.position_at_end(block);
-    codegen.builder.build_return(None);
-    codegen.verify_and_optimise(&main);
-}
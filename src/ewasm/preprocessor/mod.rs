@@ -12,22 +12,27 @@ use crate::ast::statements::{ReturnStatement, Statement};
 use crate::ast::types::{InoutType, Type};
 use crate::ast::Property;
 use crate::ast::{
-    CallerProtection, ContractDeclaration, ContractMember, Literal, Modifier, SpecialDeclaration,
-    SpecialSignatureDeclaration, StructDeclaration, StructMember, VResult,
+    mangle_function, CallerProtection, ContractDeclaration, ContractMember, Literal, Modifier,
+    SpecialDeclaration, SpecialSignatureDeclaration, StructDeclaration, StructMember, VResult,
 };
 use crate::context::Context;
 use crate::environment::Environment;
 use crate::ewasm::preprocessor::utils::*;
 use crate::utils::getters_and_setters::*;
 use crate::utils::is_init_declaration;
+use crate::utils::specifications::{insert_initialiser_checks, insert_specification_checks};
+use crate::utils::type_states::generate_entry_type_state_assertion;
 use crate::visitor::Visitor;
 use itertools::Itertools;
 
 pub struct LLVMPreProcessor {}
 
 impl LLVMPreProcessor {
-    pub(crate) const CALLER_PROTECTIONS_PARAM: &'static str = "caller";
+    pub(crate) const CALLER_PROTECTIONS_PARAM: &'static str = "_caller";
+    pub(crate) const STRUCT_INIT_TARGET: &'static str = "_tmp_var";
     pub(crate) const CALLER_WRAPPER_NAME: &'static str = "_getCaller";
+    pub(crate) const EXPONENTIATION_FUNCTION: &'static str = "_exp";
+    pub(crate) const TEMPORARY_PREFIX: &'static str = "_tmp_";
 }
 
 impl Visitor for LLVMPreProcessor {
@@ -66,7 +71,7 @@ impl Visitor for LLVMPreProcessor {
             .any(|dec| is_init_declaration(dec))
         {
             let mangler = declaration.identifier.token.clone();
-            let mangler = |name: &str| mangle_ewasm_function(name, mangler.as_str());
+            let mangler = |name: &str| mangle_function(name, mangler.as_str(), false);
 
             generate_and_add_getters_and_setters(declaration, ctx, &mangler);
         }
@@ -203,9 +208,12 @@ impl Visitor for LLVMPreProcessor {
         declaration: &mut FunctionDeclaration,
         ctx: &mut Context,
     ) -> VResult {
-        let mangled_name = mangle_ewasm_function(
+        insert_specification_checks(declaration, ctx)?;
+
+        let mangled_name = mangle_function(
             &declaration.head.identifier.token,
             declaration.head.identifier.enclosing_type.as_ref().unwrap(),
+            false,
         );
 
         declaration.mangled_identifier = Some(mangled_name);
@@ -239,8 +247,30 @@ impl Visitor for LLVMPreProcessor {
     fn finish_function_declaration(
         &mut self,
         declaration: &mut FunctionDeclaration,
-        _: &mut Context,
+        ctx: &mut Context,
     ) -> VResult {
+        // Wrappers check the state themselves when the function they call does not
+        if let (Some(contract), false) = (
+            &ctx.contract_behaviour_declaration_context,
+            declaration.is_external,
+        ) {
+            let state = Expression::BinaryExpression(BinaryExpression {
+                lhs_expression: Box::new(Expression::SelfExpression),
+                rhs_expression: Box::new(Expression::Identifier(Identifier {
+                    token: Identifier::TYPESTATE_VAR_NAME.to_string(),
+                    enclosing_type: Some(contract.identifier.token.clone()),
+                    line_info: Default::default(),
+                })),
+                op: BinOp::Dot,
+                line_info: Default::default(),
+            });
+            if let Some(assertion) =
+                generate_entry_type_state_assertion(&declaration.head.identifier.token, state, ctx)
+            {
+                declaration.body.insert(0, assertion);
+            }
+        }
+
         if declaration.is_void() {
             if let Some(Statement::ReturnStatement(_)) = declaration.body.last() {
                 return Ok(());
@@ -262,6 +292,8 @@ impl Visitor for LLVMPreProcessor {
         dec: &mut SpecialDeclaration,
         ctx: &mut Context,
     ) -> VResult {
+        insert_initialiser_checks(dec, ctx)?;
+
         // Push default variable assignments to the initialiser
         if let Some(contract_name) = &dec.head.enclosing_type {
             let vars_with_assignments = &ctx
@@ -558,7 +590,9 @@ impl Visitor for LLVMPreProcessor {
                 identifier: None,
                 expression: Expression::InoutExpression(InoutExpression {
                     ampersand_token: "&".to_string(),
-                    expression: Box::new(Expression::Identifier(Identifier::generated("tmp_var"))),
+                    expression: Box::new(Expression::Identifier(Identifier::generated(
+                        LLVMPreProcessor::STRUCT_INIT_TARGET,
+                    ))),
                 }),
             });
         } else {
@@ -571,7 +605,7 @@ impl Visitor for LLVMPreProcessor {
 
             if !Environment::is_runtime_function_call(call) {
                 // Mangles name
-                call.identifier.token = mangle_ewasm_function(&function_name, enclosing_type);
+                call.identifier.token = mangle_function(&function_name, enclosing_type, false);
 
                 // Pass in the parameter for the function to operate on. If it is a struct function,
                 // it should be an instance of that struct. Otherwise it will be the contract variable
@@ -607,7 +641,7 @@ impl Visitor for LLVMPreProcessor {
             } else if !enclosing_type.eq(crate::environment::FLINT_GLOBAL) {
                 // Mangles name
                 call.identifier.token =
-                    mangle_ewasm_function(&function_name, crate::environment::FLINT_GLOBAL);
+                    mangle_function(&function_name, crate::environment::FLINT_GLOBAL, false);
             }
         }
 
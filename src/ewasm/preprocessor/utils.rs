@@ -7,7 +7,8 @@ use crate::ast::expressions::Identifier;
 use crate::ast::statements::{ReturnStatement, Statement};
 use crate::ast::types::Type;
 use crate::ast::{
-    Assertion, BinOp, BinaryExpression, CallerProtection, InoutType, VariableDeclaration,
+    mangle_function, Assertion, BinOp, BinaryExpression, CallerProtection, InoutType, Literal,
+    SubscriptExpression, VariableDeclaration,
 };
 use crate::context::Context;
 use crate::context::ScopeContext;
@@ -28,8 +29,14 @@ pub fn generate_contract_wrapper(
 
     let contract_name = contract_behaviour_declaration.identifier.token.as_str();
 
-    // Add type state assertions
-    if !contract_behaviour_declaration.type_states.is_empty() {
+    // Add type state assertions, unless the function checks the state itself
+    if ctx
+        .environment
+        .may_call_in_disallowed_state(contract_name, &contract_behaviour_declaration.type_states)
+        && !ctx
+            .environment
+            .checks_type_state_on_entry(contract_name, &function.head.identifier.token)
+    {
         let type_state_var = BinaryExpression {
             lhs_expression: Box::new(Expression::Identifier(Identifier::generated(
                 Identifier::SELF,
@@ -61,7 +68,7 @@ pub fn generate_contract_wrapper(
         .collect::<Vec<u8>>();
 
         let condition = generate_type_state_condition(
-            Identifier::generated(Identifier::TYPESTATE_VAR_NAME),
+            Expression::Identifier(Identifier::generated(Identifier::TYPESTATE_VAR_NAME)),
             &allowed_type_states_as_u8s,
         );
 
@@ -157,10 +164,6 @@ pub fn generate_contract_wrapper(
     wrapper
 }
 
-pub fn mangle_ewasm_function(function_name: &str, enclosing: &str) -> String {
-    format!("{}_{}", enclosing, function_name)
-}
-
 pub fn construct_parameter(name: String, t: Type) -> Parameter {
     let identifier = Identifier {
         token: name,
@@ -211,6 +214,7 @@ pub fn generate_caller_protections_predicate(
                     line_info: Default::default(),
                 })),
                 Type::ArrayType(_) => None,
+                // The caller must be one of the addresses the array holds when it is called
                 Type::FixedSizedArrayType(array_type) => {
                     assert_eq!(
                         *array_type.key_type,
@@ -218,39 +222,32 @@ pub fn generate_caller_protections_predicate(
                         "Array values for caller protection must have type Address"
                     );
 
-                    if let Some(property) = ctx.environment.get_caller_protection(&c) {
-                        if let Some(Expression::ArrayLiteral(array)) = property.property.get_value()
-                        {
-                            let predicate = array
-                                .elements
-                                .iter()
-                                .cloned()
-                                .map(|c| {
-                                    Expression::BinaryExpression(BinaryExpression {
-                                        lhs_expression: Box::new(c),
-                                        rhs_expression: Box::new(Expression::Identifier(
-                                            Identifier::generated(caller_id),
+                    (0..array_type.size)
+                        .map(|index| {
+                            Expression::BinaryExpression(BinaryExpression {
+                                lhs_expression: Box::new(Expression::SubscriptExpression(
+                                    SubscriptExpression {
+                                        base_expression: ident.clone(),
+                                        index_expression: Box::new(Expression::Literal(
+                                            Literal::IntLiteral(index),
                                         )),
-                                        op: BinOp::DoubleEqual,
-                                        line_info: Default::default(),
-                                    })
-                                })
-                                .fold1(|left, right| {
-                                    Expression::BinaryExpression(BinaryExpression {
-                                        lhs_expression: Box::new(left),
-                                        rhs_expression: Box::new(right),
-                                        op: BinOp::Or,
-                                        line_info: Default::default(),
-                                    })
-                                })
-                                .unwrap();
-                            Some(predicate)
-                        } else {
-                            panic!("Mismatching types for {:?}", c)
-                        }
-                    } else {
-                        panic!("Could not find declaration for caller protection {:?}", c)
-                    }
+                                    },
+                                )),
+                                rhs_expression: Box::new(Expression::Identifier(
+                                    Identifier::generated(caller_id),
+                                )),
+                                op: BinOp::DoubleEqual,
+                                line_info: Default::default(),
+                            })
+                        })
+                        .fold1(|left, right| {
+                            Expression::BinaryExpression(BinaryExpression {
+                                lhs_expression: Box::new(left),
+                                rhs_expression: Box::new(right),
+                                op: BinOp::Or,
+                                line_info: Default::default(),
+                            })
+                        })
                 }
                 Type::DictionaryType(dict_type) => {
                     assert_eq!(
@@ -304,12 +301,11 @@ pub fn generate_caller_protections_predicate(
                                     return if ident.token != function_name {
                                         // Prevents predicate being added to the predicate function itself
                                         Some(Expression::FunctionCall(FunctionCall {
-                                            identifier: Identifier::generated(
-                                                &mangle_ewasm_function(
-                                                    &ident.token,
-                                                    enclosing_type,
-                                                ),
-                                            ),
+                                            identifier: Identifier::generated(&mangle_function(
+                                                &ident.token,
+                                                enclosing_type,
+                                                false,
+                                            )),
                                             arguments: vec![
                                                 FunctionArgument {
                                                     identifier: None,
@@ -337,9 +333,10 @@ pub fn generate_caller_protections_predicate(
                                             lhs_expression: Box::new(Expression::FunctionCall(
                                                 FunctionCall {
                                                     identifier: Identifier::generated(
-                                                        &mangle_ewasm_function(
+                                                        &mangle_function(
                                                             &ident.token,
                                                             enclosing_type,
+                                                            false,
                                                         ),
                                                     ),
                                                     arguments: vec![FunctionArgument {
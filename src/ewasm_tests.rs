@@ -1,14 +1,17 @@
 #[cfg(test)]
 mod ewasm_tests {
+    use crate::ewasm::host::{Call, Host, Message, Outcome};
     use crate::io::target::target;
-    use crate::io::*;
+    use crate::target::output::{Emit, Options};
     use crate::{ast_processor, parser};
     use inkwell::context::Context;
     use inkwell::execution_engine::{ExecutionEngine, JitFunction};
     use inkwell::module::Module;
     use inkwell::OptimizationLevel;
+    use itertools::Itertools;
     use libchisel::{checkstartfunc::*, verifyexports::*, verifyimports::*};
     use libchisel::{ModulePreset, ModuleValidator};
+    use sha3::{Digest, Keccak256};
     use std::fs;
     use std::io::Read;
     use std::path::Path;
@@ -58,42 +61,53 @@ mod ewasm_tests {
             file.read_to_string(&mut program)
                 .expect("Unable to read the file");
 
-            let mut file = fs::File::open("stdlib/ether/global.flint").unwrap_or_else(|err| {
-                prompt::error::unable_to_open_file(Path::new("stdlib/ether/global.flint"), err)
-            });
-
-            file.read_to_string(&mut program).unwrap_or_else(|err| {
-                prompt::error::unable_to_read_file(Path::new("stdlib/ether/global.flint"), err)
-            });
+            let target = target("ethereum").unwrap();
+            program.push_str(target.stdlib);
 
             let (module, environment) = parser::parse_program(&program).unwrap_or_else(|err| {
-                println!("Could not parse file: {}", err);
+                println!(
+                    "Could not parse file: {}",
+                    err.iter().map(|diagnostic| &diagnostic.message).join("\n")
+                );
                 std::process::exit(1);
             });
 
-            ast_processor::process_ast(module, environment, target("ethereum").unwrap())
+            let options = Options {
+                emit: vec![Emit::Llvm, Emit::Wasm],
+                ..Default::default()
+            };
+            let artifacts = ast_processor::process_ast(module, environment, target, &options)
                 .unwrap_or_else(|err| {
-                    println!("Could not parse invalid flint file: {}", err);
+                    println!(
+                        "Could not parse invalid flint file: {}",
+                        err.iter().map(|diagnostic| &diagnostic.message).join("\n")
+                    );
                     std::process::exit(1);
-                });
+                })
+                .artifacts;
+            let artifact = |extension: &str| {
+                let file_name = format!("{}.{}", output_file_name, extension);
+                artifacts
+                    .iter()
+                    .find(|artifact| artifact.file_name() == file_name)
+                    .unwrap_or_else(|| panic!("No {} generated", file_name))
+            };
 
             // Validate ewasm
-            let output_path = format!("output/{}.wasm", output_file_name);
-            let output_path = Path::new(output_path.as_str());
-            assert!(output_path.exists());
-            let output_wasm = fs::read(output_path).expect(&format!(
-                "Could not read wasm from contract `{}`",
-                flint_file_name
-            ));
-            assert!(vaildate_ewasm(&output_wasm));
+            assert!(vaildate_ewasm(&artifact("wasm").contents));
+
+            // Run ewasm against a mock of the EEI, which checks the signatures of its imports
+            assert_eq!(
+                run_ewasm(&artifact("wasm").contents),
+                Outcome::Finish(vec![])
+            );
 
             // Test runtime LLVM
             if let Some(test_func) = runtime_tests[test_no] {
-                let output_path = format!("output/{}.ll", output_file_name);
-                let output_path = Path::new(output_path.as_str());
-                assert!(output_path.exists());
-                let buffer = inkwell::memory_buffer::MemoryBuffer::create_from_file(output_path)
-                    .expect("Could not create memory buffer from LLVM file");
+                let buffer = inkwell::memory_buffer::MemoryBuffer::create_from_memory_range_copy(
+                    &artifact("ll").contents,
+                    output_file_name,
+                );
                 let context = Context::create();
                 let module = context
                     .create_module_from_ir(buffer)
@@ -136,6 +150,112 @@ mod ewasm_tests {
         true
     }
 
+    fn run_ewasm(module: &[u8]) -> Outcome {
+        let contract = Host::instantiate(module).unwrap_or_else(|err| panic!("{}", err));
+        Host::new([0xc; 20]).execute(&contract, &Message::new([0xa; 20]))
+    }
+
+    #[test]
+    fn test_ewasm_execution() {
+        // `Flint_transfer` pays from the contract whatever its first argument is
+        let mut program = String::from(
+            "contract Wallet {}
+
+            Wallet :: caller <- (any) {
+              public init() {}
+
+              public func withdraw(amount: Int) {
+                Flint_transfer(0x0, caller, amount)
+              }
+
+              public func doubled(amount: Int) -> Int {
+                return amount + amount
+              }
+            }",
+        );
+        let target = target("ethereum").unwrap();
+        program.push_str(target.stdlib);
+
+        let (module, environment) = parser::parse_program(&program).unwrap();
+        let options = Options {
+            emit: vec![Emit::Wasm],
+            ..Default::default()
+        };
+        let artifacts = ast_processor::process_ast(module, environment, target, &options)
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Could not compile: {}",
+                    err.iter().map(|diagnostic| &diagnostic.message).join("\n")
+                )
+            })
+            .artifacts;
+        let contract =
+            Host::instantiate(&artifacts[0].contents).unwrap_or_else(|err| panic!("{}", err));
+        let message = Message::new([0xa; 20]);
+        let mut host = Host::new([0xc; 20]);
+        host.balances.insert([0xc; 20], 15);
+
+        // Deploying the contract sends it no call data
+        assert_eq!(host.execute(&contract, &message), Outcome::Finish(vec![]));
+
+        let withdraw = message
+            .clone()
+            .with_data(call_data("withdraw(uint64)", &[10]));
+        assert_eq!(host.execute(&contract, &withdraw), Outcome::Finish(vec![]));
+
+        // The generated code stands in for `getCaller`, giving the address 1
+        let mut caller = [0; 20];
+        caller[0] = 1;
+        assert_eq!(
+            host.calls,
+            vec![Call {
+                address: caller,
+                value: 10,
+                data: vec![],
+            }]
+        );
+        assert_eq!(host.balance(&[0xc; 20]), 5);
+        assert_eq!(host.balance(&caller), 10);
+
+        // The contract cannot pay more than it holds, so the call fails and the contract reverts
+        assert_eq!(host.execute(&contract, &withdraw), Outcome::Revert(vec![]));
+        assert_eq!(host.balance(&[0xc; 20]), 5);
+        assert_eq!(host.calls.len(), 1);
+
+        // Results are returned as ABI encoded words
+        let doubled = message
+            .clone()
+            .with_data(call_data("doubled(uint64)", &[21]));
+        assert_eq!(
+            host.execute(&contract, &doubled),
+            Outcome::Finish(word(42).to_vec())
+        );
+
+        // Call data which selects no function, or leaves out arguments, is rejected
+        let unknown = message
+            .clone()
+            .with_data(call_data("deposit(uint64)", &[10]));
+        assert_eq!(host.execute(&contract, &unknown), Outcome::Revert(vec![]));
+        let missing = message.with_data(call_data("withdraw(uint64)", &[]));
+        assert_eq!(host.execute(&contract, &missing), Outcome::Revert(vec![]));
+        assert_eq!(host.calls.len(), 1);
+    }
+
+    /// The selector of the function with the signature, followed by the arguments as words
+    fn call_data(signature: &str, arguments: &[u64]) -> Vec<u8> {
+        let mut data = Keccak256::digest(signature.as_bytes())[..4].to_vec();
+        for argument in arguments {
+            data.extend_from_slice(&word(*argument));
+        }
+        data
+    }
+
+    fn word(value: u64) -> [u8; 32] {
+        let mut word = [0; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
     type VoidToVoid = unsafe extern "C" fn() -> ();
 
     fn set_up_tests<'a>(module: &'a Module) -> ExecutionEngine<'a> {
@@ -0,0 +1,898 @@
+//! Prints a module back to source in the canonical layout: four spaces of indentation, one
+//! declaration or statement per line, and a blank line between top level declarations. Blank
+//! lines within blocks are kept, though runs of them are shortened to one.
+//!
+//! Comments are not part of the syntax tree, so they are put back by position. Each comment is
+//! written before the first declaration or statement which came after it in the source, or before
+//! the closing brace of its block. A comment which shared a line with the code before it stays at
+//! the end of that line.
+
+use crate::ast::*;
+
+const INDENT: &str = "    ";
+
+pub fn format(module: &Module, source: &str) -> String {
+    let mut formatter = Formatter::new(module, source);
+    formatter.module(module);
+    formatter.comments(usize::MAX);
+    formatter.output
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: &'a [Comment],
+    /// The offsets of the brace pairs in the source, in the order the blocks open
+    blocks: Vec<(usize, usize)>,
+    /// How far through the source the code and comments written so far reach
+    cursor: usize,
+    /// The source line the last line written started on, which trailing comments are kept on
+    line: usize,
+    indent: usize,
+    /// Nothing has been written in the current block yet, so no blank line is needed
+    block_start: bool,
+    /// The next line starts a new top level declaration, so is separated from the one before
+    separate: bool,
+    output: String,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(module: &'a Module, source: &'a str) -> Self {
+        Formatter {
+            source,
+            comments: &module.comments,
+            blocks: blocks(source, &module.comments),
+            cursor: 0,
+            line: 0,
+            indent: 0,
+            block_start: true,
+            separate: false,
+            output: String::new(),
+        }
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.source[..offset.min(self.source.len())]
+            .matches('\n')
+            .count()
+            + 1
+    }
+
+    /// Whether there is a blank line between the code written so far and the offset
+    fn follows_blank_line(&self, offset: usize) -> bool {
+        let offset = offset.min(self.source.len());
+        let lines: Vec<&str> = self.source[self.cursor.min(offset)..offset]
+            .split('\n')
+            .collect();
+        lines.len() > 2
+            && lines[1..lines.len() - 1]
+                .iter()
+                .any(|line| line.trim().is_empty())
+    }
+
+    fn write_line(&mut self, text: &str, offset: Option<usize>) {
+        let blank = offset.map_or(false, |offset| self.follows_blank_line(offset));
+        if self.separate || (blank && !self.block_start) {
+            self.output.push('\n');
+        }
+        self.separate = false;
+        self.block_start = false;
+
+        for _ in 0..self.indent {
+            self.output.push_str(INDENT);
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+        self.line = offset.map_or(0, |offset| self.line_of(offset));
+        if let Some(offset) = offset {
+            self.cursor = self.cursor.max(offset);
+        }
+    }
+
+    fn append(&mut self, text: &str) {
+        self.output.pop();
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    /// Writes the comments which come before the offset in the source
+    fn comments(&mut self, offset: usize) {
+        while let Some((comment, rest)) = self.comments.split_first() {
+            if comment.line_info.offset >= offset {
+                break;
+            }
+            self.comments = rest;
+
+            let line = self.line_of(comment.line_info.offset);
+            if line == self.line && !self.output.is_empty() {
+                self.append(&format!("  {}", comment.text));
+            } else {
+                self.write_line(&comment.text, Some(comment.line_info.offset));
+            }
+            self.line = line;
+            self.cursor = comment.line_info.offset + comment.text.len();
+        }
+    }
+
+    /// Starts a declaration or statement found at the offset in the source, after the comments
+    /// before it. Those without a known offset start at the block they open, if any
+    fn start(&mut self, offset: Option<usize>, opens_block: bool) -> Option<usize> {
+        let offset = offset.or_else(|| {
+            if opens_block {
+                self.next_block().map(|(open, _)| open)
+            } else {
+                None
+            }
+        });
+        if let Some(offset) = offset {
+            self.comments(offset);
+        }
+        offset
+    }
+
+    fn next_block(&self) -> Option<(usize, usize)> {
+        self.blocks
+            .iter()
+            .find(|(open, _)| *open >= self.cursor)
+            .cloned()
+    }
+
+    /// Writes a line ending in a block, whose contents are written by `body`. An empty block is
+    /// closed on the same line. The line continues the last one written if it is a continuation,
+    /// such as an `else`
+    fn block<F>(
+        &mut self,
+        line: &str,
+        offset: Option<usize>,
+        continuation: bool,
+        empty: bool,
+        body: F,
+    ) where
+        F: FnOnce(&mut Self),
+    {
+        let (open, close) = self.next_block().unwrap_or((self.cursor, self.cursor));
+        let has_comments = self
+            .comments
+            .first()
+            .map_or(false, |comment| comment.line_info.offset < close);
+
+        let line = if empty && !has_comments {
+            format!("{} {{}}", line)
+        } else {
+            format!("{} {{", line)
+        };
+        if continuation {
+            self.append(&format!(" {}", line));
+        } else {
+            self.write_line(&line, offset);
+        }
+        self.line = self.line_of(open);
+        self.cursor = open + 1;
+
+        if !empty || has_comments {
+            self.indent += 1;
+            self.block_start = true;
+            body(self);
+            self.comments(close);
+            self.indent -= 1;
+            self.write_line("}", None);
+        }
+        self.line = self.line_of(close);
+        self.cursor = close + 1;
+    }
+
+    fn module(&mut self, module: &Module) {
+        for import in &module.imports {
+            let offset = self.start(Some(import.line_info.offset), false);
+            self.write_line(&format!("import \"{}\"", import.path), offset);
+        }
+
+        for declaration in &module.declarations {
+            self.separate = !self.output.is_empty();
+            self.top_level_declaration(declaration);
+        }
+    }
+
+    fn top_level_declaration(&mut self, declaration: &TopLevelDeclaration) {
+        match declaration {
+            TopLevelDeclaration::ContractDeclaration(contract) => {
+                let offset = self.start(Some(contract.identifier.line_info.offset), true);
+                let mut line = format!("contract {}", contract.identifier.token);
+                if !contract.type_states.is_empty() {
+                    line.push_str(&format!(" ({})", type_states(&contract.type_states)));
+                }
+                line.push_str(&conformances(&contract.conformances));
+                self.block(
+                    &line,
+                    offset,
+                    false,
+                    contract.contract_members.is_empty(),
+                    |f| {
+                        for member in &contract.contract_members {
+                            match member {
+                                ContractMember::VariableDeclaration(declaration, modifier) => {
+                                    f.property(declaration, modifier.as_ref())
+                                }
+                                ContractMember::EventDeclaration(event) => f.event(event),
+                                ContractMember::Invariant(invariant) => f.invariant(invariant),
+                            }
+                        }
+                    },
+                );
+            }
+            TopLevelDeclaration::ContractBehaviourDeclaration(behaviour) => {
+                self.contract_behaviour(behaviour)
+            }
+            TopLevelDeclaration::StructDeclaration(declaration) => {
+                let offset = self.start(Some(declaration.identifier.line_info.offset), true);
+                let line = format!(
+                    "struct {}{}",
+                    declaration.identifier.token,
+                    conformances(&declaration.conformances)
+                );
+                self.block(&line, offset, false, declaration.members.is_empty(), |f| {
+                    for member in &declaration.members {
+                        match member {
+                            StructMember::VariableDeclaration(declaration, modifier) => {
+                                f.property(declaration, modifier.as_ref())
+                            }
+                            StructMember::FunctionDeclaration(function) => f.function(function),
+                            StructMember::SpecialDeclaration(special) => f.special(special),
+                            StructMember::Invariant(invariant) => f.invariant(invariant),
+                        }
+                    }
+                });
+            }
+            TopLevelDeclaration::AssetDeclaration(declaration) => {
+                let offset = self.start(Some(declaration.identifier.line_info.offset), true);
+                let line = format!("asset {}", declaration.identifier.token);
+                self.block(&line, offset, false, declaration.members.is_empty(), |f| {
+                    for member in &declaration.members {
+                        match member {
+                            AssetMember::VariableDeclaration(declaration) => {
+                                f.property(declaration, None)
+                            }
+                            AssetMember::FunctionDeclaration(function) => f.function(function),
+                            AssetMember::SpecialDeclaration(special) => f.special(special),
+                        }
+                    }
+                });
+            }
+            TopLevelDeclaration::EnumDeclaration(declaration) => {
+                let offset = self.start(Some(declaration.identifier.line_info.offset), true);
+                let mut line = format!("enum {}", declaration.identifier.token);
+                if let Some(t) = &declaration.type_assigned {
+                    line.push_str(&format!(": {}", type_name(t)));
+                }
+                self.block(&line, offset, false, declaration.members.is_empty(), |f| {
+                    for member in &declaration.members {
+                        let offset = f.start(Some(member.identifier.line_info.offset), false);
+                        let mut line = format!("case {}", member.identifier.token);
+                        if let Some(value) = &member.hidden_value {
+                            line.push_str(&format!(" = {}", expression(value)));
+                        }
+                        f.write_line(&line, offset);
+                    }
+                });
+            }
+            TopLevelDeclaration::TraitDeclaration(declaration) => {
+                let offset = declaration
+                    .modifiers
+                    .first()
+                    .map(|modifier| &modifier.identifier)
+                    .unwrap_or(&declaration.identifier)
+                    .line_info
+                    .offset;
+                let offset = self.start(Some(offset), true);
+                for modifier in &declaration.modifiers {
+                    let line = if modifier.arguments.is_empty() {
+                        format!("@{}", modifier.identifier.token)
+                    } else {
+                        format!("@{}", function_call(modifier))
+                    };
+                    self.write_line(&line, offset);
+                }
+
+                let line = format!(
+                    "{}trait {}",
+                    if declaration.external {
+                        "external "
+                    } else {
+                        ""
+                    },
+                    declaration.identifier.token
+                );
+                let offset = if declaration.modifiers.is_empty() {
+                    offset
+                } else {
+                    None
+                };
+                self.block(&line, offset, false, declaration.members.is_empty(), |f| {
+                    for member in &declaration.members {
+                        match member {
+                            TraitMember::FunctionDeclaration(function) => f.function(function),
+                            TraitMember::SpecialDeclaration(special) => f.special(special),
+                            TraitMember::FunctionSignatureDeclaration(signature) => {
+                                let offset =
+                                    f.start(Some(signature.identifier.line_info.offset), false);
+                                let offset = f.attributes(&signature.attributes, offset);
+                                f.write_line(&function_signature(signature), offset);
+                            }
+                            TraitMember::SpecialSignatureDeclaration(signature) => {
+                                let offset = f.start(special_offset(signature), false);
+                                let offset = f.attributes(&signature.attributes, offset);
+                                f.write_line(&special_signature(signature), offset);
+                            }
+                            TraitMember::ContractBehaviourDeclaration(behaviour) => {
+                                f.contract_behaviour(behaviour)
+                            }
+                            TraitMember::EventDeclaration(event) => f.event(event),
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    fn contract_behaviour(&mut self, behaviour: &ContractBehaviourDeclaration) {
+        let offset = self.start(Some(behaviour.identifier.line_info.offset), true);
+        let mut line = behaviour.identifier.token.clone();
+        if !behaviour.type_states.is_empty() {
+            line.push_str(&format!(" @({})", type_states(&behaviour.type_states)));
+        }
+        line.push_str(" :: ");
+        if let Some(binding) = &behaviour.caller_binding {
+            line.push_str(&format!("{} <- ", binding.token));
+        }
+        let protections: Vec<&str> = behaviour
+            .caller_protections
+            .iter()
+            .map(|protection| protection.identifier.token.as_str())
+            .collect();
+        line.push_str(&format!("({})", protections.join(", ")));
+
+        self.block(&line, offset, false, behaviour.members.is_empty(), |f| {
+            for member in &behaviour.members {
+                match member {
+                    ContractBehaviourMember::FunctionDeclaration(function) => f.function(function),
+                    ContractBehaviourMember::SpecialDeclaration(special) => f.special(special),
+                    ContractBehaviourMember::FunctionSignatureDeclaration(signature) => {
+                        let offset = f.start(Some(signature.identifier.line_info.offset), false);
+                        let offset = f.attributes(&signature.attributes, offset);
+                        f.write_line(&function_signature(signature), offset);
+                    }
+                    ContractBehaviourMember::SpecialSignatureDeclaration(signature) => {
+                        let offset = f.start(special_offset(signature), false);
+                        let offset = f.attributes(&signature.attributes, offset);
+                        f.write_line(&special_signature(signature), offset);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Attributes are written on the lines before the declaration. Returns the offset of the line
+    /// the declaration itself is written on
+    fn attributes(&mut self, attributes: &[Attribute], offset: Option<usize>) -> Option<usize> {
+        for attribute in attributes {
+            self.write_line(&format!("@{}", attribute.identifier_token), offset);
+        }
+        if attributes.is_empty() {
+            offset
+        } else {
+            None
+        }
+    }
+
+    fn property(&mut self, declaration: &VariableDeclaration, modifier: Option<&Modifier>) {
+        let offset = self.start(Some(declaration.identifier.line_info.offset), false);
+        let mut line = match modifier {
+            Some(modifier) => format!("{} ", modifier),
+            None => String::new(),
+        };
+        line.push_str(&variable_declaration(declaration));
+        self.write_line(&line, offset);
+    }
+
+    fn event(&mut self, event: &EventDeclaration) {
+        let offset = self.start(Some(event.identifier.line_info.offset), false);
+        let line = format!(
+            "event {}({})",
+            event.identifier.token,
+            parameters(&event.parameter_list)
+        );
+        self.write_line(&line, offset);
+    }
+
+    fn invariant(&mut self, invariant: &Specification) {
+        let offset = self.start(Some(invariant.line_info.offset), false);
+        self.write_line(&specification(invariant), offset);
+    }
+
+    /// Preconditions and postconditions follow the signature, each on a line of its own indented
+    /// once more than the function
+    fn function(&mut self, function: &FunctionDeclaration) {
+        let offset = self.start(Some(function.head.identifier.line_info.offset), true);
+        let offset = self.attributes(&function.head.attributes, offset);
+        let mut line = function_signature(&function.head);
+        let indent = INDENT.repeat(self.indent + 1);
+        for condition in &function.specifications {
+            line.push_str(&format!("\n{}{}", indent, specification(condition)));
+        }
+        self.block(&line, offset, false, function.body.is_empty(), |f| {
+            f.statements(&function.body)
+        });
+    }
+
+    fn special(&mut self, special: &SpecialDeclaration) {
+        let offset = self.start(special_offset(&special.head), true);
+        let offset = self.attributes(&special.head.attributes, offset);
+        self.block(
+            &special_signature(&special.head),
+            offset,
+            false,
+            special.body.is_empty(),
+            |f| f.statements(&special.body),
+        );
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        let opens_block = matches!(
+            statement,
+            Statement::ForStatement(_) | Statement::IfStatement(_) | Statement::DoCatchStatement(_)
+        );
+        let offset = self.start(statement_offset(statement), opens_block);
+
+        match statement {
+            Statement::ReturnStatement(statement) => match &statement.expression {
+                Some(value) => self.write_line(&format!("return {}", expression(value)), offset),
+                None => self.write_line("return", offset),
+            },
+            Statement::Expression(value) => self.write_line(&expression(value), offset),
+            Statement::BecomeStatement(statement) => self.write_line(
+                &format!("become {}", statement.state.identifier.token),
+                offset,
+            ),
+            Statement::EmitStatement(statement) => self.write_line(
+                &format!("emit {}", function_call(&statement.function_call)),
+                offset,
+            ),
+            Statement::ForStatement(statement) => {
+                let line = format!(
+                    "for {} in {}",
+                    variable_declaration(&statement.variable),
+                    expression(&statement.iterable)
+                );
+                self.block(&line, offset, false, statement.body.is_empty(), |f| {
+                    f.statements(&statement.body)
+                });
+            }
+            Statement::IfStatement(statement) => self.if_statement(statement, offset, false),
+            Statement::DoCatchStatement(statement) => {
+                self.block("do", offset, false, statement.do_body.is_empty(), |f| {
+                    f.statements(&statement.do_body)
+                });
+                let line = format!("catch is {}", expression(&statement.error));
+                self.block(&line, None, true, statement.catch_body.is_empty(), |f| {
+                    f.statements(&statement.catch_body)
+                });
+            }
+            Statement::Assertion(assertion) => self.write_line(
+                &format!("assert {}", expression(&assertion.expression)),
+                offset,
+            ),
+        }
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement, offset: Option<usize>, continuation: bool) {
+        let line = format!("if {}", expression(&statement.condition));
+        self.block(
+            &line,
+            offset,
+            continuation,
+            statement.body.is_empty(),
+            |f| f.statements(&statement.body),
+        );
+
+        match &statement.else_body[..] {
+            [] => {}
+            [Statement::IfStatement(nested)] => {
+                let line_info = nested.condition.get_line_info();
+                if line_info.line != 0 {
+                    self.cursor = self.cursor.max(line_info.offset);
+                }
+                self.append(" else");
+                self.if_statement(nested, None, true);
+            }
+            else_body => self.block("else", None, true, false, |f| f.statements(else_body)),
+        }
+    }
+}
+
+/// Pairs up the braces in the source, skipping those in comments and string literals
+fn blocks(source: &str, comments: &[Comment]) -> Vec<(usize, usize)> {
+    let mut blocks = vec![];
+    let mut open = vec![];
+    let mut comments = comments.iter().peekable();
+    let mut in_string = false;
+    let mut skip_to = 0;
+
+    for (index, c) in source.char_indices() {
+        if index < skip_to {
+            continue;
+        }
+        while comments
+            .peek()
+            .map_or(false, |c| c.line_info.offset < index)
+        {
+            comments.next();
+        }
+        if let Some(comment) = comments.peek() {
+            if comment.line_info.offset == index && !in_string {
+                skip_to = index + comment.text.len();
+                continue;
+            }
+        }
+
+        match c {
+            '"' => in_string = !in_string,
+            '{' if !in_string => {
+                open.push(blocks.len());
+                blocks.push((index, index));
+            }
+            '}' if !in_string => {
+                if let Some(block) = open.pop() {
+                    blocks[block].1 = index;
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+fn special_offset(signature: &SpecialSignatureDeclaration) -> Option<usize> {
+    signature
+        .parameters
+        .first()
+        .map(|parameter| parameter.line_info.offset)
+}
+
+fn statement_offset(statement: &Statement) -> Option<usize> {
+    let line_info = match statement {
+        Statement::ReturnStatement(statement) => statement.line_info.clone(),
+        Statement::Expression(expression) => expression.get_line_info(),
+        Statement::BecomeStatement(statement) => statement.line_info.clone(),
+        Statement::EmitStatement(statement) => statement.function_call.identifier.line_info.clone(),
+        Statement::ForStatement(statement) => statement.variable.identifier.line_info.clone(),
+        Statement::IfStatement(statement) => statement.condition.get_line_info(),
+        Statement::DoCatchStatement(_) => LineInfo::default(),
+        Statement::Assertion(assertion) => assertion.line_info.clone(),
+    };
+    if line_info.line == 0 {
+        None
+    } else {
+        Some(line_info.offset)
+    }
+}
+
+fn type_states(type_states: &[TypeState]) -> String {
+    let names: Vec<&str> = type_states
+        .iter()
+        .map(|state| state.identifier.token.as_str())
+        .collect();
+    names.join(", ")
+}
+
+fn conformances(conformances: &[Conformance]) -> String {
+    if conformances.is_empty() {
+        return String::new();
+    }
+    let names: Vec<&str> = conformances
+        .iter()
+        .map(|conformance| conformance.identifier.token.as_str())
+        .collect();
+    format!(": {}", names.join(", "))
+}
+
+fn function_signature(signature: &FunctionSignatureDeclaration) -> String {
+    let mut line = modifiers(&signature.modifiers);
+    line.push_str(&format!(
+        "func {}({})",
+        signature.identifier.token,
+        parameters(&signature.parameters)
+    ));
+    if let Some(result) = &signature.result_type {
+        line.push_str(&format!(" -> {}", type_name(result)));
+    }
+    line.push_str(&mutates(&signature.mutates));
+    line
+}
+
+fn special_signature(signature: &SpecialSignatureDeclaration) -> String {
+    let mut line = modifiers(&signature.modifiers);
+    line.push_str(&format!(
+        "{}({})",
+        signature.special_token,
+        parameters(&signature.parameters)
+    ));
+    line.push_str(&mutates(&signature.mutates));
+    line
+}
+
+fn modifiers(modifiers: &[Modifier]) -> String {
+    let mut line = String::new();
+    for modifier in modifiers {
+        line.push_str(&format!("{} ", modifier));
+    }
+    line
+}
+
+fn mutates(mutates: &[Identifier]) -> String {
+    if mutates.is_empty() {
+        return String::new();
+    }
+    let names: Vec<String> = mutates
+        .iter()
+        .map(|identifier| match &identifier.enclosing_type {
+            Some(enclosing_type) => format!("{}.{}", enclosing_type, identifier.token),
+            None => identifier.token.clone(),
+        })
+        .collect();
+    format!(" mutates ({})", names.join(", "))
+}
+
+fn parameters(parameters: &[Parameter]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|parameter| {
+            let mut parameter_text = format!(
+                "{}: {}",
+                parameter.identifier.token,
+                type_name(&parameter.type_assignment)
+            );
+            if let Some(default) = &parameter.expression {
+                parameter_text.push_str(&format!(" = {}", expression(default)));
+            }
+            parameter_text
+        })
+        .collect();
+    parameters.join(", ")
+}
+
+fn variable_declaration(declaration: &VariableDeclaration) -> String {
+    let mut text = format!(
+        "{} {}: {}",
+        declaration.declaration_token.as_deref().unwrap_or("var"),
+        declaration.identifier.token,
+        type_name(&declaration.variable_type)
+    );
+    if let Some(value) = &declaration.expression {
+        text.push_str(&format!(" = {}", expression(value)));
+    }
+    text
+}
+
+fn type_name(t: &Type) -> String {
+    match t {
+        Type::InoutType(inout) => format!("inout {}", type_name(&inout.key_type)),
+        Type::ArrayType(array) => format!("[{}]", type_name(&array.key_type)),
+        Type::FixedSizedArrayType(array) => {
+            format!("{}[{}]", type_name(&array.key_type), array.size)
+        }
+        Type::DictionaryType(dictionary) => format!(
+            "[{}: {}]",
+            type_name(&dictionary.key_type),
+            type_name(&dictionary.value_type)
+        ),
+        Type::Solidity(solidity) => format!("{:?}", solidity).to_lowercase(),
+        _ => t.to_string(),
+    }
+}
+
+fn function_call(call: &FunctionCall) -> String {
+    let arguments: Vec<String> = call
+        .arguments
+        .iter()
+        .map(|argument| match &argument.identifier {
+            Some(label) => format!("{}: {}", label.token, expression(&argument.expression)),
+            None => expression(&argument.expression),
+        })
+        .collect();
+    format!("{}({})", call.identifier.token, arguments.join(", "))
+}
+
+fn specification(specification: &Specification) -> String {
+    format!(
+        "{}({})",
+        specification.kind.keyword(),
+        expression(&specification.expression)
+    )
+}
+
+/// The parser nests binary expressions by precedence, and brackets are kept as expressions of
+/// their own, so operands need no further brackets
+fn expression(expression: &Expression) -> String {
+    match expression {
+        Expression::Identifier(identifier) => identifier.token.clone(),
+        Expression::BinaryExpression(binary) => {
+            let lhs = self::expression(&binary.lhs_expression);
+            let rhs = self::expression(&binary.rhs_expression);
+            match binary.op {
+                BinOp::Dot => format!("{}.{}", lhs, rhs),
+                // `**` is displayed as `^` in messages, but is written `**`
+                BinOp::Power => format!("{} ** {}", lhs, rhs),
+                _ => format!("{} {} {}", lhs, binary.op, rhs),
+            }
+        }
+        Expression::InoutExpression(inout) => format!("&{}", self::expression(&inout.expression)),
+        Expression::ExternalCall(call) => format!(
+            "call {}",
+            self::expression(&Expression::BinaryExpression(call.function_call.clone()))
+        ),
+        Expression::FunctionCall(call) => function_call(call),
+        Expression::VariableDeclaration(declaration) => variable_declaration(declaration),
+        Expression::BracketedExpression(bracketed) => {
+            format!("({})", self::expression(&bracketed.expression))
+        }
+        Expression::AttemptExpression(attempt) => {
+            format!(
+                "try{} {}",
+                attempt.kind,
+                function_call(&attempt.function_call)
+            )
+        }
+        Expression::Literal(literal) => match literal {
+            Literal::BooleanLiteral(value) => value.to_string(),
+            Literal::AddressLiteral(address) => address.clone(),
+            Literal::StringLiteral(string) => format!("\"{}\"", string),
+            Literal::U8Literal(value) => value.to_string(),
+            Literal::IntLiteral(value) => value.to_string(),
+            // Debug formatting keeps the decimal point of whole numbers
+            Literal::FloatLiteral(value) => format!("{:?}", value),
+        },
+        Expression::ArrayLiteral(array) => {
+            let elements: Vec<String> = array.elements.iter().map(self::expression).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        Expression::DictionaryLiteral(dictionary) if dictionary.elements.is_empty() => {
+            "[:]".to_string()
+        }
+        Expression::DictionaryLiteral(dictionary) => {
+            let elements: Vec<String> = dictionary
+                .elements
+                .iter()
+                .map(|(key, value)| {
+                    format!("{}: {}", self::expression(key), self::expression(value))
+                })
+                .collect();
+            format!("[{}]", elements.join(", "))
+        }
+        Expression::SelfExpression => Identifier::SELF.to_string(),
+        Expression::SubscriptExpression(subscript) => format!(
+            "{}[{}]",
+            subscript.base_expression.token,
+            self::expression(&subscript.index_expression)
+        ),
+        Expression::RangeExpression(range) => format!(
+            "({}{}{})",
+            self::expression(&range.start_expression),
+            range.op,
+            self::expression(&range.end_expression)
+        ),
+        Expression::RawAssembly(assembly, _) => assembly.clone(),
+        Expression::CastExpression(cast) => format!(
+            "cast {} to {}",
+            self::expression(&cast.expression),
+            type_name(&cast.cast_type)
+        ),
+        Expression::OldExpression(old) => format!("old({})", self::expression(&old.expression)),
+        Expression::Sequence(expressions) => {
+            let expressions: Vec<String> = expressions.iter().map(self::expression).collect();
+            expressions.join(" ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compile, format, target, Options, Source};
+
+    #[test]
+    fn test_format() {
+        let source = "// Counts
+contract Counter (Counting, Stopped) {
+  var count: Int = 0 // Starts at zero
+
+
+  event Counted(by: Int)
+}
+Counter @(Counting) :: caller <- (any) {
+  public init() {}
+  @payable
+  public func add(value: Int=1) -> Int mutates (count) {
+    count += value
+    if value == 0 { return count } else if value > 10 {
+      // Too many at once
+      become Stopped
+    }
+    else {
+emit Counted(by: value)
+    }
+
+    // Done
+    return count
+  }
+}
+";
+        assert_eq!(
+            format(source).unwrap(),
+            "// Counts
+contract Counter (Counting, Stopped) {
+    var count: Int = 0  // Starts at zero
+
+    event Counted(by: Int)
+}
+
+Counter @(Counting) :: caller <- (any) {
+    public init() {}
+    @payable
+    public func add(value: Int = 1) -> Int mutates (count) {
+        count += value
+        if value == 0 {
+            return count
+        } else if value > 10 {
+            // Too many at once
+            become Stopped
+        } else {
+            emit Counted(by: value)
+        }
+
+        // Done
+        return count
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_format_behaviour_tests() {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/behaviour_tests");
+        for entry in std::fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "flint")
+            {
+                continue;
+            }
+            let name = path.to_string_lossy().into_owned();
+            let source = std::fs::read_to_string(&path).unwrap();
+
+            let formatted = format(&source).unwrap();
+            assert_eq!(format(&formatted).unwrap(), formatted, "{}", name);
+            assert_eq!(
+                formatted.matches("//").count(),
+                source.matches("//").count(),
+                "{}",
+                name
+            );
+
+            let compiles = |source: &str| {
+                compile(
+                    &[Source::new(&*name, source)],
+                    target("libra").unwrap(),
+                    &Options::default(),
+                )
+                .is_ok()
+            };
+            assert_eq!(compiles(&formatted), compiles(&source), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! Runs the programs of `tests/behaviour_tests` with scripted transactions, including those which
+//! should revert, which the eWASM runtime tests cannot check
+
+use crate::interpreter::{Failure, Interpreter, Transaction, Value};
+use crate::{target, Source};
+
+const CALLER: &str = "0x1";
+
+fn load(file_name: &str) -> Interpreter {
+    let path = format!(
+        "{}/tests/behaviour_tests/{}.flint",
+        env!("CARGO_MANIFEST_DIR"),
+        file_name
+    );
+    let text = std::fs::read_to_string(&path).expect("Unable to read the file");
+    Interpreter::new(&[Source::new(path, text)], target("libra").unwrap())
+        .unwrap_or_else(|diagnostics| panic!("Could not check {}: {:?}", file_name, diagnostics))
+}
+
+fn deploy(file_name: &str, contract: &str, arguments: &[Value]) -> Interpreter {
+    let mut interpreter = load(file_name);
+    interpreter
+        .deploy(contract, &Transaction::new(CALLER), arguments)
+        .unwrap();
+    interpreter
+}
+
+fn call(interpreter: &mut Interpreter, function: &str, arguments: &[Value]) -> Value {
+    interpreter
+        .call(&Transaction::new(CALLER), function, arguments)
+        .unwrap_or_else(|failure| panic!("`{}` failed: {}", function, failure))
+}
+
+fn reverts(interpreter: &mut Interpreter, caller: &str, function: &str, arguments: &[Value]) {
+    match interpreter.call(&Transaction::new(caller), function, arguments) {
+        Err(Failure::Revert(_)) => {}
+        result => panic!("`{}` should revert, not give {:?}", function, result),
+    }
+}
+
+fn int(i: u64) -> Value {
+    Value::Int(i)
+}
+
+fn address(address: &str) -> Value {
+    Value::address(address)
+}
+
+#[test]
+fn test_counter() {
+    let mut counter = deploy("counter", "Counter", &[]);
+    assert_eq!(call(&mut counter, "getValue", &[]), int(0));
+    call(&mut counter, "increment", &[]);
+    assert_eq!(call(&mut counter, "getValue", &[]), int(1));
+    call(&mut counter, "increment", &[]);
+    assert_eq!(call(&mut counter, "getValue", &[]), int(2));
+    call(&mut counter, "decrement", &[]);
+    assert_eq!(call(&mut counter, "getValue", &[]), int(1));
+    call(&mut counter, "decrement", &[]);
+    reverts(&mut counter, CALLER, "decrement", &[]);
+    assert_eq!(counter.property("value"), Some(&int(0)));
+}
+
+#[test]
+fn test_counter_default_parameters() {
+    let mut counter = deploy("counter_default_params", "Counter", &[]);
+    call(&mut counter, "record", &[]);
+    call(&mut counter, "record", &[]);
+    assert_eq!(
+        counter.property("count").unwrap().field("value"),
+        Some(&int(1))
+    );
+    call(&mut counter, "cancel", &[]);
+    call(&mut counter, "cancel", &[]);
+    assert_eq!(
+        counter.property("count").unwrap().field("value"),
+        Some(&int(0))
+    );
+}
+
+#[test]
+fn test_factorial() {
+    let mut factorial = deploy("factorial", "Factorial", &[]);
+    assert_eq!(call(&mut factorial, "getValue", &[]), int(0));
+    for &(n, expected) in &[(1, 1), (2, 2), (10, 3_628_800)] {
+        call(&mut factorial, "calculate", &[int(n)]);
+        assert_eq!(call(&mut factorial, "getValue", &[]), int(expected));
+    }
+    reverts(&mut factorial, CALLER, "calculate", &[int(30)]);
+}
+
+#[test]
+fn test_shapes() {
+    let mut shapes = deploy("shapes", "Shapes", &[int(10)]);
+    assert_eq!(call(&mut shapes, "area", &[]), int(200));
+    assert_eq!(call(&mut shapes, "semiPerimeter", &[]), int(30));
+    assert_eq!(call(&mut shapes, "perimeter", &[]), int(60));
+    assert_eq!(
+        call(&mut shapes, "smallerWidth", &[int(21)]),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        call(&mut shapes, "smallerWidth", &[int(19)]),
+        Value::Bool(false)
+    );
+}
+
+#[test]
+fn test_rectangle() {
+    let mut rectangle = deploy("rectangle", "Rectangle", &[int(2), int(3)]);
+    assert_eq!(call(&mut rectangle, "area", &[]), int(6));
+    call(&mut rectangle, "changeWidth", &[int(5)]);
+    assert_eq!(call(&mut rectangle, "area", &[]), int(15));
+    assert_eq!(
+        call(&mut rectangle, "changeArea", &[int(4), int(4)]),
+        int(16)
+    );
+}
+
+#[test]
+fn test_assert() {
+    let mut assert = deploy("assert", "Assert", &[]);
+    call(&mut assert, "shouldNotCrash", &[]);
+    reverts(&mut assert, CALLER, "shouldCrash", &[]);
+}
+
+#[test]
+fn test_traffic_lights() {
+    let mut lights = deploy("traffic_lights", "TrafficLights", &[]);
+    assert_eq!(lights.state(), Some("Red"));
+    reverts(&mut lights, CALLER, "moveToGreen", &[]);
+    call(&mut lights, "moveToAmber", &[]);
+    assert_eq!(call(&mut lights, "getSignal", &[]), int(1));
+    assert_eq!(lights.state(), Some("Amber"));
+    reverts(&mut lights, CALLER, "moveToAmber", &[]);
+    call(&mut lights, "moveToGreen", &[]);
+    assert_eq!(call(&mut lights, "getSignal", &[]), int(2));
+    call(&mut lights, "moveToAmber", &[]);
+    call(&mut lights, "moveToRed", &[]);
+    assert_eq!(call(&mut lights, "getSignal", &[]), int(0));
+    assert_eq!(lights.state(), Some("Red"));
+}
+
+#[test]
+fn test_operators() {
+    let mut operators = deploy("operators", "Operators", &[]);
+    let mut binary = |function: &str, a: Value, b: Value| call(&mut operators, function, &[a, b]);
+    assert_eq!(binary("lt", int(5), int(10)), Value::Bool(true));
+    assert_eq!(binary("ge", int(5), int(10)), Value::Bool(false));
+    assert_eq!(binary("plus", int(10), int(5)), int(15));
+    assert_eq!(binary("minus", int(10), int(5)), int(5));
+    assert_eq!(binary("times", int(10), int(5)), int(50));
+    assert_eq!(binary("divide", int(10), int(5)), int(2));
+    assert_eq!(binary("divide", int(11), int(5)), int(2));
+    assert_eq!(binary("remainder", int(12), int(5)), int(2));
+    assert_eq!(binary("power", int(2), int(10)), int(1024));
+    assert_eq!(binary("notEqual", int(2), int(2)), Value::Bool(false));
+    assert_eq!(
+        binary("orOp", Value::Bool(false), Value::Bool(true)),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        binary("andOp", Value::Bool(false), Value::Bool(true)),
+        Value::Bool(false)
+    );
+    assert_eq!(call(&mut operators, "getTest", &[]), Value::Bool(true));
+    reverts(&mut operators, CALLER, "divide", &[int(1), int(0)]);
+    reverts(&mut operators, CALLER, "minus", &[int(1), int(2)]);
+    reverts(&mut operators, CALLER, "power", &[int(2), int(64)]);
+}
+
+#[test]
+fn test_inits() {
+    let mut inits = deploy("inits", "Inits", &[int(10), Value::Bool(true)]);
+    assert_eq!(call(&mut inits, "getA", &[]), int(10));
+    assert_eq!(call(&mut inits, "getB", &[]), address("0x1000"));
+    assert_eq!(call(&mut inits, "getS", &[]), Value::Bool(true));
+    assert_eq!(
+        call(&mut inits, "getZ", &[]),
+        address("0x72981077347248757091884308802679")
+    );
+    call(&mut inits, "setT", &[int(5), Value::Bool(true)]);
+    assert_eq!(call(&mut inits, "getTx", &[]), int(5));
+    assert_eq!(call(&mut inits, "getTy", &[]), Value::Bool(true));
+    assert_eq!(call(&mut inits, "getTs", &[]), Value::Bool(false));
+}
+
+#[test]
+fn test_memory() {
+    let mut memory = deploy("memory", "Memory", &[]);
+    assert_eq!(call(&mut memory, "getSa", &[]), int(0));
+    assert_eq!(call(&mut memory, "getSs", &[]), address("0x0"));
+    assert_eq!(call(&mut memory, "getVx", &[]), int(1));
+    call(&mut memory, "setS", &[int(1), address("0x1")]);
+    assert_eq!(call(&mut memory, "getSa", &[]), int(2));
+    assert_eq!(call(&mut memory, "getSs", &[]), address("0x1"));
+    call(&mut memory, "setV1", &[int(2)]);
+    assert_eq!(call(&mut memory, "getVx", &[]), int(2));
+    call(&mut memory, "setV2", &[int(2)]);
+    assert_eq!(call(&mut memory, "getVx", &[]), int(3));
+    call(&mut memory, "setV3", &[Value::Bool(true), int(2), int(3)]);
+    assert_eq!(call(&mut memory, "getVx", &[]), int(3));
+    call(&mut memory, "setV3", &[Value::Bool(false), int(2), int(3)]);
+    assert_eq!(call(&mut memory, "getVx", &[]), int(4));
+}
+
+#[test]
+fn test_rock_paper_scissors() {
+    let mut game = deploy("rockpaperscissors", "RockPaperScissors", &[]);
+    assert_eq!(call(&mut game, "getWinner", &[]), Value::Bool(false));
+    for left in 0..3 {
+        for right in 0..3 {
+            call(&mut game, "leftWins", &[int(left), int(right)]);
+            let expected =
+                (left, right) == (0, 2) || (left, right) == (1, 0) || (left, right) == (2, 1);
+            assert_eq!(call(&mut game, "getWinner", &[]), Value::Bool(expected));
+        }
+    }
+}
+
+#[test]
+fn test_public_and_visible() {
+    let mut contract = deploy("public_and_visible", "MyContract", &[]);
+    assert_eq!(call(&mut contract, "getValue", &[]), int(0));
+    assert_eq!(call(&mut contract, "getOtherValue", &[]), int(0));
+    call(&mut contract, "setOtherValue", &[int(9)]);
+    assert_eq!(call(&mut contract, "getOtherValue", &[]), int(9));
+    reverts(&mut contract, CALLER, "setValue", &[int(9)]);
+}
+
+#[test]
+fn test_typestates_counter() {
+    let mut counter = deploy("typestates_counter", "Counter", &[]);
+    assert_eq!(counter.state(), Some("Unset"));
+    reverts(&mut counter, CALLER, "reset", &[]);
+    call(&mut counter, "increment", &[int(1)]);
+    assert_eq!(call(&mut counter, "getCount", &[]), int(1));
+    call(&mut counter, "reset", &[]);
+    assert_eq!(call(&mut counter, "getCount", &[]), int(0));
+    reverts(&mut counter, CALLER, "reset", &[]);
+}
+
+#[test]
+fn test_property_modification() {
+    let mut contract = deploy("property_modification", "PropertyModification", &[]);
+    assert_eq!(call(&mut contract, "getU", &[]), int(2));
+    assert_eq!(call(&mut contract, "getVx", &[]), int(4));
+    call(&mut contract, "v", &[]);
+    assert_eq!(call(&mut contract, "getVx", &[]), int(4));
+}
+
+#[test]
+fn test_structs() {
+    let mut c = deploy("structs", "C", &[]);
+    assert_eq!(call(&mut c, "getAx", &[]), int(0));
+    call(&mut c, "setAx", &[int(10)]);
+    assert_eq!(call(&mut c, "getAx", &[]), int(10));
+    assert_eq!(call(&mut c, "getAy", &[]), Value::Bool(false));
+    call(&mut c, "setAy", &[Value::Bool(true)]);
+    assert_eq!(call(&mut c, "getAy", &[]), Value::Bool(true));
+
+    assert_eq!(call(&mut c, "getBxx", &[]), int(0));
+    call(&mut c, "setBxx", &[int(0)]);
+    assert_eq!(call(&mut c, "getBxx", &[]), int(0));
+    assert_eq!(call(&mut c, "getBxx2", &[]), int(0));
+    assert_eq!(call(&mut c, "getBxx3", &[]), int(256));
+    call(&mut c, "setBxx3", &[int(5)]);
+    assert_eq!(call(&mut c, "getBxx", &[]), int(5));
+
+    assert_eq!(call(&mut c, "getCxx", &[]), int(0));
+    call(&mut c, "setCxx", &[int(10)]);
+    assert_eq!(call(&mut c, "getCxx", &[]), int(10));
+    call(&mut c, "setCxx2", &[int(5)]);
+    assert_eq!(call(&mut c, "getCxx", &[]), int(5));
+
+    assert_eq!(call(&mut c, "getBxy", &[]), Value::Bool(false));
+    call(&mut c, "setBxy", &[Value::Bool(true)]);
+    assert_eq!(call(&mut c, "getBxy", &[]), Value::Bool(true));
+    assert_eq!(call(&mut c, "getBy", &[]), int(0));
+    call(&mut c, "setBy", &[int(5)]);
+    assert_eq!(call(&mut c, "getBy", &[]), int(5));
+
+    assert_eq!(call(&mut c, "getSize", &[]), int(0));
+    assert_eq!(call(&mut c, "get", &[int(0)]), int(0));
+    call(&mut c, "append", &[int(5)]);
+    assert_eq!(call(&mut c, "getSize", &[]), int(1));
+    assert_eq!(call(&mut c, "get", &[int(0)]), int(5));
+    assert_eq!(call(&mut c, "getD", &[]), int(5));
+    assert_eq!(call(&mut c, "getE", &[]), Value::Bool(true));
+}
+
+#[test]
+fn test_caller_protections_counter() {
+    let owner = "0x10000000000000000000000000000000";
+    let mut counter = deploy("callerprotections_counter", "Counter2", &[]);
+    assert_eq!(call(&mut counter, "getCount", &[]), int(0));
+    assert_eq!(call(&mut counter, "getOwner", &[]), address(owner));
+    assert_eq!(call(&mut counter, "getFriend", &[]), address(CALLER));
+    reverts(&mut counter, CALLER, "increment", &[]);
+    reverts(&mut counter, "0x2", "switch", &[]);
+
+    call(&mut counter, "switch", &[]);
+    assert_eq!(call(&mut counter, "getOwner", &[]), address(CALLER));
+    assert_eq!(call(&mut counter, "getFriend", &[]), address(owner));
+    call(&mut counter, "increment", &[]);
+    assert_eq!(call(&mut counter, "getCount", &[]), int(1));
+
+    call(&mut counter, "switch", &[]);
+    assert_eq!(call(&mut counter, "getFriend", &[]), address(CALLER));
+    reverts(&mut counter, CALLER, "increment", &[]);
+}
+
+#[test]
+fn test_caller_protections_lottery() {
+    let mut lottery = deploy("callerprotections_lottery", "Lottery", &[]);
+    assert_eq!(call(&mut lottery, "getFirstPerson", &[]), address("0x0"));
+    assert_eq!(call(&mut lottery, "getSecondPerson", &[]), address("0x1"));
+    assert_eq!(call(&mut lottery, "getLastPerson", &[]), address("0x2"));
+    reverts(&mut lottery, CALLER, "outOfBounds", &[]);
+
+    // The caller is the manager, and is in both players and people
+    assert_eq!(call(&mut lottery, "getWinnings", &[]), int(1000));
+    assert_eq!(
+        call(&mut lottery, "firstAddressIsWinner", &[]),
+        Value::Bool(false)
+    );
+    call(&mut lottery, "changeAddress", &[]);
+    assert_eq!(call(&mut lottery, "getLastPerson", &[]), address("0x3"));
+    reverts(&mut lottery, CALLER, "isWinner", &[]);
+
+    // The winner is permitted by the function returning their address, and is one of the players
+    let winner = Transaction::new("0x2");
+    assert_eq!(lottery.call(&winner, "getWinnings", &[]), Ok(int(1000)));
+    assert_eq!(lottery.call(&winner, "changeAddress", &[]), Ok(Value::Void));
+    reverts(&mut lottery, "0x4", "getWinnings", &[]);
+    reverts(&mut lottery, "0x4", "changeAddress", &[]);
+}
+
+#[test]
+fn test_caller_protections_lucky() {
+    let mut lottery = deploy("callerprotections_lucky", "Lottery", &[]);
+    reverts(&mut lottery, CALLER, "getWinnings", &[]);
+    let winner = Transaction::new("0x2");
+    assert_eq!(lottery.call(&winner, "getWinnings", &[]), Ok(int(1000)));
+    assert_eq!(lottery.call(&winner, "getWinner", &[]), Ok(address("0x2")));
+    assert_eq!(lottery.call(&winner, "withdraw", &[]), Ok(int(1000)));
+}
+
+#[test]
+fn test_caller_protections_members() {
+    let mut club = deploy("callerprotections_members", "Club", &[]);
+    assert_eq!(call(&mut club, "greeting", &[]), int(2));
+    reverts(&mut club, "0x3", "greeting", &[]);
+
+    // Members are checked against the array as it is when they call, not as it was declared
+    reverts(&mut club, "0x3", "invite", &[address("0x4")]);
+    call(&mut club, "invite", &[address("0x3")]);
+    let member = Transaction::new("0x3");
+    assert_eq!(
+        club.call(&member, "invite", &[address("0x4")]),
+        Ok(Value::Void)
+    );
+
+    let founder = Transaction::new("0x2");
+    assert_eq!(
+        club.call(&founder, "replaceFounder", &[address("0x5")]),
+        Ok(Value::Void)
+    );
+    reverts(&mut club, "0x2", "replaceFounder", &[address("0x2")]);
+    let new_founder = Transaction::new("0x5");
+    assert_eq!(
+        club.call(&new_founder, "replaceFounder", &[address("0x2")]),
+        Ok(Value::Void)
+    );
+}
+
+#[test]
+fn test_dynamic_check() {
+    let mut check = deploy("dynamic_check", "DynamicCheck", &[]);
+    call(&mut check, "tryBang", &[int(3)]);
+    assert_eq!(
+        call(&mut check, "tryQuestion", &[int(2)]),
+        Value::Bool(true)
+    );
+
+    // The second owner may call the functions, but not `bar`, which only the owner may call
+    let owner = Transaction::new("0x2");
+    assert_eq!(
+        check.call(&owner, "tryQuestion", &[int(2)]),
+        Ok(Value::Bool(false))
+    );
+    reverts(&mut check, "0x2", "tryBang", &[int(3)]);
+    reverts(&mut check, "0x3", "tryQuestion", &[int(2)]);
+}
+
+#[test]
+fn test_dynamic_check_any() {
+    // Anyone may call the functions, which check the caller against `bar` when they call it
+    let mut check = deploy("dynamic_check_any", "AnyoneDynamicCheck", &[]);
+    call(&mut check, "tryBang", &[int(3)]);
+    assert_eq!(
+        check.call(&Transaction::new("0x3"), "tryQuestion", &[int(2)]),
+        Ok(Value::Bool(false))
+    );
+    reverts(&mut check, "0x3", "tryBang", &[int(3)]);
+}
+
+#[test]
+fn test_arrays() {
+    let mut arrays = deploy("arrays", "Arrays", &[]);
+    for &(index, expected) in &[(0, 1), (1, 2), (2, 3)] {
+        assert_eq!(call(&mut arrays, "get", &[int(index)]), int(expected));
+    }
+    call(&mut arrays, "set", &[int(0), int(5)]);
+    call(&mut arrays, "set", &[int(2), int(7)]);
+    assert_eq!(
+        arrays.property("nums"),
+        Some(&Value::Array(vec![int(5), int(2), int(7)]))
+    );
+    reverts(&mut arrays, CALLER, "get", &[int(3)]);
+    reverts(&mut arrays, CALLER, "set", &[int(3), int(1)]);
+}
+
+#[test]
+fn test_dynamic_arrays() {
+    let mut arrays = deploy("dynamic_arrays", "DynamicArrays", &[]);
+    call(&mut arrays, "insert", &[int(1), int(9)]);
+    call(&mut arrays, "insert", &[int(4), int(8)]);
+    assert_eq!(
+        arrays.property("nums"),
+        Some(&Value::Array(vec![int(1), int(9), int(2), int(3), int(8)]))
+    );
+    call(&mut arrays, "remove", &[int(0)]);
+    call(&mut arrays, "set", &[int(0), int(4)]);
+    assert_eq!(call(&mut arrays, "get", &[int(0)]), int(4));
+    assert_eq!(call(&mut arrays, "get", &[int(3)]), int(8));
+    reverts(&mut arrays, CALLER, "get", &[int(4)]);
+    reverts(&mut arrays, CALLER, "remove", &[int(4)]);
+    reverts(&mut arrays, CALLER, "insert", &[int(6), int(1)]);
+}
+
+#[test]
+fn test_specifications() {
+    let mut vault = deploy("specifications", "Vault", &[]);
+    call(&mut vault, "deposit", &[int(400)]);
+    call(&mut vault, "depositTwice", &[int(200)]);
+    assert_eq!(call(&mut vault, "getBalance", &[]), int(800));
+    assert_eq!(call(&mut vault, "withdraw", &[int(300)]), int(500));
+
+    // A precondition which does not hold, then the invariant, which no change may break
+    reverts(&mut vault, CALLER, "deposit", &[int(0)]);
+    reverts(&mut vault, CALLER, "deposit", &[int(600)]);
+    reverts(&mut vault, CALLER, "depositTwice", &[int(300)]);
+    reverts(&mut vault, CALLER, "overdraw", &[]);
+    assert_eq!(vault.property("balance"), Some(&int(500)));
+
+    call(&mut vault, "close", &[]);
+    assert_eq!(vault.state(), Some("Closed"));
+}
+
+#[test]
+fn test_initialiser_invariants() {
+    let mut budget = deploy("initialiser_invariants", "Budget", &[int(500), int(0)]);
+    assert_eq!(call(&mut budget, "getLimit", &[]), int(500));
+    call(&mut budget, "raise", &[int(400)]);
+    reverts(&mut budget, CALLER, "raise", &[int(200)]);
+    assert_eq!(call(&mut budget, "getLimit", &[]), int(900));
+    deploy("initialiser_invariants", "Budget", &[int(500), int(200)]);
+
+    // Each invariant is checked once the initialiser has given the properties their values
+    for arguments in &[[int(2000), int(0)], [int(100), int(200)]] {
+        let mut budget = load("initialiser_invariants");
+        let deployed = budget.deploy("Budget", &Transaction::new(CALLER), arguments);
+        assert!(matches!(deployed, Err(Failure::Revert(_))));
+    }
+}
+
+#[test]
+fn test_runtime_functions() {
+    let mut money = deploy("runtime_functions", "Money", &[]);
+    assert_eq!(call(&mut money, "balance", &[]), int(0));
+    money.set_balance(CALLER, 50);
+    assert_eq!(call(&mut money, "balance", &[]), int(50));
+}
+
+#[test]
+fn test_reverted_transactions_have_no_effect() {
+    let mut counter = deploy("callerprotections_counter", "Counter2", &[]);
+    call(&mut counter, "switch", &[]);
+    call(&mut counter, "increment", &[]);
+    reverts(&mut counter, CALLER, "getCount", &[int(1)]);
+    reverts(&mut counter, CALLER, "increment", &[int(1)]);
+    reverts(&mut counter, CALLER, "setCount", &[int(5)]);
+    assert_eq!(counter.property("count"), Some(&int(1)));
+
+    let payment = Transaction::new(CALLER).with_value(5);
+    match counter.call(&payment, "increment", &[]) {
+        Err(Failure::Revert(reason)) => assert!(reason.contains("not payable")),
+        result => panic!("Paid a function which is not payable: {:?}", result),
+    }
+    assert_eq!(counter.property("count"), Some(&int(1)));
+}
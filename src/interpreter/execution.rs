@@ -0,0 +1,842 @@
+use super::program::Callable;
+use super::{Failure, Interpreter, Outcome, Value};
+use crate::ast::*;
+use std::rc::Rc;
+
+/// Deep enough for the recursion of any sensible contract, and shallow enough not to overflow the
+/// stack of the interpreter
+const MAX_CALL_DEPTH: usize = 128;
+
+/// The locals of a call. Inout parameters are references to the places of their arguments
+#[derive(Debug, Default)]
+pub(super) struct Frame {
+    slots: Vec<Value>,
+    scopes: Vec<Vec<(String, Binding)>>,
+    /// What `self` is, the contract or a struct, which the names of properties are relative to
+    receiver: Option<Place>,
+    enclosing: String,
+    caller_binding: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Binding {
+    Slot(usize),
+    Reference(Place),
+}
+
+pub(super) enum Local {
+    Value(Value),
+    Reference(Place),
+}
+
+/// Where a value is held, so that it can be assigned to or passed as `inout`
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Place {
+    root: Root,
+    path: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Root {
+    Contract,
+    Slot(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Field(String),
+    Index(Value),
+}
+
+impl Place {
+    pub fn contract() -> Place {
+        Place {
+            root: Root::Contract,
+            path: vec![],
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Place {
+        self.then(Step::Field(name.to_string()))
+    }
+
+    fn then(&self, step: Step) -> Place {
+        let mut place = self.clone();
+        place.path.push(step);
+        place
+    }
+}
+
+enum Flow {
+    Next,
+    Return(Value),
+}
+
+fn revert<T, S: Into<String>>(reason: S) -> Outcome<T> {
+    Err(Failure::Revert(reason.into()))
+}
+
+fn unsupported<T, S: Into<String>>(reason: S) -> Outcome<T> {
+    Err(Failure::Unsupported(reason.into()))
+}
+
+impl Interpreter {
+    /// A value of the type with the default values of its properties, before any initialiser runs
+    pub(super) fn instantiate(&mut self, type_name: &str) -> Outcome<Value> {
+        let properties = match self.program.types.get(type_name) {
+            Some(definition) => definition.properties.clone(),
+            None => return unsupported(format!("`{}` is not a type", type_name)),
+        };
+
+        self.frames.push(Frame {
+            enclosing: type_name.to_string(),
+            ..Default::default()
+        });
+        let fields: Outcome<Vec<_>> = properties
+            .iter()
+            .map(|(declaration, _)| {
+                let value = match &declaration.expression {
+                    Some(expression) => self.eval(expression)?,
+                    None => self.default_value(&declaration.variable_type)?,
+                };
+                Ok((declaration.identifier.token.clone(), value))
+            })
+            .collect();
+        self.frames.pop();
+
+        Ok(Value::Struct(type_name.to_string(), fields?))
+    }
+
+    /// The value of a property or variable of the type which has not been assigned to
+    pub(super) fn default_value(&mut self, value_type: &Type) -> Outcome<Value> {
+        Ok(match value_type {
+            Type::Int => Value::Int(0),
+            Type::Bool => Value::Bool(false),
+            Type::Address => Value::address("0x0"),
+            Type::String => Value::String(String::new()),
+            Type::InoutType(inout) => self.default_value(&inout.key_type)?,
+            Type::ArrayType(_) => Value::Array(vec![]),
+            Type::FixedSizedArrayType(array) => {
+                let element = self.default_value(&array.key_type)?;
+                Value::Array(vec![element; array.size as usize])
+            }
+            Type::DictionaryType(_) => Value::Dictionary(vec![]),
+            Type::UserDefinedType(identifier) => {
+                let name = &identifier.token;
+                match self.program.enums.get(name).and_then(|cases| cases.first()) {
+                    Some((case, _)) => self.enum_case(name, &case.clone())?,
+                    None => self.instantiate(name)?,
+                }
+            }
+            _ => return unsupported(format!("Values of type {} cannot be created", value_type)),
+        })
+    }
+
+    /// Cases are their raw values, or their positions if they have none
+    fn enum_case(&mut self, enum_name: &str, case: &str) -> Outcome<Value> {
+        let cases = self.program.enums[enum_name].clone();
+        match cases.iter().position(|(name, _)| name == case) {
+            Some(index) => match &cases[index].1 {
+                Some(raw_value) => self.eval(raw_value),
+                None => Ok(Value::Int(index as u64)),
+            },
+            None => unsupported(format!("`{}` has no case `{}`", enum_name, case)),
+        }
+    }
+
+    /// Runs the function with the locals as its parameters
+    pub(super) fn invoke(
+        &mut self,
+        callable: &Rc<Callable>,
+        enclosing: &str,
+        receiver: Option<Place>,
+        locals: Vec<Local>,
+    ) -> Outcome<Value> {
+        if self.frames.len() > MAX_CALL_DEPTH {
+            return revert(format!("Calls are nested deeper than {}", MAX_CALL_DEPTH));
+        }
+
+        let mut frame = Frame {
+            receiver,
+            enclosing: enclosing.to_string(),
+            caller_binding: callable.caller_binding.clone(),
+            ..Default::default()
+        };
+        let mut parameters = vec![];
+        for (parameter, local) in callable.parameters.iter().zip(locals) {
+            let binding = match local {
+                Local::Value(value) => {
+                    frame.slots.push(value);
+                    Binding::Slot(frame.slots.len() - 1)
+                }
+                Local::Reference(place) => Binding::Reference(place),
+            };
+            parameters.push((parameter.identifier.token.clone(), binding));
+        }
+        frame.scopes.push(parameters);
+
+        self.frames.push(frame);
+        let flow = self.execute(&callable.body);
+        self.frames.pop();
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            Flow::Next => Ok(Value::Void),
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn execute(&mut self, statements: &[Statement]) -> Outcome<Flow> {
+        self.frame().scopes.push(vec![]);
+        let flow = statements
+            .iter()
+            .map(|statement| self.statement(statement))
+            .find(|flow| !matches!(flow, Ok(Flow::Next)))
+            .unwrap_or(Ok(Flow::Next));
+        self.frame().scopes.pop();
+        flow
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Outcome<Flow> {
+        match statement {
+            Statement::Expression(expression) => {
+                self.eval(expression)?;
+            }
+            Statement::ReturnStatement(ReturnStatement { expression, .. }) => {
+                let value = match expression {
+                    Some(expression) => self.eval(expression)?,
+                    None => Value::Void,
+                };
+                return Ok(Flow::Return(value));
+            }
+            Statement::BecomeStatement(become_statement) => match &mut self.contract {
+                Some(contract) => {
+                    contract.state = Some(become_statement.state.identifier.token.clone())
+                }
+                None => return unsupported("`become` outside a contract"),
+            },
+            Statement::EmitStatement(emit) => {
+                let arguments: Outcome<Vec<_>> = emit
+                    .function_call
+                    .arguments
+                    .iter()
+                    .map(|argument| self.eval(&argument.expression))
+                    .collect();
+                self.events.push(super::Event {
+                    name: emit.function_call.identifier.token.clone(),
+                    arguments: arguments?,
+                });
+            }
+            Statement::ForStatement(for_statement) => {
+                let elements = match self.eval(&for_statement.iterable)? {
+                    Value::Array(elements) => elements,
+                    Value::Dictionary(entries) => entries.into_iter().map(|(_, v)| v).collect(),
+                    value => return unsupported(format!("Cannot iterate over {}", value)),
+                };
+                for element in elements {
+                    self.frame().scopes.push(vec![]);
+                    self.declare(&for_statement.variable.identifier.token, element);
+                    let flow = self.execute(&for_statement.body);
+                    self.frame().scopes.pop();
+                    if let Flow::Return(value) = flow? {
+                        return Ok(Flow::Return(value));
+                    }
+                }
+            }
+            Statement::IfStatement(if_statement) => {
+                return if self.condition(&if_statement.condition)? {
+                    self.execute(&if_statement.body)
+                } else {
+                    self.execute(&if_statement.else_body)
+                };
+            }
+            Statement::DoCatchStatement(do_catch) => {
+                return match self.execute(&do_catch.do_body) {
+                    Err(Failure::Revert(_)) => self.execute(&do_catch.catch_body),
+                    flow => flow,
+                };
+            }
+            Statement::Assertion(assertion) => {
+                if !self.condition(&assertion.expression)? {
+                    return revert(format!(
+                        "Assertion failed on line {}",
+                        assertion.line_info.line
+                    ));
+                }
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn condition(&mut self, expression: &Expression) -> Outcome<bool> {
+        match self.eval(expression)? {
+            Value::Bool(b) => Ok(b),
+            value => unsupported(format!("{} is not a condition", value)),
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        let frame = self.frame();
+        frame.slots.push(value);
+        let binding = Binding::Slot(frame.slots.len() - 1);
+        frame
+            .scopes
+            .last_mut()
+            .unwrap()
+            .push((name.to_string(), binding));
+    }
+
+    /// A place holding the value, for values which are used as places, such as the receivers of
+    /// function calls
+    fn temporary(&mut self, value: Value) -> Place {
+        let frame_index = self.frames.len() - 1;
+        let frame = self.frame();
+        frame.slots.push(value);
+        Place {
+            root: Root::Slot(frame_index, frame.slots.len() - 1),
+            path: vec![],
+        }
+    }
+
+    pub(super) fn eval(&mut self, expression: &Expression) -> Outcome<Value> {
+        match expression {
+            Expression::Literal(literal) => Value::from_literal(literal)
+                .map_or_else(|| unsupported("Floating point numbers"), Ok),
+            Expression::ArrayLiteral(array) => Ok(Value::Array(
+                array
+                    .elements
+                    .iter()
+                    .map(|e| self.eval(e))
+                    .collect::<Outcome<_>>()?,
+            )),
+            Expression::DictionaryLiteral(dictionary) => Ok(Value::Dictionary(
+                dictionary
+                    .elements
+                    .iter()
+                    .map(|(key, value)| Ok((self.eval(key)?, self.eval(value)?)))
+                    .collect::<Outcome<_>>()?,
+            )),
+            Expression::RangeExpression(range) => {
+                let start = self.int(&range.start_expression)?;
+                let end = self.int(&range.end_expression)?;
+                let end = if range.op == "..." { end + 1 } else { end };
+                Ok(Value::Array((start..end).map(Value::Int).collect()))
+            }
+            Expression::BracketedExpression(b) => self.eval(&b.expression),
+            Expression::InoutExpression(i) => self.eval(&i.expression),
+            Expression::CastExpression(c) => self.eval(&c.expression),
+            Expression::VariableDeclaration(declaration) => {
+                let value = match &declaration.expression {
+                    Some(expression) => self.eval(expression)?,
+                    None => self.default_value(&declaration.variable_type)?,
+                };
+                self.declare(&declaration.identifier.token, value);
+                Ok(Value::Void)
+            }
+            Expression::BinaryExpression(b) => self.binary(b),
+            Expression::FunctionCall(call) => self.call_function(call, None),
+            Expression::AttemptExpression(attempt) => self.attempt(attempt),
+            Expression::Sequence(expressions) => {
+                let mut value = Value::Void;
+                for expression in expressions {
+                    value = self.eval(expression)?;
+                }
+                Ok(value)
+            }
+            Expression::Identifier(_)
+            | Expression::SelfExpression
+            | Expression::SubscriptExpression(_) => {
+                let place = self.place(expression)?;
+                self.read(&place)
+            }
+            Expression::ExternalCall(_) => unsupported("Calls to other contracts"),
+            Expression::RawAssembly(_, _) => unsupported("Assembly"),
+            Expression::OldExpression(_) => unsupported("`old` outside a postcondition"),
+        }
+    }
+
+    fn int(&mut self, expression: &Expression) -> Outcome<u64> {
+        match self.eval(expression)? {
+            Value::Int(i) => Ok(i),
+            value => unsupported(format!("{} is not an Int", value)),
+        }
+    }
+
+    fn binary(&mut self, b: &BinaryExpression) -> Outcome<Value> {
+        let (lhs, rhs) = (&*b.lhs_expression, &*b.rhs_expression);
+        match &b.op {
+            BinOp::Dot => {
+                if let Expression::FunctionCall(call) = rhs {
+                    let receiver = self.place(lhs)?;
+                    return self.call_function(call, Some(receiver));
+                }
+                let place = self.member(b)?;
+                self.read(&place)
+            }
+            BinOp::Equal => {
+                let value = self.eval(rhs)?;
+                if let Expression::VariableDeclaration(declaration) = lhs {
+                    self.declare(&declaration.identifier.token, value);
+                } else {
+                    let place = self.place(lhs)?;
+                    self.write(&place, value)?;
+                }
+                Ok(Value::Void)
+            }
+            op if op.is_assignment_shorthand() => {
+                let value = self.eval(rhs)?;
+                let place = self.place(lhs)?;
+                let current = self.read(&place)?;
+                let value = arithmetic(&op.get_assignment_shorthand(), current, value)?;
+                self.write(&place, value)?;
+                Ok(Value::Void)
+            }
+            BinOp::And => Ok(Value::Bool(self.condition(lhs)? && self.condition(rhs)?)),
+            BinOp::Or => Ok(Value::Bool(self.condition(lhs)? || self.condition(rhs)?)),
+            op => {
+                let (lhs, rhs) = (self.eval(lhs)?, self.eval(rhs)?);
+                arithmetic(op, lhs, rhs)
+            }
+        }
+    }
+
+    /// The place an expression refers to. Expressions which are not variables, properties or
+    /// elements are evaluated into temporary places
+    fn place(&mut self, expression: &Expression) -> Outcome<Place> {
+        match expression {
+            Expression::Identifier(identifier) => self.identifier(identifier),
+            Expression::SelfExpression => match &self.frame().receiver {
+                Some(receiver) => Ok(receiver.clone()),
+                None => unsupported("`self` outside a type"),
+            },
+            Expression::BracketedExpression(b) => self.place(&b.expression),
+            Expression::InoutExpression(i) => self.place(&i.expression),
+            Expression::SubscriptExpression(subscript) => {
+                let base = self.identifier(&subscript.base_expression)?;
+                let index = self.eval(&subscript.index_expression)?;
+                Ok(base.then(Step::Index(index)))
+            }
+            Expression::BinaryExpression(b) if b.op == BinOp::Dot => self.member(b),
+            _ => {
+                let value = self.eval(expression)?;
+                Ok(self.temporary(value))
+            }
+        }
+    }
+
+    /// The place of a property, element or enumeration case, or a temporary place holding the result
+    /// of a function call
+    fn member(&mut self, b: &BinaryExpression) -> Outcome<Place> {
+        let (lhs, rhs) = (&*b.lhs_expression, &*b.rhs_expression);
+        if let Expression::Identifier(enum_name) = lhs {
+            if self.is_enum(&enum_name.token) {
+                if let Expression::Identifier(case) = rhs {
+                    let value = self.enum_case(&enum_name.token, &case.token)?;
+                    return Ok(self.temporary(value));
+                }
+            }
+        }
+
+        let base = self.place(lhs)?;
+        match rhs {
+            Expression::Identifier(property) => Ok(base.field(&property.token)),
+            Expression::SubscriptExpression(subscript) => {
+                let index = self.eval(&subscript.index_expression)?;
+                Ok(base
+                    .field(&subscript.base_expression.token)
+                    .then(Step::Index(index)))
+            }
+            Expression::FunctionCall(call) => {
+                let value = self.call_function(call, Some(base))?;
+                Ok(self.temporary(value))
+            }
+            _ => unsupported(format!("Cannot access {:?}", rhs)),
+        }
+    }
+
+    fn is_enum(&self, name: &str) -> bool {
+        self.program.enums.contains_key(name) && !self.is_local(name)
+    }
+
+    fn is_local(&self, name: &str) -> bool {
+        let frame = self.frames.last().unwrap();
+        frame
+            .scopes
+            .iter()
+            .flatten()
+            .any(|(local, _)| local == name)
+    }
+
+    /// Names are locals, then the caller binding, then properties of `self`, unless they have been
+    /// resolved as properties, which locals do not shadow
+    fn identifier(&mut self, identifier: &Identifier) -> Outcome<Place> {
+        let name = &identifier.token;
+        let frame_index = self.frames.len() - 1;
+        let frame = &self.frames[frame_index];
+
+        if let Some(receiver) = &frame.receiver {
+            if identifier.enclosing_type.as_ref() == Some(&frame.enclosing)
+                && self.program.has_property(&frame.enclosing, name)
+            {
+                return Ok(receiver.field(name));
+            }
+        }
+
+        let binding = frame
+            .scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(local, _)| local == name)
+            .map(|(_, binding)| binding.clone());
+        match binding {
+            Some(Binding::Slot(slot)) => Ok(Place {
+                root: Root::Slot(frame_index, slot),
+                path: vec![],
+            }),
+            Some(Binding::Reference(place)) => Ok(place),
+            None if frame.caller_binding.as_ref() == Some(name) => {
+                let caller = Value::Address(self.transaction.caller.clone());
+                Ok(self.temporary(caller))
+            }
+            None => match &frame.receiver {
+                Some(receiver) if self.program.has_property(&frame.enclosing, name) => {
+                    Ok(receiver.field(name))
+                }
+                _ => unsupported(format!("`{}` is not in scope", name)),
+            },
+        }
+    }
+
+    pub(super) fn read(&mut self, place: &Place) -> Outcome<Value> {
+        self.value_mut(place.root, &place.path)
+            .map(|value| value.clone())
+    }
+
+    /// Writes the value to the place. Assigning to a key which is not in a dictionary adds it
+    pub(super) fn write(&mut self, place: &Place, value: Value) -> Outcome<()> {
+        match place.path.split_last() {
+            Some((Step::Index(key), path)) => {
+                if let Value::Dictionary(entries) = self.value_mut(place.root, path)? {
+                    match entries.iter_mut().find(|(k, _)| k == key) {
+                        Some(entry) => entry.1 = value,
+                        None => entries.push((key.clone(), value)),
+                    }
+                    return Ok(());
+                }
+            }
+            Some(_) => {}
+            None => {
+                *self.value_mut(place.root, &[])? = value;
+                return Ok(());
+            }
+        }
+        *self.value_mut(place.root, &place.path)? = value;
+        Ok(())
+    }
+
+    fn value_mut(&mut self, root: Root, path: &[Step]) -> Outcome<&mut Value> {
+        let mut value = match root {
+            Root::Contract => match &mut self.contract {
+                Some(contract) => &mut contract.properties,
+                None => return unsupported("Contract properties before deployment"),
+            },
+            Root::Slot(frame, slot) => &mut self.frames[frame].slots[slot],
+        };
+
+        for step in path {
+            value = match (value, step) {
+                (Value::Struct(name, fields), Step::Field(field)) => {
+                    let name = name.clone();
+                    match fields.iter_mut().find(|(f, _)| f == field) {
+                        Some((_, value)) => value,
+                        None => return unsupported(format!("`{}` has no `{}`", name, field)),
+                    }
+                }
+                (Value::Array(elements), Step::Index(Value::Int(index))) => {
+                    let length = elements.len();
+                    match elements.get_mut(*index as usize) {
+                        Some(element) => element,
+                        None => {
+                            return revert(format!(
+                                "Index {} is out of bounds of an array of length {}",
+                                index, length
+                            ))
+                        }
+                    }
+                }
+                (Value::Dictionary(entries), Step::Index(key)) => {
+                    match entries.iter_mut().find(|(k, _)| k == key) {
+                        Some((_, value)) => value,
+                        None => return revert(format!("Key {} is not in the dictionary", key)),
+                    }
+                }
+                (value, step) => {
+                    return unsupported(format!("Cannot access {:?} of {}", step, value))
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    fn type_of(&mut self, place: &Place) -> Outcome<String> {
+        match self.value_mut(place.root, &place.path)? {
+            Value::Struct(name, _) => Ok(name.clone()),
+            value => unsupported(format!("{} has no functions", value)),
+        }
+    }
+
+    /// Calls a function of the receiver, or of `self` if there is none. Calls of types run their
+    /// initialisers, and functions of the runtime are built in
+    fn call_function(&mut self, call: &FunctionCall, receiver: Option<Place>) -> Outcome<Value> {
+        let name = &call.identifier.token;
+        let labels = || {
+            call.arguments
+                .iter()
+                .map(|a| a.identifier.as_ref().map(|i| &*i.token))
+        };
+
+        let (enclosing, receiver) = match receiver {
+            Some(receiver) => (self.type_of(&receiver)?, Some(receiver)),
+            None => {
+                if name.starts_with(crate::environment::FLINT_RUNTIME_PREFIX) {
+                    if let Some(value) = self.runtime_function(call)? {
+                        return Ok(value);
+                    }
+                }
+                if self.program.types.contains_key(name) {
+                    return self.construct(name, call);
+                }
+                let frame = self.frame();
+                (frame.enclosing.clone(), frame.receiver.clone())
+            }
+        };
+
+        let (callable, enclosing, receiver) =
+            match self.program.function(&enclosing, name, labels()) {
+                Some(callable) => (callable, enclosing, receiver),
+                None => {
+                    let global = crate::environment::FLINT_GLOBAL;
+                    match self.program.function(global, name, labels()) {
+                        Some(callable) => {
+                            let value = self.instantiate(global)?;
+                            (callable, global.to_string(), Some(self.temporary(value)))
+                        }
+                        None => {
+                            return unsupported(format!(
+                                "No function `{}` of `{}`",
+                                name, enclosing
+                            ))
+                        }
+                    }
+                }
+            };
+
+        let locals = self.arguments(&callable, &call.arguments)?;
+        self.invoke(&callable, &enclosing, receiver, locals)
+    }
+
+    fn construct(&mut self, type_name: &str, call: &FunctionCall) -> Outcome<Value> {
+        let labels = call
+            .arguments
+            .iter()
+            .map(|a| a.identifier.as_ref().map(|i| &*i.token));
+        let value = self.instantiate(type_name)?;
+        match self.program.initialiser(type_name, labels) {
+            Some(initialiser) => {
+                let locals = self.arguments(&initialiser, &call.arguments)?;
+                let receiver = self.temporary(value);
+                self.invoke(&initialiser, type_name, Some(receiver.clone()), locals)?;
+                self.read(&receiver)
+            }
+            None if call.arguments.is_empty() => Ok(value),
+            None => unsupported(format!("No initialiser of `{}` for {:?}", type_name, call)),
+        }
+    }
+
+    /// Evaluates the arguments for the parameters, those for `inout` parameters as places
+    fn arguments(
+        &mut self,
+        callable: &Callable,
+        arguments: &[FunctionArgument],
+    ) -> Outcome<Vec<Local>> {
+        let mut arguments = arguments.iter().peekable();
+        let mut locals = vec![];
+        for parameter in &callable.parameters {
+            let name = &parameter.identifier.token;
+            let argument = match arguments.peek() {
+                Some(a)
+                    if a.identifier
+                        .as_ref()
+                        .map_or(true, |label| label.token == *name) =>
+                {
+                    arguments.next()
+                }
+                _ => None,
+            };
+            let expression = match (argument, &parameter.expression) {
+                (Some(argument), _) => &argument.expression,
+                (None, Some(default)) => default,
+                (None, None) => return unsupported(format!("No argument for `{}`", name)),
+            };
+            locals.push(if parameter.is_inout() {
+                Local::Reference(self.place(expression)?)
+            } else {
+                Local::Value(self.eval(expression)?)
+            });
+        }
+        Ok(locals)
+    }
+
+    /// `try!` reverts if the caller may not call the function, `try?` is whether the function was
+    /// called
+    fn attempt(&mut self, attempt: &AttemptExpression) -> Outcome<Value> {
+        let call = &attempt.function_call;
+        let contract = match &self.contract {
+            Some(contract) => contract.name.clone(),
+            None => return unsupported("`try` outside a contract"),
+        };
+        let labels = call
+            .arguments
+            .iter()
+            .map(|a| a.identifier.as_ref().map(|i| &*i.token));
+        let callable = match self
+            .program
+            .function(&contract, &call.identifier.token, labels)
+        {
+            Some(callable) => callable,
+            None => return unsupported(format!("No function `{}` to try", call.identifier.token)),
+        };
+
+        let permitted =
+            callable.is_unprotected() || self.is_permitted(&callable.caller_protections)?;
+        match (permitted, attempt.is_soft()) {
+            (true, true) => {
+                self.call_function(call, None)?;
+                Ok(Value::Bool(true))
+            }
+            (true, false) => self.call_function(call, None),
+            (false, true) => Ok(Value::Bool(false)),
+            (false, false) => revert(format!(
+                "The caller {} is not permitted to call `{}`",
+                self.transaction.caller, callable.name
+            )),
+        }
+    }
+
+    /// The functions of the runtime which are not written in Flint, or `None` if the function is
+    /// declared in the standard library
+    fn runtime_function(&mut self, call: &FunctionCall) -> Outcome<Option<Value>> {
+        let arguments: Vec<_> = call.arguments.iter().map(|a| &a.expression).collect();
+        let address = |value: Value| match value {
+            Value::Address(address) => Ok(address),
+            value => unsupported(format!("{} is not an address", value)),
+        };
+
+        Ok(Some(match (&*call.identifier.token, &arguments[..]) {
+            ("Flint_balanceOf", [account]) | ("Flint_balanceOf_Inner", [account]) => {
+                let account = address(self.eval(account)?)?;
+                Value::Int(self.balances.get(&account).cloned().unwrap_or(0))
+            }
+            ("Flint_array_length", [array]) => match self.eval(array)? {
+                Value::Array(elements) => Value::Int(elements.len() as u64),
+                value => return unsupported(format!("{} is not an array", value)),
+            },
+            ("Flint_array_insert", [array, index, element]) => {
+                let place = self.place(array)?;
+                let index = self.int(index)? as usize;
+                let element = self.eval(element)?;
+                match self.value_mut(place.root, &place.path)? {
+                    Value::Array(elements) if index <= elements.len() => {
+                        elements.insert(index, element)
+                    }
+                    Value::Array(_) => return revert(format!("Cannot insert at index {}", index)),
+                    value => return unsupported(format!("{} is not an array", value)),
+                }
+                Value::Void
+            }
+            ("Flint_array_remove", [array, index]) => {
+                let place = self.place(array)?;
+                let index = self.int(index)? as usize;
+                match self.value_mut(place.root, &place.path)? {
+                    Value::Array(elements) if index < elements.len() => {
+                        elements.remove(index);
+                    }
+                    Value::Array(_) => return revert(format!("Cannot remove index {}", index)),
+                    value => return unsupported(format!("{} is not an array", value)),
+                }
+                Value::Void
+            }
+            ("Flint_transfer", [from, to, amount])
+            | ("Flint_transfer_Inner", [from, to, amount]) => {
+                let source = self.place(from)?;
+                let to = address(self.eval(to)?)?;
+                let amount = self.int(amount)?;
+
+                // The source is an account on Ethereum, and a coin on Libra
+                let held = match self.read(&source)? {
+                    Value::Address(account) => {
+                        let held = self.balances.get(&account).cloned().unwrap_or(0);
+                        if amount <= held {
+                            self.balances.insert(account, held - amount);
+                        }
+                        held
+                    }
+                    Value::Struct(_, _) => {
+                        let raw_value = source.field("rawValue");
+                        let held = self.read(&raw_value)?.as_int().unwrap_or(0);
+                        if amount <= held {
+                            self.write(&raw_value, Value::Int(held - amount))?;
+                        }
+                        held
+                    }
+                    value => return unsupported(format!("Cannot transfer from {}", value)),
+                };
+                if amount > held {
+                    return revert(format!("Cannot transfer {} from {}", amount, held));
+                }
+                let balance = self.balances.entry(to).or_insert(0);
+                *balance = balance
+                    .checked_add(amount)
+                    .map_or_else(|| revert("Balance overflowed"), Ok)?;
+                Value::Void
+            }
+            _ => return Ok(None),
+        }))
+    }
+}
+
+fn arithmetic(op: &BinOp, lhs: Value, rhs: Value) -> Outcome<Value> {
+    let overflow = || Failure::Revert(format!("Overflow in {} {} {}", lhs, op, rhs));
+
+    Ok(match (op, &lhs, &rhs) {
+        (BinOp::DoubleEqual, _, _) => Value::Bool(lhs == rhs),
+        (BinOp::NotEqual, _, _) => Value::Bool(lhs != rhs),
+        (op, Value::Int(l), Value::Int(r)) => match op {
+            BinOp::Plus => Value::Int(l.checked_add(*r).ok_or_else(overflow)?),
+            BinOp::Minus => Value::Int(l.checked_sub(*r).ok_or_else(overflow)?),
+            BinOp::Times => Value::Int(l.checked_mul(*r).ok_or_else(overflow)?),
+            BinOp::Power => {
+                let exponent = std::convert::TryFrom::try_from(*r).map_err(|_| overflow())?;
+                Value::Int(l.checked_pow(exponent).ok_or_else(overflow)?)
+            }
+            BinOp::OverflowingPlus => Value::Int(l.wrapping_add(*r)),
+            BinOp::OverflowingMinus => Value::Int(l.wrapping_sub(*r)),
+            BinOp::OverflowingTimes => Value::Int(l.wrapping_mul(*r)),
+            BinOp::Divide | BinOp::Percent if *r == 0 => {
+                return revert(format!("Division by zero in {} {} {}", l, op, r))
+            }
+            BinOp::Divide => Value::Int(l / r),
+            BinOp::Percent => Value::Int(l % r),
+            BinOp::LessThan => Value::Bool(l < r),
+            BinOp::LessThanOrEqual => Value::Bool(l <= r),
+            BinOp::GreaterThan => Value::Bool(l > r),
+            BinOp::GreaterThanOrEqual => Value::Bool(l >= r),
+            _ => return unsupported(format!("{} {} {}", lhs, op, rhs)),
+        },
+        _ => return unsupported(format!("{} {} {}", lhs, op, rhs)),
+    })
+}
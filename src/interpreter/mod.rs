@@ -0,0 +1,534 @@
+//! A reference interpreter, which runs checked Flint programs directly rather than compiling them.
+//! It gives the behaviour every target should have: transactions are sent to a deployed contract
+//! by a caller, checked against the type states and caller protections of the function called,
+//! and revert, undoing their changes, when an assertion or dynamic check fails. Ints are 64 bit
+//! and arithmetic which overflows reverts
+
+mod execution;
+mod program;
+mod value;
+
+#[cfg(test)]
+mod behaviour_tests;
+
+pub use self::value::Value;
+
+use self::execution::Frame;
+use self::program::{Callable, Program};
+use crate::ast::Visitable;
+use crate::diagnostic::{Diagnostic, SourceMap};
+use crate::target::currency::Currency;
+use crate::target::Target;
+use crate::utils::specifications::SpecificationChecks;
+use crate::{ast_processor, Source};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+pub struct Interpreter {
+    program: Program,
+    currency: Currency,
+    contract: Option<Contract>,
+    balances: HashMap<String, u64>,
+    events: Vec<Event>,
+    frames: Vec<Frame>,
+    transaction: Transaction,
+}
+
+#[derive(Debug, Clone)]
+struct Contract {
+    name: String,
+    properties: Value,
+    state: Option<String>,
+}
+
+/// Who sends a transaction, and how much currency comes with it for a `@payable` function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub caller: String,
+    pub value: u64,
+}
+
+impl Transaction {
+    pub fn new(caller: &str) -> Transaction {
+        let caller = match Value::address(caller) {
+            Value::Address(address) => address,
+            _ => unreachable!(),
+        };
+        Transaction { caller, value: 0 }
+    }
+
+    pub fn with_value(self, value: u64) -> Transaction {
+        Transaction { value, ..self }
+    }
+}
+
+/// An event emitted by a transaction which did not revert
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub arguments: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// The transaction reverted, and its changes were undone
+    Revert(String),
+    /// The program uses something the interpreter cannot run, such as a call to another contract
+    Unsupported(String),
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Revert(reason) => write!(f, "Reverted: {}", reason),
+            Failure::Unsupported(reason) => write!(f, "Unsupported: {}", reason),
+        }
+    }
+}
+
+pub(super) type Outcome<T> = Result<T, Failure>;
+
+impl Interpreter {
+    /// Loads and checks the sources with the files they import and the standard library of the
+    /// target. The target gives the library and currency, but not which constructs can be run
+    pub fn new(sources: &[Source], target: Target) -> Result<Interpreter, Vec<Diagnostic>> {
+        let (module, environment) =
+            crate::parse_sources(sources, &target, None, &mut SourceMap::default())?;
+        let (mut module, mut context) =
+            ast_processor::check_semantics(module, environment, &target)?;
+        context.check_specifications = true;
+        module
+            .visit(&mut SpecificationChecks, &mut context)
+            .map_err(|error| vec![Diagnostic::from(error)])?;
+
+        Ok(Interpreter {
+            program: Program::new(&module),
+            currency: target.currency,
+            contract: None,
+            balances: HashMap::new(),
+            events: vec![],
+            frames: vec![],
+            transaction: Transaction::new("0x0"),
+        })
+    }
+
+    /// Deploys a new instance of the contract, replacing any deployed before, by running its
+    /// public initialiser
+    pub fn deploy(
+        &mut self,
+        contract: &str,
+        transaction: &Transaction,
+        arguments: &[Value],
+    ) -> Result<(), Failure> {
+        let is_contract = self
+            .program
+            .types
+            .get(contract)
+            .map_or(false, |definition| definition.is_contract);
+        if !is_contract {
+            return Err(Failure::Revert(format!("`{}` is not a contract", contract)));
+        }
+
+        self.transact(transaction, |interpreter| {
+            interpreter.contract = None;
+            let properties = interpreter.instantiate(contract)?;
+            interpreter.contract = Some(Contract {
+                name: contract.to_string(),
+                properties,
+                state: None,
+            });
+
+            let initialiser = interpreter.program.entry_point(
+                contract,
+                None,
+                arguments.len(),
+                &interpreter.currency,
+            );
+            match initialiser {
+                Some(initialiser) => interpreter.enter(&initialiser, arguments).map(|_| ()),
+                None if arguments.is_empty() => Ok(()),
+                None => Err(Failure::Revert(format!(
+                    "`{}` has no public initialiser taking {} arguments",
+                    contract,
+                    arguments.len()
+                ))),
+            }
+        })
+    }
+
+    /// Calls a public function of the deployed contract, or the accessor of a public or visible
+    /// property
+    pub fn call(
+        &mut self,
+        transaction: &Transaction,
+        function: &str,
+        arguments: &[Value],
+    ) -> Result<Value, Failure> {
+        let contract = match &self.contract {
+            Some(contract) => contract.name.clone(),
+            None => return Err(Failure::Revert(String::from("No contract is deployed"))),
+        };
+
+        self.transact(transaction, |interpreter| {
+            let callable = interpreter.program.entry_point(
+                &contract,
+                Some(function),
+                arguments.len(),
+                &interpreter.currency,
+            );
+            match callable {
+                Some(callable) => interpreter.enter(&callable, arguments),
+                None => interpreter.accessor(&contract, function, arguments),
+            }
+        })
+    }
+
+    /// The value of a property of the deployed contract
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.contract.as_ref()?.properties.field(name)
+    }
+
+    /// The type state of the deployed contract, until its first `become` there is none
+    pub fn state(&self) -> Option<&str> {
+        self.contract.as_ref()?.state.as_deref()
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn balance(&self, account: &str) -> u64 {
+        match Value::address(account) {
+            Value::Address(address) => self.balances.get(&address).cloned().unwrap_or(0),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_balance(&mut self, account: &str, amount: u64) {
+        if let Value::Address(address) = Value::address(account) {
+            self.balances.insert(address, amount);
+        }
+    }
+
+    /// Runs the transaction, undoing its changes if it fails
+    fn transact<T, F: FnOnce(&mut Interpreter) -> Outcome<T>>(
+        &mut self,
+        transaction: &Transaction,
+        run: F,
+    ) -> Result<T, Failure> {
+        let contract = self.contract.clone();
+        let balances = self.balances.clone();
+        let events = self.events.len();
+
+        self.transaction = transaction.clone();
+        self.frames = vec![Frame::default()];
+        let result = run(self);
+        self.frames.clear();
+
+        if result.is_err() {
+            self.contract = contract;
+            self.balances = balances;
+            self.events.truncate(events);
+        }
+        result
+    }
+
+    /// Calls a function of the contract from outside it, once its type states, caller protections
+    /// and arguments are checked
+    fn enter(&mut self, callable: &Rc<Callable>, arguments: &[Value]) -> Outcome<Value> {
+        let contract = self.contract.as_ref().unwrap();
+        let (name, state) = (contract.name.clone(), contract.state.clone());
+
+        if !callable.is_in_any_state()
+            && !state
+                .as_ref()
+                .map_or(false, |s| callable.type_states.contains(s))
+        {
+            return Err(Failure::Revert(format!(
+                "`{}` cannot be called in {}",
+                callable.name,
+                state.map_or(String::from("no state"), |s| format!("state {}", s))
+            )));
+        }
+
+        if !callable.is_unprotected() && !self.is_permitted(&callable.caller_protections)? {
+            return Err(Failure::Revert(format!(
+                "The caller {} is not permitted to call `{}`",
+                self.transaction.caller, callable.name
+            )));
+        }
+
+        if self.transaction.value > 0 && !callable.is_payable {
+            return Err(Failure::Revert(format!(
+                "`{}` is not payable",
+                callable.name
+            )));
+        }
+
+        let mut locals = vec![];
+        let mut arguments = arguments.iter();
+        let payment = callable.payment_parameter(&self.currency);
+        for (index, parameter) in callable.parameters.iter().enumerate() {
+            let parameter_type = &parameter.type_assignment;
+            if Some(index) == payment {
+                locals.push(self.payment(parameter_type)?);
+                continue;
+            }
+
+            let argument = match (arguments.next(), &parameter.expression) {
+                (Some(argument), _) => argument.clone(),
+                (None, Some(default)) => self.eval(default)?,
+                (None, None) => {
+                    return Err(Failure::Revert(format!(
+                        "Missing argument `{}` of `{}`",
+                        parameter.identifier.token, callable.name
+                    )))
+                }
+            };
+            if !argument.has_type(parameter_type) {
+                return Err(Failure::Revert(format!(
+                    "Argument `{}` of `{}` should be of type {}, not {}",
+                    parameter.identifier.token, callable.name, parameter_type, argument
+                )));
+            }
+            locals.push(argument);
+        }
+        if arguments.next().is_some() {
+            return Err(Failure::Revert(format!(
+                "Too many arguments for `{}`",
+                callable.name
+            )));
+        }
+
+        let receiver = execution::Place::contract();
+        let locals = locals.into_iter().map(execution::Local::Value).collect();
+        self.invoke(callable, &name, Some(receiver), locals)
+    }
+
+    /// The currency sent with a transaction, as a value of the currency type of the parameter it is
+    /// passed to
+    fn payment(&mut self, currency_type: &crate::ast::Type) -> Outcome<Value> {
+        let mut payment = self.default_value(currency_type)?;
+        if let Value::Struct(_, fields) = &mut payment {
+            for (field, value) in fields {
+                if field == "rawValue" {
+                    *value = Value::Int(self.transaction.value);
+                }
+            }
+        }
+        Ok(payment)
+    }
+
+    /// Properties declared `visible` can be read with `get<Property>`, and those declared `public`
+    /// can also be written with `set<Property>`
+    fn accessor(&mut self, contract: &str, function: &str, arguments: &[Value]) -> Outcome<Value> {
+        use crate::ast::Modifier;
+
+        let definition = &self.program.types[contract];
+        let property = definition.properties.iter().find(|(declaration, _)| {
+            let name = &declaration.identifier.token;
+            let capitalised = name[..1].to_uppercase() + &name[1..];
+            function == format!("get{}", capitalised) || function == format!("set{}", capitalised)
+        });
+
+        let unknown = || {
+            Failure::Revert(format!(
+                "`{}` has no public function `{}` taking {} arguments",
+                contract,
+                function,
+                arguments.len()
+            ))
+        };
+        let (declaration, modifier) = property.cloned().ok_or_else(unknown)?;
+        let place = execution::Place::contract().field(&declaration.identifier.token);
+        match (function.starts_with("get"), modifier, arguments) {
+            (true, Some(_), []) => self.read(&place),
+            (false, Some(Modifier::Public), [value])
+                if value.has_type(&declaration.variable_type) =>
+            {
+                self.write(&place, value.clone())?;
+                Ok(Value::Void)
+            }
+            _ => Err(unknown()),
+        }
+    }
+
+    /// Whether the caller of the transaction satisfies any of the caller protections, which are
+    /// `any`, properties holding addresses, or functions returning an address or taking one and
+    /// returning whether it may call
+    fn is_permitted(&mut self, protections: &[String]) -> Outcome<bool> {
+        let contract = self.contract.as_ref().unwrap().name.clone();
+        let caller = Value::Address(self.transaction.caller.clone());
+
+        for protection in protections {
+            if protection == "any" {
+                return Ok(true);
+            }
+
+            let permitted = if self.program.has_property(&contract, protection) {
+                match self.read(&execution::Place::contract().field(protection))? {
+                    Value::Array(elements) => elements.contains(&caller),
+                    Value::Dictionary(entries) => entries.iter().any(|(_, v)| *v == caller),
+                    value => value == caller,
+                }
+            } else if let Some(function) =
+                self.program
+                    .function(&contract, protection, std::iter::empty())
+            {
+                let receiver = Some(execution::Place::contract());
+                self.invoke(&function, &contract, receiver, vec![])? == caller
+            } else if let Some(function) =
+                self.program
+                    .function(&contract, protection, std::iter::once(None))
+            {
+                let receiver = Some(execution::Place::contract());
+                let locals = vec![execution::Local::Value(caller.clone())];
+                self.invoke(&function, &contract, receiver, locals)? == Value::Bool(true)
+            } else {
+                return Err(Failure::Unsupported(format!(
+                    "Caller protection `{}` is neither a property nor a function",
+                    protection
+                )));
+            };
+
+            if permitted {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interpreter::{Failure, Interpreter, Transaction, Value};
+    use crate::{target, Source};
+
+    const WALLET: &str = "contract Wallet (Open, Closed) {
+  var funds: Libra = Libra()
+  var last: Address = 0x0
+  let owner: Address
+}
+
+Wallet :: caller <- (any) {
+  public init() {
+    owner = caller
+    become Open
+  }
+
+  public func balance() -> Int {
+    return funds.getRawValue()
+  }
+
+  public func fees(count: Int) -> Int {
+    var total: Int = 0
+    for let i: Int in (0..<4) {
+      if i < count {
+        total += 5
+      }
+    }
+    return total
+  }
+}
+
+Wallet @(Open) :: caller <- (any) {
+  @payable
+  public func deposit(value: Libra) mutates (funds, last) {
+    last = caller
+    funds.deposit(value)
+  }
+}
+
+Wallet @(Open) :: (owner) {
+  public func pay(to: Address, amount: Int) mutates (funds) {
+    Flint_transfer(&funds, to, amount)
+  }
+
+  public func close() {
+    become Closed
+  }
+}
+";
+
+    const TALLY: &str = "contract Tally {
+  var total: Int = 0
+  invariant(total <= 10)
+}
+
+Tally :: (any) {
+  public init() {}
+
+  public func add(amount: Int) -> Int mutates (total) {
+    do {
+      total += amount
+      return total
+    } catch is e {}
+    return total
+  }
+}
+";
+
+    #[test]
+    fn test_wallet() {
+        let mut wallet = Interpreter::new(
+            &[Source::new("wallet.flint", WALLET)],
+            target("libra").unwrap(),
+        )
+        .unwrap_or_else(|diagnostics| panic!("{:?}", diagnostics));
+        let (owner, other) = (Transaction::new("0xa"), Transaction::new("0xb"));
+        wallet.deploy("Wallet", &owner, &[]).unwrap();
+        assert_eq!(wallet.state(), Some("Open"));
+
+        wallet
+            .call(&other.clone().with_value(30), "deposit", &[])
+            .unwrap();
+        assert_eq!(wallet.call(&owner, "balance", &[]), Ok(Value::Int(30)));
+        assert_eq!(wallet.property("last"), Some(&Value::address("0xb")));
+        assert_eq!(
+            wallet.call(&owner, "fees", &[Value::Int(3)]),
+            Ok(Value::Int(15))
+        );
+
+        let to = Value::address("0xc");
+        assert!(matches!(
+            wallet.call(&other, "pay", &[to.clone(), Value::Int(10)]),
+            Err(Failure::Revert(_))
+        ));
+        assert!(matches!(
+            wallet.call(&owner, "pay", &[to.clone(), Value::Int(31)]),
+            Err(Failure::Revert(_))
+        ));
+        wallet.call(&owner, "pay", &[to, Value::Int(10)]).unwrap();
+        assert_eq!(wallet.balance("0xc"), 10);
+        assert_eq!(wallet.call(&owner, "balance", &[]), Ok(Value::Int(20)));
+
+        wallet.call(&owner, "close", &[]).unwrap();
+        assert!(matches!(
+            wallet.call(&other.with_value(5), "deposit", &[]),
+            Err(Failure::Revert(_))
+        ));
+        assert_eq!(wallet.call(&owner, "balance", &[]), Ok(Value::Int(20)));
+    }
+
+    #[test]
+    fn test_return_from_do() {
+        let mut tally = Interpreter::new(
+            &[Source::new("tally.flint", TALLY)],
+            target("libra").unwrap(),
+        )
+        .unwrap_or_else(|diagnostics| panic!("{:?}", diagnostics));
+        let caller = Transaction::new("0xa");
+        tally.deploy("Tally", &caller, &[]).unwrap();
+
+        assert_eq!(
+            tally.call(&caller, "add", &[Value::Int(4)]),
+            Ok(Value::Int(4))
+        );
+        assert!(matches!(
+            tally.call(&caller, "add", &[Value::Int(7)]),
+            Err(Failure::Revert(_))
+        ));
+        assert_eq!(tally.property("total"), Some(&Value::Int(4)));
+    }
+}
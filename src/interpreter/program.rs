@@ -0,0 +1,389 @@
+use crate::ast::*;
+use crate::target::currency::Currency;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The declarations of a checked program, arranged for looking up what a call runs
+#[derive(Debug, Default)]
+pub(super) struct Program {
+    pub types: HashMap<String, TypeDefinition>,
+    pub enums: HashMap<String, Vec<(String, Option<Expression>)>>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct TypeDefinition {
+    pub is_contract: bool,
+    pub properties: Rc<Vec<(VariableDeclaration, Option<Modifier>)>>,
+    pub functions: Vec<Rc<Callable>>,
+    pub initialisers: Vec<Rc<Callable>>,
+    pub type_states: Vec<String>,
+    conformances: Vec<String>,
+}
+
+/// A function or initialiser, with the caller binding, caller protections and type states of the
+/// behaviour block it was declared in
+#[derive(Debug)]
+pub(super) struct Callable {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub body: Vec<Statement>,
+    pub is_public: bool,
+    pub is_payable: bool,
+    pub caller_binding: Option<String>,
+    pub caller_protections: Vec<String>,
+    pub type_states: Vec<String>,
+}
+
+impl Callable {
+    fn function(
+        declaration: &FunctionDeclaration,
+        behaviour: Option<&ContractBehaviourDeclaration>,
+    ) -> Callable {
+        let head = &declaration.head;
+        Callable {
+            name: head.identifier.token.clone(),
+            parameters: head.parameters.clone(),
+            body: declaration.body.clone(),
+            is_public: head.is_public(),
+            is_payable: head.is_payable(),
+            ..Callable::block(behaviour)
+        }
+    }
+
+    fn special(
+        declaration: &SpecialDeclaration,
+        behaviour: Option<&ContractBehaviourDeclaration>,
+    ) -> Callable {
+        let head = &declaration.head;
+        Callable {
+            name: head.special_token.clone(),
+            parameters: head.parameters.clone(),
+            body: declaration.body.clone(),
+            is_public: declaration.is_public(),
+            is_payable: head
+                .attributes
+                .iter()
+                .any(|a| a.identifier_token == "payable"),
+            ..Callable::block(behaviour)
+        }
+    }
+
+    fn block(behaviour: Option<&ContractBehaviourDeclaration>) -> Callable {
+        Callable {
+            name: String::new(),
+            parameters: vec![],
+            body: vec![],
+            is_public: false,
+            is_payable: false,
+            caller_binding: behaviour
+                .and_then(|b| b.caller_binding.as_ref())
+                .map(|c| c.token.clone()),
+            caller_protections: behaviour
+                .map(|b| {
+                    b.caller_protections
+                        .iter()
+                        .map(|c| c.identifier.token.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            type_states: behaviour
+                .map(|b| {
+                    b.type_states
+                        .iter()
+                        .map(|s| s.identifier.token.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The initialiser of a struct which declares none, which takes the properties without default
+    /// values and assigns them
+    fn memberwise_initialiser(declaration: &StructDeclaration) -> Callable {
+        let initialiser = SpecialInformation::default_initialiser(declaration).declaration;
+        let body = initialiser
+            .head
+            .parameters
+            .iter()
+            .map(|parameter| {
+                // The parameters are named after the properties, whose identifiers they share
+                let argument = Identifier {
+                    enclosing_type: None,
+                    ..parameter.identifier.clone()
+                };
+                let property = Expression::BinaryExpression(BinaryExpression {
+                    lhs_expression: Box::new(Expression::SelfExpression),
+                    rhs_expression: Box::new(Expression::Identifier(parameter.identifier.clone())),
+                    op: BinOp::Dot,
+                    line_info: Default::default(),
+                });
+                Statement::Expression(Expression::BinaryExpression(BinaryExpression {
+                    lhs_expression: Box::new(property),
+                    rhs_expression: Box::new(Expression::Identifier(argument)),
+                    op: BinOp::Equal,
+                    line_info: Default::default(),
+                }))
+            })
+            .collect();
+        Callable {
+            body,
+            ..Callable::special(&initialiser, None)
+        }
+    }
+
+    /// Whether the arguments, given by their labels, can be passed to the parameters in order, the
+    /// parameters without arguments taking their default values
+    pub fn accepts<'a, I: Iterator<Item = Option<&'a str>>>(&self, labels: I) -> bool {
+        let mut labels = labels.peekable();
+        for parameter in &self.parameters {
+            match labels.peek() {
+                Some(label) if label.map_or(true, |l| l == parameter.identifier.token) => {
+                    labels.next();
+                }
+                _ if parameter.expression.is_some() => {}
+                _ => return false,
+            }
+        }
+        labels.next().is_none()
+    }
+
+    /// The parameter a payable function receives the currency sent with a transaction in, which
+    /// the transaction passes no argument for
+    pub fn payment_parameter(&self, currency: &Currency) -> Option<usize> {
+        if !self.is_payable {
+            return None;
+        }
+        self.parameters
+            .iter()
+            .position(|p| p.type_assignment.is_currency_type(currency))
+    }
+
+    /// Whether a transaction can call this with the number of arguments
+    fn accepts_transaction(&self, arguments: usize, currency: &Currency) -> bool {
+        let payment = self.payment_parameter(currency);
+        let parameters: Vec<_> = (0..self.parameters.len())
+            .filter(|index| Some(*index) != payment)
+            .map(|index| &self.parameters[index])
+            .collect();
+        arguments <= parameters.len()
+            && parameters[arguments..]
+                .iter()
+                .all(|parameter| parameter.expression.is_some())
+    }
+
+    pub fn is_unprotected(&self) -> bool {
+        self.caller_protections.is_empty() || self.caller_protections.iter().any(|c| c == "any")
+    }
+
+    pub fn is_in_any_state(&self) -> bool {
+        self.type_states.is_empty() || self.type_states.iter().any(|s| s == "any")
+    }
+}
+
+impl Program {
+    pub fn new(module: &Module) -> Program {
+        let mut program = Program::default();
+        let mut traits = HashMap::new();
+
+        for declaration in &module.declarations {
+            match declaration {
+                TopLevelDeclaration::ContractDeclaration(contract) => {
+                    let definition = program.definition(&contract.identifier);
+                    definition.is_contract = true;
+                    definition.properties = Rc::new(
+                        contract
+                            .contract_members
+                            .iter()
+                            .filter_map(|member| match member {
+                                ContractMember::VariableDeclaration(v, modifier) => {
+                                    Some((v.clone(), modifier.clone()))
+                                }
+                                ContractMember::EventDeclaration(_)
+                                | ContractMember::Invariant(_) => None,
+                            })
+                            .collect(),
+                    );
+                    definition.type_states = contract
+                        .type_states
+                        .iter()
+                        .map(|s| s.identifier.token.clone())
+                        .collect();
+                    definition.conformances = contract
+                        .conformances
+                        .iter()
+                        .map(Conformance::name)
+                        .collect();
+                }
+                TopLevelDeclaration::ContractBehaviourDeclaration(behaviour) => {
+                    let definition = program.definition(&behaviour.identifier);
+                    for member in &behaviour.members {
+                        match member {
+                            ContractBehaviourMember::FunctionDeclaration(f) => definition
+                                .functions
+                                .push(Rc::new(Callable::function(f, Some(behaviour)))),
+                            ContractBehaviourMember::SpecialDeclaration(s) if s.is_init() => {
+                                definition
+                                    .initialisers
+                                    .push(Rc::new(Callable::special(s, Some(behaviour))))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                TopLevelDeclaration::StructDeclaration(declaration) => {
+                    let definition = program.definition(&declaration.identifier);
+                    let mut properties = vec![];
+                    for member in &declaration.members {
+                        match member {
+                            StructMember::VariableDeclaration(v, modifier) => {
+                                properties.push((v.clone(), modifier.clone()))
+                            }
+                            StructMember::FunctionDeclaration(f) => definition
+                                .functions
+                                .push(Rc::new(Callable::function(f, None))),
+                            StructMember::SpecialDeclaration(s) if s.is_init() => definition
+                                .initialisers
+                                .push(Rc::new(Callable::special(s, None))),
+                            StructMember::SpecialDeclaration(_) | StructMember::Invariant(_) => {}
+                        }
+                    }
+                    if definition.initialisers.is_empty() {
+                        definition
+                            .initialisers
+                            .push(Rc::new(Callable::memberwise_initialiser(declaration)));
+                    }
+                    definition.properties = Rc::new(properties);
+                    definition.conformances = declaration
+                        .conformances
+                        .iter()
+                        .map(Conformance::name)
+                        .collect();
+                }
+                TopLevelDeclaration::AssetDeclaration(declaration) => {
+                    let definition = program.definition(&declaration.identifier);
+                    let mut properties = vec![];
+                    for member in &declaration.members {
+                        match member {
+                            AssetMember::VariableDeclaration(v) => {
+                                properties.push((v.clone(), None))
+                            }
+                            AssetMember::FunctionDeclaration(f) => definition
+                                .functions
+                                .push(Rc::new(Callable::function(f, None))),
+                            AssetMember::SpecialDeclaration(s) if s.is_init() => definition
+                                .initialisers
+                                .push(Rc::new(Callable::special(s, None))),
+                            AssetMember::SpecialDeclaration(_) => {}
+                        }
+                    }
+                    definition.properties = Rc::new(properties);
+                }
+                TopLevelDeclaration::EnumDeclaration(declaration) => {
+                    program.enums.insert(
+                        declaration.identifier.token.clone(),
+                        declaration
+                            .members
+                            .iter()
+                            .map(|m| (m.identifier.token.clone(), m.hidden_value.clone()))
+                            .collect(),
+                    );
+                }
+                TopLevelDeclaration::TraitDeclaration(declaration) => {
+                    let functions: Vec<_> = declaration
+                        .members
+                        .iter()
+                        .filter_map(|member| match member {
+                            TraitMember::FunctionDeclaration(f) => Some(f.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    traits.insert(declaration.identifier.token.clone(), functions);
+                }
+            }
+        }
+
+        // Traits give their functions with bodies to the types which conform to them and do not
+        // declare their own
+        for definition in program.types.values_mut() {
+            for conformance in &definition.conformances {
+                for function in traits.get(conformance).into_iter().flatten() {
+                    let name = &function.head.identifier.token;
+                    if !definition.functions.iter().any(|f| f.name == *name) {
+                        definition
+                            .functions
+                            .push(Rc::new(Callable::function(function, None)));
+                    }
+                }
+            }
+        }
+
+        program
+    }
+
+    fn definition(&mut self, identifier: &Identifier) -> &mut TypeDefinition {
+        self.types.entry(identifier.token.clone()).or_default()
+    }
+
+    pub fn has_property(&self, type_name: &str, property: &str) -> bool {
+        self.types.get(type_name).map_or(false, |definition| {
+            definition
+                .properties
+                .iter()
+                .any(|(declaration, _)| declaration.identifier.token == property)
+        })
+    }
+
+    /// The first function of the type with the name which accepts the arguments
+    pub fn function<'a, I: Iterator<Item = Option<&'a str>>>(
+        &self,
+        type_name: &str,
+        name: &str,
+        labels: I,
+    ) -> Option<Rc<Callable>> {
+        let labels: Vec<_> = labels.collect();
+        self.types
+            .get(type_name)?
+            .functions
+            .iter()
+            .find(|f| f.name == name && f.accepts(labels.iter().cloned()))
+            .cloned()
+    }
+
+    pub fn initialiser<'a, I: Iterator<Item = Option<&'a str>>>(
+        &self,
+        type_name: &str,
+        labels: I,
+    ) -> Option<Rc<Callable>> {
+        let labels: Vec<_> = labels.collect();
+        self.types
+            .get(type_name)?
+            .initialisers
+            .iter()
+            .find(|f| f.accepts(labels.iter().cloned()))
+            .cloned()
+    }
+
+    /// The public function of the contract with the name, or its public initialiser if there is no
+    /// name, which a transaction passing the number of arguments calls
+    pub fn entry_point(
+        &self,
+        contract: &str,
+        name: Option<&str>,
+        arguments: usize,
+        currency: &Currency,
+    ) -> Option<Rc<Callable>> {
+        let definition = self.types.get(contract)?;
+        let callables = match name {
+            Some(_) => &definition.functions,
+            None => &definition.initialisers,
+        };
+        callables
+            .iter()
+            .find(|c| {
+                c.is_public
+                    && name.map_or(true, |name| c.name == name)
+                    && c.accepts_transaction(arguments, currency)
+            })
+            .cloned()
+    }
+}
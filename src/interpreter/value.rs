@@ -0,0 +1,180 @@
+use crate::ast::{Literal, Type};
+use std::fmt::{Display, Formatter, Result};
+
+/// A Flint value. Structs, arrays and dictionaries are held by value, so assigning one copies it,
+/// as both targets do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(u64),
+    Bool(bool),
+    Address(String),
+    String(String),
+    Struct(String, Vec<(String, Value)>),
+    Array(Vec<Value>),
+    Dictionary(Vec<(Value, Value)>),
+    Void,
+}
+
+impl Value {
+    /// An address written in hexadecimal, with or without `0x`. Leading zeros are dropped, so that
+    /// addresses of different widths compare equal
+    pub fn address(address: &str) -> Value {
+        let digits = address.trim_start_matches("0x").to_lowercase();
+        let digits = digits.trim_start_matches('0');
+        Value::Address(format!(
+            "0x{}",
+            if digits.is_empty() { "0" } else { digits }
+        ))
+    }
+
+    pub(super) fn from_literal(literal: &Literal) -> Option<Value> {
+        match literal {
+            Literal::BooleanLiteral(b) => Some(Value::Bool(*b)),
+            Literal::AddressLiteral(a) => Some(Value::address(a)),
+            Literal::StringLiteral(s) => Some(Value::String(s.clone())),
+            Literal::U8Literal(u) => Some(Value::Int(u64::from(*u))),
+            Literal::IntLiteral(i) => Some(Value::Int(*i)),
+            Literal::FloatLiteral(_) => None,
+        }
+    }
+
+    pub(super) fn has_type(&self, value_type: &Type) -> bool {
+        match (self, value_type) {
+            (_, Type::InoutType(inout)) => self.has_type(&inout.key_type),
+            (Value::Int(_), Type::Int)
+            | (Value::Bool(_), Type::Bool)
+            | (Value::Address(_), Type::Address)
+            | (Value::String(_), Type::String) => true,
+            (Value::Struct(name, _), Type::UserDefinedType(identifier)) => {
+                *name == identifier.token
+            }
+            (Value::Array(elements), Type::ArrayType(array)) => {
+                elements.iter().all(|e| e.has_type(&array.key_type))
+            }
+            (Value::Array(elements), Type::FixedSizedArrayType(array)) => {
+                elements.len() as u64 == array.size
+                    && elements.iter().all(|e| e.has_type(&array.key_type))
+            }
+            (Value::Dictionary(entries), Type::DictionaryType(dictionary)) => {
+                entries.iter().all(|(key, value)| {
+                    key.has_type(&dictionary.key_type) && value.has_type(&dictionary.value_type)
+                })
+            }
+            // Enumeration cases are their raw values
+            (Value::Int(_), Type::UserDefinedType(_))
+            | (Value::String(_), Type::UserDefinedType(_)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<u64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The value of a property of a struct
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(_, fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl From<u64> for Value {
+    fn from(i: u64) -> Self {
+        Value::Int(i)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Address(a) => write!(f, "{}", a),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Struct(name, fields) => {
+                write!(f, "{}(", name)?;
+                for (index, (field, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", field, value)?;
+                }
+                write!(f, ")")
+            }
+            Value::Array(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Value::Dictionary(entries) if entries.is_empty() => write!(f, "[:]"),
+            Value::Dictionary(entries) => {
+                write!(f, "[")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Void => write!(f, "()"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interpreter::Value;
+
+    #[test]
+    fn test_address() {
+        assert_eq!(Value::address("0x0001"), Value::address("1"));
+        assert_eq!(Value::address("0xAb"), Value::address("0xab"));
+        assert_eq!(Value::address("0x00"), Value::Address(String::from("0x0")));
+        assert_eq!(
+            Value::address("0x10000000000000000000000000000000").to_string(),
+            "0x10000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let value = Value::Struct(
+            String::from("V"),
+            vec![
+                (
+                    String::from("x"),
+                    Value::Array(vec![Value::Int(1), Value::Int(2)]),
+                ),
+                (String::from("y"), Value::Dictionary(vec![])),
+            ],
+        );
+        assert_eq!(value.to_string(), "V(x: [1, 2], y: [:])");
+    }
+}
@@ -0,0 +1,195 @@
+use json::JsonValue;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE_NAME: &str = "flint_config.json";
+const CONFIG_ENV_VAR: &str = "FLINT_CONFIG";
+const PLATFORM_CONFIG_DIRECTORY: &str = "flint";
+const USER_CONFIG_DIRECTORY: &str = ".flint";
+const JSON_LLC_KEY: &str = "llcPath";
+const JSON_WASM_LD_KEY: &str = "wasm-ldPath";
+const JSON_LIBRA_KEY: &str = "libraPath";
+
+/// The external tools used to compile LLVM to eWASM
+#[derive(Debug, PartialEq)]
+pub struct Tools {
+    pub llc: String,
+    pub wasm_ld: String,
+}
+
+/// Tools missing from the flint config, or when there is no config at all, are looked for on the
+/// PATH, preferring the LLVM 10 versions the code generation is written against
+pub fn tools(config: Option<&Path>) -> Result<Tools, String> {
+    let config = match find_config(config)? {
+        Some(path) => read_config(&path)?,
+        None => JsonValue::new_object(),
+    };
+
+    Ok(Tools {
+        llc: tool(&config, JSON_LLC_KEY, &["llc-10", "llc"])?,
+        wasm_ld: tool(&config, JSON_WASM_LD_KEY, &["wasm-ld-10", "wasm-ld"])?,
+    })
+}
+
+/// The Libra checkout the Move IR behaviour tests are run in, if the flint config names one
+pub fn libra_path(config: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let config = match find_config(config)? {
+        Some(path) => read_config(&path)?,
+        None => return Ok(None),
+    };
+
+    Ok(config[JSON_LIBRA_KEY]
+        .as_str()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from))
+}
+
+/// The config given on the command line, then the one named by `$FLINT_CONFIG`, then the nearest
+/// `flint_config.json` in the current directory or its parents, then the one in the user's config
+/// directories. Only a config which was asked for explicitly has to exist
+pub fn find_config(config: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let explicit = config
+        .map(Path::to_path_buf)
+        .or_else(|| env::var_os(CONFIG_ENV_VAR).map(PathBuf::from));
+    if let Some(path) = explicit {
+        return if path.is_file() {
+            Ok(Some(path))
+        } else {
+            Err(format!("Could not find flint config `{}`", path.display()))
+        };
+    }
+
+    let project_config = env::current_dir().ok().and_then(|directory| {
+        directory
+            .ancestors()
+            .map(|directory| directory.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    });
+    let user_config = || {
+        user_config_directories(|name| env::var_os(name))
+            .into_iter()
+            .map(|directory| directory.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    };
+
+    Ok(project_config.or_else(user_config))
+}
+
+/// The `flint` directory in the platform's config directory, which is `$XDG_CONFIG_HOME` or
+/// `~/.config` on Linux, then `~/.flint`
+fn user_config_directories(var: impl Fn(&str) -> Option<OsString>) -> Vec<PathBuf> {
+    let home = var("HOME").map(PathBuf::from);
+    let platform = if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home.as_ref()
+            .map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|directory| directory.is_absolute())
+            .or_else(|| home.as_ref().map(|home| home.join(".config")))
+    };
+
+    platform
+        .map(|directory| directory.join(PLATFORM_CONFIG_DIRECTORY))
+        .into_iter()
+        .chain(home.map(|home| home.join(USER_CONFIG_DIRECTORY)))
+        .collect()
+}
+
+fn read_config(path: &Path) -> Result<JsonValue, String> {
+    let config = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read flint config `{}`: {}", path.display(), err))?;
+    match json::parse(&config) {
+        Ok(config) if config.is_object() => Ok(config),
+        Ok(_) => Err(format!(
+            "Flint config `{}` should be a JSON object",
+            path.display()
+        )),
+        Err(err) => Err(format!(
+            "Could not parse flint config `{}`: {}",
+            path.display(),
+            err
+        )),
+    }
+}
+
+fn tool(config: &JsonValue, key: &str, candidates: &[&str]) -> Result<String, String> {
+    match config[key].as_str() {
+        Some(path) if !path.is_empty() => Ok(path.to_string()),
+        _ => candidates
+            .iter()
+            .find(|candidate| is_on_path(candidate))
+            .map(|candidate| candidate.to_string())
+            .ok_or_else(|| {
+                format!(
+                    "Could not find {}, either add it to the PATH or set `{}` in {}",
+                    candidates.join(" or "),
+                    key,
+                    CONFIG_FILE_NAME
+                )
+            }),
+    }
+}
+
+fn is_on_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|directory| directory.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::config::{find_config, tool, user_config_directories};
+    use std::ffi::OsString;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_tool_from_config() {
+        let config = json::parse(r#"{"llcPath": "/opt/llvm/bin/llc", "wasm-ldPath": ""}"#).unwrap();
+
+        assert_eq!(
+            tool(&config, "llcPath", &["llc"]),
+            Ok("/opt/llvm/bin/llc".to_string())
+        );
+
+        let error = tool(&config, "wasm-ldPath", &["flint-test-missing-wasm-ld"]).unwrap_err();
+        assert!(error.contains("flint-test-missing-wasm-ld"));
+        assert!(error.contains("`wasm-ldPath`"));
+    }
+
+    #[test]
+    fn test_explicit_config_must_exist() {
+        assert!(find_config(Some(Path::new("flint-test-missing-config.json"))).is_err());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_user_config_directories() {
+        let environment = |xdg_config_home: &'static str| {
+            move |name: &str| match name {
+                "HOME" => Some(OsString::from("/home/flint")),
+                "XDG_CONFIG_HOME" => Some(OsString::from(xdg_config_home)),
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            user_config_directories(environment("/etc/xdg")),
+            vec![
+                PathBuf::from("/etc/xdg/flint"),
+                PathBuf::from("/home/flint/.flint")
+            ]
+        );
+        assert_eq!(
+            user_config_directories(environment("")),
+            vec![
+                PathBuf::from("/home/flint/.config/flint"),
+                PathBuf::from("/home/flint/.flint")
+            ]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use crate::ast::LineInfo;
+use crate::diagnostic::{code, Diagnostic, SourceMap};
+use crate::parser::parse_imports;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Adds the input files and every file they import to the source map, so that they are parsed as
+/// one module. Imports are resolved relative to the file containing them, and each file is only
+/// added once, however many files import it
+pub fn load_program(
+    inputs: Vec<(&Path, String)>,
+    source_map: &mut SourceMap,
+) -> Result<(), Vec<Diagnostic>> {
+    let mut loader = Loader {
+        source_map,
+        loaded: HashSet::new(),
+        importing: vec![],
+        diagnostics: vec![],
+    };
+    for (path, program) in inputs {
+        let canonical_path = canonical(path);
+        if !loader.loaded.contains(&canonical_path) {
+            loader.load(path, canonical_path, program);
+        }
+    }
+
+    if loader.diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(loader.diagnostics)
+    }
+}
+
+struct Loader<'a> {
+    source_map: &'a mut SourceMap,
+    loaded: HashSet<PathBuf>,
+    importing: Vec<PathBuf>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Loader<'a> {
+    /// Files are added after the files they import, so that a declaration in a file conflicts with
+    /// the one it imported rather than the other way round
+    fn load(&mut self, path: &Path, canonical_path: PathBuf, program: String) {
+        self.importing.push(canonical_path.clone());
+
+        // Imports are found by parsing the file on its own, so their offsets are within it, and
+        // their errors are located once the file is added to the program
+        let mut errors = vec![];
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        for import in parse_imports(&program) {
+            let imported = directory.join(&import.path);
+
+            let canonical_import = match imported.canonicalize() {
+                Ok(canonical_import) => canonical_import,
+                Err(err) => {
+                    errors.push((
+                        import.line_info,
+                        Diagnostic::error(
+                            code::INVALID_IMPORT,
+                            format!(
+                                "Unable to find imported file `{}`: {}",
+                                imported.display(),
+                                err
+                            ),
+                        ),
+                    ));
+                    continue;
+                }
+            };
+
+            if let Some(index) = self
+                .importing
+                .iter()
+                .position(|path| *path == canonical_import)
+            {
+                let cycle = self.importing[index..]
+                    .iter()
+                    .chain(std::iter::once(&canonical_import))
+                    .map(|path| file_name(path))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                errors.push((
+                    import.line_info,
+                    Diagnostic::error(
+                        code::INVALID_IMPORT,
+                        format!("Cyclic import of `{}`", import.path),
+                    )
+                    .with_note(format!("the files import each other as {}", cycle)),
+                ));
+                continue;
+            }
+
+            if self.loaded.contains(&canonical_import) {
+                continue;
+            }
+
+            match fs::read_to_string(&imported) {
+                Ok(program) => self.load(&imported, canonical_import, program),
+                Err(err) => errors.push((
+                    import.line_info,
+                    Diagnostic::error(
+                        code::INVALID_IMPORT,
+                        format!(
+                            "Unable to read imported file `{}`: {}",
+                            imported.display(),
+                            err
+                        ),
+                    ),
+                )),
+            }
+        }
+
+        let file = self.source_map.add_file(path.to_string_lossy(), program);
+        for (line_info, diagnostic) in errors {
+            self.diagnostics.push(diagnostic.with_span(&LineInfo {
+                offset: self.source_map.start(file) + line_info.offset,
+                file,
+                ..line_info
+            }));
+        }
+
+        self.importing.pop();
+        self.loaded.insert(canonical_path);
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
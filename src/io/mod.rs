@@ -1,2 +1,4 @@
-pub(crate) mod prompt;
-pub(crate) mod target;
+pub mod config;
+pub(crate) mod loader;
+pub mod prompt;
+pub mod target;
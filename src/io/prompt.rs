@@ -1,42 +1,274 @@
+use crate::diagnostic::ErrorFormat;
+use crate::target::output::{Emit, Options};
 use crate::target::Target;
 use std::path::PathBuf;
 
 pub struct Configuration {
-    pub bin: PathBuf,
-    pub file: PathBuf,
+    pub inputs: Vec<PathBuf>,
     pub target: Target,
+    /// Replaces the standard library built into the compiler
+    pub stdlib: Option<PathBuf>,
+    pub out_dir: PathBuf,
+    pub quiet: bool,
+    pub error_format: ErrorFormat,
+    pub options: Options,
 }
 
-pub fn process<I: Iterator<Item = String>>(arguments: &mut I) -> Option<Configuration> {
-    let bin = PathBuf::from(arguments.next().unwrap_or_default());
-    let target = arguments.next().or_else(instructions)?;
-    if &*target == "-h" {
-        instructions()?
-    } else if &*target == "-v" {
-        version()?
+/// `flint fmt` formats the inputs in place, or with `--check` only reports those it would change
+pub struct FormatConfiguration {
+    pub inputs: Vec<PathBuf>,
+    pub check: bool,
+    pub error_format: ErrorFormat,
+}
+
+/// The status the compiler exits with, so that build scripts can tell why it failed
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    pub const USAGE: i32 = 1;
+    pub const IO: i32 = 2;
+    pub const SYNTAX: i32 = 3;
+    pub const SEMANTIC: i32 = 4;
+    pub const GENERATION: i32 = 5;
+    pub const UNFORMATTED: i32 = 6;
+}
+
+const DEFAULT_OUT_DIR: &str = "output";
+
+/// Options may appear anywhere among the target and inputs. Those taking a value accept it either
+/// as the next argument or after an `=`. Returns the exit code if the compiler should not run
+pub fn process<I: Iterator<Item = String>>(arguments: &mut I) -> Result<Configuration, i32> {
+    arguments.next();
+
+    let mut positional = vec![];
+    let mut out_dir = PathBuf::from(DEFAULT_OUT_DIR);
+    let mut stdlib = None;
+    let mut quiet = false;
+    let mut error_format = ErrorFormat::default();
+    let mut emit = vec![];
+    let mut options = Options::default();
+
+    while let Some(argument) = arguments.next() {
+        let (flag, inline_value) = match argument.find('=') {
+            Some(index) if argument.starts_with("--") => {
+                (&argument[..index], Some(argument[index + 1..].to_string()))
+            }
+            _ => (&*argument, None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| arguments.next())
+                .ok_or_else(|| usage(format!("`{}` requires a value", flag)))
+        };
+
+        match flag {
+            "-h"
+            | "--help"
+            | "-v"
+            | "--version"
+            | "-q"
+            | "--quiet"
+            | "--no-specification-checks"
+                if inline_value.is_some() =>
+            {
+                return Err(usage(format!("`{}` does not take a value", flag)));
+            }
+            "-h" | "--help" => {
+                instructions();
+                return Err(exit_code::SUCCESS);
+            }
+            "-v" | "--version" => {
+                version();
+                return Err(exit_code::SUCCESS);
+            }
+            "-q" | "--quiet" => quiet = true,
+            "--no-specification-checks" => options.check_specifications = false,
+            "-o" | "--out-dir" => out_dir = PathBuf::from(value()?),
+            "--stdlib" => stdlib = Some(PathBuf::from(value()?)),
+            "--config" => options.config = Some(PathBuf::from(value()?)),
+            "--error-format" => error_format = value()?.parse().map_err(usage)?,
+            "--emit" => {
+                for kind in value()?.split(',') {
+                    emit.push(kind.parse::<Emit>().map_err(usage)?);
+                }
+            }
+            "-O0" | "-O1" | "-O2" | "-O3" => options.optimisation = flag[2..].parse().unwrap(),
+            _ if flag.starts_with("-O") => {
+                return Err(usage(format!(
+                    "Unknown optimisation level `{}`, expected -O0, -O1, -O2 or -O3",
+                    flag
+                )));
+            }
+            _ if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(usage(format!("Unknown option `{}`", flag)));
+            }
+            _ => positional.push(argument.clone()),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let target = match positional.next() {
+        Some(target) => target,
+        None => {
+            instructions();
+            return Err(exit_code::USAGE);
+        }
+    };
+    let target = super::target::target(&target).ok_or_else(|| {
+        usage(format!(
+            "Unknown target `{}`, expected `libra` or `ethereum`",
+            target
+        ))
+    })?;
+
+    let inputs: Vec<PathBuf> = positional.map(PathBuf::from).collect();
+    if inputs.is_empty() {
+        return Err(usage("No input files"));
+    }
+
+    if let Some(kind) = emit
+        .iter()
+        .find(|kind| !Emit::PROGRAM.contains(kind) && !target.emits.contains(kind))
+    {
+        return Err(usage(format!(
+            "The {} target cannot emit `{}`",
+            target.name, kind
+        )));
+    }
+    options.emit = if emit.is_empty() {
+        target.default_emits.to_vec()
+    } else {
+        emit
+    };
+    if let Some(name) = inputs[0].file_stem() {
+        options.name = name.to_string_lossy().into_owned();
     }
-    let target = super::target::target(&*target).or_else(instructions)?;
 
-    let file = arguments.next().or_else(instructions)?;
-    let file = PathBuf::from(file);
-    Some(Configuration { bin, file, target })
+    Ok(Configuration {
+        inputs,
+        target,
+        stdlib,
+        out_dir,
+        quiet,
+        error_format,
+        options,
+    })
 }
 
-fn instructions<A>() -> Option<A> {
+/// Processes the arguments after `flint fmt`. Returns the exit code if the formatter should not run
+pub fn process_format<I: Iterator<Item = String>>(
+    arguments: &mut I,
+) -> Result<FormatConfiguration, i32> {
+    arguments.next();
+    arguments.next();
+
+    let mut inputs = vec![];
+    let mut check = false;
+    let mut error_format = ErrorFormat::default();
+
+    while let Some(argument) = arguments.next() {
+        let (flag, inline_value) = match argument.find('=') {
+            Some(index) if argument.starts_with("--") => {
+                (&argument[..index], Some(argument[index + 1..].to_string()))
+            }
+            _ => (&*argument, None),
+        };
+
+        match flag {
+            "-h" | "--help" | "--check" if inline_value.is_some() => {
+                return Err(usage(format!("`{}` does not take a value", flag)));
+            }
+            "-h" | "--help" => {
+                instructions();
+                return Err(exit_code::SUCCESS);
+            }
+            "--check" => check = true,
+            "--error-format" => {
+                error_format = inline_value
+                    .or_else(|| arguments.next())
+                    .ok_or_else(|| usage(format!("`{}` requires a value", flag)))?
+                    .parse()
+                    .map_err(usage)?
+            }
+            _ if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(usage(format!("Unknown option `{}`", flag)));
+            }
+            _ => inputs.push(PathBuf::from(&argument)),
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err(usage("No input files"));
+    }
+    Ok(FormatConfiguration {
+        inputs,
+        check,
+        error_format,
+    })
+}
+
+fn usage<S: std::fmt::Display>(message: S) -> i32 {
+    eprintln!("error: {}", message);
+    eprintln!("Run `flint --help` to see the available options");
+    exit_code::USAGE
+}
+
+fn instructions() {
     println!(
         "\
-flint [TARGET] [INPUT]
+flint [OPTIONS] TARGET INPUT...
+flint fmt [--check] INPUT...
 
 TARGET:         Output code to build
     libra           Move IR for the Libra
     ethereum        eWASM for Ethereum
 
-INPUT:          Input flint file to compile"
+INPUT:          Flint files to compile together, along with the files they import
+
+OPTIONS:
+    -o, --out-dir <DIR>
+                    Where to write the output files, `output` by default
+    --emit=<KIND>[,<KIND>...]
+                    What to output, from
+                        ast, env, preprocessed  for either target
+                        states                  the type states of each contract, for
+                                                either target, as Graphviz and JSON
+                        access-report           who may call each function of each
+                                                contract and in which states, for either
+                                                target, as Markdown and JSON
+                        mvir                    for libra, the default
+                        llvm, wat, wasm, abi    for ethereum, wasm and abi by default
+    --config <PATH> The flint config giving the paths of llc and wasm-ld, otherwise
+                    $FLINT_CONFIG, the nearest flint_config.json, or
+                    ~/.flint/flint_config.json. Without one, llc-10 or llc and
+                    wasm-ld-10 or wasm-ld are looked for on the PATH
+    --stdlib <PATH> Compile against this standard library instead of the built in one
+    -O0, -O1, -O2, -O3
+                    How much llc optimises eWASM output, -O3 by default
+    --no-specification-checks
+                    Do not assert preconditions, postconditions and invariants when
+                    the contract runs, for production builds
+    -q, --quiet     Do not list the files written
+    --error-format=<human|json>
+                    How errors are reported, json prints one object per line
+    -h, --help      Print this message
+    -v, --version   Print the version
+
+FMT:            Rewrite the inputs in the canonical layout, keeping their comments
+    --check         List the inputs which are not formatted instead of rewriting them
+
+EXIT STATUS:
+    0               Compiled successfully
+    1               Invalid command-line arguments
+    2               A file could not be read or written
+    3               The program has syntax errors
+    4               The program has semantic errors
+    5               Code could not be generated, for example if llc failed
+    6               With `fmt --check`, an input is not formatted"
     );
-    None
 }
 
-fn version<A>() -> Option<A> {
+fn version() {
     println!(
         r"Flint 2 - unreleased development build
 
@@ -54,10 +286,10 @@ thesis.
  %/      \%   /_/   /_/_/_/ /_/\__/   /____/
 "
     );
-    None
 }
 
 pub mod error {
+    use crate::diagnostic::{code, Diagnostic, ErrorFormat, SourceMap};
     use std::path::Path;
 
     pub fn unable_to_open_file(path: &Path, error: std::io::Error) -> ! {
@@ -66,7 +298,7 @@ pub mod error {
             path.to_str().unwrap_or_default(),
             error
         );
-        std::process::exit(2)
+        std::process::exit(super::exit_code::IO)
     }
 
     pub fn unable_to_read_file(path: &Path, error: std::io::Error) -> ! {
@@ -75,16 +307,179 @@ pub mod error {
             path.to_str().unwrap_or_default(),
             error
         );
-        std::process::exit(2)
+        std::process::exit(super::exit_code::IO)
+    }
+
+    pub fn unable_to_write_file(path: &Path, error: std::io::Error) -> ! {
+        eprintln!("Unable to write file `{}`: {}", path.display(), error);
+        std::process::exit(super::exit_code::IO)
+    }
+
+    /// Compilation stops at the first stage which finds errors, so they all have the same status
+    pub fn compilation_failed(
+        diagnostics: &[Diagnostic],
+        source_map: &SourceMap,
+        error_format: ErrorFormat,
+    ) -> ! {
+        report(diagnostics, source_map, error_format);
+
+        let status = match diagnostics
+            .iter()
+            .find(|d| d.is_error())
+            .and_then(|d| d.code)
+        {
+            Some(code::SYNTAX_ERROR) | Some(code::INVALID_IMPORT) => super::exit_code::SYNTAX,
+            Some(code::CODE_GENERATION) | Some(code::UNSUPPORTED_FEATURE) => {
+                super::exit_code::GENERATION
+            }
+            _ => super::exit_code::SEMANTIC,
+        };
+        std::process::exit(status)
+    }
+
+    /// The outputs of a program with warnings are still written
+    pub fn compiled_with_warnings(
+        warnings: &[Diagnostic],
+        source_map: &SourceMap,
+        error_format: ErrorFormat,
+    ) {
+        for warning in warnings {
+            eprintln!("{}", warning.render(source_map, error_format));
+        }
+
+        if error_format == ErrorFormat::Human && !warnings.is_empty() {
+            eprintln!(
+                "Compiled with {} warning{}",
+                warnings.len(),
+                if warnings.len() == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    /// Only files which parse can be formatted, so the errors are all syntax errors. The other
+    /// files are still formatted
+    pub fn unable_to_format_file(
+        path: &Path,
+        diagnostics: &[Diagnostic],
+        source_map: &SourceMap,
+        error_format: ErrorFormat,
+    ) {
+        report_with(
+            diagnostics,
+            source_map,
+            error_format,
+            &format!("Could not format `{}`", path.display()),
+        );
+    }
+
+    fn report(diagnostics: &[Diagnostic], source_map: &SourceMap, error_format: ErrorFormat) {
+        report_with(diagnostics, source_map, error_format, "Could not compile")
+    }
+
+    fn report_with(
+        diagnostics: &[Diagnostic],
+        source_map: &SourceMap,
+        error_format: ErrorFormat,
+        summary: &str,
+    ) {
+        for diagnostic in diagnostics {
+            eprintln!("{}", diagnostic.render(source_map, error_format));
+        }
+
+        if error_format == ErrorFormat::Human {
+            let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+            eprintln!(
+                "{} due to {} previous error{}",
+                summary,
+                errors,
+                if errors == 1 { "" } else { "s" }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::prompt::{exit_code, process, process_format};
+    use crate::target::output::Emit;
+    use std::path::PathBuf;
+
+    fn arguments(arguments: &str) -> impl Iterator<Item = String> + '_ {
+        std::iter::once("flint")
+            .chain(arguments.split(' '))
+            .map(str::to_string)
+    }
+
+    #[test]
+    fn test_process_options() {
+        let configuration = process(&mut arguments(
+            "-q ethereum a.flint --emit=llvm,abi -O1 b.flint --out-dir build --stdlib std.flint --no-specification-checks",
+        ))
+        .ok()
+        .unwrap();
+
+        assert_eq!(
+            configuration.inputs,
+            vec![PathBuf::from("a.flint"), PathBuf::from("b.flint")]
+        );
+        assert_eq!(configuration.target.name, "eWASM");
+        assert_eq!(configuration.out_dir, PathBuf::from("build"));
+        assert_eq!(configuration.stdlib, Some(PathBuf::from("std.flint")));
+        assert!(configuration.quiet);
+        assert_eq!(configuration.options.emit, vec![Emit::Llvm, Emit::Abi]);
+        assert_eq!(configuration.options.optimisation, 1);
+        assert_eq!(configuration.options.name, "a");
+        assert!(!configuration.options.check_specifications);
     }
 
-    pub fn parse_failed(error: &str) -> ! {
-        eprintln!("Parse error: {}", error);
-        std::process::exit(3)
+    #[test]
+    fn test_process_defaults() {
+        let configuration = process(&mut arguments("libra main.flint")).ok().unwrap();
+
+        assert_eq!(configuration.out_dir, PathBuf::from("output"));
+        assert_eq!(configuration.stdlib, None);
+        assert_eq!(configuration.options.emit, vec![Emit::MoveIR]);
+        assert!(configuration.options.check_specifications);
     }
 
-    pub fn semantic_check_failed(error: &dyn std::error::Error) -> ! {
-        eprintln!("Semantic check error: {}", error);
-        std::process::exit(4)
+    #[test]
+    fn test_process_invalid_arguments() {
+        for invalid in &[
+            "libra",
+            "move main.flint",
+            "libra main.flint --emit=wasm",
+            "libra main.flint -O4",
+            "libra main.flint --out-dir",
+            "libra main.flint --verbose",
+            "libra main.flint --no-specification-checks=yes",
+        ] {
+            assert_eq!(
+                process(&mut arguments(invalid)).err(),
+                Some(exit_code::USAGE),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_format() {
+        let configuration = process_format(&mut arguments("fmt a.flint --check b.flint"))
+            .ok()
+            .unwrap();
+        assert_eq!(
+            configuration.inputs,
+            vec![PathBuf::from("a.flint"), PathBuf::from("b.flint")]
+        );
+        assert!(configuration.check);
+
+        for invalid in &["fmt", "fmt --check=yes a.flint", "fmt a.flint -o build"] {
+            assert_eq!(
+                process_format(&mut arguments(invalid)).err(),
+                Some(exit_code::USAGE),
+                "{}",
+                invalid
+            );
+        }
     }
 }
@@ -0,0 +1,151 @@
+//! The Flint compiler. `compile` turns Flint source code into the outputs of a target, held in
+//! memory, so that tools can call the compiler directly rather than running the `flint` binary
+
+mod ewasm;
+mod ewasm_tests;
+
+mod ast;
+mod ast_processor;
+mod context;
+pub mod diagnostic;
+mod environment;
+mod formatter;
+pub mod interpreter;
+pub mod io;
+pub mod lsp;
+mod moveir;
+mod parser;
+mod semantic_analysis;
+pub mod target;
+mod type_assigner;
+mod type_checker;
+mod utils;
+mod visitor;
+
+pub use self::diagnostic::{Diagnostic, SourceMap};
+pub use self::io::target::target;
+pub use self::target::output::{Artifact, Compilation, Emit, Options};
+pub use self::target::Target;
+
+use std::path::Path;
+
+/// A source file, named by its path. The files it imports are read relative to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+}
+
+impl Source {
+    pub fn new<N: Into<String>, T: Into<String>>(name: N, text: T) -> Source {
+        Source {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+}
+
+/// Compiles the sources together, along with the files they import and the standard library, into
+/// the outputs requested by the options
+pub fn compile(
+    sources: &[Source],
+    target: Target,
+    options: &Options,
+) -> Result<Compilation, Vec<Diagnostic>> {
+    compile_with_source_map(sources, target, options, &mut SourceMap::default())
+}
+
+/// Diagnostics can only be rendered with the files the program was loaded from, which are added to
+/// the source map
+pub fn compile_with_source_map(
+    sources: &[Source],
+    target: Target,
+    options: &Options,
+    source_map: &mut SourceMap,
+) -> Result<Compilation, Vec<Diagnostic>> {
+    let (module, environment) =
+        parse_sources(sources, &target, options.stdlib.as_ref(), source_map)?;
+    ast_processor::process_ast(module, environment, target, options)
+}
+
+/// Parses the sources with the files they import and the standard library, which is the target's
+/// unless another is given
+pub(crate) fn parse_sources(
+    sources: &[Source],
+    target: &Target,
+    stdlib: Option<&Source>,
+    source_map: &mut SourceMap,
+) -> Result<(ast::Module, environment::Environment), Vec<Diagnostic>> {
+    let inputs = sources
+        .iter()
+        .map(|source| (Path::new(&source.name), source.text.clone()))
+        .collect();
+    io::loader::load_program(inputs, source_map)?;
+
+    match stdlib {
+        Some(stdlib) => source_map.add_file(&stdlib.name, stdlib.text.clone()),
+        None => source_map.add_file(target.stdlib_name, target.stdlib),
+    };
+
+    parser::parse_files(&source_map)
+}
+
+/// Formats a source file in the canonical layout, keeping its comments, or returns its syntax
+/// errors. Only the file itself is parsed, so the files it imports need not exist
+pub fn format(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let (module, _) = parser::parse_program(source)?;
+    Ok(formatter::format(&module, source))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::diagnostic::code;
+    use crate::{compile, target, Emit, Options, Source};
+
+    const COUNTER: &str = "contract Counter {
+  var count: Int = 0
+}
+
+Counter :: (any) {
+  public init() {}
+
+  public func increment() mutates (count) {
+    count += 1
+  }
+}
+";
+
+    #[test]
+    fn test_compile_in_memory() {
+        let options = Options {
+            emit: vec![Emit::Ast, Emit::MoveIR],
+            ..Default::default()
+        };
+        let artifacts = compile(
+            &[Source::new("counter.flint", COUNTER)],
+            target("libra").unwrap(),
+            &options,
+        )
+        .unwrap()
+        .artifacts;
+
+        let file_names: Vec<String> = artifacts.iter().map(|a| a.file_name()).collect();
+        assert_eq!(file_names, vec!["main.ast", "Counter.mvir"]);
+        assert!(artifacts[1].text().unwrap().contains("module Counter"));
+    }
+
+    #[test]
+    fn test_compile_reports_diagnostics() {
+        let diagnostics = compile(
+            &[Source::new(
+                "counter.flint",
+                COUNTER.replace("count += 1", "cont += 1"),
+            )],
+            target("libra").unwrap(),
+            &Options::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some(code::UNDECLARED_IDENTIFIER));
+    }
+}
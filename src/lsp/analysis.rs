@@ -0,0 +1,1081 @@
+use crate::ast::*;
+use crate::ast_processor::analyse;
+use crate::context::{Context, ScopeContext};
+use crate::diagnostic::{Diagnostic, Severity, SourceMap};
+use crate::environment::{Environment, FLINT_RUNTIME_PREFIX};
+use crate::lsp::protocol::{path_to_uri, range, Position};
+use crate::target::Target;
+use crate::type_checker::ExpressionChecker;
+use crate::visitor::Visitor;
+use crate::{io, parser};
+use itertools::Itertools;
+use json::JsonValue;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// A document checked as the compiler checks it, along with the files it imports and the standard
+/// library. The module and context the passes leave behind answer questions about the document
+pub struct Analysis {
+    uri: String,
+    text: String,
+    source_map: SourceMap,
+    start: usize,
+    program: Option<(Module, Context)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// What the cursor is on, with the identifier it is on
+enum Symbol {
+    Expression {
+        expression: Expression,
+        enclosing_type: String,
+        type_states: Vec<TypeState>,
+        caller_protections: Vec<CallerProtection>,
+        scope: ScopeContext,
+    },
+    Variable(VariableDeclaration),
+    Parameter(Parameter),
+    Function(FunctionSignatureDeclaration),
+    Type(String),
+    State {
+        contract: String,
+        state: String,
+    },
+}
+
+impl Analysis {
+    pub fn new(uri: &str, path: &str, text: &str, target: &Target) -> Analysis {
+        let mut source_map = SourceMap::default();
+        let mut diagnostics = vec![];
+        if let Err(errors) =
+            io::loader::load_program(vec![(Path::new(path), text.to_string())], &mut source_map)
+        {
+            diagnostics.extend(errors);
+        }
+        source_map.add_file(target.stdlib_name, target.stdlib);
+        let start = source_map.file_offset(path).unwrap_or_default();
+
+        let program = match parser::parse_files(&source_map) {
+            Ok((module, environment)) => {
+                // The passes are written for programs which compile, and can panic on ones which
+                // are half written. The document is then checked again when it next changes
+                let analysed =
+                    panic::catch_unwind(AssertUnwindSafe(|| analyse(module, environment, target)));
+                analysed.ok().map(|(module, context, errors)| {
+                    diagnostics.extend(errors);
+                    (module, context)
+                })
+            }
+            Err(errors) => {
+                diagnostics.extend(errors);
+                None
+            }
+        };
+
+        Analysis {
+            uri: uri.to_string(),
+            text: text.to_string(),
+            source_map,
+            start,
+            program,
+            diagnostics,
+        }
+    }
+
+    /// Whether the document could be parsed and checked, so that it can be queried
+    pub fn is_checked(&self) -> bool {
+        self.program.is_some()
+    }
+
+    /// Diagnostics found in other files, such as an imported file, are shown at the start of the
+    /// document, as the client only asked about this one
+    pub fn diagnostics(&self) -> JsonValue {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let range = diagnostic
+                    .primary
+                    .as_ref()
+                    .and_then(|label| self.range(&label.line_info, self.word_length(&label.line_info)))
+                    .unwrap_or_else(|| range(Position::default(), Position::default()));
+                let message = diagnostic
+                    .notes
+                    .iter()
+                    .chain(&diagnostic.suggestions)
+                    .fold(diagnostic.message.clone(), |message, note| {
+                        format!("{}\n{}", message, note)
+                    });
+                let related = diagnostic
+                    .secondary
+                    .iter()
+                    .filter_map(|label| {
+                        Some(json::object! {
+                            location: self.location(&label.line_info, self.word_length(&label.line_info))?,
+                            message: label.message.clone().unwrap_or_default(),
+                        })
+                    })
+                    .collect::<json::Array>();
+
+                json::object! {
+                    range: range,
+                    severity: match diagnostic.severity {
+                        Severity::Error => 1,
+                        Severity::Warning => 2,
+                    },
+                    code: diagnostic.code,
+                    source: "flint",
+                    message: message,
+                    relatedInformation: related,
+                }
+            })
+            .collect::<json::Array>()
+            .into()
+    }
+
+    pub fn hover(&mut self, position: Position) -> Option<JsonValue> {
+        let (identifier, symbol) = self.locate(position)?;
+        let environment = &self.program.as_ref()?.1.environment;
+
+        let text = match symbol {
+            Symbol::Expression {
+                expression,
+                enclosing_type,
+                type_states,
+                caller_protections,
+                scope,
+            } => {
+                if let Expression::FunctionCall(call) = &expression {
+                    let enclosing_type = call
+                        .identifier
+                        .enclosing_type
+                        .as_ref()
+                        .unwrap_or(&enclosing_type);
+                    if let Some(function) =
+                        function(environment, enclosing_type, &call.identifier.token)
+                    {
+                        return self.hover_text(&identifier, signature(&function.declaration.head));
+                    }
+                }
+
+                let expression_type = environment.get_expression_type(
+                    &expression,
+                    &enclosing_type,
+                    &type_states,
+                    &caller_protections,
+                    &scope,
+                );
+                if let Type::Error = expression_type {
+                    return None;
+                }
+                format!("{}: {}", identifier.token, expression_type)
+            }
+            Symbol::Variable(declaration) => format!(
+                "{} {}: {}",
+                declaration.declaration_token.as_deref().unwrap_or("var"),
+                declaration.identifier.token,
+                declaration.variable_type
+            ),
+            Symbol::Parameter(parameter) => format!(
+                "{}: {}",
+                parameter.identifier.token, parameter.type_assignment
+            ),
+            Symbol::Function(head) => signature(&head),
+            Symbol::Type(name) => format!("{} {}", type_keyword(environment, &name)?, name),
+            Symbol::State { contract, state } => format!("{} @({})", contract, state),
+        };
+        self.hover_text(&identifier, text)
+    }
+
+    pub fn definition(&mut self, position: Position) -> Option<JsonValue> {
+        let (_, symbol) = self.locate(position)?;
+        let environment = &self.program.as_ref()?.1.environment;
+
+        let definition = match symbol {
+            Symbol::Expression {
+                expression: Expression::Identifier(identifier),
+                enclosing_type,
+                scope,
+                ..
+            } => {
+                let local = scope
+                    .declaration(&identifier.token)
+                    .filter(|_| identifier.enclosing_type.is_none())
+                    .map(|declaration| declaration.identifier);
+                let enclosing_type = identifier
+                    .enclosing_type
+                    .as_ref()
+                    .unwrap_or(&enclosing_type);
+                local
+                    .or_else(|| {
+                        environment
+                            .types
+                            .get(enclosing_type)?
+                            .properties
+                            .get(&identifier.token)
+                            .map(|property| property.property.get_identifier())
+                    })
+                    .or_else(|| type_declaration(environment, &identifier.token).cloned())
+            }
+            Symbol::Expression {
+                expression: Expression::FunctionCall(call),
+                enclosing_type,
+                ..
+            } => {
+                let enclosing_type = call
+                    .identifier
+                    .enclosing_type
+                    .as_ref()
+                    .unwrap_or(&enclosing_type);
+                function(environment, enclosing_type, &call.identifier.token)
+                    .map(|function| function.declaration.head.identifier.clone())
+                    .or_else(|| type_declaration(environment, &call.identifier.token).cloned())
+            }
+            Symbol::Expression { .. } => None,
+            Symbol::Variable(declaration) => Some(declaration.identifier),
+            Symbol::Parameter(parameter) => Some(parameter.identifier),
+            Symbol::Function(head) => Some(head.identifier),
+            Symbol::Type(name) => type_declaration(environment, &name).cloned(),
+            Symbol::State { contract, state } => environment
+                .types
+                .get(&contract)?
+                .type_states
+                .iter()
+                .find(|type_state| type_state.identifier.token == state)
+                .map(|type_state| type_state.identifier.clone()),
+        }?;
+
+        self.location(&definition.line_info, definition.token.len())
+    }
+
+    /// Completes the word before the cursor in the text, which may have been edited since it was
+    /// analysed. After a `.`, the members of the type of the expression before it are offered,
+    /// otherwise the variables, members and types in scope
+    pub fn completion(&mut self, text: &str, position: Position) -> JsonValue {
+        let line_start = text[..position.offset(text)]
+            .rfind('\n')
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let before = &text[line_start..position.offset(text)];
+        let before = before.trim_end_matches(is_identifier_char);
+        let receiver = before.strip_suffix('.').map(|receiver| {
+            let start = receiver
+                .rfind(|c: char| !is_identifier_char(c) && c != '.')
+                .map(|index| index + 1)
+                .unwrap_or(0);
+            receiver[start..].split('.').collect::<Vec<_>>()
+        });
+
+        let offset = self.start + position.offset(&self.text).min(self.text.len());
+        let (module, context) = match &mut self.program {
+            Some(program) => program,
+            None => return JsonValue::new_array(),
+        };
+        let mut finder = ScopeFinder {
+            start: self.start,
+            offset,
+            found: None,
+        };
+        let _ = module.visit(&mut finder, context);
+        let (enclosing_type, scope) = finder.found.unwrap_or_default();
+        let environment = &context.environment;
+
+        let mut items = vec![];
+        match receiver {
+            Some(receiver) => {
+                if let Some(receiver_type) =
+                    receiver_type(environment, &scope, &enclosing_type, &receiver)
+                {
+                    items.extend(member_items(environment, &receiver_type));
+                }
+            }
+            None => {
+                for declaration in scope.local_variables.iter().rev() {
+                    items.push(completion_item(
+                        &declaration.identifier.token,
+                        6,
+                        declaration.variable_type.to_string(),
+                    ));
+                }
+                for parameter in &scope.parameters {
+                    items.push(completion_item(
+                        &parameter.identifier.token,
+                        6,
+                        parameter.type_assignment.to_string(),
+                    ));
+                }
+                items.extend(member_items(environment, &enclosing_type));
+                for (declarations, kind) in &[
+                    (&environment.contract_declarations, 7),
+                    (&environment.struct_declarations, 22),
+                    (&environment.asset_declarations, 22),
+                    (&environment.trait_declarations, 8),
+                    (&environment.enum_declarations, 13),
+                    (&environment.event_declarations, 23),
+                ] {
+                    for declaration in declarations.iter() {
+                        items.push(completion_item(&declaration.token, *kind, String::new()));
+                    }
+                }
+            }
+        }
+
+        items
+            .into_iter()
+            .filter(|item| {
+                let label = item["label"].as_str().unwrap_or_default();
+                !is_reserved_identifier(label) && !label.starts_with(FLINT_RUNTIME_PREFIX)
+            })
+            .unique_by(|item| item["label"].to_string())
+            .collect::<json::Array>()
+            .into()
+    }
+
+    /// The contracts, protection blocks and other declarations in the document, with their members
+    pub fn symbols(&self) -> JsonValue {
+        let module = match &self.program {
+            Some((module, _)) => module,
+            None => return JsonValue::new_array(),
+        };
+
+        let mut declarations = module
+            .declarations
+            .iter()
+            .filter_map(|declaration| {
+                let identifier = declaration_identifier(declaration);
+                self.position(&identifier.line_info)
+                    .map(|position| (position, identifier, declaration))
+            })
+            .collect::<Vec<_>>();
+        declarations.sort_by_key(|(position, _, _)| (position.line, position.character));
+
+        let end = Position::at_offset(&self.text, self.text.len());
+        let starts = declarations
+            .iter()
+            .skip(1)
+            .map(|(position, _, _)| Position {
+                line: position.line,
+                character: 0,
+            })
+            .chain(std::iter::once(end))
+            .collect::<Vec<_>>();
+
+        declarations
+            .iter()
+            .zip(starts)
+            .filter_map(|((position, identifier, declaration), end)| {
+                let (name, kind, children) = self.declaration_symbol(declaration);
+                let start = Position {
+                    line: position.line,
+                    character: 0,
+                };
+                Some(json::object! {
+                    name: name,
+                    kind: kind,
+                    range: range(start, end),
+                    selectionRange: self.range(&identifier.line_info, identifier.token.len())?,
+                    children: children,
+                })
+            })
+            .collect::<json::Array>()
+            .into()
+    }
+
+    fn declaration_symbol(&self, declaration: &TopLevelDeclaration) -> (String, u8, json::Array) {
+        let mut children = vec![];
+        let (name, kind) = match declaration {
+            TopLevelDeclaration::ContractDeclaration(contract) => {
+                for member in &contract.contract_members {
+                    match member {
+                        ContractMember::VariableDeclaration(declaration, _) => {
+                            children.push((&declaration.identifier, variable_kind(declaration)))
+                        }
+                        ContractMember::EventDeclaration(event) => {
+                            children.push((&event.identifier, 24))
+                        }
+                        ContractMember::Invariant(_) => {}
+                    }
+                }
+                for type_state in &contract.type_states {
+                    children.push((&type_state.identifier, 22));
+                }
+                (contract.identifier.token.clone(), 5)
+            }
+            TopLevelDeclaration::ContractBehaviourDeclaration(behaviour) => {
+                for member in &behaviour.members {
+                    if let ContractBehaviourMember::FunctionDeclaration(function) = member {
+                        children.push((&function.head.identifier, 6));
+                    }
+                }
+                (protection_block_name(behaviour), 3)
+            }
+            TopLevelDeclaration::StructDeclaration(declaration) => {
+                for member in &declaration.members {
+                    match member {
+                        StructMember::VariableDeclaration(declaration, _) => {
+                            children.push((&declaration.identifier, variable_kind(declaration)))
+                        }
+                        StructMember::FunctionDeclaration(function) => {
+                            children.push((&function.head.identifier, 6))
+                        }
+                        StructMember::SpecialDeclaration(_) | StructMember::Invariant(_) => {}
+                    }
+                }
+                (declaration.identifier.token.clone(), 23)
+            }
+            TopLevelDeclaration::AssetDeclaration(declaration) => {
+                for member in &declaration.members {
+                    match member {
+                        AssetMember::VariableDeclaration(declaration) => {
+                            children.push((&declaration.identifier, variable_kind(declaration)))
+                        }
+                        AssetMember::FunctionDeclaration(function) => {
+                            children.push((&function.head.identifier, 6))
+                        }
+                        AssetMember::SpecialDeclaration(_) => {}
+                    }
+                }
+                (declaration.identifier.token.clone(), 23)
+            }
+            TopLevelDeclaration::EnumDeclaration(declaration) => {
+                for member in &declaration.members {
+                    children.push((&member.identifier, 22));
+                }
+                (declaration.identifier.token.clone(), 10)
+            }
+            TopLevelDeclaration::TraitDeclaration(declaration) => {
+                for member in &declaration.members {
+                    match member {
+                        TraitMember::FunctionDeclaration(function) => {
+                            children.push((&function.head.identifier, 6))
+                        }
+                        TraitMember::FunctionSignatureDeclaration(signature) => {
+                            children.push((&signature.identifier, 6))
+                        }
+                        TraitMember::EventDeclaration(event) => {
+                            children.push((&event.identifier, 24))
+                        }
+                        _ => {}
+                    }
+                }
+                (declaration.identifier.token.clone(), 11)
+            }
+        };
+
+        let children = children
+            .into_iter()
+            .filter_map(|(identifier, kind)| {
+                let range = self.range(&identifier.line_info, identifier.token.len())?;
+                Some(json::object! {
+                    name: &*identifier.token,
+                    kind: kind,
+                    range: range.clone(),
+                    selectionRange: range,
+                })
+            })
+            .collect();
+        (name, kind, children)
+    }
+
+    fn locate(&mut self, position: Position) -> Option<(Identifier, Symbol)> {
+        let offset = self.start + position.offset(&self.text);
+        let (module, context) = self.program.as_mut()?;
+        let mut locator = Locator {
+            offset,
+            symbol: None,
+        };
+        let _ = module.visit(&mut locator, context);
+        locator.symbol
+    }
+
+    fn hover_text(&self, identifier: &Identifier, text: String) -> Option<JsonValue> {
+        Some(json::object! {
+            contents: {
+                kind: "markdown",
+                value: format!("```flint\n{}\n```", text),
+            },
+            range: self.range(&identifier.line_info, identifier.token.len()),
+        })
+    }
+
+    /// The position in the document, if the node is in it
+    fn position(&self, line_info: &LineInfo) -> Option<Position> {
+        if line_info.line == 0
+            || line_info.offset < self.start
+            || line_info.offset > self.start + self.text.len()
+        {
+            return None;
+        }
+        Some(Position::at_offset(
+            &self.text,
+            line_info.offset - self.start,
+        ))
+    }
+
+    fn range(&self, line_info: &LineInfo, length: usize) -> Option<JsonValue> {
+        let start = self.position(line_info)?;
+        let end = Position::at_offset(&self.text, line_info.offset - self.start + length);
+        Some(range(start, end))
+    }
+
+    /// Nodes in other files are found on disk, unless they are in the embedded standard library
+    fn location(&self, line_info: &LineInfo, length: usize) -> Option<JsonValue> {
+        if let Some(range) = self.range(line_info, length) {
+            return Some(json::object! { uri: &*self.uri, range: range });
+        }
+
+        let location = self.source_map.locate(line_info)?;
+        let path = Path::new(location.file).canonicalize().ok()?;
+        let preceding = location
+            .source_line
+            .chars()
+            .take(location.column - 1)
+            .collect::<String>();
+        let start = Position {
+            line: location.line - 1,
+            character: preceding.encode_utf16().count(),
+        };
+        let end = Position {
+            character: start.character + length,
+            ..start
+        };
+        Some(json::object! {
+            uri: path_to_uri(&path.to_string_lossy()),
+            range: range(start, end),
+        })
+    }
+
+    fn word_length(&self, line_info: &LineInfo) -> usize {
+        let offset = line_info.offset.saturating_sub(self.start);
+        self.text
+            .get(offset..)
+            .map(|rest| {
+                rest.find(|c: char| !is_identifier_char(c))
+                    .unwrap_or(rest.len())
+                    .max(1)
+            })
+            .unwrap_or(1)
+    }
+}
+
+/// Finds the innermost named node at an offset. Nodes are visited from the outside in, so the first
+/// one found is kept unless a later one is nested inside it
+struct Locator {
+    offset: usize,
+    symbol: Option<(Identifier, Symbol)>,
+}
+
+impl Locator {
+    fn contains(&self, identifier: &Identifier) -> bool {
+        identifier.line_info.line != 0
+            && identifier.line_info.offset <= self.offset
+            && self.offset <= identifier.line_info.offset + identifier.token.len()
+    }
+
+    fn found(&mut self, identifier: &Identifier, symbol: Symbol) {
+        if self.symbol.is_none() && self.contains(identifier) {
+            self.symbol = Some((identifier.clone(), symbol));
+        }
+    }
+
+    fn found_expression(&mut self, identifier: &Identifier, expression: Expression, ctx: &Context) {
+        if self.symbol.is_none() && self.contains(identifier) {
+            let enclosing_type = ctx
+                .enclosing_type_identifier()
+                .map(|identifier| identifier.token.clone())
+                .unwrap_or_default();
+            self.found(
+                identifier,
+                Symbol::Expression {
+                    expression,
+                    enclosing_type,
+                    type_states: ctx.type_states().to_vec(),
+                    caller_protections: ctx.caller_protections().to_vec(),
+                    scope: ctx.scope_or_default().clone(),
+                },
+            );
+        }
+    }
+
+    fn found_states(&mut self, contract: &Identifier, type_states: &[TypeState]) {
+        for type_state in type_states {
+            self.found(
+                &type_state.identifier,
+                Symbol::State {
+                    contract: contract.token.clone(),
+                    state: type_state.identifier.token.clone(),
+                },
+            );
+        }
+    }
+}
+
+impl Visitor for Locator {
+    fn start_contract_declaration(
+        &mut self,
+        t: &mut ContractDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.found(&t.identifier, Symbol::Type(t.identifier.token.clone()));
+        self.found_states(&t.identifier, &t.type_states);
+        Ok(())
+    }
+
+    fn start_contract_behaviour_declaration(
+        &mut self,
+        t: &mut ContractBehaviourDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.found(&t.identifier, Symbol::Type(t.identifier.token.clone()));
+        self.found_states(&t.identifier, &t.type_states);
+        Ok(())
+    }
+
+    fn start_struct_declaration(
+        &mut self,
+        t: &mut StructDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.found(&t.identifier, Symbol::Type(t.identifier.token.clone()));
+        Ok(())
+    }
+
+    fn start_asset_declaration(&mut self, t: &mut AssetDeclaration, _ctx: &mut Context) -> VResult {
+        self.found(&t.identifier, Symbol::Type(t.identifier.token.clone()));
+        Ok(())
+    }
+
+    fn start_trait_declaration(&mut self, t: &mut TraitDeclaration, _ctx: &mut Context) -> VResult {
+        self.found(&t.identifier, Symbol::Type(t.identifier.token.clone()));
+        Ok(())
+    }
+
+    fn start_variable_declaration(
+        &mut self,
+        t: &mut VariableDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.found(&t.identifier, Symbol::Variable(t.clone()));
+        Ok(())
+    }
+
+    fn start_function_declaration(
+        &mut self,
+        t: &mut FunctionDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.found(&t.head.identifier, Symbol::Function(t.head.clone()));
+        Ok(())
+    }
+
+    fn start_statement(&mut self, t: &mut Statement, ctx: &mut Context) -> VResult {
+        if let Statement::BecomeStatement(statement) = t {
+            if let Some(contract) = ctx.enclosing_type_identifier().cloned() {
+                self.found_states(&contract, std::slice::from_ref(&statement.state));
+            }
+        }
+        Ok(())
+    }
+
+    fn start_caller_protection(&mut self, t: &mut CallerProtection, ctx: &mut Context) -> VResult {
+        self.found_expression(
+            &t.identifier,
+            Expression::Identifier(t.identifier.clone()),
+            ctx,
+        );
+        Ok(())
+    }
+
+    fn start_expression(&mut self, t: &mut Expression, ctx: &mut Context) -> VResult {
+        match &*t {
+            Expression::Identifier(identifier) => self.found_expression(identifier, t.clone(), ctx),
+            Expression::FunctionCall(call) => {
+                self.found_expression(&call.identifier, t.clone(), ctx)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn start_parameter(&mut self, t: &mut Parameter, _ctx: &mut Context) -> VResult {
+        self.found(&t.identifier, Symbol::Parameter(t.clone()));
+        Ok(())
+    }
+
+    fn start_type(&mut self, t: &mut Type, _ctx: &mut Context) -> VResult {
+        if let Type::UserDefinedType(identifier) = t {
+            self.found(identifier, Symbol::Type(identifier.token.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Finds the scope at an offset, from the last node in the document before it
+struct ScopeFinder {
+    start: usize,
+    offset: usize,
+    found: Option<(String, ScopeContext)>,
+}
+
+impl ScopeFinder {
+    fn record(&mut self, line_info: &LineInfo, ctx: &Context, scope: ScopeContext) {
+        if line_info.line != 0 && self.start <= line_info.offset && line_info.offset <= self.offset
+        {
+            let enclosing_type = ctx
+                .enclosing_type_identifier()
+                .map(|identifier| identifier.token.clone())
+                .unwrap_or_default();
+            self.found = Some((enclosing_type, scope));
+        }
+    }
+}
+
+impl Visitor for ScopeFinder {
+    fn finish_variable_declaration(
+        &mut self,
+        t: &mut VariableDeclaration,
+        ctx: &mut Context,
+    ) -> VResult {
+        self.record(&t.identifier.line_info, ctx, ctx.scope_or_default().clone());
+        Ok(())
+    }
+
+    /// The parameters are only added to the scope after the signature is visited
+    fn start_function_declaration(
+        &mut self,
+        t: &mut FunctionDeclaration,
+        ctx: &mut Context,
+    ) -> VResult {
+        let mut scope = ctx.scope_or_default().clone();
+        scope.parameters.extend(t.head.parameters.iter().cloned());
+        self.record(&t.head.identifier.line_info, ctx, scope);
+        Ok(())
+    }
+
+    fn start_expression(&mut self, t: &mut Expression, ctx: &mut Context) -> VResult {
+        self.record(&t.get_line_info(), ctx, ctx.scope_or_default().clone());
+        Ok(())
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn function<'a>(
+    environment: &'a Environment,
+    type_id: &str,
+    name: &str,
+) -> Option<&'a FunctionInformation> {
+    environment.types.get(type_id)?.functions.get(name)?.first()
+}
+
+fn type_declaration<'a>(environment: &'a Environment, name: &str) -> Option<&'a Identifier> {
+    environment
+        .contract_declarations
+        .iter()
+        .chain(&environment.struct_declarations)
+        .chain(&environment.asset_declarations)
+        .chain(&environment.trait_declarations)
+        .chain(&environment.enum_declarations)
+        .chain(&environment.event_declarations)
+        .find(|declaration| declaration.token == name)
+}
+
+fn type_keyword(environment: &Environment, name: &str) -> Option<&'static str> {
+    let declared = |declarations: &[Identifier]| {
+        declarations
+            .iter()
+            .any(|declaration| declaration.token == name)
+    };
+    if declared(&environment.contract_declarations) {
+        Some("contract")
+    } else if declared(&environment.struct_declarations) {
+        Some("struct")
+    } else if declared(&environment.asset_declarations) {
+        Some("asset")
+    } else if declared(&environment.trait_declarations) {
+        Some("trait")
+    } else if declared(&environment.enum_declarations) {
+        Some("enum")
+    } else if declared(&environment.event_declarations) {
+        Some("event")
+    } else {
+        None
+    }
+}
+
+fn signature(head: &FunctionSignatureDeclaration) -> String {
+    let parameters = head
+        .parameters
+        .iter()
+        .map(|parameter| {
+            format!(
+                "{}: {}",
+                parameter.identifier.token, parameter.type_assignment
+            )
+        })
+        .join(", ");
+    match &head.result_type {
+        Some(result_type) => format!(
+            "func {}({}) -> {}",
+            head.identifier.token, parameters, result_type
+        ),
+        None => format!("func {}({})", head.identifier.token, parameters),
+    }
+}
+
+fn protection_block_name(behaviour: &ContractBehaviourDeclaration) -> String {
+    let mut name = behaviour.identifier.token.clone();
+    if !behaviour.type_states.is_empty() {
+        let states = behaviour
+            .type_states
+            .iter()
+            .map(|state| &state.identifier.token)
+            .join(", ");
+        name.push_str(&format!(" @({})", states));
+    }
+    name.push_str(" :: ");
+    if let Some(binding) = &behaviour.caller_binding {
+        name.push_str(&format!("{} <- ", binding.token));
+    }
+    let protections = behaviour
+        .caller_protections
+        .iter()
+        .map(|protection| &protection.identifier.token)
+        .join(", ");
+    name.push_str(&format!("({})", protections));
+    name
+}
+
+fn declaration_identifier(declaration: &TopLevelDeclaration) -> &Identifier {
+    match declaration {
+        TopLevelDeclaration::ContractDeclaration(declaration) => &declaration.identifier,
+        TopLevelDeclaration::ContractBehaviourDeclaration(declaration) => &declaration.identifier,
+        TopLevelDeclaration::StructDeclaration(declaration) => &declaration.identifier,
+        TopLevelDeclaration::AssetDeclaration(declaration) => &declaration.identifier,
+        TopLevelDeclaration::EnumDeclaration(declaration) => &declaration.identifier,
+        TopLevelDeclaration::TraitDeclaration(declaration) => &declaration.identifier,
+    }
+}
+
+fn variable_kind(declaration: &VariableDeclaration) -> u8 {
+    if declaration.is_constant() {
+        14
+    } else {
+        8
+    }
+}
+
+/// Only user defined types have members
+fn user_defined_type(variable_type: &Type) -> Option<String> {
+    match variable_type {
+        Type::InoutType(inout) => user_defined_type(&inout.key_type),
+        Type::UserDefinedType(identifier) => Some(identifier.token.clone()),
+        _ => None,
+    }
+}
+
+fn receiver_type(
+    environment: &Environment,
+    scope: &ScopeContext,
+    enclosing_type: &str,
+    receiver: &[&str],
+) -> Option<String> {
+    let (first, rest) = receiver.split_first()?;
+    let mut receiver_type = if *first == "self" {
+        enclosing_type.to_string()
+    } else if let Some(variable_type) = scope.type_for(first) {
+        user_defined_type(&variable_type)?
+    } else if let Some(property) = environment
+        .types
+        .get(enclosing_type)
+        .and_then(|info| info.properties.get(*first))
+    {
+        user_defined_type(property.get_type())?
+    } else if environment.types.contains_key(*first) {
+        first.to_string()
+    } else {
+        return None;
+    };
+
+    for member in rest {
+        let property = environment
+            .types
+            .get(&receiver_type)?
+            .properties
+            .get(*member)?;
+        receiver_type = user_defined_type(property.get_type())?;
+    }
+    Some(receiver_type)
+}
+
+fn member_items(environment: &Environment, type_id: &str) -> Vec<JsonValue> {
+    let info = match environment.types.get(type_id) {
+        Some(info) => info,
+        None => return vec![],
+    };
+
+    let properties = info
+        .ordered_properties
+        .iter()
+        .chain(info.properties.keys().sorted())
+        .filter_map(|name| {
+            let property = info.properties.get(name)?;
+            let kind = match property.property {
+                Property::VariableDeclaration(_, _) => 5,
+                Property::EnumCase(_) => 20,
+            };
+            Some(completion_item(name, kind, property.get_type().to_string()))
+        });
+    let functions = info
+        .functions
+        .iter()
+        .sorted_by_key(|(name, _)| *name)
+        .filter_map(|(name, functions)| {
+            let function = functions.first()?;
+            Some(completion_item(
+                name,
+                2,
+                signature(&function.declaration.head),
+            ))
+        });
+    properties.chain(functions).collect()
+}
+
+fn completion_item(label: &str, kind: u8, detail: String) -> JsonValue {
+    json::object! {
+        label: label,
+        kind: kind,
+        detail: detail,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lsp::analysis::Analysis;
+    use crate::lsp::protocol::Position;
+    use crate::target;
+
+    const COUNTER: &str = "contract Counter (Counting, Stopped) {
+  var count: Int = 0
+  var owner: Address
+}
+
+Counter :: caller <- (any) {
+  public init() {
+    self.owner = caller
+    become Counting
+  }
+}
+
+Counter @(Counting) :: (owner) {
+  public func increment(amount: Int) mutates (count) {
+    count += amount
+  }
+
+  public func total() -> Int {
+    let current: Int = count
+    return current
+  }
+}
+";
+
+    fn analyse(text: &str) -> Analysis {
+        Analysis::new(
+            "file:///counter.flint",
+            "/counter.flint",
+            text,
+            &target("libra").unwrap(),
+        )
+    }
+
+    fn hover(analysis: &mut Analysis, line: usize, character: usize) -> String {
+        let hover = analysis.hover(Position { line, character }).unwrap();
+        hover["contents"]["value"].to_string()
+    }
+
+    #[test]
+    fn test_hover_and_definition() {
+        let mut analysis = analyse(COUNTER);
+        assert!(analysis.is_checked());
+        let diagnostics = analysis.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["code"], "W0001");
+        assert_eq!(diagnostics[0]["severity"], 2);
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 28);
+
+        assert_eq!(hover(&mut analysis, 14, 6), "```flint\ncount: Int\n```");
+        assert_eq!(hover(&mut analysis, 14, 15), "```flint\namount: Int\n```");
+        assert_eq!(
+            hover(&mut analysis, 13, 15),
+            "```flint\nfunc increment(amount: Int)\n```"
+        );
+        assert_eq!(
+            hover(&mut analysis, 18, 9),
+            "```flint\nlet current: Int\n```"
+        );
+        assert_eq!(
+            hover(&mut analysis, 12, 12),
+            "```flint\nCounter @(Counting)\n```"
+        );
+
+        let definition = |analysis: &mut Analysis, line, character| {
+            let definition = analysis.definition(Position { line, character }).unwrap();
+            assert_eq!(definition["uri"], "file:///counter.flint");
+            let start = &definition["range"]["start"];
+            (
+                start["line"].as_usize().unwrap(),
+                start["character"].as_usize().unwrap(),
+            )
+        };
+        assert_eq!(definition(&mut analysis, 19, 12), (18, 8));
+        assert_eq!(definition(&mut analysis, 7, 10), (2, 6));
+        assert_eq!(definition(&mut analysis, 12, 25), (2, 6));
+        assert_eq!(definition(&mut analysis, 8, 12), (0, 18));
+    }
+
+    #[test]
+    fn test_completion_and_symbols() {
+        let mut analysis = analyse(COUNTER);
+        let edited = COUNTER.replace("    let current", "    self.\n    let current");
+        let items = analysis.completion(
+            &edited,
+            Position {
+                line: 18,
+                character: 9,
+            },
+        );
+        let labels: Vec<&str> = items
+            .members()
+            .filter_map(|item| item["label"].as_str())
+            .collect();
+        assert_eq!(labels, vec!["count", "owner", "increment", "total"]);
+
+        let symbols = analysis.symbols();
+        let names: Vec<&str> = symbols
+            .members()
+            .filter_map(|symbol| symbol["name"].as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Counter",
+                "Counter :: caller <- (any)",
+                "Counter @(Counting) :: (owner)"
+            ]
+        );
+        assert_eq!(symbols[2]["children"][0]["name"], "increment");
+        assert_eq!(symbols[0]["range"]["end"]["line"], 5);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let analysis = analyse(&COUNTER.replace("count += amount", "cont += amount"));
+        let diagnostics = analysis.diagnostics();
+        assert_eq!(diagnostics[0]["code"], "E0002");
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 14);
+        assert_eq!(diagnostics[0]["range"]["end"]["character"], 8);
+
+        assert!(!analyse("contract {").is_checked());
+    }
+}
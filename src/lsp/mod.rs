@@ -0,0 +1,274 @@
+//! A language server for Flint, speaking the Language Server Protocol over stdio. Each open
+//! document is checked as the compiler would check it whenever it changes, and the module and
+//! environment the passes leave behind answer hover, go to definition, completion and document
+//! symbol requests
+
+mod analysis;
+mod protocol;
+
+use self::analysis::Analysis;
+use self::protocol::{read_message, uri_to_path, write_message, Position};
+use crate::target::Target;
+use json::JsonValue;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+
+/// The target programs are checked for, which clients can set with the `target` initialization
+/// option
+const DEFAULT_TARGET: &str = "libra";
+
+struct Document {
+    text: String,
+    /// The last analysis which could be checked, kept while the document does not parse
+    analysis: Option<Analysis>,
+}
+
+struct Server<W: Write> {
+    output: W,
+    target: String,
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+/// Serves requests until the client exits. Returns whether the client asked the server to shut down
+/// before exiting, as the exit code should say
+pub fn run<R: BufRead, W: Write>(mut input: R, output: W) -> io::Result<bool> {
+    let mut server = Server {
+        output,
+        target: DEFAULT_TARGET.to_string(),
+        documents: HashMap::new(),
+        shutdown: false,
+    };
+
+    while let Some(message) = read_message(&mut input)? {
+        if message["method"] == "exit" {
+            break;
+        }
+        server.handle(&message)?;
+    }
+    Ok(server.shutdown)
+}
+
+impl<W: Write> Server<W> {
+    fn handle(&mut self, message: &JsonValue) -> io::Result<()> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = &message["id"];
+
+        let result = match method {
+            "initialize" => {
+                if let Some(target) = params["initializationOptions"]["target"].as_str() {
+                    self.target = target.to_string();
+                }
+                Ok(json::object! {
+                    capabilities: {
+                        textDocumentSync: 1,
+                        hoverProvider: true,
+                        definitionProvider: true,
+                        completionProvider: {
+                            triggerCharacters: ["."],
+                        },
+                        documentSymbolProvider: true,
+                    },
+                    serverInfo: {
+                        name: "flint-lsp",
+                        version: env!("CARGO_PKG_VERSION"),
+                    },
+                })
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                self.update(document["uri"].as_str(), document["text"].as_str())?;
+                return Ok(());
+            }
+            "textDocument/didChange" => {
+                // Only full changes are asked for, so the last one is the whole document
+                let text = params["contentChanges"]
+                    .members()
+                    .last()
+                    .and_then(|change| change["text"].as_str());
+                self.update(params["textDocument"]["uri"].as_str(), text)?;
+                return Ok(());
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+                    self.publish_diagnostics(uri, JsonValue::new_array())?;
+                }
+                return Ok(());
+            }
+            "textDocument/hover" => self.query(params, |analysis, _, position| {
+                analysis.hover(position).unwrap_or(JsonValue::Null)
+            }),
+            "textDocument/definition" => self.query(params, |analysis, _, position| {
+                analysis.definition(position).unwrap_or(JsonValue::Null)
+            }),
+            "textDocument/completion" => self.query(params, |analysis, text, position| {
+                analysis.completion(text, position)
+            }),
+            "textDocument/documentSymbol" => {
+                self.query(params, |analysis, _, _| analysis.symbols())
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method `{}`", method))),
+        };
+
+        // Notifications have no id, and are not answered
+        if id.is_null() {
+            return Ok(());
+        }
+        let response = match result {
+            Ok(result) => json::object! {
+                jsonrpc: "2.0",
+                id: id.clone(),
+                result: result,
+            },
+            Err((code, message)) => json::object! {
+                jsonrpc: "2.0",
+                id: id.clone(),
+                error: {
+                    code: code,
+                    message: message,
+                },
+            },
+        };
+        write_message(&mut self.output, &response)
+    }
+
+    fn update(&mut self, uri: Option<&str>, text: Option<&str>) -> io::Result<()> {
+        let (uri, text) = match (uri, text) {
+            (Some(uri), Some(text)) => (uri, text),
+            _ => return Ok(()),
+        };
+
+        let analysis = Analysis::new(uri, &uri_to_path(uri), text, &self.target());
+        let diagnostics = analysis.diagnostics();
+        let document = self
+            .documents
+            .entry(uri.to_string())
+            .or_insert_with(|| Document {
+                text: String::new(),
+                analysis: None,
+            });
+        document.text = text.to_string();
+        if analysis.is_checked() || document.analysis.is_none() {
+            document.analysis = Some(analysis);
+        }
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    /// Requests are answered from the last analysis of the document which could be checked
+    fn query<F>(&mut self, params: &JsonValue, answer: F) -> Result<JsonValue, (i32, String)>
+    where
+        F: FnOnce(&mut Analysis, &str, Position) -> JsonValue,
+    {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let document = self
+            .documents
+            .get_mut(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown document `{}`", uri)))?;
+        let position = Position::from_json(&params["position"]).unwrap_or_default();
+        Ok(match &mut document.analysis {
+            Some(analysis) => answer(analysis, &document.text, position),
+            None => JsonValue::Null,
+        })
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: JsonValue) -> io::Result<()> {
+        let notification = json::object! {
+            jsonrpc: "2.0",
+            method: "textDocument/publishDiagnostics",
+            params: {
+                uri: uri,
+                diagnostics: diagnostics,
+            },
+        };
+        write_message(&mut self.output, &notification)
+    }
+
+    /// An unknown target falls back to the default, as there is no way to report it to the user
+    /// before a document is opened
+    fn target(&self) -> Target {
+        crate::target(&self.target)
+            .unwrap_or_else(|| crate::target(DEFAULT_TARGET).expect("default target exists"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lsp::protocol::{read_message, write_message};
+    use crate::lsp::run;
+    use json::JsonValue;
+
+    fn session(messages: &[JsonValue]) -> Vec<JsonValue> {
+        let mut input = vec![];
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = vec![];
+        assert!(run(&input[..], &mut output).unwrap());
+
+        let mut output = &output[..];
+        let mut responses = vec![];
+        while let Some(response) = read_message(&mut output).unwrap() {
+            responses.push(response);
+        }
+        responses
+    }
+
+    #[test]
+    fn test_session() {
+        let text = "contract Counter {\n  var count: Int = 0\n}\n\nCounter :: (any) {\n  public init() {}\n\n  public func get() -> Int {\n    return cont\n  }\n}\n";
+        let responses = session(&[
+            json::object! { jsonrpc: "2.0", id: 1, method: "initialize", params: {} },
+            json::object! { jsonrpc: "2.0", method: "initialized", params: {} },
+            json::object! {
+                jsonrpc: "2.0",
+                method: "textDocument/didOpen",
+                params: {
+                    textDocument: {
+                        uri: "untitled:counter.flint",
+                        languageId: "flint",
+                        version: 1,
+                        text: text,
+                    },
+                },
+            },
+            json::object! {
+                jsonrpc: "2.0",
+                id: 2,
+                method: "textDocument/hover",
+                params: {
+                    textDocument: { uri: "untitled:counter.flint" },
+                    position: { line: 1, character: 7 },
+                },
+            },
+            json::object! { jsonrpc: "2.0", id: 3, method: "textDocument/formatting", params: {} },
+            json::object! { jsonrpc: "2.0", id: 4, method: "shutdown" },
+            json::object! { jsonrpc: "2.0", method: "exit" },
+        ]);
+
+        assert_eq!(responses.len(), 5);
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["hoverProvider"],
+            true
+        );
+        assert_eq!(responses[1]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(
+            responses[1]["params"]["diagnostics"][0]["range"]["start"]["line"],
+            8
+        );
+        assert_eq!(
+            responses[2]["result"]["contents"]["value"],
+            "```flint\nvar count: Int\n```"
+        );
+        assert_eq!(responses[3]["error"]["code"], -32601);
+        assert!(responses[4]["result"].is_null());
+    }
+}
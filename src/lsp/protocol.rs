@@ -0,0 +1,186 @@
+use json::JsonValue;
+use std::io::{self, BufRead, Write};
+
+/// Reads a message framed by a `Content-Length` header, returning `None` at the end of the input
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<JsonValue>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header")
+    })?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    let content = String::from_utf8(content)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    json::parse(&content)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &JsonValue) -> io::Result<()> {
+    let content = message.dump();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+/// Positions in the protocol count lines from zero and columns in UTF-16 code units
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+impl Position {
+    pub fn from_json(position: &JsonValue) -> Option<Position> {
+        Some(Position {
+            line: position["line"].as_usize()?,
+            character: position["character"].as_usize()?,
+        })
+    }
+
+    /// The byte offset of the position in the text, clamped to the end of its line
+    pub fn offset(self, text: &str) -> usize {
+        let mut line_start = 0;
+        for _ in 0..self.line {
+            match text[line_start..].find('\n') {
+                Some(index) => line_start += index + 1,
+                None => return text.len(),
+            }
+        }
+
+        let mut units = 0;
+        for (index, c) in text[line_start..].char_indices() {
+            if units >= self.character || c == '\n' {
+                return line_start + index;
+            }
+            units += c.len_utf16();
+        }
+        text.len()
+    }
+
+    pub fn at_offset(text: &str, offset: usize) -> Position {
+        let preceding = &text[..offset.min(text.len())];
+        let line_start = preceding.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        Position {
+            line: preceding.matches('\n').count(),
+            character: preceding[line_start..].encode_utf16().count(),
+        }
+    }
+
+    pub fn to_json(self) -> JsonValue {
+        json::object! {
+            line: self.line,
+            character: self.character,
+        }
+    }
+}
+
+pub fn range(start: Position, end: Position) -> JsonValue {
+    json::object! {
+        start: start.to_json(),
+        end: end.to_json(),
+    }
+}
+
+/// Converts between `file` URIs and paths. Other URIs, such as those of unsaved files, are used as
+/// the name of the file
+pub fn uri_to_path(uri: &str) -> String {
+    match uri.strip_prefix("file://") {
+        Some(path) => percent_decode(path),
+        None => uri.to_string(),
+    }
+}
+
+pub fn path_to_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = text
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lsp::protocol::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let mut output = vec![];
+        write_message(&mut output, &json::object! { id: 1, method: "shutdown" }).unwrap();
+        assert!(output.starts_with(b"Content-Length: 28\r\n\r\n"));
+
+        let message = read_message(&mut &output[..]).unwrap().unwrap();
+        assert_eq!(message["method"], "shutdown");
+        assert!(read_message(&mut &b""[..]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_positions() {
+        let text = "contract C {\n  var é: Int\n}\n";
+        let position = Position {
+            line: 1,
+            character: 7,
+        };
+        assert_eq!(position.offset(text), 21);
+        assert_eq!(Position::at_offset(text, 21), position);
+        assert_eq!(
+            Position {
+                line: 1,
+                character: 99
+            }
+            .offset(text),
+            26
+        );
+
+        assert_eq!(
+            uri_to_path(&path_to_uri("/tmp/my contract.flint")),
+            "/tmp/my contract.flint"
+        );
+    }
+}
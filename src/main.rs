@@ -1,47 +1,99 @@
-mod ewasm;
-mod ewasm_tests;
-
-mod ast;
-mod ast_processor;
-mod context;
-mod environment;
-mod io;
-mod moveir;
-mod parser;
-mod semantic_analysis;
-mod target;
-mod type_assigner;
-mod type_checker;
-mod utils;
-mod visitor;
-
-use self::io::*;
+use flint::io::prompt;
+use flint::{Source, SourceMap};
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
+use std::path::Path;
 use std::process::exit;
 
 fn main() {
-    let configuration = prompt::process(&mut env::args()).unwrap_or_else(|| exit(1));
+    let arguments: Vec<String> = env::args().collect();
+    if arguments.get(1).map(String::as_str) == Some("fmt") {
+        format(arguments);
+        return;
+    }
 
-    let mut file = File::open(&configuration.file)
-        .unwrap_or_else(|err| prompt::error::unable_to_open_file(&configuration.file, err));
+    let mut configuration =
+        prompt::process(&mut arguments.into_iter()).unwrap_or_else(|code| exit(code));
 
-    let mut program = String::new();
-    file.read_to_string(&mut program)
-        .unwrap_or_else(|err| prompt::error::unable_to_read_file(&configuration.file, err));
+    let sources: Vec<Source> = configuration
+        .inputs
+        .iter()
+        .map(|path| Source::new(path.to_string_lossy(), read_file(path)))
+        .collect();
+    if let Some(path) = &configuration.stdlib {
+        configuration.options.stdlib = Some(Source::new(path.to_string_lossy(), read_file(path)));
+    }
+
+    let error_format = configuration.error_format;
+    let mut source_map = SourceMap::default();
+    let compilation = flint::compile_with_source_map(
+        &sources,
+        configuration.target,
+        &configuration.options,
+        &mut source_map,
+    )
+    .unwrap_or_else(|err| prompt::error::compilation_failed(&err, &source_map, error_format));
+    prompt::error::compiled_with_warnings(&compilation.warnings, &source_map, error_format);
+
+    let out_dir = &configuration.out_dir;
+    fs::create_dir_all(out_dir)
+        .unwrap_or_else(|err| prompt::error::unable_to_write_file(out_dir, err));
+    for artifact in compilation.artifacts {
+        let path = out_dir.join(artifact.file_name());
+        fs::write(&path, &artifact.contents)
+            .unwrap_or_else(|err| prompt::error::unable_to_write_file(&path, err));
+        if !configuration.quiet {
+            println!("successfully wrote to {}", path.display());
+        }
+    }
+}
 
-    let mut file = File::open(&configuration.target.stdlib_path).unwrap_or_else(|err| {
-        prompt::error::unable_to_open_file(&configuration.target.stdlib_path, err)
-    });
+fn format(arguments: Vec<String>) {
+    let configuration =
+        prompt::process_format(&mut arguments.into_iter()).unwrap_or_else(|code| exit(code));
 
-    file.read_to_string(&mut program).unwrap_or_else(|err| {
-        prompt::error::unable_to_read_file(&configuration.target.stdlib_path, err)
-    });
+    let mut unformatted = false;
+    let mut failed = false;
+    for path in &configuration.inputs {
+        let source = read_file(path);
+        let formatted = match flint::format(&source) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                let mut source_map = SourceMap::default();
+                source_map.add_file(path.to_string_lossy(), source);
+                let error_format = configuration.error_format;
+                prompt::error::unable_to_format_file(path, &err, &source_map, error_format);
+                failed = true;
+                continue;
+            }
+        };
 
-    let (module, environment) =
-        parser::parse_program(&program).unwrap_or_else(|err| prompt::error::parse_failed(&*err));
+        if formatted == source {
+            continue;
+        }
+        if configuration.check {
+            println!("{} is not formatted", path.display());
+            unformatted = true;
+        } else {
+            fs::write(path, formatted)
+                .unwrap_or_else(|err| prompt::error::unable_to_write_file(path, err));
+        }
+    }
 
-    ast_processor::process_ast(module, environment, configuration.target)
-        .unwrap_or_else(|err| prompt::error::semantic_check_failed(&*err));
+    if failed {
+        exit(prompt::exit_code::SYNTAX);
+    } else if unformatted {
+        exit(prompt::exit_code::UNFORMATTED);
+    }
+}
+
+fn read_file(path: &Path) -> String {
+    let mut file =
+        File::open(path).unwrap_or_else(|err| prompt::error::unable_to_open_file(path, err));
+
+    let mut program = String::new();
+    file.read_to_string(&mut program)
+        .unwrap_or_else(|err| prompt::error::unable_to_read_file(path, err));
+    program
 }
@@ -17,12 +17,13 @@ use crate::ast::{
     mangle_dictionary, ArrayType, AssetDeclaration, BinOp, ContractBehaviourDeclaration,
     ContractBehaviourMember, ContractDeclaration, ContractMember, Expression, FixedSizedArrayType,
     Identifier, InoutType, Statement, StructDeclaration, TraitDeclaration, Type,
-    VariableDeclaration,
+    VariableDeclaration, GETTER_PREFIX,
 };
 use crate::context::ScopeContext;
 use crate::environment::Environment;
 use crate::moveir::identifier::MoveSelf;
 use crate::moveir::preprocessor::MovePreProcessor;
+use crate::target::libra;
 
 pub struct MoveContract {
     pub contract_declaration: ContractDeclaration,
@@ -39,7 +40,11 @@ impl MoveContract {
     pub(crate) fn generate(&self) -> String {
         let import_code = self.generate_imports();
 
-        let runtime_functions = MoveRuntimeFunction::get_all_functions().join("\n\n");
+        let mut runtime_functions = MoveRuntimeFunction::get_all_functions();
+        if self.has_array_caller_protection() {
+            runtime_functions.push(MoveRuntimeFunction::get_array_contains());
+        }
+        let runtime_functions = runtime_functions.join("\n\n");
         let functions = self
             .contract_behaviour_declarations
             .clone()
@@ -133,6 +138,7 @@ impl MoveContract {
             .asset_declarations
             .clone()
             .into_iter()
+            .filter(|a| a.identifier.token != libra::currency().identifier)
             .map(|a| MoveAsset {
                 declaration: a,
                 environment: self.environment.clone(),
@@ -269,7 +275,11 @@ impl MoveContract {
                 Option::from(self.environment.clone()),
             );
             let property_type = property_type.generate(&function_context);
-            let identifier = format!("__this_{}", property.identifier.token);
+            let identifier = format!(
+                "{}{}",
+                MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+                property.identifier.token
+            );
 
             field_declarations.push(MoveIRStatement::Expression(
                 MoveIRExpression::VariableDeclaration(MoveIRVariableDeclaration {
@@ -288,7 +298,11 @@ impl MoveContract {
 
         for property in properties {
             if let Some(ref expr) = property.expression {
-                let identifier = format!("__this_{}", property.identifier.token);
+                let identifier = format!(
+                    "{}{}",
+                    MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+                    property.identifier.token
+                );
 
                 if let crate::ast::expressions::Expression::ArrayLiteral(array) = &**expr {
                     let elements: Vec<MoveIRExpression> = array
@@ -421,7 +435,11 @@ impl MoveContract {
                 (
                     p.identifier.token.clone(),
                     MoveIRExpression::Transfer(MoveIRTransfer::Move(Box::from(
-                        MoveIRExpression::Identifier(format!("__this_{}", p.identifier.token)),
+                        MoveIRExpression::Identifier(format!(
+                            "{}{}",
+                            MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+                            p.identifier.token
+                        )),
                     ))),
                 )
             })
@@ -525,7 +543,11 @@ impl MoveContract {
 
         let initialiser: String;
         let publisher: String;
-        parameters.push(format!("account: {}", MovePreProcessor::SIGNER_TYPE));
+        parameters.push(format!(
+            "{}: {}",
+            MovePreProcessor::PUBLISHER_PARAM,
+            MovePreProcessor::SIGNER_TYPE
+        ));
         let parameters = parameters.join(", ");
 
         if !dict_names.is_empty() {
@@ -537,15 +559,21 @@ impl MoveContract {
             );
 
             params_values = if !params_values.is_empty() {
-                format!("{}, copy(account)", params_values)
+                format!(
+                    "{}, copy({})",
+                    params_values,
+                    MovePreProcessor::PUBLISHER_PARAM
+                )
             } else {
-                "copy(account)".to_string()
+                format!("copy({})", MovePreProcessor::PUBLISHER_PARAM)
             };
 
-            publisher = format!("public publish({params}) acquires {dict_names} {{ \n let t: Self.T; \nt = Self.new({values});\n move_to<T>(move(account), move(t)); \nreturn; \n }}",
+            publisher = format!("public publish({params}) acquires {dict_names} {{ \n let {contract}: Self.T; \n{contract} = Self.new({values});\n move_to<T>(move({account}), move({contract})); \nreturn; \n }}",
                                 params = parameters,
                                 dict_names = dict_names,
-                                values = params_values);
+                                values = params_values,
+                                contract = MovePreProcessor::CONTRACT_VARIABLE,
+                                account = MovePreProcessor::PUBLISHER_PARAM);
         } else {
             initialiser = format!(
                 "new({params}): Self.T {{ \n{body}\n }} \n",
@@ -553,9 +581,11 @@ impl MoveContract {
                 body = body,
             );
 
-            publisher = format!("public publish({params}) {{ \n let t: Self.T; \nt = Self.new({values});\n move_to<T>(move(account), move(t)); \nreturn; \n }}",
+            publisher = format!("public publish({params}) {{ \n let {contract}: Self.T; \n{contract} = Self.new({values});\n move_to<T>(move({account}), move({contract})); \nreturn; \n }}",
                                 params = parameters,
-                                values = params_values);
+                                values = params_values,
+                                contract = MovePreProcessor::CONTRACT_VARIABLE,
+                                account = MovePreProcessor::PUBLISHER_PARAM);
         }
 
         return format!("module {name} {{ \n  {imports} \n resource T {{ \n {members} \n }} {dict_resources} \n {assets}  \n {structs} \n {init} \n {publish}\n {asset_functions} \n \n {struct_functions} \n {functions} \n {runtime} \n {dict_runtime} }}"
@@ -566,6 +596,22 @@ impl MoveContract {
         );
     }
 
+    /// Whether a caller protection of the contract is an array of addresses, which is checked by
+    /// looking for the caller in it
+    fn has_array_caller_protection(&self) -> bool {
+        let contract = &self.contract_declaration.identifier.token;
+        self.contract_behaviour_declarations
+            .iter()
+            .flat_map(|declaration| &declaration.caller_protections)
+            .filter_map(|protection| self.environment.property(&protection.name(), contract))
+            .any(|property| {
+                matches!(
+                    property.property.get_type(),
+                    Type::ArrayType(_) | Type::FixedSizedArrayType(_)
+                )
+            })
+    }
+
     fn generate_imports(&self) -> String {
         let imports = self.external_traits.clone();
         let mut imports: Vec<MoveIRStatement> = imports
@@ -649,7 +695,7 @@ impl MoveContract {
                                 .generate(&function_context);
 
                             let f_name = format!("Self._insert_{}", r_name);
-                            let caller_argument = Identifier::generated("account");
+                            let caller_argument = Identifier::generated(MovePreProcessor::PUBLISHER_PARAM);
                             let caller_argument = MoveIdentifier {
                                 identifier: caller_argument.clone(),
                                 position: Default::default(),
@@ -669,7 +715,7 @@ impl MoveContract {
                 }
 
                 format!(
-                    "_get_{r_name}(_address_this: address): {r_type} acquires {r_name} {{
+                    "{getter}{r_name}(_address_this: address): {r_type} acquires {r_name} {{
     let this: &mut Self.{r_name};
     let temp: &{r_type};
     let result: {r_type};
@@ -695,6 +741,7 @@ impl MoveContract {
     }}
     return;
   }}",
+                    getter = GETTER_PREFIX,
                     r_name = r_name,
                     r_type = result_type
                 )
@@ -2,6 +2,7 @@ use super::function::FunctionContext;
 use super::ir::{MoveIRExpression, MoveIRFieldDeclaration, MoveIRVariableDeclaration};
 use super::r#type::MoveType;
 use crate::ast::VariableDeclaration;
+use crate::moveir::preprocessor::MovePreProcessor;
 
 pub(crate) struct MoveFieldDeclaration {
     pub declaration: VariableDeclaration,
@@ -45,7 +46,7 @@ impl MoveVariableDeclaration {
 
         if self.declaration.identifier.is_self() {
             return MoveIRExpression::VariableDeclaration(MoveIRVariableDeclaration {
-                identifier: "this".to_string(),
+                identifier: MovePreProcessor::SELF_PARAM.to_string(),
                 declaration_type: ir_type,
             });
         }
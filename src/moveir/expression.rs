@@ -61,6 +61,7 @@ impl MoveExpression {
             }
             .generate(function_context),
             Expression::AttemptExpression(_) => panic!("Should have been removed in preprocessor"),
+            Expression::OldExpression(_) => panic!("Should have been removed in preprocessor"),
             Expression::Literal(l) => {
                 MoveIRExpression::Literal(MoveLiteralToken { token: l }.generate())
             }
@@ -299,7 +300,8 @@ impl MoveSubscriptExpression {
             }
             Type::DictionaryType(_) => {
                 let f_name = format!(
-                    "Self._get_{}",
+                    "Self.{}{}",
+                    GETTER_PREFIX,
                     mangle_dictionary(&self.expression.base_expression.token)
                 );
                 MoveIRExpression::FunctionCall(MoveIRFunctionCall {
@@ -171,6 +171,8 @@ impl MoveFunction {
                     new_statements.push(MoveIRStatement::Expression(new_expr));
                     release_references = remaining;
                 } else {
+                    // A later use the reference cannot be moved into must keep earlier ones copies
+                    release_references.retain(|reference| !copies_reference(reference, statement));
                     new_statements.push(statement.clone());
                 }
             }
@@ -3,6 +3,7 @@ use super::ir::{MoveIRExpression, MoveIROperation, MoveIRTransfer};
 use super::property_access::MovePropertyAccess;
 use super::MovePosition;
 use crate::ast::{Expression, Identifier};
+use crate::moveir::preprocessor::MovePreProcessor;
 use crate::target::libra;
 
 pub(crate) struct MoveIdentifier {
@@ -20,7 +21,8 @@ impl MoveIdentifier {
         // Checks the enclosing type of the identifier is the type of what we are in
         if Some(&function_context.enclosing_type) == self.identifier.enclosing_type.as_ref() {
             return if function_context.is_constructor {
-                let name = "__this_".to_owned() + &self.identifier.token.clone();
+                let name = MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX.to_owned()
+                    + &self.identifier.token.clone();
 
                 if let MovePosition::Left = self.position {
                     MoveIRExpression::Identifier(name)
@@ -149,6 +151,6 @@ impl MoveSelf {
     }
 
     pub fn name(&self) -> String {
-        "this".to_string()
+        MovePreProcessor::SELF_PARAM.to_string()
     }
 }
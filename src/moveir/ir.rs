@@ -396,6 +396,32 @@ pub enum MoveIROperation {
     Reference(Box<MoveIRExpression>),
 }
 
+impl MoveIROperation {
+    pub fn operands(&self) -> Vec<&MoveIRExpression> {
+        match self {
+            MoveIROperation::Add(l, r)
+            | MoveIROperation::Minus(l, r)
+            | MoveIROperation::Times(l, r)
+            | MoveIROperation::Divide(l, r)
+            | MoveIROperation::Modulo(l, r)
+            | MoveIROperation::GreaterThan(l, r)
+            | MoveIROperation::GreaterThanEqual(l, r)
+            | MoveIROperation::LessThan(l, r)
+            | MoveIROperation::LessThanEqual(l, r)
+            | MoveIROperation::Equal(l, r)
+            | MoveIROperation::NotEqual(l, r)
+            | MoveIROperation::And(l, r)
+            | MoveIROperation::Or(l, r)
+            | MoveIROperation::Power(l, r) => vec![l, r],
+            MoveIROperation::Not(e)
+            | MoveIROperation::Access(e, _)
+            | MoveIROperation::Dereference(e)
+            | MoveIROperation::MutableReference(e)
+            | MoveIROperation::Reference(e) => vec![e],
+        }
+    }
+}
+
 impl fmt::Display for MoveIROperation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -1,7 +1,6 @@
+use crate::target::output::{Artifact, Emit, Options};
+use crate::target::GenerationResult;
 use crate::type_checker::ExpressionChecker;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
 
 use self::contract::MoveContract;
 use super::ast::*;
@@ -19,7 +18,7 @@ mod ir;
 mod literal;
 pub mod preprocessor;
 mod property_access;
-mod runtime_function;
+pub(crate) mod runtime_function;
 mod statement;
 mod r#struct;
 mod r#type;
@@ -39,7 +38,11 @@ impl Default for MovePosition {
     }
 }
 
-pub fn generate(module: &Module, context: &mut Context) {
+pub fn generate(module: &Module, context: &mut Context, options: &Options) -> GenerationResult {
+    if !options.emits(Emit::MoveIR) {
+        return Ok(vec![]);
+    }
+
     let trait_declarations: Vec<TraitDeclaration> = module
         .declarations
         .clone()
@@ -100,31 +103,20 @@ pub fn generate(module: &Module, context: &mut Context) {
         })
         .collect();
 
-    for contract in contracts {
-        let c = contract.generate();
-
-        let mut code = CodeGen {
-            code: "".to_string(),
-            indent_level: 0,
-            indent_size: 2,
-        };
-
-        code.add(c);
-        print!("{}", code.code);
-
-        let name = contract.contract_declaration.identifier.token.clone();
-        let path = &format!("output/{name}.mvir", name = name);
-        let path = Path::new(path);
-        let display = path.display();
+    let artifacts = contracts
+        .iter()
+        .map(|contract| {
+            let mut code = CodeGen {
+                code: "".to_string(),
+                indent_level: 0,
+                indent_size: 2,
+            };
+            code.add(contract.generate());
 
-        let mut file = match File::create(&path) {
-            Err(why) => panic!("couldn't create {}: {}", display, why),
-            Ok(file) => file,
-        };
+            let name = &contract.contract_declaration.identifier.token;
+            Artifact::new(Emit::MoveIR, name, code.code)
+        })
+        .collect();
 
-        match file.write_all(code.code.as_bytes()) {
-            Err(why) => panic!("couldn't write to {}: {}", display, why),
-            Ok(_) => println!("successfully wrote to {}", display),
-        }
-    }
+    Ok(artifacts)
 }
@@ -2,15 +2,18 @@ use self::utils::*;
 use crate::ast::Literal::BooleanLiteral;
 use crate::ast::*;
 use crate::context::*;
+use crate::diagnostic::{code, Diagnostic};
 use crate::environment::*;
 use crate::environment::{
     FLINT_GLOBAL, FLINT_GLOBAL_ARRAY_INSERT, FLINT_GLOBAL_ARRAY_LENGTH, FLINT_GLOBAL_ARRAY_REMOVE,
     FLINT_GLOBAL_TRANSFER,
 };
-use crate::moveir::preprocessor::utils::generate_caller_protections_predicate;
+use crate::moveir::preprocessor::utils::generate_attempt_predicate;
 use crate::type_checker::ExpressionChecker;
 use crate::utils::getters_and_setters::generate_and_add_getters_and_setters;
 use crate::utils::is_init_declaration;
+use crate::utils::specifications::{insert_initialiser_checks, insert_specification_checks};
+use crate::utils::type_states::generate_entry_type_state_assertion;
 use crate::visitor::Visitor;
 
 pub mod utils;
@@ -20,6 +23,17 @@ pub(crate) struct MovePreProcessor {}
 impl MovePreProcessor {
     pub(crate) const CALLER_PROTECTIONS_PARAM: &'static str = "_contract_caller";
     pub(crate) const SIGNER_TYPE: &'static str = "&signer";
+    pub(crate) const PAYMENT_AMOUNT_PARAM: &'static str = "_payment_amount";
+    pub(crate) const CONTRACT_ADDRESS_PARAM: &'static str = "_address_this";
+    pub(crate) const RETURN_VARIABLE: &'static str = "_ret";
+    pub(crate) const PUBLISHER_PARAM: &'static str = "_account";
+    pub(crate) const WITHDRAW_RUNTIME_FUNCTION: &'static str = "Flint_withdraw";
+    pub(crate) const ARRAY_CONTAINS_RUNTIME_FUNCTION: &'static str = "Flint_array_contains";
+    pub(crate) const SELF_PARAM: &'static str = "_this";
+    pub(crate) const CONSTRUCTOR_PROPERTY_PREFIX: &'static str = "__this_";
+    pub(crate) const CONTRACT_VARIABLE: &'static str = "_contract";
+    pub(crate) const PREASSIGN_VARIABLE: &'static str = "_temp_move_preassign";
+    pub(crate) const EMPTY_STRUCT_FIELD: &'static str = "__dummy_to_prevent_empty_struct__";
 }
 
 impl Visitor for MovePreProcessor {
@@ -48,7 +62,7 @@ impl Visitor for MovePreProcessor {
                 .push(ContractMember::VariableDeclaration(
                     VariableDeclaration {
                         declaration_token: None,
-                        identifier: Identifier::generated("__dummy_to_prevent_empty_struct__"),
+                        identifier: Identifier::generated(MovePreProcessor::EMPTY_STRUCT_FIELD),
                         variable_type: Type::Bool,
                         expression: Some(Box::from(Expression::Literal(BooleanLiteral(true)))),
                     },
@@ -208,7 +222,7 @@ impl Visitor for MovePreProcessor {
             declaration.members.push(StructMember::VariableDeclaration(
                 VariableDeclaration {
                     declaration_token: None,
-                    identifier: Identifier::generated("__dummy_to_prevent_empty_struct__"),
+                    identifier: Identifier::generated(MovePreProcessor::EMPTY_STRUCT_FIELD),
                     variable_type: Type::Bool,
                     expression: Some(Box::from(Expression::Literal(BooleanLiteral(true)))),
                 },
@@ -273,12 +287,14 @@ impl Visitor for MovePreProcessor {
         declaration: &mut FunctionDeclaration,
         ctx: &mut Context,
     ) -> VResult {
+        insert_specification_checks(declaration, ctx)?;
+
         let enclosing_identifier = ctx
             .enclosing_type_identifier()
             .map(|id| id.token.to_string())
             .unwrap_or_default();
 
-        let mangled_name = mangle_function_move(
+        let mangled_name = mangle_function(
             &declaration.head.identifier.token,
             &enclosing_identifier,
             false,
@@ -407,6 +423,20 @@ impl Visitor for MovePreProcessor {
 
         let function_declaration = declaration;
 
+        let state = Expression::BinaryExpression(BinaryExpression {
+            lhs_expression: Box::new(Expression::SelfExpression),
+            rhs_expression: Box::new(Expression::Identifier(Identifier::generated(
+                Identifier::TYPESTATE_VAR_NAME,
+            ))),
+            op: BinOp::Dot,
+            line_info: Default::default(),
+        });
+        statements.extend(generate_entry_type_state_assertion(
+            &function_declaration.head.identifier.token,
+            state,
+            ctx,
+        ));
+
         let mut deletions = delete_declarations(function_declaration.body.clone());
 
         statements.append(&mut deletions);
@@ -435,7 +465,7 @@ impl Visitor for MovePreProcessor {
         } else {
             let variable_declaration = VariableDeclaration {
                 declaration_token: None,
-                identifier: Identifier::generated("ret"),
+                identifier: Identifier::generated(MovePreProcessor::RETURN_VARIABLE),
                 variable_type: function_declaration
                     .head
                     .result_type
@@ -458,6 +488,8 @@ impl Visitor for MovePreProcessor {
         declaration: &mut SpecialDeclaration,
         ctx: &mut Context,
     ) -> VResult {
+        insert_initialiser_checks(declaration, ctx)?;
+
         let members = declaration.body.clone();
 
         let members: Vec<Statement> = members
@@ -550,7 +582,8 @@ impl Visitor for MovePreProcessor {
             let state_variable = if context.special_declaration_context.is_some() {
                 // Special declarations have no 'this' yet as it is being constructed
                 Expression::Identifier(Identifier::generated(&format!(
-                    "__this_{}",
+                    "{}{}",
+                    MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
                     Identifier::TYPESTATE_VAR_NAME,
                 )))
             } else {
@@ -660,24 +693,14 @@ impl Visitor for MovePreProcessor {
             }
         } else if let Expression::AttemptExpression(expr) = expression {
             if let Some(contract_ctx) = &_ctx.contract_behaviour_declaration_context {
-                let caller_protections: Vec<CallerProtection> =
-                    contract_ctx.caller_protections.clone();
-                let caller_protections: Vec<CallerProtection> = caller_protections
-                    .into_iter()
-                    .filter(|protection| !protection.is_any())
-                    .collect();
-
-                if caller_protections.is_empty() {
-                    panic!("Dynamic checking of caller protections from a function with no caller protections is not currently implemented due to MoveIR constraints");
-                }
-
-                if let Some(predicate) = generate_caller_protections_predicate(
-                    &caller_protections,
-                    MovePreProcessor::CALLER_PROTECTIONS_PARAM,
+                // The caller protections of the function called are checked against the signer of
+                // the transaction, which every function of the contract is given
+                let predicate = generate_attempt_predicate(
+                    &expr.function_call,
                     &contract_ctx.identifier,
-                    &expr.function_call.identifier.token,
                     &_ctx,
-                ) {
+                );
+                if let Some(predicate) = predicate {
                     match expr.kind.as_str() {
                         "!" => {
                             let function_call =
@@ -766,8 +789,17 @@ impl Visitor for MovePreProcessor {
                         }
                         _ => {}
                     }
+                } else if expr.kind == "?" {
+                    // A call to a function anyone may call always succeeds
+                    let mut function_call = expr.function_call.clone();
+                    self.start_function_call(&mut function_call, _ctx)?;
+                    _ctx.pre_statements
+                        .push(Statement::Expression(Expression::FunctionCall(
+                            function_call,
+                        )));
+                    *expression = Expression::Literal(Literal::BooleanLiteral(true));
                 } else {
-                    panic!("Invalid predicate generated for attempt expression")
+                    *expression = Expression::FunctionCall(expr.function_call.clone());
                 }
             }
         }
@@ -861,7 +893,7 @@ impl Visitor for MovePreProcessor {
                 function_context
                     .declaration
                     .tags
-                    .push(format!("_dictionary_{}", expr.base_expression.token));
+                    .push(mangle_dictionary(&expr.base_expression.token));
             } else if !ctx.is_lvalue {
                 let array_id = Identifier::generated(&expr.base_expression.token);
                 let array_dec = VariableDeclaration {
@@ -895,13 +927,81 @@ impl Visitor for MovePreProcessor {
     fn start_function_call(&mut self, call: &mut FunctionCall, ctx: &mut Context) -> VResult {
         if Environment::is_runtime_function_call(call) {
             if FLINT_GLOBAL_TRANSFER == call.identifier.token.as_str() {
-                // This simply changes the first argument of this function call to be the signer
-                // type rather than the address, since as of yet we are unable to access signer types apart
-                // from the caller
-                // TODO remove this once we are able to store and pass around signer types
-                call.arguments[0].expression = Expression::Identifier(Identifier::generated(
-                    MovePreProcessor::CALLER_PROTECTIONS_PARAM,
-                ));
+                // Transfers pay out of a `Libra` value held by the contract and never out of the
+                // caller's account, the caller only signs for the deposit into the payee's account
+                let enclosing_type = ctx
+                    .enclosing_type_identifier()
+                    .map(|id| id.token.to_string())
+                    .unwrap_or_default();
+                let pays_from_currency = match call.arguments.first().map(|a| &a.expression) {
+                    Some(Expression::InoutExpression(inout)) => ctx
+                        .environment
+                        .get_expression_type(
+                            &inout.expression,
+                            &enclosing_type,
+                            &[],
+                            &[],
+                            ctx.scope_context.as_ref().unwrap_or_default(),
+                        )
+                        .is_currency_type(&ctx.target.currency),
+                    _ => false,
+                };
+
+                if !pays_from_currency {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::INVALID_CALL,
+                            format!(
+                                "`{}` can only pay out `{}` held by the contract on {}",
+                                FLINT_GLOBAL_TRANSFER,
+                                ctx.target.currency.identifier,
+                                call.identifier.line_info
+                            ),
+                        )
+                        .with_span(&call.identifier.line_info),
+                    ));
+                }
+
+                call.arguments.push(FunctionArgument {
+                    identifier: None,
+                    expression: Expression::Identifier(Identifier::generated(
+                        MovePreProcessor::CALLER_PROTECTIONS_PARAM,
+                    )),
+                });
+            } else if call.identifier.token == MovePreProcessor::WITHDRAW_RUNTIME_FUNCTION {
+                // Only a public payable function is called with the consent of its caller to take
+                // funds out of their account, which the caller signs for
+                let payable = ctx
+                    .function_declaration_context
+                    .as_ref()
+                    .map(|function_context| {
+                        function_context.declaration.is_public()
+                            && function_context.declaration.is_payable()
+                    })
+                    .unwrap_or(false);
+                if !payable {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::INVALID_CALL,
+                            format!(
+                                "`{}` can only withdraw from the account of the caller within a public `@payable` function on {}",
+                                MovePreProcessor::WITHDRAW_RUNTIME_FUNCTION,
+                                call.identifier.line_info
+                            ),
+                        )
+                        .with_span(&call.identifier.line_info),
+                    ));
+                }
+
+                call.arguments.insert(
+                    0,
+                    FunctionArgument {
+                        identifier: None,
+                        expression: Expression::Identifier(Identifier::generated(
+                            MovePreProcessor::CALLER_PROTECTIONS_PARAM,
+                        )),
+                    },
+                );
             } else if call.identifier.token.as_str() == FLINT_GLOBAL_ARRAY_REMOVE
                 || call.identifier.token.as_str() == FLINT_GLOBAL_ARRAY_INSERT
                 || call.identifier.token.as_str() == FLINT_GLOBAL_ARRAY_LENGTH
@@ -1233,9 +1333,7 @@ impl Visitor for MovePreProcessor {
                         return Ok(());
                     }
 
-                    if !expression_type.is_currency_type(&ctx.target.currency)
-                        && !expression_type.is_external_resource(ctx.environment.clone())
-                    {
+                    if !expression_type.is_external_resource(ctx.environment.clone()) {
                         borrow_local = true;
                     }
                 } else {
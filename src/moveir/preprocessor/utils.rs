@@ -1,5 +1,5 @@
 use crate::ast::{
-    mangle_function_move, ArrayType, Assertion, BinOp, BinaryExpression, CallerProtection,
+    mangle_function, ArrayType, Assertion, BinOp, BinaryExpression, CallerProtection,
     ContractBehaviourDeclaration, Expression, FixedSizedArrayType, FunctionArgument, FunctionCall,
     FunctionDeclaration, Identifier, InoutExpression, InoutType, Parameter, ReturnStatement,
     Statement, Type, VariableDeclaration,
@@ -204,7 +204,7 @@ pub fn generate_contract_wrapper(
     context: &mut Context,
 ) -> FunctionDeclaration {
     let mut wrapper = function.clone();
-    wrapper.mangled_identifier = Option::from(mangle_function_move(
+    wrapper.mangled_identifier = Option::from(mangle_function(
         &function.head.identifier.token,
         &"".to_string(),
         true,
@@ -219,7 +219,7 @@ pub fn generate_contract_wrapper(
     }
 
     let contract_address_parameter = Parameter {
-        identifier: Identifier::generated("address_this"),
+        identifier: Identifier::generated(MovePreProcessor::CONTRACT_ADDRESS_PARAM),
         type_assignment: Type::Address,
         expression: None,
         line_info: Default::default(),
@@ -251,8 +251,11 @@ pub fn generate_contract_wrapper(
         .filter(|c| c.is_any())
         .collect();
 
-    let (state_properties, protection_functions) =
-        split_caller_protections(&contract_behaviour_declaration, &context);
+    let (state_properties, protection_functions) = split_caller_protections(
+        &contract_behaviour_declaration.caller_protections,
+        &contract_behaviour_declaration.identifier,
+        &context,
+    );
 
     if !contract_behaviour_declaration.caller_protections.is_empty()
         && caller_protections.is_empty()
@@ -273,7 +276,14 @@ pub fn generate_contract_wrapper(
             wrapper.body.push(Statement::Assertion(assertion));
         }
     }
-    let is_stateful = !contract_behaviour_declaration.type_states.is_empty();
+    // Functions called within the contract in states they may not allow check the state on entry
+    let contract_name = &contract_behaviour_declaration.identifier.token;
+    let is_stateful = context
+        .environment
+        .may_call_in_disallowed_state(contract_name, &contract_behaviour_declaration.type_states)
+        && !context
+            .environment
+            .checks_type_state_on_entry(contract_name, &function.head.identifier.token);
     if is_stateful {
         let type_state_declaration = VariableDeclaration {
             declaration_token: None,
@@ -292,7 +302,10 @@ pub fn generate_contract_wrapper(
     let self_assignment = BinaryExpression {
         lhs_expression: Box::new(Expression::SelfExpression),
         rhs_expression: Box::new(Expression::RawAssembly(
-            "borrow_global_mut<T>(copy(address_this))".to_string(),
+            format!(
+                "borrow_global_mut<T>(copy({}))",
+                MovePreProcessor::CONTRACT_ADDRESS_PARAM
+            ),
             Some(original_parameter.type_assignment),
         )),
         op: BinOp::Equal,
@@ -345,14 +358,15 @@ pub fn generate_contract_wrapper(
                 type_state_assignment,
             )));
 
-        let contract_name = &contract_behaviour_declaration.identifier.token;
         let allowed_type_states_as_u8s = extract_allowed_states(
             &contract_behaviour_declaration.type_states,
             &context.environment.get_contract_type_states(contract_name),
         )
         .collect::<Vec<u8>>();
-        let condition =
-            generate_type_state_condition(state_identifier, &allowed_type_states_as_u8s);
+        let condition = generate_type_state_condition(
+            Expression::Identifier(state_identifier),
+            &allowed_type_states_as_u8s,
+        );
         let assertion = Assertion {
             expression: Expression::BinaryExpression(condition),
             line_info: contract_behaviour_declaration.identifier.line_info.clone(),
@@ -361,6 +375,12 @@ pub fn generate_contract_wrapper(
         wrapper.body.push(Statement::Assertion(assertion));
     }
 
+    if function.is_public() {
+        if let Some(payable_parameter) = function.first_payable_param(context) {
+            withdraw_payment_from_caller(&mut wrapper, payable_parameter);
+        }
+    }
+
     let arguments = function
         .head
         .parameters
@@ -405,6 +425,62 @@ pub fn generate_contract_wrapper(
     wrapper
 }
 
+// The wrapper takes the amount to pay rather than a coin, and withdraws that amount from the
+// caller so it can be passed on as the payable parameter of the wrapped function
+fn withdraw_payment_from_caller(wrapper: &mut FunctionDeclaration, payable_parameter: Parameter) {
+    let amount_parameter = Parameter {
+        identifier: Identifier::generated(MovePreProcessor::PAYMENT_AMOUNT_PARAM),
+        type_assignment: Type::Int,
+        expression: None,
+        line_info: payable_parameter.line_info.clone(),
+    };
+
+    let replace_payable_parameter = |parameters: &mut Vec<Parameter>| {
+        for parameter in parameters.iter_mut() {
+            if parameter.identifier.token == payable_parameter.identifier.token {
+                *parameter = amount_parameter.clone();
+            }
+        }
+    };
+
+    replace_payable_parameter(&mut wrapper.head.parameters);
+    if let Some(ref mut scope) = wrapper.scope_context {
+        replace_payable_parameter(&mut scope.parameters);
+    }
+
+    let coin_declaration = VariableDeclaration {
+        declaration_token: None,
+        identifier: payable_parameter.identifier.clone(),
+        variable_type: payable_parameter.type_assignment.clone(),
+        expression: None,
+    };
+    wrapper
+        .body
+        .push(Statement::Expression(Expression::VariableDeclaration(
+            coin_declaration,
+        )));
+
+    let withdrawal = BinaryExpression {
+        lhs_expression: Box::new(Expression::Identifier(payable_parameter.identifier)),
+        rhs_expression: Box::new(Expression::RawAssembly(
+            format!(
+                "Self.{withdraw}(copy({caller}), move({amount}))",
+                withdraw = MovePreProcessor::WITHDRAW_RUNTIME_FUNCTION,
+                caller = MovePreProcessor::CALLER_PROTECTIONS_PARAM,
+                amount = MovePreProcessor::PAYMENT_AMOUNT_PARAM
+            ),
+            Some(payable_parameter.type_assignment),
+        )),
+        op: BinOp::Equal,
+        line_info: payable_parameter.line_info,
+    };
+    wrapper
+        .body
+        .push(Statement::Expression(Expression::BinaryExpression(
+            withdrawal,
+        )));
+}
+
 pub fn expand_properties(expression: Expression, ctx: &mut Context, borrow: bool) -> Expression {
     match expression.clone() {
         Expression::Identifier(i) => {
@@ -600,7 +676,7 @@ pub fn pre_assign(
         })
     };
 
-    let mut temp_identifier = Identifier::generated("_temp_move_preassign");
+    let mut temp_identifier = Identifier::generated(MovePreProcessor::PREASSIGN_VARIABLE);
     let statements: Vec<BinaryExpression> = ctx
         .pre_statements
         .clone()
@@ -754,14 +830,14 @@ pub fn mangle_function_call_name(
                 let param_types = declaration.head.parameters;
                 let _param_types: Vec<Type> =
                     param_types.into_iter().map(|p| p.type_assignment).collect();
-                Some(mangle_function_move(
+                Some(mangle_function(
                     &declaration.head.identifier.token,
                     &enclosing_type,
                     false,
                 ))
             }
 
-            FunctionCallMatchResult::MatchedInitializer(_i) => Some(mangle_function_move(
+            FunctionCallMatchResult::MatchedInitializer(_i) => Some(mangle_function(
                 "init",
                 &function_call.identifier.token,
                 false,
@@ -771,9 +847,9 @@ pub fn mangle_function_call_name(
             FunctionCallMatchResult::MatchedGlobalFunction(fi) => {
                 let declaration = fi.declaration;
 
-                Some(mangle_function_move(
+                Some(mangle_function(
                     &declaration.head.identifier.token,
-                    "Flint",
+                    crate::environment::FLINT_GLOBAL,
                     false,
                 ))
             }
@@ -794,7 +870,7 @@ pub fn mangle_function_call_name(
                 if let CallableInformation::FunctionInformation(fi) = candidate {
                     let declaration = &fi.declaration;
 
-                    Some(mangle_function_move(
+                    Some(mangle_function(
                         &declaration.head.identifier.token,
                         &enclosing_type,
                         false,
@@ -882,21 +958,21 @@ fn struct_is_mutable_reference(
     true
 }
 
+/// Caller protections which are properties of the contract, and those which are functions
 fn split_caller_protections(
-    contract_behaviour_declaration: &ContractBehaviourDeclaration,
+    caller_protections: &[CallerProtection],
+    contract: &Identifier,
     context: &Context,
 ) -> (Vec<CallerProtection>, Vec<CallerProtection>) {
     let mut state_properties = vec![];
     let mut functions = vec![];
 
-    for caller_protection in &contract_behaviour_declaration.caller_protections {
+    for caller_protection in caller_protections {
         let mut ident = caller_protection.clone().identifier;
-        ident.enclosing_type =
-            Option::from(contract_behaviour_declaration.identifier.token.clone());
-        let en_ident = contract_behaviour_declaration.identifier.clone();
+        ident.enclosing_type = Option::from(contract.token.clone());
         let c_type = context.environment.get_expression_type(
             &Expression::Identifier(ident.clone()),
-            &en_ident.token,
+            &contract.token,
             &[],
             &[],
             &ScopeContext {
@@ -908,7 +984,9 @@ fn split_caller_protections(
 
         match c_type {
             Type::Address => state_properties.push(caller_protection.clone()),
-            Type::ArrayType(_) => state_properties.push(caller_protection.clone()),
+            Type::ArrayType(_) | Type::FixedSizedArrayType(_) => {
+                state_properties.push(caller_protection.clone())
+            }
             Type::DictionaryType(_) => state_properties.push(caller_protection.clone()),
             _ => functions.push(caller_protection.clone()),
         }
@@ -917,6 +995,95 @@ fn split_caller_protections(
     (state_properties, functions)
 }
 
+/// Checks the caller protections of the function a `try!` or `try?` calls against the signer of
+/// the transaction, from within the function making the call. Unlike in a wrapper, the contract
+/// is already borrowed, so functions used as caller protections are called directly
+pub fn generate_attempt_predicate(
+    function_call: &FunctionCall,
+    contract: &Identifier,
+    context: &Context,
+) -> Option<Expression> {
+    let caller_protections = context.environment.callee_caller_protections(
+        function_call,
+        &contract.token,
+        context.scope_or_default(),
+    );
+    let (state_properties, functions) =
+        split_caller_protections(&caller_protections, contract, context);
+    let caller_address = || {
+        Expression::RawAssembly(
+            format!(
+                "Signer.address_of(copy({}))",
+                MovePreProcessor::CALLER_PROTECTIONS_PARAM
+            ),
+            None,
+        )
+    };
+
+    let function_predicates = functions.into_iter().filter_map(|protection| {
+        let function = context
+            .environment
+            .types
+            .get(&contract.token)?
+            .functions
+            .get(&protection.identifier.token)?
+            .first()?;
+        let mut arguments = vec![FunctionArgument {
+            identifier: None,
+            expression: Expression::SelfExpression,
+        }];
+        let is_predicate = function.declaration.head.is_predicate();
+        if is_predicate {
+            arguments.push(FunctionArgument {
+                identifier: None,
+                expression: caller_address(),
+            });
+        }
+        let call = Expression::FunctionCall(FunctionCall {
+            identifier: Identifier {
+                token: protection.identifier.token.clone(),
+                enclosing_type: Some(contract.token.clone()),
+                line_info: Default::default(),
+            },
+            arguments,
+            mangled_identifier: Some(Identifier::generated(&mangle_function(
+                &protection.identifier.token,
+                &contract.token,
+                false,
+            ))),
+        });
+
+        Some(if is_predicate {
+            call
+        } else {
+            Expression::BinaryExpression(BinaryExpression {
+                lhs_expression: Box::new(call),
+                rhs_expression: Box::new(caller_address()),
+                op: BinOp::DoubleEqual,
+                line_info: Default::default(),
+            })
+        })
+    });
+
+    generate_caller_protections_predicate(
+        &state_properties,
+        MovePreProcessor::CALLER_PROTECTIONS_PARAM,
+        contract,
+        &function_call.identifier.token,
+        context,
+    )
+    .into_iter()
+    .chain(function_predicates)
+    .fold1(|left, right| {
+        Expression::BinaryExpression(BinaryExpression {
+            lhs_expression: Box::new(left),
+            rhs_expression: Box::new(right),
+            op: BinOp::Or,
+            line_info: Default::default(),
+        })
+    })
+}
+
 pub fn generate_caller_protections_predicate(
     caller_protections: &[CallerProtection],
     caller_id: &str,
@@ -953,54 +1120,37 @@ pub fn generate_caller_protections_predicate(
                     op: BinOp::DoubleEqual,
                     line_info: Default::default(),
                 })),
-                Type::FixedSizedArrayType(_) | Type::ArrayType(_) => {
-                    let array_type = match c_type {
-                        Type::FixedSizedArrayType(FixedSizedArrayType { key_type, .. }) => {
-                            *key_type
-                        }
-                        Type::ArrayType(ArrayType { key_type }) => *key_type,
-                        _ => panic!(),
-                    };
-
+                // The caller must be one of the addresses the array holds when it is called
+                Type::FixedSizedArrayType(FixedSizedArrayType { key_type, .. })
+                | Type::ArrayType(ArrayType { key_type }) => {
                     assert_eq!(
-                        array_type,
+                        *key_type,
                         Type::Address,
                         "Array values for caller protection must have type Address"
                     );
-                    if let Some(property) = context.environment.get_caller_protection(&c) {
-                        if let Some(Expression::ArrayLiteral(array)) = property.property.get_value()
-                        {
-                            let predicate = array
-                                .elements
-                                .iter()
-                                .cloned()
-                                .map(|c| {
-                                    Expression::BinaryExpression(BinaryExpression {
-                                        lhs_expression: Box::new(c),
-                                        rhs_expression: Box::new(Expression::RawAssembly(
-                                            format!("Signer.address_of(copy({}))", caller_id),
-                                            None,
-                                        )),
-                                        op: BinOp::DoubleEqual,
-                                        line_info: Default::default(),
-                                    })
-                                })
-                                .fold1(|left, right| {
-                                    Expression::BinaryExpression(BinaryExpression {
-                                        lhs_expression: Box::new(left),
-                                        rhs_expression: Box::new(right),
-                                        op: BinOp::Or,
-                                        line_info: Default::default(),
-                                    })
-                                })
-                                .unwrap();
-                            Some(predicate)
-                        } else {
-                            panic!("Mismatching types for {:?}", c)
-                        }
-                    } else {
-                        panic!("{:?} not found in caller protections", c)
-                    }
+                    Some(Expression::FunctionCall(FunctionCall {
+                        identifier: Identifier::generated(&format!(
+                            "{}<address>",
+                            MovePreProcessor::ARRAY_CONTAINS_RUNTIME_FUNCTION
+                        )),
+                        arguments: vec![
+                            FunctionArgument {
+                                identifier: None,
+                                expression: Expression::InoutExpression(InoutExpression {
+                                    ampersand_token: "&".to_string(),
+                                    expression: Box::new(Expression::Identifier(ident)),
+                                }),
+                            },
+                            FunctionArgument {
+                                identifier: None,
+                                expression: Expression::RawAssembly(
+                                    format!("Signer.address_of(copy({}))", caller_id),
+                                    None,
+                                ),
+                            },
+                        ],
+                        mangled_identifier: None,
+                    }))
                 }
                 Type::DictionaryType(dict_type) => {
                     assert_eq!(
@@ -1061,7 +1211,7 @@ pub fn generate_caller_protections_predicate(
                                                     identifier: None,
                                                     expression: Expression::Identifier(
                                                         Identifier {
-                                                            token: "address_this".to_string(),
+                                                            token: MovePreProcessor::CONTRACT_ADDRESS_PARAM.to_string(),
                                                             enclosing_type: None,
                                                             line_info: Default::default(),
                                                         },
@@ -1095,7 +1245,7 @@ pub fn generate_caller_protections_predicate(
                                                         identifier: None,
                                                         expression: Expression::Identifier(
                                                             Identifier {
-                                                                token: "address_this".to_string(),
+                                                                token: MovePreProcessor::CONTRACT_ADDRESS_PARAM.to_string(),
                                                                 enclosing_type: None,
                                                                 line_info: Default::default(),
                                                             },
@@ -14,6 +14,23 @@ pub(crate) enum MoveRuntimeFunction {
 }
 
 impl MoveRuntimeFunction {
+    const ALL: &'static [MoveRuntimeFunction] = &[
+        MoveRuntimeFunction::AppendToArrayInt,
+        MoveRuntimeFunction::GetFromArrayInt,
+        MoveRuntimeFunction::AssignToFixedArray,
+        MoveRuntimeFunction::RevertIfGreater,
+        MoveRuntimeFunction::Transfer,
+        MoveRuntimeFunction::WithdrawAll,
+        MoveRuntimeFunction::Power,
+    ];
+
+    /// Whether the given name is that of one of the runtime functions
+    pub fn is_runtime_function(name: &str) -> bool {
+        MoveRuntimeFunction::ALL
+            .iter()
+            .any(|function| function.name() == name)
+    }
+
     pub fn revert_if_greater(value: MoveIRExpression, max: MoveIRExpression) -> MoveIRExpression {
         MoveIRExpression::FunctionCall(MoveIRFunctionCall {
             identifier: MoveRuntimeFunction::RevertIfGreater.mangle_runtime(),
@@ -29,7 +46,8 @@ impl MoveRuntimeFunction {
     }
 
     pub fn get_power() -> String {
-        "_Power(b: u64, e: u64): u64 {
+        MoveRuntimeFunction::Power.name()
+            + "(b: u64, e: u64): u64 {
         let ret: u64;
         let i: u64;
         ret = 1;
@@ -42,12 +60,26 @@ impl MoveRuntimeFunction {
         _ = move(e);
         _ = move(i);
         return move(ret); 
+    }"
+    }
+
+    /// Only generated for contracts with an array of addresses as a caller protection, which is
+    /// what calls it
+    pub fn get_array_contains() -> String {
+        "public Flint_array_contains<S: copyable>(nums: &mut vector<S>, value: S): bool {
+        let found: bool;
+        found = Vector.contains<S>(freeze(move(nums)), &value);
+        return move(found);
     }"
         .to_string()
     }
 
+    pub fn name(&self) -> String {
+        format!("_{}", self)
+    }
+
     pub fn mangle_runtime(&self) -> String {
-        format!("Self._{}", self)
+        format!("Self.{}", self.name())
     }
 
     pub fn get_all_functions() -> Vec<String> {
@@ -63,32 +95,56 @@ impl MoveRuntimeFunction {
             return LibraAccount.balance<LBR.LBR>(move(account));
         }
 
-        public Flint_transfer(from: &signer, other: address, amount: u64) {
-            let old_balance_sender: u64;
-            let new_balance_sender: u64;
+        Flint_transfer(from: &mut Libra.Libra<LBR.LBR>, to: address, amount: u64, payer: &signer) {
+            let coin: Libra.Libra<LBR.LBR>;
             let old_balance_recipient: u64;
             let new_balance_recipient: u64;
-            let from_address: address;
-            let with_cap: LibraAccount.WithdrawCapability;
 
-            from_address = Signer.address_of(copy(from));
+            old_balance_recipient = LibraAccount.balance<LBR.LBR>(copy(to));
 
-            old_balance_sender = LibraAccount.balance<LBR.LBR>(copy(from_address));
-            old_balance_recipient = LibraAccount.balance<LBR.LBR>(copy(other));
+            coin = Libra.withdraw<LBR.LBR>(move(from), copy(amount));
+            LibraAccount.deposit<LBR.LBR>(move(payer), copy(to), move(coin), h\"\", h\"\");
 
-            with_cap = LibraAccount.extract_withdraw_capability(copy(from));
-            LibraAccount.pay_from<LBR.LBR>(&with_cap, copy(other), copy(amount), h\"\", h\"\");
-            LibraAccount.restore_withdraw_capability(move(with_cap));
+            new_balance_recipient = LibraAccount.balance<LBR.LBR>(move(to));
+            assert(copy(new_balance_recipient) == copy(old_balance_recipient) + move(amount), 77);
 
-            new_balance_sender = LibraAccount.balance<LBR.LBR>(move(from_address));
-            new_balance_recipient = LibraAccount.balance<LBR.LBR>(move(other));
+            return;
+        }
 
-            assert(copy(new_balance_sender) == copy(old_balance_sender) - copy(amount), 77);
-            assert(copy(new_balance_recipient) == copy(old_balance_recipient) + move(amount), 77);
+        Libra__init(): Libra.Libra<LBR.LBR> {
+            return Libra.zero<LBR.LBR>();
+        }
+
+        Libra__getRawValue(this: &mut Libra.Libra<LBR.LBR>): u64 {
+            return Libra.value<LBR.LBR>(freeze(move(this)));
+        }
+
+        Libra__deposit(this: &mut Libra.Libra<LBR.LBR>, coin: Libra.Libra<LBR.LBR>) {
+            Libra.deposit<LBR.LBR>(move(this), move(coin));
+            return;
+        }
 
+        Libra__transfer(this: &mut Libra.Libra<LBR.LBR>, source: &mut Libra.Libra<LBR.LBR>, amount: u64) {
+            let coin: Libra.Libra<LBR.LBR>;
+            coin = Libra.withdraw<LBR.LBR>(move(source), move(amount));
+            Libra.deposit<LBR.LBR>(move(this), move(coin));
             return;
         }
 
+        Flint_withdraw(from: &signer, amount: u64): Libra.Libra<LBR.LBR> {
+            let from_address: address;
+            let with_cap: LibraAccount.WithdrawCapability;
+            let coin: Libra.Libra<LBR.LBR>;
+
+            from_address = Signer.address_of(copy(from));
+
+            with_cap = LibraAccount.extract_withdraw_capability(move(from));
+            coin = LibraAccount.withdraw_from<LBR.LBR>(&with_cap, move(from_address), move(amount), h\"\");
+            LibraAccount.restore_withdraw_capability(move(with_cap));
+
+            return move(coin);
+        }
+
         public Flint_array_remove<S: copyable>(nums: &mut vector<S>, i: u64) {
             let result: S;
             result = Vector.remove<S>(move(nums), move(i));
@@ -2,6 +2,7 @@ use super::call::MoveFunctionCall;
 use super::expression::MoveExpression;
 use super::function::FunctionContext;
 use super::ir::{MoveIRAssignment, MoveIRExpression, MoveIRIf, MoveIRStatement};
+use super::preprocessor::MovePreProcessor;
 use crate::ast::{
     EmitStatement, ForStatement, Identifier, IfStatement, ReturnStatement, Statement,
 };
@@ -106,7 +107,7 @@ impl MoveReturnStatement {
         }
 
         let return_identifier = Identifier {
-            token: "ret".to_string(),
+            token: MovePreProcessor::RETURN_VARIABLE.to_string(),
             enclosing_type: None,
             line_info: self.statement.line_info.clone(),
         };
@@ -9,11 +9,12 @@ use super::r#type::MoveType;
 use super::statement::MoveStatement;
 use super::MovePosition;
 use crate::ast::{
-    BinOp, Expression, FunctionDeclaration, Identifier, Modifier, SpecialDeclaration, Statement,
-    StructDeclaration, StructMember, Type, VariableDeclaration,
+    mangle_function, BinOp, Expression, FunctionDeclaration, Identifier, Modifier,
+    SpecialDeclaration, Statement, StructDeclaration, StructMember, Type, VariableDeclaration,
 };
 use crate::context::ScopeContext;
 use crate::environment::Environment;
+use crate::moveir::preprocessor::MovePreProcessor;
 
 pub(crate) struct MoveStruct {
     pub struct_declaration: StructDeclaration,
@@ -223,10 +224,10 @@ impl MoveStructInitialiser {
         let body = self.generate_body();
 
         format!(
-            "{modifiers} {name}_init({parameters}): {result_type} {{ \n\n {body} \n\n }}",
+            "{modifiers} {name}({parameters}): {result_type} {{ \n\n {body} \n\n }}",
             modifiers = modifiers,
             result_type = result_type,
-            name = self.identifier.token,
+            name = mangle_function("init", &self.identifier.token, false),
             parameters = parameters,
             body = body
         )
@@ -250,7 +251,11 @@ impl MoveStructInitialiser {
                 Option::from(self.environment.clone()),
             )
             .generate(&function_context);
-            let name = format!("__this_{}", property.identifier.token);
+            let name = format!(
+                "{}{}",
+                MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+                property.identifier.token
+            );
             function_context.emit(MoveIRStatement::Expression(
                 MoveIRExpression::VariableDeclaration(MoveIRVariableDeclaration {
                     identifier: name,
@@ -259,6 +264,28 @@ impl MoveStructInitialiser {
             ));
         }
 
+        // Default values are given first, so the initialiser may replace them
+        for property in &self.properties {
+            if let Some(ref expr) = property.expression {
+                function_context.emit(MoveIRStatement::Expression(MoveIRExpression::Assignment(
+                    crate::moveir::ir::MoveIRAssignment {
+                        identifier: format!(
+                            "{}{}",
+                            MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+                            property.identifier.token
+                        ),
+                        expression: Box::from(
+                            crate::moveir::expression::MoveExpression {
+                                expression: *expr.clone(),
+                                position: Default::default(),
+                            }
+                            .generate(&function_context),
+                        ),
+                    },
+                )));
+            }
+        }
+
         let mut unassigned: Vec<Identifier> = self
             .properties
             .clone()
@@ -266,7 +293,11 @@ impl MoveStructInitialiser {
             .map(|v| v.identifier)
             .collect();
         let mut statements = self.declaration.body.clone();
-        while !(statements.is_empty() || unassigned.is_empty()) {
+        // Assertions, such as those of the invariants, only read the values, so are made before
+        // the struct is constructed
+        while !statements.is_empty()
+            && (!unassigned.is_empty() || matches!(statements[0], Statement::Assertion(_)))
+        {
             let statement = statements.remove(0);
             if let Statement::Expression(e) = statement.clone() {
                 if let Expression::BinaryExpression(b) = e {
@@ -311,22 +342,11 @@ impl MoveStructInitialiser {
         let fields = fields
             .into_iter()
             .map(|f| {
-                let name = format!("__this_{}", f.identifier.token);
-                if let Some(expr) = f.expression {
-                    let statement = MoveIRStatement::Expression(MoveIRExpression::Assignment(
-                        crate::moveir::ir::MoveIRAssignment {
-                            identifier: name.clone(),
-                            expression: Box::from(
-                                crate::moveir::expression::MoveExpression {
-                                    expression: *expr,
-                                    position: Default::default(),
-                                }
-                                .generate(&function_context),
-                            ),
-                        },
-                    ));
-                    function_context.emit(statement);
-                }
+                let name = format!(
+                    "{}{}",
+                    MovePreProcessor::CONSTRUCTOR_PROPERTY_PREFIX,
+                    f.identifier.token
+                );
                 (
                     f.identifier.token,
                     MoveIRExpression::Transfer(MoveIRTransfer::Move(Box::from(
@@ -357,7 +377,7 @@ impl MoveStructInitialiser {
             .generate(&function_context);
 
             let emit = MoveIRExpression::VariableDeclaration(MoveIRVariableDeclaration {
-                identifier: "this".to_string(),
+                identifier: MovePreProcessor::SELF_PARAM.to_string(),
                 declaration_type: MoveIRType::MutableReference(Box::from(self_type.clone())),
             });
             function_context.emit(MoveIRStatement::Expression(emit));
@@ -40,8 +40,7 @@ impl MoveType {
                 });
 
                 if comp == "Libra" {
-                    let string = format!("Self.{}", s);
-                    return MoveIRType::Resource(string);
+                    return MoveIRType::Resource("Libra.Libra<LBR.LBR>".to_string());
                 }
                 if function_context.enclosing_type == *s {
                     let string = "Self.T".to_string();
@@ -175,6 +174,10 @@ pub(crate) mod move_runtime_types {
             name: "Signer".to_string(),
             address: "0x1".to_string(),
         });
+        let libra = MoveIRStatement::Import(MoveIRModuleImport {
+            name: "Libra".to_string(),
+            address: "0x1".to_string(),
+        });
         let lbr = MoveIRStatement::Import(MoveIRModuleImport {
             name: "LBR".to_string(),
             address: "0x1".to_string(),
@@ -187,6 +190,6 @@ pub(crate) mod move_runtime_types {
             name: "Vector".to_string(),
             address: "0x1".to_string(),
         });
-        vec![signer, vector, lbr, libra_account]
+        vec![signer, vector, libra, lbr, libra_account]
     }
 }
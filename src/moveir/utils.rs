@@ -1,8 +1,9 @@
 use super::ir::{
-    MoveIRAssignment, MoveIRExpression, MoveIRFunctionCall, MoveIROperation, MoveIRTransfer,
-    MoveIRVector,
+    MoveIRAssignment, MoveIRBlock, MoveIRExpression, MoveIRFunctionCall, MoveIRIf, MoveIROperation,
+    MoveIRStatement, MoveIRTransfer, MoveIRVector,
 };
 use crate::ast::{Expression, Statement};
+use crate::moveir::preprocessor::MovePreProcessor;
 
 fn remove_move_op(op: &MoveIROperation, statement: &Statement) -> Option<MoveIRExpression> {
     match op {
@@ -255,7 +256,8 @@ fn remove_move(statement: &Statement, expression: &MoveIRExpression) -> Option<M
                 MoveIRExpression::Transfer(transfer) => {
                     if let MoveIRTransfer::Copy(identifier) = transfer {
                         if let MoveIRExpression::Identifier(id) = &**identifier {
-                            if id == &variable.token || (variable.token == "self" && *id == "this")
+                            if id == &variable.token
+                                || (variable.token == "self" && id == MovePreProcessor::SELF_PARAM)
                             {
                                 return Some(MoveIRExpression::Transfer(MoveIRTransfer::Move(
                                     Box::new(MoveIRExpression::Identifier(id.to_string())),
@@ -280,7 +282,8 @@ fn remove_move(statement: &Statement, expression: &MoveIRExpression) -> Option<M
                 }
                 MoveIRExpression::Assignment(assignment) => {
                     if assignment.identifier.contains(&variable.token)
-                        || (assignment.identifier.contains("this") && variable.token == "self")
+                        || (assignment.identifier.contains(MovePreProcessor::SELF_PARAM)
+                            && variable.token == "self")
                     {
                         return Some(MoveIRExpression::Assignment(MoveIRAssignment {
                             identifier: assignment.identifier.clone().replace("copy", "move"),
@@ -314,6 +317,88 @@ fn remove_move(statement: &Statement, expression: &MoveIRExpression) -> Option<M
     None
 }
 
+/// Whether a statement the release of a reference cannot be moved into, such as an `if`
+/// statement, still copies the reference
+pub fn copies_reference(statement: &Statement, ir_statement: &MoveIRStatement) -> bool {
+    if let Statement::Expression(Expression::BinaryExpression(be)) = statement {
+        if let Expression::Identifier(variable) = &*be.rhs_expression {
+            let name = if variable.token == "self" {
+                MovePreProcessor::SELF_PARAM.to_string()
+            } else {
+                variable.token.clone()
+            };
+            return statement_copies(ir_statement, &name);
+        }
+    }
+    false
+}
+
+fn statement_copies(statement: &MoveIRStatement, name: &str) -> bool {
+    match statement {
+        MoveIRStatement::Block(block) => block_copies(block, name),
+        MoveIRStatement::If(MoveIRIf {
+            expression,
+            block,
+            else_block,
+        }) => {
+            expression_copies(expression, name)
+                || block_copies(block, name)
+                || else_block.iter().any(|block| block_copies(block, name))
+        }
+        MoveIRStatement::Expression(expression)
+        | MoveIRStatement::Return(expression)
+        | MoveIRStatement::Assert(expression, _) => expression_copies(expression, name),
+        MoveIRStatement::Inline(code) => code_copies(code, name),
+        _ => false,
+    }
+}
+
+fn block_copies(block: &MoveIRBlock, name: &str) -> bool {
+    block
+        .statements
+        .iter()
+        .any(|statement| statement_copies(statement, name))
+}
+
+fn expression_copies(expression: &MoveIRExpression, name: &str) -> bool {
+    match expression {
+        MoveIRExpression::Transfer(MoveIRTransfer::Copy(copied)) => {
+            matches!(&**copied, MoveIRExpression::Identifier(id) if id == name)
+                || expression_copies(copied, name)
+        }
+        MoveIRExpression::Transfer(MoveIRTransfer::Move(moved)) => expression_copies(moved, name),
+        MoveIRExpression::FunctionCall(call) => call
+            .arguments
+            .iter()
+            .any(|argument| expression_copies(argument, name)),
+        MoveIRExpression::StructConstructor(constructor) => constructor
+            .fields
+            .iter()
+            .any(|(_, field)| expression_copies(field, name)),
+        MoveIRExpression::Assignment(assignment) => {
+            code_copies(&assignment.identifier, name)
+                || expression_copies(&assignment.expression, name)
+        }
+        MoveIRExpression::Operation(operation) => operation
+            .operands()
+            .into_iter()
+            .any(|operand| expression_copies(operand, name)),
+        MoveIRExpression::Vector(vector) => vector
+            .elements
+            .iter()
+            .any(|element| expression_copies(element, name)),
+        MoveIRExpression::Inline(code) => code_copies(code, name),
+        _ => false,
+    }
+}
+
+/// The targets of assignments and inline code are only kept as text, in which a copy of the
+/// reference is written as it is generated
+fn code_copies(code: &str, name: &str) -> bool {
+    let copy = MoveIRTransfer::Copy(Box::new(MoveIRExpression::Identifier(name.to_string())));
+    code.contains(&copy.to_string())
+}
+
 pub fn remove_moves<T: IntoIterator<Item = Statement>>(
     statements: T,
     expression: MoveIRExpression,
@@ -342,10 +427,12 @@ mod test {
     use crate::ast::types::Type;
     use crate::ast::LineInfo;
     use crate::moveir::ir::MoveIRExpression;
+    use crate::moveir::ir::MoveIRLiteral;
     use crate::moveir::ir::MoveIROperation;
     use crate::moveir::ir::MoveIRTransfer;
 
-    use crate::moveir::utils::remove_moves;
+    use crate::moveir::ir::{MoveIRBlock, MoveIRIf, MoveIRStatement};
+    use crate::moveir::utils::{copies_reference, remove_moves};
 
     #[test]
     fn test_remove_moves() {
@@ -384,7 +471,9 @@ mod test {
                         enclosing_type: None,
                         line_info: LineInfo {
                             line: 2,
+                            column: 9,
                             offset: 37,
+                            file: 0,
                         },
                     })),
                 })),
@@ -394,13 +483,17 @@ mod test {
                 enclosing_type: None,
                 line_info: LineInfo {
                     line: 18,
+                    column: 9,
                     offset: 377,
+                    file: 0,
                 },
             })),
             op: Equal,
             line_info: LineInfo {
                 line: 18,
+                column: 9,
                 offset: 377,
+                file: 0,
             },
         }));
 
@@ -436,4 +529,42 @@ mod test {
             ))
         );
     }
+
+    #[test]
+    fn test_copies_reference() {
+        let reference = Expression(BinaryExpression(BinaryExpression {
+            lhs_expression: Box::new(RawAssembly("_".to_string(), None)),
+            rhs_expression: Box::new(Identifier(Identifier::generated("_temp__3"))),
+            op: Equal,
+            line_info: Default::default(),
+        }));
+        let transfer = |transfer: fn(Box<MoveIRExpression>) -> MoveIRTransfer, name: &str| {
+            MoveIRExpression::Transfer(transfer(Box::new(MoveIRExpression::Identifier(
+                name.to_string(),
+            ))))
+        };
+        let if_statement = |expression| {
+            MoveIRStatement::If(MoveIRIf {
+                expression: MoveIRExpression::Literal(MoveIRLiteral::Bool(true)),
+                block: MoveIRBlock {
+                    statements: vec![MoveIRStatement::Expression(expression)],
+                },
+                else_block: None,
+            })
+        };
+
+        let copied = MoveIRExpression::Operation(MoveIROperation::Access(
+            Box::new(MoveIRExpression::Operation(MoveIROperation::Dereference(
+                Box::new(transfer(MoveIRTransfer::Copy, "_temp__3")),
+            ))),
+            "width".to_string(),
+        ));
+        assert!(copies_reference(&reference, &if_statement(copied)));
+
+        // Neither a move of the reference nor a copy of another variable which it prefixes is one
+        let moved = transfer(MoveIRTransfer::Move, "_temp__3");
+        assert!(!copies_reference(&reference, &if_statement(moved)));
+        let other = transfer(MoveIRTransfer::Copy, "_temp__31");
+        assert!(!copies_reference(&reference, &if_statement(other)));
+    }
 }
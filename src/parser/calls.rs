@@ -4,11 +4,10 @@ use crate::parser::identifiers::*;
 use crate::parser::operators::{colon, left_parens, right_parens};
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::sequence::preceded;
 
-pub fn parse_external_call(i: Span) -> nom::IResult<Span, ExternalCall> {
+pub fn parse_external_call(i: Span) -> IResult<ExternalCall> {
     let (i, _) = tag("call")(i)?;
     let (i, _) = whitespace(i)?;
     let function_arguments = vec![];
@@ -21,7 +20,7 @@ pub fn parse_external_call(i: Span) -> nom::IResult<Span, ExternalCall> {
     Ok((i, external_call))
 }
 
-pub fn parse_function_call(i: Span) -> nom::IResult<Span, FunctionCall> {
+pub fn parse_function_call(i: Span) -> IResult<FunctionCall> {
     let (i, identifier) = parse_identifier(i)?;
     let (i, arguments) = parse_function_call_arguments(i)?;
     let function_call = FunctionCall {
@@ -32,18 +31,18 @@ pub fn parse_function_call(i: Span) -> nom::IResult<Span, FunctionCall> {
     Ok((i, function_call))
 }
 
-fn parse_function_call_arguments(i: Span) -> nom::IResult<Span, Vec<FunctionArgument>> {
+fn parse_function_call_arguments(i: Span) -> IResult<Vec<FunctionArgument>> {
     let (i, _) = left_parens(i)?;
     let (i, arguments) = nom::multi::separated_list(
         tag(","),
         preceded(whitespace, parse_function_call_argument),
     )(i)?;
     let (i, _) = whitespace(i)?;
-    let (i, _) = right_parens(i)?;
+    let (i, _) = closing(right_parens, parse_function_call_argument)(i)?;
     Ok((i, arguments))
 }
 
-fn parse_function_call_argument(i: Span) -> nom::IResult<Span, FunctionArgument> {
+fn parse_function_call_argument(i: Span) -> IResult<FunctionArgument> {
     alt((
         map(
             nom::sequence::separated_pair(
@@ -2,9 +2,9 @@ use crate::ast::{
     AssetDeclaration, AssetMember, Attribute, CallerProtection, Conformance,
     ContractBehaviourDeclaration, ContractBehaviourMember, ContractDeclaration, ContractMember,
     EnumDeclaration, EnumMember, EventDeclaration, FunctionCall, FunctionDeclaration,
-    FunctionSignatureDeclaration, Identifier, Modifier, SpecialDeclaration,
-    SpecialSignatureDeclaration, StructDeclaration, StructMember, TopLevelDeclaration,
-    TraitDeclaration, TraitMember, Type, VariableDeclaration,
+    FunctionSignatureDeclaration, Identifier, ImportDeclaration, Modifier, SpecialDeclaration,
+    SpecialSignatureDeclaration, Specification, SpecificationKind, StructDeclaration, StructMember,
+    TopLevelDeclaration, TraitDeclaration, TraitMember, Type, VariableDeclaration,
 };
 use crate::parser::calls::parse_function_call;
 use crate::parser::expressions::*;
@@ -16,29 +16,47 @@ use crate::parser::type_states::*;
 use crate::parser::types::*;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::multi::many0;
 use nom::sequence::preceded;
 
-pub fn parse_top_level_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
-    let (i, top) = alt((
-        parse_contract_declaration,
-        map(
-            parse_contract_behaviour_declaration,
-            TopLevelDeclaration::ContractBehaviourDeclaration,
-        ),
-        parse_struct_declaration,
-        parse_asset_declaration,
-        parse_enum_declaration,
-        parse_trait_declaration,
-    ))(i)?;
+pub fn parse_top_level_declaration(i: Span) -> IResult<TopLevelDeclaration> {
+    let (i, top) = expected(
+        "declaration",
+        alt((
+            parse_contract_declaration,
+            map(
+                parse_contract_behaviour_declaration,
+                TopLevelDeclaration::ContractBehaviourDeclaration,
+            ),
+            parse_struct_declaration,
+            parse_asset_declaration,
+            parse_enum_declaration,
+            parse_trait_declaration,
+        )),
+    )(i)?;
     Ok((i, top))
 }
 
+// import declaration
+
+pub fn parse_import_declaration(i: Span) -> IResult<ImportDeclaration> {
+    let line_info = line_info(&i);
+    let (i, _) = tag("import")(i)?;
+    let (i, _) = nom::character::complete::space1(i)?;
+    let (i, _) = tag("\"")(i)?;
+    let (i, path) = nom::bytes::complete::is_not("\"\n")(i)?;
+    let (i, _) = tag("\"")(i)?;
+    let import_declaration = ImportDeclaration {
+        path: path.fragment().to_string(),
+        line_info,
+    };
+    Ok((i, import_declaration))
+}
+
 // Event declaration
 
-fn parse_event_declaration(i: Span) -> nom::IResult<Span, EventDeclaration> {
+fn parse_event_declaration(i: Span) -> IResult<EventDeclaration> {
     let (i, _event_token) = tag("event")(i)?;
     let (i, _) = whitespace(i)?;
     let (i, identifier) = parse_identifier(i)?;
@@ -53,7 +71,7 @@ fn parse_event_declaration(i: Span) -> nom::IResult<Span, EventDeclaration> {
 
 // contract declaration
 
-fn parse_contract_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
+fn parse_contract_declaration(i: Span) -> IResult<TopLevelDeclaration> {
     let (i, _) = tag("contract")(i)?;
     let (i, identifier) = preceded(nom::character::complete::space0, parse_identifier)(i)?;
     let (i, _) = whitespace(i)?;
@@ -81,7 +99,7 @@ fn parse_contract_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration
     }
 
     let (i, _) = whitespace(i)?;
-    let (i, _) = right_brace(i)?;
+    let (i, _) = closing(right_brace, preceded(whitespace, parse_contract_member))(i)?;
     let contract = ContractDeclaration {
         identifier,
         contract_members,
@@ -91,16 +109,23 @@ fn parse_contract_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration
     Ok((i, TopLevelDeclaration::ContractDeclaration(contract)))
 }
 
-fn parse_contract_member(i: Span) -> nom::IResult<Span, ContractMember> {
-    alt((
-        map(parse_event_declaration, ContractMember::EventDeclaration),
-        map(parse_variable_declaration_enclosing, |(dec, modifier)| {
-            ContractMember::VariableDeclaration(dec, modifier)
-        }),
-    ))(i)
+fn parse_contract_member(i: Span) -> IResult<ContractMember> {
+    expected(
+        "declaration",
+        alt((
+            map(parse_event_declaration, ContractMember::EventDeclaration),
+            map(parse_variable_declaration_enclosing, |(dec, modifier)| {
+                ContractMember::VariableDeclaration(dec, modifier)
+            }),
+            map(
+                parse_specification(SpecificationKind::Invariant),
+                ContractMember::Invariant,
+            ),
+        )),
+    )(i)
 }
 
-fn parse_conformances(i: Span) -> nom::IResult<Span, Vec<Conformance>> {
+fn parse_conformances(i: Span) -> IResult<Vec<Conformance>> {
     let (i, colon_token) = nom::combinator::opt(colon)(i)?;
     if colon_token.is_none() {
         return Ok((i, Vec::new()));
@@ -116,9 +141,7 @@ fn parse_conformances(i: Span) -> nom::IResult<Span, Vec<Conformance>> {
 
 // contract behaviour declaration
 
-fn parse_contract_behaviour_declaration(
-    i: Span,
-) -> nom::IResult<Span, ContractBehaviourDeclaration> {
+fn parse_contract_behaviour_declaration(i: Span) -> IResult<ContractBehaviourDeclaration> {
     let (i, identifier) = parse_identifier(i)?;
     let (i, _) = whitespace(i)?;
     let (i, at_token) = nom::combinator::opt(at)(i)?;
@@ -158,7 +181,10 @@ fn parse_contract_behaviour_declaration(
         }
     }
 
-    let (i, _) = right_brace(i)?;
+    let (i, _) = closing(
+        right_brace,
+        preceded(whitespace, parse_contract_behaviour_member),
+    )(i)?;
     let contract_behaviour_declaration = ContractBehaviourDeclaration {
         members,
         identifier,
@@ -169,28 +195,31 @@ fn parse_contract_behaviour_declaration(
     Ok((i, contract_behaviour_declaration))
 }
 
-fn parse_contract_behaviour_member(i: Span) -> nom::IResult<Span, ContractBehaviourMember> {
-    alt((
-        map(
-            parse_function_declaration,
-            ContractBehaviourMember::FunctionDeclaration,
-        ),
-        map(
-            parse_special_declaration,
-            ContractBehaviourMember::SpecialDeclaration,
-        ),
-        map(
-            parse_special_signature_declaration,
-            ContractBehaviourMember::SpecialSignatureDeclaration,
-        ),
-        map(
-            parse_function_signature_declaration,
-            ContractBehaviourMember::FunctionSignatureDeclaration,
-        ),
-    ))(i)
+fn parse_contract_behaviour_member(i: Span) -> IResult<ContractBehaviourMember> {
+    expected(
+        "function declaration",
+        alt((
+            map(
+                parse_function_declaration,
+                ContractBehaviourMember::FunctionDeclaration,
+            ),
+            map(
+                parse_special_declaration,
+                ContractBehaviourMember::SpecialDeclaration,
+            ),
+            map(
+                parse_special_signature_declaration,
+                ContractBehaviourMember::SpecialSignatureDeclaration,
+            ),
+            map(
+                parse_function_signature_declaration,
+                ContractBehaviourMember::FunctionSignatureDeclaration,
+            ),
+        )),
+    )(i)
 }
 
-fn parse_caller_binding(i: Span) -> nom::IResult<Span, Identifier> {
+fn parse_caller_binding(i: Span) -> IResult<Identifier> {
     let (i, identifier) = parse_identifier(i)?;
     let (i, _) = whitespace(i)?;
     let (i, _) = left_arrow(i)?;
@@ -198,13 +227,13 @@ fn parse_caller_binding(i: Span) -> nom::IResult<Span, Identifier> {
 }
 
 #[allow(dead_code)]
-fn parse_protection_binding(i: Span) -> nom::IResult<Span, Identifier> {
+fn parse_protection_binding(i: Span) -> IResult<Identifier> {
     let (i, identifier) = parse_identifier(i)?;
     let (i, _) = left_arrow(i)?;
     Ok((i, identifier))
 }
 
-fn parse_caller_protection_group(i: Span) -> nom::IResult<Span, Vec<CallerProtection>> {
+fn parse_caller_protection_group(i: Span) -> IResult<Vec<CallerProtection>> {
     let (i, identifiers) = parse_identifier_group(i)?;
     let caller_protections = identifiers
         .into_iter()
@@ -217,7 +246,7 @@ fn parse_caller_protection_group(i: Span) -> nom::IResult<Span, Vec<CallerProtec
 
 fn parse_variable_declaration_enclosing(
     i: Span,
-) -> nom::IResult<Span, (VariableDeclaration, Option<Modifier>)> {
+) -> IResult<(VariableDeclaration, Option<Modifier>)> {
     let (i, modifier) = nom::combinator::opt(parse_modifier)(i)?;
     let (i, _) = whitespace(i)?;
     let (i, declaration_token) = alt((tag("var"), tag("let")))(i)?;
@@ -245,7 +274,7 @@ fn parse_variable_declaration_enclosing(
     Ok((i, (variable_declaration, modifier)))
 }
 
-pub fn parse_variable_declaration(i: Span) -> nom::IResult<Span, VariableDeclaration> {
+pub fn parse_variable_declaration(i: Span) -> IResult<VariableDeclaration> {
     let (i, _) = parse_modifiers(i)?;
     let (i, _) = whitespace(i)?;
     let (i, declaration_token) = alt((tag("var"), tag("let")))(i)?;
@@ -264,7 +293,7 @@ pub fn parse_variable_declaration(i: Span) -> nom::IResult<Span, VariableDeclara
 
 // enum declaration
 
-fn parse_enum_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
+fn parse_enum_declaration(i: Span) -> IResult<TopLevelDeclaration> {
     let (i, enum_token) = tag("enum")(i)?;
     let (i, identifier) = preceded(nom::character::complete::space0, parse_identifier)(i)?;
     let (i, type_annotation) = nom::combinator::opt(parse_type_annotation)(i)?;
@@ -285,7 +314,7 @@ fn parse_enum_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
     }
     let members = enum_members;
     let (i, _) = whitespace(i)?;
-    let (i, _) = right_brace(i)?;
+    let (i, _) = closing(right_brace, parse_enum_member)(i)?;
     let enum_declaration = EnumDeclaration {
         enum_token: enum_token.to_string(),
         identifier,
@@ -295,7 +324,7 @@ fn parse_enum_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
     Ok((i, TopLevelDeclaration::EnumDeclaration(enum_declaration)))
 }
 
-fn parse_enum_member(i: Span) -> nom::IResult<Span, EnumMember> {
+fn parse_enum_member(i: Span) -> IResult<EnumMember> {
     let (i, case_token) = tag("case")(i)?;
     let (i, identifier) = preceded(nom::character::complete::space0, parse_identifier)(i)?;
     let (i, equal_token) = nom::combinator::opt(preceded(whitespace, equal_operator))(i)?;
@@ -323,7 +352,7 @@ fn parse_enum_member(i: Span) -> nom::IResult<Span, EnumMember> {
 
 // special declaration
 
-fn parse_special_declaration(i: Span) -> nom::IResult<Span, SpecialDeclaration> {
+fn parse_special_declaration(i: Span) -> IResult<SpecialDeclaration> {
     let (i, signature) = parse_special_signature_declaration(i)?;
     let (i, _) = whitespace(i)?;
     let (i, statements) = parse_code_block(i)?;
@@ -337,7 +366,7 @@ fn parse_special_declaration(i: Span) -> nom::IResult<Span, SpecialDeclaration>
     Ok((i, special_declaration))
 }
 
-fn parse_special_signature_declaration(i: Span) -> nom::IResult<Span, SpecialSignatureDeclaration> {
+fn parse_special_signature_declaration(i: Span) -> IResult<SpecialSignatureDeclaration> {
     let (i, attributes) = parse_attributes(i)?;
     let (i, modifiers) = parse_modifiers(i)?;
     let (i, special_token) = alt((tag("init"), tag("fallback")))(i)?;
@@ -355,11 +384,11 @@ fn parse_special_signature_declaration(i: Span) -> nom::IResult<Span, SpecialSig
     Ok((i, special_signature_declaration))
 }
 
-fn parse_attributes(i: Span) -> nom::IResult<Span, Vec<Attribute>> {
+fn parse_attributes(i: Span) -> IResult<Vec<Attribute>> {
     many0(nom::sequence::terminated(parse_attribute, whitespace))(i)
 }
 
-fn parse_attribute(i: Span) -> nom::IResult<Span, Attribute> {
+fn parse_attribute(i: Span) -> IResult<Attribute> {
     let (i, at) = at(i)?;
     let (i, identifier) = parse_identifier(i)?;
     let attribute = Attribute {
@@ -369,7 +398,7 @@ fn parse_attribute(i: Span) -> nom::IResult<Span, Attribute> {
     Ok((i, attribute))
 }
 
-fn parse_mutates(i: Span) -> nom::IResult<Span, Vec<Identifier>> {
+fn parse_mutates(i: Span) -> IResult<Vec<Identifier>> {
     let identifiers = Vec::new();
     let (i, mutates) = nom::combinator::opt(tag("mutates"))(i)?;
     if mutates.is_none() {
@@ -393,13 +422,21 @@ fn parse_mutates(i: Span) -> nom::IResult<Span, Vec<Identifier>> {
 
 // function declaration
 
-fn parse_function_declaration(i: Span) -> nom::IResult<Span, FunctionDeclaration> {
+fn parse_function_declaration(i: Span) -> IResult<FunctionDeclaration> {
     let (i, signature) = parse_function_signature_declaration(i)?;
+    let (i, specifications) = many0(preceded(
+        whitespace,
+        alt((
+            parse_specification(SpecificationKind::Pre),
+            parse_specification(SpecificationKind::Post),
+        )),
+    ))(i)?;
     let (i, _) = whitespace(i)?;
     let (i, statements) = parse_code_block(i)?;
 
     let function_declaration = FunctionDeclaration {
         head: signature,
+        specifications,
         body: statements,
         scope_context: None,
         tags: vec![],
@@ -409,9 +446,7 @@ fn parse_function_declaration(i: Span) -> nom::IResult<Span, FunctionDeclaration
     Ok((i, function_declaration))
 }
 
-fn parse_function_signature_declaration(
-    i: Span,
-) -> nom::IResult<Span, FunctionSignatureDeclaration> {
+fn parse_function_signature_declaration(i: Span) -> IResult<FunctionSignatureDeclaration> {
     let (i, attributes) = parse_attributes(i)?;
     let payable = attributes.iter().any(|a| a.identifier_token == "payable");
     let (i, _) = whitespace(i)?;
@@ -438,7 +473,30 @@ fn parse_function_signature_declaration(
     Ok((i, function_signature_declaration))
 }
 
-fn parse_result(i: Span) -> nom::IResult<Span, Option<Type>> {
+// specifications
+
+fn parse_specification<'a>(
+    kind: SpecificationKind,
+) -> impl Fn(Span<'a>) -> IResult<'a, Specification> {
+    move |i| {
+        let line_info = line_info(&i);
+        let (i, _) = tag(kind.keyword())(i)?;
+        let (i, _) = nom::character::complete::space0(i)?;
+        let (i, _) = left_parens(i)?;
+        let (i, _) = whitespace(i)?;
+        let (i, expression) = parse_expression(i)?;
+        let (i, _) = whitespace(i)?;
+        let (i, _) = right_parens(i)?;
+        let specification = Specification {
+            kind,
+            expression,
+            line_info,
+        };
+        Ok((i, specification))
+    }
+}
+
+fn parse_result(i: Span) -> IResult<Option<Type>> {
     let (i, token) = nom::combinator::opt(right_arrow)(i)?;
     if token.is_none() {
         return Ok((i, None));
@@ -450,7 +508,7 @@ fn parse_result(i: Span) -> nom::IResult<Span, Option<Type>> {
 
 // asset declaration
 
-fn parse_asset_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
+fn parse_asset_declaration(i: Span) -> IResult<TopLevelDeclaration> {
     let (i, _struct_token) = tag("asset")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, identifier) = parse_identifier(i)?;
@@ -461,7 +519,7 @@ fn parse_asset_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
         nom::character::complete::multispace0,
     ))(i)?;
     let (i, _) = whitespace(i)?;
-    let (i, _) = right_brace(i)?;
+    let (i, _) = closing(right_brace, preceded(whitespace, parse_asset_member))(i)?;
     let asset_declaration = AssetDeclaration {
         identifier,
         members,
@@ -469,19 +527,22 @@ fn parse_asset_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
     Ok((i, TopLevelDeclaration::AssetDeclaration(asset_declaration)))
 }
 
-fn parse_asset_member(i: Span) -> nom::IResult<Span, AssetMember> {
-    alt((
-        map(parse_function_declaration, AssetMember::FunctionDeclaration),
-        map(parse_special_declaration, AssetMember::SpecialDeclaration),
-        map(parse_variable_declaration_enclosing, |(dec, _)| {
-            AssetMember::VariableDeclaration(dec)
-        }),
-    ))(i)
+fn parse_asset_member(i: Span) -> IResult<AssetMember> {
+    expected(
+        "declaration",
+        alt((
+            map(parse_function_declaration, AssetMember::FunctionDeclaration),
+            map(parse_special_declaration, AssetMember::SpecialDeclaration),
+            map(parse_variable_declaration_enclosing, |(dec, _)| {
+                AssetMember::VariableDeclaration(dec)
+            }),
+        )),
+    )(i)
 }
 
 // struct declaration
 
-fn parse_struct_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
+fn parse_struct_declaration(i: Span) -> IResult<TopLevelDeclaration> {
     let (i, _) = tag("struct")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, identifier) = parse_identifier(i)?;
@@ -505,11 +566,12 @@ fn parse_struct_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration>
             StructMember::SpecialDeclaration(dec) => {
                 dec.head.enclosing_type = Some(identifier.token.clone())
             }
+            StructMember::Invariant(_) => {}
         }
     }
 
     let (i, _) = whitespace(i)?;
-    let (i, _) = right_brace(i)?;
+    let (i, _) = closing(right_brace, preceded(whitespace, parse_struct_member))(i)?;
     let struct_declaration = StructDeclaration {
         identifier,
         conformances,
@@ -521,22 +583,29 @@ fn parse_struct_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration>
     ))
 }
 
-fn parse_struct_member(i: Span) -> nom::IResult<Span, StructMember> {
-    alt((
-        map(
-            parse_function_declaration,
-            StructMember::FunctionDeclaration,
-        ),
-        map(parse_special_declaration, StructMember::SpecialDeclaration),
-        map(parse_variable_declaration_enclosing, |(dec, modifier)| {
-            StructMember::VariableDeclaration(dec, modifier)
-        }),
-    ))(i)
+fn parse_struct_member(i: Span) -> IResult<StructMember> {
+    expected(
+        "declaration",
+        alt((
+            map(
+                parse_function_declaration,
+                StructMember::FunctionDeclaration,
+            ),
+            map(parse_special_declaration, StructMember::SpecialDeclaration),
+            map(parse_variable_declaration_enclosing, |(dec, modifier)| {
+                StructMember::VariableDeclaration(dec, modifier)
+            }),
+            map(
+                parse_specification(SpecificationKind::Invariant),
+                StructMember::Invariant,
+            ),
+        )),
+    )(i)
 }
 
 // trait declaration
 
-fn parse_trait_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
+fn parse_trait_declaration(i: Span) -> IResult<TopLevelDeclaration> {
     let (i, modifiers) = many0(nom::sequence::terminated(
         preceded(whitespace, parse_trait_modifier),
         whitespace,
@@ -552,7 +621,7 @@ fn parse_trait_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
         preceded(whitespace, parse_trait_member),
         nom::character::complete::multispace0,
     ))(i)?;
-    let (i, _) = right_brace(i)?;
+    let (i, _) = closing(right_brace, preceded(whitespace, parse_trait_member))(i)?;
     let trait_declaration = TraitDeclaration {
         external: external.is_some(),
         identifier,
@@ -562,7 +631,7 @@ fn parse_trait_declaration(i: Span) -> nom::IResult<Span, TopLevelDeclaration> {
     Ok((i, TopLevelDeclaration::TraitDeclaration(trait_declaration)))
 }
 
-fn parse_trait_modifier(i: Span) -> nom::IResult<Span, FunctionCall> {
+fn parse_trait_modifier(i: Span) -> IResult<FunctionCall> {
     let (i, _) = tag("@")(i)?;
     let (i, fc) = nom::combinator::opt(parse_function_call)(i)?;
     if let Some(fc) = fc {
@@ -578,35 +647,38 @@ fn parse_trait_modifier(i: Span) -> nom::IResult<Span, FunctionCall> {
     Ok((i, fc))
 }
 
-fn parse_trait_member(i: Span) -> nom::IResult<Span, TraitMember> {
-    alt((
-        map(parse_function_declaration, TraitMember::FunctionDeclaration),
-        map(parse_special_declaration, TraitMember::SpecialDeclaration),
-        map(
-            parse_function_signature_declaration,
-            TraitMember::FunctionSignatureDeclaration,
-        ),
-        map(
-            parse_special_signature_declaration,
-            TraitMember::SpecialSignatureDeclaration,
-        ),
-        map(parse_event_declaration, TraitMember::EventDeclaration),
-        map(
-            parse_contract_behaviour_declaration,
-            TraitMember::ContractBehaviourDeclaration,
-        ),
-    ))(i)
+fn parse_trait_member(i: Span) -> IResult<TraitMember> {
+    expected(
+        "declaration",
+        alt((
+            map(parse_function_declaration, TraitMember::FunctionDeclaration),
+            map(parse_special_declaration, TraitMember::SpecialDeclaration),
+            map(
+                parse_function_signature_declaration,
+                TraitMember::FunctionSignatureDeclaration,
+            ),
+            map(
+                parse_special_signature_declaration,
+                TraitMember::SpecialSignatureDeclaration,
+            ),
+            map(parse_event_declaration, TraitMember::EventDeclaration),
+            map(
+                parse_contract_behaviour_declaration,
+                TraitMember::ContractBehaviourDeclaration,
+            ),
+        )),
+    )(i)
 }
 
 #[cfg(test)]
 mod test {
     use crate::ast::*;
     use crate::parser::declarations::*;
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     #[test]
     fn test_parse_contract_member() {
-        let input = LocatedSpan::new("var minter: Address");
+        let input = new_span("var minter: Address");
         let (_rest, result) = parse_contract_member(input).expect("Error parsing contract member");
         assert_eq!(
             result,
@@ -617,7 +689,12 @@ mod test {
                     identifier: Identifier {
                         token: String::from("minter"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 0 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 1,
+                            offset: 0,
+                            file: 0,
+                        },
                     },
 
                     variable_type: Type::Address,
@@ -628,10 +705,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_invariant() {
+        let input = new_span("invariant(open)");
+        let (_rest, result) = parse_contract_member(input).expect("Error parsing invariant");
+        assert_eq!(
+            result,
+            ContractMember::Invariant(Specification {
+                kind: SpecificationKind::Invariant,
+                expression: Expression::Identifier(Identifier {
+                    token: String::from("open"),
+                    enclosing_type: None,
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 11,
+                        offset: 10,
+                        file: 0,
+                    },
+                }),
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
+            })
+        );
+    }
+
     #[test]
     fn test_parse_caller_binding() {
         let input = "caller <-";
-        let input = LocatedSpan::new(input);
+        let input = new_span(input);
         let result = parse_caller_binding(input);
         match result {
             Ok((_c, b)) => assert_eq!(b, Identifier::generated("caller")),
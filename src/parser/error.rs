@@ -0,0 +1,156 @@
+use crate::diagnostic::{code, Diagnostic};
+use crate::parser::utils::{line_info, Span};
+use nom::error::{ErrorKind, ParseError};
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expected {
+    Token(&'static str),
+    Syntax(&'static str),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Token(token) => write!(f, "`{}`", token),
+            Expected::Syntax(syntax) => write!(f, "{}", syntax),
+        }
+    }
+}
+
+/// The furthest point the parser reached before failing, with what could have come next there.
+/// When alternatives fail, the error of the one that got furthest is kept, as it is the one the
+/// programmer most likely meant to write
+#[derive(Debug, PartialEq)]
+pub struct SyntaxError<'a> {
+    pub span: Span<'a>,
+    pub expected: Vec<Expected>,
+}
+
+impl<'a> SyntaxError<'a> {
+    pub fn expected(span: Span<'a>, expected: Expected) -> SyntaxError<'a> {
+        SyntaxError {
+            span,
+            expected: vec![expected],
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.span.location_offset()
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        // Point at the unexpected token rather than the whitespace before it
+        let fragment = self.span.fragment();
+        let found = fragment.trim_start_matches(&[' ', '\t'][..]);
+        let skipped = fragment.len() - found.len();
+        let mut line_info = line_info(&self.span);
+        line_info.column += skipped as u32;
+        line_info.offset += skipped;
+
+        let message = if self.expected.is_empty() {
+            format!("Unexpected {}", describe(found))
+        } else {
+            format!(
+                "Expected {}, found {}",
+                join(&self.expected),
+                describe(found)
+            )
+        };
+        Diagnostic::error(code::SYNTAX_ERROR, message).with_span(&line_info)
+    }
+}
+
+impl<'a> ParseError<Span<'a>> for SyntaxError<'a> {
+    fn from_error_kind(input: Span<'a>, _kind: ErrorKind) -> Self {
+        SyntaxError {
+            span: input,
+            expected: vec![],
+        }
+    }
+
+    fn append(_input: Span<'a>, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        match self.offset().cmp(&other.offset()) {
+            Ordering::Greater => self,
+            Ordering::Less => other,
+            Ordering::Equal => {
+                for expected in other.expected {
+                    if !self.expected.contains(&expected) {
+                        self.expected.push(expected);
+                    }
+                }
+                self
+            }
+        }
+    }
+}
+
+fn describe(found: &str) -> String {
+    if found.is_empty() {
+        return "end of file".to_string();
+    }
+    if found.starts_with('\n') || found.starts_with("\r\n") {
+        return "end of line".to_string();
+    }
+
+    let length = found
+        .char_indices()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+        .last()
+        .map(|(index, c)| index + c.len_utf8())
+        .unwrap_or_else(|| found.chars().next().map(char::len_utf8).unwrap_or(0));
+    format!("`{}`", &found[..length])
+}
+
+fn join(expected: &[Expected]) -> String {
+    let expected: Vec<String> = expected.iter().map(Expected::to_string).collect();
+    match expected.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => expected.join(""),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::error::*;
+    use crate::parser::utils::new_span;
+
+    #[test]
+    fn test_furthest_error_is_kept() {
+        let input = new_span("var x Int");
+        let (rest, _) = nom::bytes::complete::tag::<_, _, SyntaxError>("var x")(input).unwrap();
+
+        let near = SyntaxError::expected(input, Expected::Token("let"));
+        let far = SyntaxError::expected(rest, Expected::Token(":"));
+        assert_eq!(near.or(far).offset(), 5);
+
+        let near = SyntaxError::expected(input, Expected::Token("let"));
+        let far = SyntaxError::expected(rest, Expected::Token(":"));
+        assert_eq!(far.or(near).offset(), 5);
+
+        let merged = SyntaxError::expected(rest, Expected::Token(":"))
+            .or(SyntaxError::expected(rest, Expected::Token("=")));
+        assert_eq!(
+            merged.expected,
+            vec![Expected::Token(":"), Expected::Token("=")]
+        );
+    }
+
+    #[test]
+    fn test_syntax_error_message() {
+        let input = new_span("func f(x Int)");
+        let (rest, _) = nom::bytes::complete::tag::<_, _, SyntaxError>("func f(x")(input).unwrap();
+        let error = SyntaxError::expected(rest, Expected::Token(":"))
+            .or(SyntaxError::expected(rest, Expected::Token(",")))
+            .or(SyntaxError::expected(rest, Expected::Token(")")));
+
+        let diagnostic = error.to_diagnostic();
+        assert_eq!(diagnostic.message, "Expected `:`, `,` or `)`, found `Int`");
+        assert_eq!(diagnostic.primary.unwrap().line_info.offset, 9);
+    }
+}
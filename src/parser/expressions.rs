@@ -1,6 +1,6 @@
 use crate::ast::{
     AttemptExpression, BinaryExpression, BracketedExpression, CastExpression, Expression,
-    Identifier, InoutExpression, LineInfo, RangeExpression, SubscriptExpression,
+    Identifier, InoutExpression, OldExpression, RangeExpression, SubscriptExpression,
 };
 use crate::parser::calls::*;
 use crate::parser::declarations::parse_variable_declaration;
@@ -10,41 +10,45 @@ use crate::parser::operators::*;
 use crate::parser::types::*;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::sequence::preceded;
 
-pub fn parse_expression(i: Span) -> nom::IResult<Span, Expression> {
-    alt((
-        map(parse_inout_expression, Expression::InoutExpression),
-        map(parse_external_call, Expression::ExternalCall),
-        map(parse_cast_expression, Expression::CastExpression),
-        map(parse_binary_expression, Expression::BinaryExpression),
-        map(tag(Identifier::SELF), |_| Expression::SelfExpression),
-        map(parse_subscript_expression, Expression::SubscriptExpression),
-        map(parse_attempt_expression, Expression::AttemptExpression),
-        map(parse_function_call, Expression::FunctionCall),
-        map(parse_variable_declaration, Expression::VariableDeclaration),
-        map(parse_literal, Expression::Literal),
-        map(parse_identifier, Expression::Identifier),
-        map(parse_bracketed_expression, Expression::BracketedExpression),
-        map(parse_array_literal, Expression::ArrayLiteral),
-        map(parse_dictionary_literal, Expression::DictionaryLiteral),
-        map(
-            parse_dictionary_empty_literal,
-            Expression::DictionaryLiteral,
-        ),
-        map(parse_range_expression, Expression::RangeExpression),
-    ))(i)
+pub fn parse_expression(i: Span) -> IResult<Expression> {
+    expected(
+        "expression",
+        alt((
+            map(parse_inout_expression, Expression::InoutExpression),
+            map(parse_external_call, Expression::ExternalCall),
+            map(parse_cast_expression, Expression::CastExpression),
+            map(parse_binary_expression, Expression::BinaryExpression),
+            map(tag(Identifier::SELF), |_| Expression::SelfExpression),
+            map(parse_subscript_expression, Expression::SubscriptExpression),
+            map(parse_attempt_expression, Expression::AttemptExpression),
+            map(parse_old_expression, Expression::OldExpression),
+            map(parse_function_call, Expression::FunctionCall),
+            map(parse_variable_declaration, Expression::VariableDeclaration),
+            map(parse_literal, Expression::Literal),
+            map(parse_identifier, Expression::Identifier),
+            map(parse_bracketed_expression, Expression::BracketedExpression),
+            map(parse_array_literal, Expression::ArrayLiteral),
+            map(parse_dictionary_literal, Expression::DictionaryLiteral),
+            map(
+                parse_dictionary_empty_literal,
+                Expression::DictionaryLiteral,
+            ),
+            map(parse_range_expression, Expression::RangeExpression),
+        )),
+    )(i)
 }
 
-pub fn parse_expression_left(i: Span) -> nom::IResult<Span, Expression> {
+pub fn parse_expression_left(i: Span) -> IResult<Expression> {
     alt((
         map(parse_inout_expression, Expression::InoutExpression),
         map(parse_external_call, Expression::ExternalCall),
         map(parse_cast_expression, Expression::CastExpression),
         map(tag(Identifier::SELF), |_| Expression::SelfExpression),
         map(parse_subscript_expression, Expression::SubscriptExpression),
+        map(parse_old_expression, Expression::OldExpression),
         map(parse_function_call, Expression::FunctionCall),
         map(parse_attempt_expression, Expression::AttemptExpression),
         map(parse_variable_declaration, Expression::VariableDeclaration),
@@ -60,7 +64,7 @@ pub fn parse_expression_left(i: Span) -> nom::IResult<Span, Expression> {
     ))(i)
 }
 
-fn parse_subscript_expression(i: Span) -> nom::IResult<Span, SubscriptExpression> {
+fn parse_subscript_expression(i: Span) -> IResult<SubscriptExpression> {
     let (i, identifier) = parse_identifier(i)?;
     let (i, _) = left_square_bracket(i)?;
     let (i, expression) = parse_expression(i)?;
@@ -72,7 +76,7 @@ fn parse_subscript_expression(i: Span) -> nom::IResult<Span, SubscriptExpression
     Ok((i, subscript_expression))
 }
 
-fn parse_range_expression(i: Span) -> nom::IResult<Span, RangeExpression> {
+fn parse_range_expression(i: Span) -> IResult<RangeExpression> {
     let (i, _) = left_parens(i)?;
     let (i, start_literal) = parse_literal(i)?;
     let (i, op) = alt((half_open_range, closed_range))(i)?;
@@ -86,7 +90,7 @@ fn parse_range_expression(i: Span) -> nom::IResult<Span, RangeExpression> {
     Ok((i, range_expression))
 }
 
-fn parse_cast_expression(i: Span) -> nom::IResult<Span, CastExpression> {
+fn parse_cast_expression(i: Span) -> IResult<CastExpression> {
     let (i, _) = tag("cast")(i)?;
     let (i, _) = whitespace(i)?;
     let (i, expression) = parse_expression(i)?;
@@ -101,7 +105,7 @@ fn parse_cast_expression(i: Span) -> nom::IResult<Span, CastExpression> {
     Ok((i, cast_expression))
 }
 
-fn parse_inout_expression(i: Span) -> nom::IResult<Span, InoutExpression> {
+fn parse_inout_expression(i: Span) -> IResult<InoutExpression> {
     let (i, _) = ampersand(i)?;
     let (i, expression) = parse_expression(i)?;
     let inout_expression = InoutExpression {
@@ -111,7 +115,7 @@ fn parse_inout_expression(i: Span) -> nom::IResult<Span, InoutExpression> {
     Ok((i, inout_expression))
 }
 
-fn parse_bracketed_expression(i: Span) -> nom::IResult<Span, BracketedExpression> {
+fn parse_bracketed_expression(i: Span) -> IResult<BracketedExpression> {
     let (i, _) = left_parens(i)?;
     let (i, expression) = parse_expression(i)?;
     let (i, _) = right_parens(i)?;
@@ -121,7 +125,7 @@ fn parse_bracketed_expression(i: Span) -> nom::IResult<Span, BracketedExpression
     Ok((i, bracketed_expression))
 }
 
-fn parse_attempt_expression(i: Span) -> nom::IResult<Span, AttemptExpression> {
+fn parse_attempt_expression(i: Span) -> IResult<AttemptExpression> {
     let (i, _) = tag("try")(i)?;
     let (i, kind) = alt((bang, question))(i)?;
     let (i, _) = whitespace(i)?;
@@ -134,7 +138,20 @@ fn parse_attempt_expression(i: Span) -> nom::IResult<Span, AttemptExpression> {
     Ok((i, attempt_expression))
 }
 
-pub fn parse_binary_expression(input: Span) -> nom::IResult<Span, BinaryExpression> {
+fn parse_old_expression(i: Span) -> IResult<OldExpression> {
+    let line_info = line_info(&i);
+    let (i, _) = tag("old")(i)?;
+    let (i, _) = left_parens(i)?;
+    let (i, expression) = parse_expression(i)?;
+    let (i, _) = right_parens(i)?;
+    let old_expression = OldExpression {
+        expression: Box::new(expression),
+        line_info,
+    };
+    Ok((i, old_expression))
+}
+
+pub fn parse_binary_expression(input: Span) -> IResult<BinaryExpression> {
     let (i, _) = parse_expression_left(input)?;
     let _ = preceded(whitespace, parse_binary_op)(i)?;
     let (i, expression) = parse_binary_expression_precedence(input, 0)?;
@@ -148,11 +165,8 @@ pub fn parse_binary_expression(input: Span) -> nom::IResult<Span, BinaryExpressi
 pub fn parse_binary_expression_precedence(
     i: Span,
     operator_precedence: i32,
-) -> nom::IResult<Span, Expression> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
+) -> IResult<Expression> {
+    let line_info = line_info(&i);
     let (i, lhs_expression) = parse_expression_left(i)?;
     let mut lhs_expression = lhs_expression;
     let mut result = lhs_expression.clone();
@@ -193,11 +207,11 @@ mod test {
 
     use crate::ast::*;
     use crate::parser::expressions::*;
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     #[test]
     fn test_parse_inout_expression() {
-        let input = LocatedSpan::new("&expression");
+        let input = new_span("&expression");
         let (_rest, result) = parse_expression(input).expect("Error parsing inout expression");
         assert_eq!(
             result,
@@ -206,7 +220,12 @@ mod test {
                 expression: Box::new(Expression::Identifier(Identifier {
                     token: String::from("expression"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 })),
             })
         );
@@ -214,7 +233,7 @@ mod test {
 
     #[test]
     fn test_parse_bracketed_expression() {
-        let input = LocatedSpan::new("(expression)");
+        let input = new_span("(expression)");
         let (_rest, result) = parse_expression(input).expect("Error parsing bracketed expression");
         assert_eq!(
             result,
@@ -222,7 +241,12 @@ mod test {
                 expression: Box::new(Expression::Identifier(Identifier {
                     token: String::from("expression"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 }))
             })
         );
@@ -230,7 +254,7 @@ mod test {
 
     #[test]
     fn test_parse_attempt_expression() {
-        let input = LocatedSpan::new("try?foo()");
+        let input = new_span("try?foo()");
         let (_rest, result) =
             parse_attempt_expression(input).expect("Error parsing attempt expression");
         assert_eq!(
@@ -241,7 +265,12 @@ mod test {
                     identifier: Identifier {
                         token: String::from("foo"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 0 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 1,
+                            offset: 0,
+                            file: 0,
+                        },
                     },
 
                     arguments: vec![],
@@ -254,7 +283,7 @@ mod test {
 
     #[test]
     fn test_parse_subscript_expression() {
-        let input = LocatedSpan::new("base[index]");
+        let input = new_span("base[index]");
         let (_rest, result) = parse_expression(input).expect("Error parsing subscript expression");
         assert_eq!(
             result,
@@ -262,13 +291,23 @@ mod test {
                 base_expression: Identifier {
                     token: String::from("base"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
 
                 index_expression: Box::new(Expression::Identifier(Identifier {
                     token: String::from("index"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 })),
             })
         );
@@ -276,7 +315,7 @@ mod test {
 
     #[test]
     fn test_parse_binary_expression() {
-        let input = LocatedSpan::new("x ** 2");
+        let input = new_span("x ** 2");
         let (_rest, result) = parse_expression(input).expect("Error parsing binary expression");
         assert_eq!(
             result,
@@ -284,19 +323,29 @@ mod test {
                 lhs_expression: Box::new(Expression::Identifier(Identifier {
                     token: String::from("x"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 })),
 
                 rhs_expression: Box::new(Expression::Literal(Literal::IntLiteral(2))),
                 op: BinOp::Power,
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             })
         );
     }
 
     #[test]
     fn test_parse_self_expression() {
-        let input = LocatedSpan::new("self.rectangle.width");
+        let input = new_span("self.rectangle.width");
         let (_rest, result) = parse_expression(input).expect("Error parsing self expression");
         assert_eq!(
             result,
@@ -306,28 +355,45 @@ mod test {
                     rhs_expression: Box::new(Expression::Identifier(Identifier {
                         token: String::from("rectangle"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 5 }
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 6,
+                            offset: 5,
+                            file: 0,
+                        }
                     })),
                     op: BinOp::Dot,
-                    line_info: LineInfo { line: 1, offset: 0 }
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    }
                 })),
                 rhs_expression: Box::new(Expression::Identifier(Identifier {
                     token: String::from("width"),
                     enclosing_type: None,
                     line_info: LineInfo {
                         line: 1,
-                        offset: 15
+                        column: 16,
+                        offset: 15,
+                        file: 0,
                     }
                 })),
                 op: BinOp::Dot,
-                line_info: LineInfo { line: 1, offset: 0 }
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                }
             })
         );
     }
 
     #[test]
     fn test_parse_cast_expression() {
-        let input = LocatedSpan::new("cast x to Int");
+        let input = new_span("cast x to Int");
         let (_rest, result) = parse_expression(input).expect("Error parsing cast expression");
         assert_eq!(
             result,
@@ -335,7 +401,12 @@ mod test {
                 expression: Box::new(Expression::Identifier(Identifier {
                     token: String::from("x"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 })),
 
                 cast_type: Type::Int
@@ -345,7 +416,7 @@ mod test {
 
     #[test]
     fn test_parse_range_expression() {
-        let input = LocatedSpan::new("(0..<3)");
+        let input = new_span("(0..<3)");
         let (_rest, result) = parse_expression(input).expect("Error parsing range expression");
         assert_eq!(
             result,
@@ -1,16 +1,12 @@
-use crate::ast::{Identifier, LineInfo};
+use crate::ast::Identifier;
 use crate::parser::operators::*;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::multi::many0;
 use nom::sequence::preceded;
 
-pub fn parse_enclosing_identifier(i: Span) -> nom::IResult<Span, Identifier> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
+pub fn parse_enclosing_identifier(i: Span) -> IResult<Identifier> {
+    let line_info = line_info(&i);
     let (i, enclosing_type) = parse_identifier(i)?;
     let (i, _) = dot_operator(i)?;
     let (i, identifier) = parse_identifier(i)?;
@@ -22,12 +18,12 @@ pub fn parse_enclosing_identifier(i: Span) -> nom::IResult<Span, Identifier> {
     Ok((i, identifier))
 }
 
-pub fn parse_identifier(i: Span) -> nom::IResult<Span, Identifier> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
-    let (i, head) = alt((nom::character::complete::alpha1, tag("_")))(i)?;
+pub fn parse_identifier(i: Span) -> IResult<Identifier> {
+    let line_info = line_info(&i);
+    let (i, head) = expected(
+        "identifier",
+        alt((nom::character::complete::alpha1, tag("_"))),
+    )(i)?;
     let (i, tail) = nom::combinator::recognize(many0(alt((
         nom::character::complete::alphanumeric1,
         tag("_"),
@@ -43,11 +39,11 @@ pub fn parse_identifier(i: Span) -> nom::IResult<Span, Identifier> {
     Ok((i, identifier))
 }
 
-pub fn parse_identifier_list(i: Span) -> nom::IResult<Span, Vec<Identifier>> {
+pub fn parse_identifier_list(i: Span) -> IResult<Vec<Identifier>> {
     nom::multi::separated_list(tag(","), preceded(whitespace, parse_identifier))(i)
 }
 
-pub fn parse_identifier_group(i: Span) -> nom::IResult<Span, Vec<Identifier>> {
+pub fn parse_identifier_group(i: Span) -> IResult<Vec<Identifier>> {
     let (i, _) = left_parens(i)?;
     let (i, identifier_list) = parse_identifier_list(i)?;
     let (i, _) = right_parens(i)?;
@@ -56,28 +52,33 @@ pub fn parse_identifier_group(i: Span) -> nom::IResult<Span, Vec<Identifier>> {
 
 #[cfg(test)]
 mod test {
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     use crate::ast::*;
     use crate::parser::identifiers::*;
 
     #[test]
     fn test_parse_identifier() {
-        let input = LocatedSpan::new("id");
+        let input = new_span("id");
         let (_rest, result) = parse_identifier(input).expect("Error with parsing identifier");
         assert_eq!(
             result,
             Identifier {
                 token: String::from("id"),
                 enclosing_type: None,
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             }
         );
     }
 
     #[test]
     fn test_parse_identifier_list() {
-        let input = LocatedSpan::new("first, second, third");
+        let input = new_span("first, second, third");
         let (_rest, result) =
             parse_identifier_list(input).expect("Error with parsing identifier list");
         assert_eq!(
@@ -86,17 +87,32 @@ mod test {
                 Identifier {
                     token: String::from("first"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
                 Identifier {
                     token: String::from("second"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
                 Identifier {
                     token: String::from("third"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 }
             ]
         );
@@ -104,7 +120,7 @@ mod test {
 
     #[test]
     fn test_parse_identifier_group() {
-        let input = LocatedSpan::new("(first, second, third)");
+        let input = new_span("(first, second, third)");
         let (_rest, result) =
             parse_identifier_group(input).expect("Error with parsing identifier group");
         assert_eq!(
@@ -113,17 +129,32 @@ mod test {
                 Identifier {
                     token: String::from("first"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
                 Identifier {
                     token: String::from("second"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
                 Identifier {
                     token: String::from("third"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 }
             ]
         );
@@ -3,10 +3,9 @@ use crate::parser::expressions::*;
 use crate::parser::operators::*;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::sequence::preceded;
 
-pub fn parse_literal(i: Span) -> nom::IResult<Span, Literal> {
+pub fn parse_literal(i: Span) -> IResult<Literal> {
     alt((
         address_literal,
         parse_boolean_literal,
@@ -16,35 +15,35 @@ pub fn parse_literal(i: Span) -> nom::IResult<Span, Literal> {
     ))(i)
 }
 
-fn address_literal(i: Span) -> nom::IResult<Span, Literal> {
+fn address_literal(i: Span) -> IResult<Literal> {
     let (i, _) = tag("0x")(i)?;
     let (i, address) = nom::character::complete::hex_digit1(i)?;
     let string = format!("0x{}", address.to_string());
     Ok((i, Literal::AddressLiteral(string)))
 }
 
-fn parse_boolean_literal(i: Span) -> nom::IResult<Span, Literal> {
+fn parse_boolean_literal(i: Span) -> IResult<Literal> {
     alt((true_literal, false_literal))(i)
 }
 
-fn true_literal(i: Span) -> nom::IResult<Span, Literal> {
+fn true_literal(i: Span) -> IResult<Literal> {
     let (i, _) = tag("true")(i)?;
     Ok((i, Literal::BooleanLiteral(true)))
 }
 
-fn false_literal(i: Span) -> nom::IResult<Span, Literal> {
+fn false_literal(i: Span) -> IResult<Literal> {
     let (i, _) = tag("false")(i)?;
     Ok((i, Literal::BooleanLiteral(false)))
 }
 
-pub fn integer(input: Span) -> nom::IResult<Span, Literal> {
+pub fn integer(input: Span) -> IResult<Literal> {
     let (i, int) = nom::combinator::map_res(nom::character::complete::digit1, |s: Span| {
         s.fragment().parse::<u64>()
     })(input)?;
     Ok((i, Literal::IntLiteral(int)))
 }
 
-fn float(input: Span) -> nom::IResult<Span, Literal> {
+fn float(input: Span) -> IResult<Literal> {
     let (i, float) = nom::combinator::map_res(
         nom::combinator::recognize(nom::sequence::delimited(
             nom::character::complete::digit1,
@@ -56,21 +55,21 @@ fn float(input: Span) -> nom::IResult<Span, Literal> {
     Ok((i, Literal::FloatLiteral(float)))
 }
 
-fn string_literal(i: Span) -> nom::IResult<Span, Literal> {
+fn string_literal(i: Span) -> IResult<Literal> {
     let (i, _) = tag("\"")(i)?;
     let (i, string) = nom::bytes::complete::take_until("\"")(i)?;
     let (i, _) = tag("\"")(i)?;
     Ok((i, Literal::StringLiteral(string.to_string())))
 }
 
-pub fn parse_dictionary_empty_literal(i: Span) -> nom::IResult<Span, DictionaryLiteral> {
+pub fn parse_dictionary_empty_literal(i: Span) -> IResult<DictionaryLiteral> {
     let (i, _) = left_square_bracket(i)?;
     let (i, _) = colon(i)?;
     let (i, _) = right_square_bracket(i)?;
     Ok((i, DictionaryLiteral { elements: vec![] }))
 }
 
-pub fn parse_dictionary_literal(i: Span) -> nom::IResult<Span, DictionaryLiteral> {
+pub fn parse_dictionary_literal(i: Span) -> IResult<DictionaryLiteral> {
     let (i, _) = left_square_bracket(i)?;
     let (i, elements) = nom::multi::separated_nonempty_list(
         tag(","),
@@ -83,7 +82,7 @@ pub fn parse_dictionary_literal(i: Span) -> nom::IResult<Span, DictionaryLiteral
     Ok((i, DictionaryLiteral { elements }))
 }
 
-fn parse_dictionary_element(i: Span) -> nom::IResult<Span, (Expression, Expression)> {
+fn parse_dictionary_element(i: Span) -> IResult<(Expression, Expression)> {
     let (i, expression1) = parse_expression_left(i)?;
     let (i, _) = colon(i)?;
     let (i, _) = whitespace(i)?;
@@ -91,7 +90,7 @@ fn parse_dictionary_element(i: Span) -> nom::IResult<Span, (Expression, Expressi
     Ok((i, (expression1, expression2)))
 }
 
-pub fn parse_array_literal(i: Span) -> nom::IResult<Span, ArrayLiteral> {
+pub fn parse_array_literal(i: Span) -> IResult<ArrayLiteral> {
     let (i, _) = left_square_bracket(i)?;
     let (i, expressions) = nom::multi::separated_list(
         tag(","),
@@ -1,5 +1,6 @@
 mod calls;
 mod declarations;
+mod error;
 mod expressions;
 mod identifiers;
 mod literals;
@@ -15,54 +16,224 @@ use nom_locate::LocatedSpan;
 
 use nom::multi::many0;
 
-use crate::ast::Module;
+use crate::ast::{Comment, ImportDeclaration, Module, TopLevelDeclaration};
+use crate::diagnostic::{code, Diagnostic, SourceMap};
 use crate::environment::Environment;
-use crate::parser::declarations::parse_top_level_declaration;
+use crate::parser::declarations::{parse_import_declaration, parse_top_level_declaration};
+use crate::parser::error::{Expected, SyntaxError};
+use crate::parser::operators::parse_comment;
 use crate::parser::utils::*;
 
+/// Parses a program made of a single file, reporting every syntax error in it
 pub fn parse_program(i: &str) -> ParseResult {
-    let input = LocatedSpan::new(i);
-    let result = parse_module(input);
-
-    match result {
-        Ok((remaining, module)) => {
-            if remaining.fragment().is_empty() {
-                let mut environment: Environment = Default::default();
-                environment.build(module.clone());
-                Ok((module, environment))
-            } else {
-                Err(format!("Could not parse {:#?}", remaining.fragment()))
+    let module = parse_file(i, FileStart::default())?;
+    let mut environment: Environment = Default::default();
+    environment.build(module.clone());
+    Ok((module, environment))
+}
+
+/// Parses each file of the program on its own, so their nodes are located within them, and joins
+/// their declarations into one module
+pub fn parse_files(source_map: &SourceMap) -> ParseResult {
+    let mut module = Module {
+        declarations: vec![],
+        imports: vec![],
+        comments: vec![],
+    };
+    let mut diagnostics = vec![];
+    for (file, offset, text) in source_map.files() {
+        match parse_file(text, FileStart { file, offset }) {
+            Ok(file) => {
+                module.declarations.extend(file.declarations);
+                module.imports.extend(file.imports);
+                module.comments.extend(file.comments);
+            }
+            Err(errors) => diagnostics.extend(errors),
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    let mut environment: Environment = Default::default();
+    environment.build(module.clone());
+    Ok((module, environment))
+}
+
+/// Parses a file, reporting every syntax error in it rather than only the first. After each error
+/// the code around it is skipped and the file parsed again, so later errors are found too
+fn parse_file(i: &str, start: FileStart) -> Result<Module, Vec<Diagnostic>> {
+    let mut source = i.to_string();
+    let mut diagnostics = vec![];
+
+    loop {
+        let error = match parse_module(LocatedSpan::new_extra(&source, start)) {
+            Ok((remaining, module)) if remaining.fragment().is_empty() => {
+                if !diagnostics.is_empty() {
+                    return Err(diagnostics);
+                }
+                return Ok(module);
+            }
+            Ok((remaining, _)) => {
+                SyntaxError::expected(remaining, Expected::Syntax("declaration")).to_diagnostic()
+            }
+            Err(nom::Err::Failure(error)) | Err(nom::Err::Error(error)) => error.to_diagnostic(),
+            Err(nom::Err::Incomplete(_)) => {
+                diagnostics.push(Diagnostic::error(
+                    code::SYNTAX_ERROR,
+                    "Could not parse. Not enough data",
+                ));
+                return Err(diagnostics);
+            }
+        };
+
+        let offset = error
+            .primary
+            .as_ref()
+            .map(|label| label.line_info.offset - start.offset)
+            .unwrap_or_default();
+        diagnostics.push(error);
+        if !skip_erroneous_code(&mut source, offset) {
+            return Err(diagnostics);
+        }
+    }
+}
+
+/// Blanks out the line containing the error, and the rest of any block opened on it, so that the
+/// next attempt resumes at the following statement or declaration. Braces closing blocks opened
+/// before the line are kept, and so are line breaks, so the code after it keeps its location.
+/// Returns whether anything was skipped
+fn skip_erroneous_code(source: &mut String, offset: usize) -> bool {
+    let offset = offset.min(source.len());
+    let start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+
+    let mut depth = 0;
+    let mut skipped = false;
+    let mut end = source.len();
+    let mut blanked = String::new();
+    for (index, c) in source[start..].char_indices() {
+        match c {
+            '\n' if depth == 0 => {
+                end = start + index;
+                break;
+            }
+            '\n' => {
+                blanked.push(c);
+                continue;
+            }
+            '}' if depth == 0 => {
+                blanked.push(c);
+                continue;
             }
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
         }
-        Err(nom::Err::Failure((i, _))) => Err(format!("Could not parse {:#?}", i.fragment())),
-        Err(nom::Err::Error((i, _))) => Err(format!("Could not parse {:#?}", i.fragment())),
-        _ => Err("Could not parse. Not enough data".to_string()),
+        skipped |= !c.is_whitespace();
+        blanked.push_str(&" ".repeat(c.len_utf8()));
     }
+
+    source.replace_range(start..end, &blanked);
+    skipped
+}
+
+/// The files imported by a source file, which are needed before the program can be parsed as a
+/// whole. A file which does not parse imports nothing, its errors are reported with the program's
+pub fn parse_imports(i: &str) -> Vec<ImportDeclaration> {
+    parse_module(new_span(i))
+        .map(|(_, module)| module.imports)
+        .unwrap_or_default()
 }
 
-fn parse_module(i: Span) -> nom::IResult<Span, Module> {
-    let (i, _) = whitespace(i)?;
-    let (i, declarations) = many0(nom::sequence::terminated(
-        parse_top_level_declaration,
+enum ModuleItem {
+    Import(ImportDeclaration),
+    Declaration(TopLevelDeclaration),
+}
+
+fn parse_module(input: Span) -> IResult<Module> {
+    let (i, _) = whitespace(input)?;
+    let (i, items) = many0(nom::sequence::terminated(
+        nom::branch::alt((
+            nom::combinator::map(parse_import_declaration, ModuleItem::Import),
+            nom::combinator::map(parse_top_level_declaration, ModuleItem::Declaration),
+        )),
         whitespace,
     ))(i)?;
-    Ok((i, Module { declarations }))
+
+    // Declarations end at the first one which fails to parse, so report where it failed
+    if !i.fragment().is_empty() {
+        parse_top_level_declaration(i)?;
+    }
+
+    let mut module = Module {
+        declarations: vec![],
+        imports: vec![],
+        comments: parse_comments(input),
+    };
+    for item in items {
+        match item {
+            ModuleItem::Import(import) => module.imports.push(import),
+            ModuleItem::Declaration(declaration) => module.declarations.push(declaration),
+        }
+    }
+    Ok((i, module))
+}
+
+/// The grammar skips comments as whitespace wherever they appear, so they are collected by scanning
+/// the source instead. String literals are stepped over, as they may contain `//`
+fn parse_comments(mut i: Span) -> Vec<Comment> {
+    let mut comments = vec![];
+    loop {
+        let start = match i
+            .fragment()
+            .find(&['"', '/'][..])
+            .and_then(|start| nom::bytes::complete::take::<_, _, SyntaxError>(start)(i).ok())
+        {
+            Some((start, _)) => start,
+            None => return comments,
+        };
+
+        let next = if start.fragment().starts_with('"') {
+            start.fragment()[1..]
+                .find('"')
+                .map(|end| end + 2)
+                .and_then(|end| nom::bytes::complete::take::<_, _, SyntaxError>(end)(start).ok())
+                .map(|(next, _)| next)
+        } else if start.fragment().starts_with("//") {
+            // A comment at the end of the file has no line ending, and ends the file
+            let (next, comment) = parse_comment(start).unwrap_or((new_span(""), start));
+            comments.push(Comment {
+                text: comment.fragment().trim_end().to_string(),
+                line_info: line_info(&comment),
+            });
+            Some(next)
+        } else {
+            nom::bytes::complete::take::<_, _, SyntaxError>(1usize)(start)
+                .ok()
+                .map(|(next, _)| next)
+        };
+
+        match next {
+            Some(next) if !next.fragment().is_empty() => i = next,
+            _ => return comments,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     use crate::ast::{
         ContractDeclaration, ContractMember, DictionaryLiteral, DictionaryType, EventDeclaration,
         Expression, Identifier, LineInfo, Module, Parameter, TopLevelDeclaration, Type, TypeState,
         VariableDeclaration,
     };
-    use crate::parser::parse_module;
+    use crate::parser::{parse_imports, parse_module, parse_program};
 
     #[test]
     fn test_parse_module() {
-        let input = LocatedSpan::new(
+        let input = new_span(
             "contract Coin (Antique, Old, New, Invalid) {
                 var minter: Address
                         var balance: [Address: Int] = [:]
@@ -80,7 +251,12 @@ mod tests {
                         identifier: Identifier {
                             token: String::from("Coin"),
                             enclosing_type: None,
-                            line_info: LineInfo { line: 1, offset: 9 },
+                            line_info: LineInfo {
+                                line: 1,
+                                column: 10,
+                                offset: 9,
+                                file: 0,
+                            },
                         },
 
                         contract_members: vec![
@@ -93,7 +269,9 @@ mod tests {
                                         enclosing_type: Some("Coin".to_string()),
                                         line_info: LineInfo {
                                             line: 2,
+                                            column: 21,
                                             offset: 65,
+                                            file: 0,
                                         },
                                     },
 
@@ -111,7 +289,9 @@ mod tests {
                                         enclosing_type: Some("Coin".to_string()),
                                         line_info: LineInfo {
                                             line: 3,
+                                            column: 29,
                                             offset: 109,
+                                            file: 0,
                                         },
                                     },
 
@@ -132,7 +312,9 @@ mod tests {
                                     enclosing_type: None,
                                     line_info: LineInfo {
                                         line: 4,
+                                        column: 25,
                                         offset: 163,
+                                        file: 0,
                                     },
                                 },
 
@@ -143,7 +325,9 @@ mod tests {
                                             enclosing_type: None,
                                             line_info: LineInfo {
                                                 line: 4,
+                                                column: 40,
                                                 offset: 178,
+                                                file: 0,
                                             },
                                         },
 
@@ -151,7 +335,9 @@ mod tests {
                                         expression: None,
                                         line_info: LineInfo {
                                             line: 4,
+                                            column: 40,
                                             offset: 178,
+                                            file: 0,
                                         },
                                     },
                                     Parameter {
@@ -160,14 +346,18 @@ mod tests {
                                             enclosing_type: None,
                                             line_info: LineInfo {
                                                 line: 4,
+                                                column: 55,
                                                 offset: 193,
+                                                file: 0,
                                             },
                                         },
                                         type_assignment: Type::Address,
                                         expression: None,
                                         line_info: LineInfo {
                                             line: 4,
+                                            column: 55,
                                             offset: 193,
+                                            file: 0,
                                         },
                                     },
                                     Parameter {
@@ -176,14 +366,18 @@ mod tests {
                                             enclosing_type: None,
                                             line_info: LineInfo {
                                                 line: 4,
+                                                column: 68,
                                                 offset: 206,
+                                                file: 0,
                                             },
                                         },
                                         type_assignment: Type::Int,
                                         expression: None,
                                         line_info: LineInfo {
                                             line: 4,
+                                            column: 68,
                                             offset: 206,
+                                            file: 0,
                                         },
                                     }
                                 ],
@@ -196,7 +390,9 @@ mod tests {
                                     enclosing_type: None,
                                     line_info: LineInfo {
                                         line: 1,
+                                        column: 16,
                                         offset: 15,
+                                        file: 0,
                                     }
                                 }
                             },
@@ -206,7 +402,9 @@ mod tests {
                                     enclosing_type: None,
                                     line_info: LineInfo {
                                         line: 1,
+                                        column: 25,
                                         offset: 24,
+                                        file: 0,
                                     }
                                 }
                             },
@@ -216,7 +414,9 @@ mod tests {
                                     enclosing_type: None,
                                     line_info: LineInfo {
                                         line: 1,
+                                        column: 30,
                                         offset: 29,
+                                        file: 0,
                                     }
                                 }
                             },
@@ -226,15 +426,100 @@ mod tests {
                                     enclosing_type: None,
                                     line_info: LineInfo {
                                         line: 1,
+                                        column: 35,
                                         offset: 34,
+                                        file: 0,
                                     }
                                 }
                             }
                         ],
                         conformances: vec![]
                     }
-                )]
+                )],
+                imports: vec![],
+                comments: vec![],
             }
         );
     }
+    #[test]
+    fn test_parse_program_reports_every_syntax_error() {
+        let program = "contract Counter {
+  var count Int
+}
+
+Counter :: (any) {
+  public func increment(x: Int) mutates (count) {
+    count += x
+  }
+
+  public func get() -> Int {
+    if count == 0 {
+      return 1 ]
+    }
+    return count
+  }
+}
+";
+        let diagnostics = parse_program(program).unwrap_err();
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected `:`, found `Int`",
+                "Expected statement or `}`, found `]`"
+            ]
+        );
+
+        let offsets: Vec<usize> = diagnostics
+            .iter()
+            .map(|d| d.primary.as_ref().unwrap().line_info.offset)
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![program.find("Int").unwrap(), program.find(']').unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_parse_imports() {
+        let imports = parse_imports(
+            "import \"token.flint\"
+import \"../shared/point.flint\"
+
+contract Wallet {}",
+        );
+
+        let paths: Vec<&str> = imports.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["token.flint", "../shared/point.flint"]);
+        assert_eq!(imports[1].line_info.line, 2);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let program = "// A wallet
+contract Wallet {
+  var name: String = \"https://flint\" // Not a comment, then a comment
+}
+// At the end
+";
+        let (_, module) = parse_module(new_span(program)).expect("Error parsing module");
+
+        let comments: Vec<(&str, u32)> = module
+            .comments
+            .iter()
+            .map(|c| (c.text.as_str(), c.line_info.line))
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("// A wallet", 1),
+                ("// Not a comment, then a comment", 3),
+                ("// At the end", 5),
+            ]
+        );
+        assert_eq!(
+            module.comments[1].line_info.offset,
+            program.find("// Not").unwrap()
+        );
+    }
 }
@@ -1,7 +1,6 @@
 use crate::ast::BinOp;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 
 pub fn get_operator_precedence(op: &BinOp) -> i32 {
     match op {
@@ -31,7 +30,7 @@ pub fn get_operator_precedence(op: &BinOp) -> i32 {
     }
 }
 
-pub fn parse_binary_op(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn parse_binary_op(i: Span) -> IResult<BinOp> {
     alt((
         double_equal_operator,
         not_equal_operator,
@@ -56,196 +55,199 @@ pub fn parse_binary_op(i: Span) -> nom::IResult<Span, BinOp> {
     ))(i)
 }
 
-pub fn greater_than_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn greater_than_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag(">=")(i)?;
     Ok((i, BinOp::GreaterThanOrEqual))
 }
 
-pub fn less_than_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn less_than_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("<=")(i)?;
     Ok((i, BinOp::LessThanOrEqual))
 }
 
-pub fn power_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn power_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("**")(i)?;
     Ok((i, BinOp::Power))
 }
 
-pub fn times_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn times_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("*")(i)?;
     Ok((i, BinOp::Times))
 }
 
-pub fn divide_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn divide_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("/")(i)?;
     Ok((i, BinOp::Divide))
 }
 
-pub fn and_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn and_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("&&")(i)?;
     Ok((i, BinOp::And))
 }
 
-pub fn or_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn or_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("||")(i)?;
     Ok((i, BinOp::Or))
 }
 
-pub fn double_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn double_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("==")(i)?;
     Ok((i, BinOp::DoubleEqual))
 }
 
-pub fn not_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn not_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("!=")(i)?;
     Ok((i, BinOp::NotEqual))
 }
 
-pub fn plus_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn plus_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("+")(i)?;
     Ok((i, BinOp::Plus))
 }
 
-pub fn parse_comment(i: Span) -> nom::IResult<Span, Span> {
-    let (i, _) = tag("//")(i)?;
-    let (i, _) = nom::combinator::opt(nom::bytes::complete::is_not("\n"))(i)?;
+/// Returns the comment, from the `//` to the end of the line
+pub fn parse_comment(i: Span) -> IResult<Span> {
+    let (i, comment) = nom::combinator::recognize(nom::sequence::preceded(
+        tag("//"),
+        nom::combinator::opt(nom::bytes::complete::is_not("\n")),
+    ))(i)?;
     let (i, _) = tag("\n")(i)?;
-    Ok((i, i))
+    Ok((i, comment))
 }
 
-pub fn minus_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn minus_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("-")(i)?;
     Ok((i, BinOp::Minus))
 }
 
-pub fn plus_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn plus_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("+=")(i)?;
     Ok((i, BinOp::PlusEqual))
 }
 
-pub fn minus_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn minus_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("-=")(i)?;
     Ok((i, BinOp::MinusEqual))
 }
 
-pub fn times_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn times_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("*=")(i)?;
     Ok((i, BinOp::TimesEqual))
 }
 
-pub fn divide_equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn divide_equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("/=")(i)?;
     Ok((i, BinOp::DivideEqual))
 }
 
-pub fn equal_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn equal_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("=")(i)?;
     Ok((i, BinOp::Equal))
 }
 
-pub fn dot_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn dot_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag(".")(i)?;
     Ok((i, BinOp::Dot))
 }
 
-pub fn less_than_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn less_than_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("<")(i)?;
     Ok((i, BinOp::LessThan))
 }
 
-pub fn greater_than_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn greater_than_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag(">")(i)?;
     Ok((i, BinOp::GreaterThan))
 }
 
-pub fn left_brace(i: Span) -> nom::IResult<Span, Span> {
+pub fn left_brace(i: Span) -> IResult<Span> {
     tag("{")(i)
 }
 
-pub fn right_brace(i: Span) -> nom::IResult<Span, Span> {
+pub fn right_brace(i: Span) -> IResult<Span> {
     tag("}")(i)
 }
 
-pub fn left_square_bracket(i: Span) -> nom::IResult<Span, Span> {
+pub fn left_square_bracket(i: Span) -> IResult<Span> {
     tag("[")(i)
 }
 
-pub fn right_square_bracket(i: Span) -> nom::IResult<Span, Span> {
+pub fn right_square_bracket(i: Span) -> IResult<Span> {
     tag("]")(i)
 }
 
-pub fn colon(i: Span) -> nom::IResult<Span, Span> {
+pub fn colon(i: Span) -> IResult<Span> {
     tag(":")(i)
 }
 
-pub fn double_colon(i: Span) -> nom::IResult<Span, Span> {
+pub fn double_colon(i: Span) -> IResult<Span> {
     tag("::")(i)
 }
 
-pub fn left_parens(i: Span) -> nom::IResult<Span, Span> {
+pub fn left_parens(i: Span) -> IResult<Span> {
     tag("(")(i)
 }
 
-pub fn right_parens(i: Span) -> nom::IResult<Span, Span> {
+pub fn right_parens(i: Span) -> IResult<Span> {
     tag(")")(i)
 }
 
-pub fn at(i: Span) -> nom::IResult<Span, Span> {
+pub fn at(i: Span) -> IResult<Span> {
     tag("@")(i)
 }
 
-pub fn right_arrow(i: Span) -> nom::IResult<Span, Span> {
+pub fn right_arrow(i: Span) -> IResult<Span> {
     tag("->")(i)
 }
 
-pub fn left_arrow(i: Span) -> nom::IResult<Span, Span> {
+pub fn left_arrow(i: Span) -> IResult<Span> {
     tag("<-")(i)
 }
 
 #[allow(dead_code)]
-pub fn comma(i: Span) -> nom::IResult<Span, Span> {
+pub fn comma(i: Span) -> IResult<Span> {
     tag(",")(i)
 }
 
 #[allow(dead_code)]
-pub fn semi_colon(i: Span) -> nom::IResult<Span, Span> {
+pub fn semi_colon(i: Span) -> IResult<Span> {
     tag(";")(i)
 }
 
 #[allow(dead_code)]
-pub fn double_slash(i: Span) -> nom::IResult<Span, Span> {
+pub fn double_slash(i: Span) -> IResult<Span> {
     tag("//")(i)
 }
 
-pub fn remainder_operator(i: Span) -> nom::IResult<Span, BinOp> {
+pub fn remainder_operator(i: Span) -> IResult<BinOp> {
     let (i, _) = tag("%")(i)?;
     Ok((i, BinOp::Percent))
 }
 
 #[allow(dead_code)]
-pub fn double_dot(i: Span) -> nom::IResult<Span, Span> {
+pub fn double_dot(i: Span) -> IResult<Span> {
     tag("..")(i)
 }
 
 #[allow(dead_code)]
-pub fn ampersand(i: Span) -> nom::IResult<Span, Span> {
+pub fn ampersand(i: Span) -> IResult<Span> {
     tag("&")(i)
 }
 
 #[allow(dead_code)]
-pub fn bang(i: Span) -> nom::IResult<Span, Span> {
+pub fn bang(i: Span) -> IResult<Span> {
     tag("!")(i)
 }
 
 #[allow(dead_code)]
-pub fn question(i: Span) -> nom::IResult<Span, Span> {
+pub fn question(i: Span) -> IResult<Span> {
     tag("?")(i)
 }
 
-pub fn half_open_range(i: Span) -> nom::IResult<Span, Span> {
+pub fn half_open_range(i: Span) -> IResult<Span> {
     tag("..<")(i)
 }
 
-pub fn closed_range(i: Span) -> nom::IResult<Span, Span> {
+pub fn closed_range(i: Span) -> IResult<Span> {
     tag("...")(i)
 }
@@ -1,25 +1,21 @@
-use crate::ast::{LineInfo, Parameter};
+use crate::ast::Parameter;
 use crate::parser::expressions::*;
 use crate::parser::identifiers::*;
 use crate::parser::operators::*;
 use crate::parser::types::*;
 use crate::parser::utils::*;
-use nom::bytes::complete::tag;
 use nom::sequence::preceded;
 
-pub fn parse_parameter_list(i: Span) -> nom::IResult<Span, Vec<Parameter>> {
+pub fn parse_parameter_list(i: Span) -> IResult<Vec<Parameter>> {
     let (i, _) = left_parens(i)?;
     let (i, vector) =
         nom::multi::separated_list(tag(","), preceded(whitespace, parse_parameter))(i)?;
-    let (i, _) = right_parens(i)?;
+    let (i, _) = closing(right_parens, preceded(whitespace, parse_parameter))(i)?;
     Ok((i, vector))
 }
 
-fn parse_parameter(i: Span) -> nom::IResult<Span, Parameter> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
+fn parse_parameter(i: Span) -> IResult<Parameter> {
+    let line_info = line_info(&i);
     let (i, identifier) = parse_identifier(i)?;
     let (i, type_assigned) = parse_type_annotation(i)?;
     let (i, equal) = nom::combinator::opt(preceded(whitespace, equal_operator))(i)?;
@@ -44,14 +40,14 @@ fn parse_parameter(i: Span) -> nom::IResult<Span, Parameter> {
 
 #[cfg(test)]
 mod test {
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     use crate::ast::*;
     use crate::parser::parameters::*;
 
     #[test]
     fn test_parse_parameter() {
-        let input = LocatedSpan::new("first: Int");
+        let input = new_span("first: Int");
         let (_rest, result) = parse_parameter(input).expect("Error parsing parameter");
         assert_eq!(
             result,
@@ -59,16 +55,26 @@ mod test {
                 identifier: Identifier {
                     token: String::from("first"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
 
                 type_assignment: Type::Int,
                 expression: None,
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             }
         );
 
-        let input = LocatedSpan::new("first: Int = second");
+        let input = new_span("first: Int = second");
         let (_rest, result) =
             parse_parameter(input).expect("Error parsing parameter with expression");
         assert_eq!(
@@ -77,17 +83,32 @@ mod test {
                 identifier: Identifier {
                     token: String::from("first"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 },
 
                 type_assignment: Type::Int,
                 expression: Some(Expression::Identifier(Identifier {
                     token: String::from("second"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 })),
 
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             }
         );
     }
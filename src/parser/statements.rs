@@ -1,6 +1,6 @@
 use crate::ast::{
     Assertion, BecomeStatement, DoCatchStatement, EmitStatement, ForStatement, IfStatement,
-    LineInfo, ReturnStatement, Statement,
+    ReturnStatement, Statement,
 };
 use crate::parser::calls::parse_function_call;
 use crate::parser::declarations::parse_variable_declaration;
@@ -8,12 +8,11 @@ use crate::parser::expressions::parse_expression;
 use crate::parser::type_states::parse_type_state;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::multi::many0;
 use nom::sequence::preceded;
 
-pub fn parse_statements(i: Span) -> nom::IResult<Span, Vec<Statement>> {
+pub fn parse_statements(i: Span) -> IResult<Vec<Statement>> {
     let (i, statements) = many0(nom::sequence::terminated(
         preceded(whitespace, parse_statement),
         whitespace,
@@ -21,36 +20,36 @@ pub fn parse_statements(i: Span) -> nom::IResult<Span, Vec<Statement>> {
     Ok((i, statements))
 }
 
-fn parse_statement(i: Span) -> nom::IResult<Span, Statement> {
-    alt((
-        parse_return_statement,
-        parse_become_statement,
-        parse_emit_statement,
-        parse_for_statement,
-        parse_if_statement,
-        parse_docatch_statement,
-        parse_assert_statement,
-        map(parse_expression, Statement::Expression),
-    ))(i)
+pub fn parse_statement(i: Span) -> IResult<Statement> {
+    expected(
+        "statement",
+        alt((
+            parse_return_statement,
+            parse_become_statement,
+            parse_emit_statement,
+            parse_for_statement,
+            parse_if_statement,
+            parse_docatch_statement,
+            parse_assert_statement,
+            map(parse_expression, Statement::Expression),
+        )),
+    )(i)
 }
 
-fn parse_assert_statement(i: Span) -> nom::IResult<Span, Statement> {
+fn parse_assert_statement(i: Span) -> IResult<Statement> {
     let (i, _) = tag("assert")(i)?;
     let (i, _) = nom::character::complete::space1(i)?;
     let (i, expression) = parse_expression(i)?;
 
     let assertion = Assertion {
         expression,
-        line_info: LineInfo {
-            line: i.location_line(),
-            offset: i.location_offset(),
-        },
+        line_info: line_info(&i),
     };
 
     Ok((i, Statement::Assertion(assertion)))
 }
 
-fn parse_docatch_statement(i: Span) -> nom::IResult<Span, Statement> {
+fn parse_docatch_statement(i: Span) -> IResult<Statement> {
     let (i, _) = tag("do")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, do_body) = parse_code_block(i)?;
@@ -70,7 +69,7 @@ fn parse_docatch_statement(i: Span) -> nom::IResult<Span, Statement> {
     Ok((i, Statement::DoCatchStatement(do_catch_statement)))
 }
 
-fn parse_if_statement(i: Span) -> nom::IResult<Span, Statement> {
+fn parse_if_statement(i: Span) -> IResult<Statement> {
     let (i, _) = tag("if")(i)?;
     let (i, _) = whitespace(i)?;
     let (i, condition) = parse_expression(i)?;
@@ -101,7 +100,7 @@ fn parse_if_statement(i: Span) -> nom::IResult<Span, Statement> {
     Ok((i, Statement::IfStatement(if_statement)))
 }
 
-fn parse_for_statement(i: Span) -> nom::IResult<Span, Statement> {
+fn parse_for_statement(i: Span) -> IResult<Statement> {
     let (i, _) = tag("for")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, variable) = parse_variable_declaration(i)?;
@@ -120,7 +119,7 @@ fn parse_for_statement(i: Span) -> nom::IResult<Span, Statement> {
     Ok((i, Statement::ForStatement(Box::new(for_statement))))
 }
 
-pub fn parse_emit_statement(i: Span) -> nom::IResult<Span, Statement> {
+pub fn parse_emit_statement(i: Span) -> IResult<Statement> {
     let (i, _) = tag("emit")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, function_call) = parse_function_call(i)?;
@@ -128,11 +127,8 @@ pub fn parse_emit_statement(i: Span) -> nom::IResult<Span, Statement> {
     Ok((i, Statement::EmitStatement(emit_statement)))
 }
 
-fn parse_become_statement(i: Span) -> nom::IResult<Span, Statement> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
+fn parse_become_statement(i: Span) -> IResult<Statement> {
+    let line_info = line_info(&i);
     let (i, _) = tag("become")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, state) = parse_type_state(i)?;
@@ -140,11 +136,8 @@ fn parse_become_statement(i: Span) -> nom::IResult<Span, Statement> {
     Ok((i, Statement::BecomeStatement(become_statement)))
 }
 
-fn parse_return_statement(i: Span) -> nom::IResult<Span, Statement> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
+fn parse_return_statement(i: Span) -> IResult<Statement> {
+    let line_info = line_info(&i);
     let (i, _) = tag("return")(i)?;
     let (i, _) = nom::character::complete::space0(i)?;
     let (i, expression) = nom::combinator::opt(parse_expression)(i)?;
@@ -158,14 +151,14 @@ fn parse_return_statement(i: Span) -> nom::IResult<Span, Statement> {
 
 #[cfg(test)]
 mod tests {
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     use crate::ast::{BinOp::*, Literal::*, *};
     use crate::parser::statements::*;
 
     #[test]
     fn test_assert_statement() {
-        let input = LocatedSpan::new("assert true");
+        let input = new_span("assert true");
         let (_, result) = parse_assert_statement(input).expect("Error with assert statement");
         assert_eq!(
             result,
@@ -173,12 +166,14 @@ mod tests {
                 expression: Expression::Literal(Literal::BooleanLiteral(true)),
                 line_info: LineInfo {
                     line: 1,
+                    column: 12,
                     offset: 11,
+                    file: 0,
                 },
             })
         );
 
-        let input = LocatedSpan::new("assert ((1 + 2) == 3)");
+        let input = new_span("assert ((1 + 2) == 3)");
         let (_, result) = parse_assert_statement(input).expect("Error with assert statement");
         assert_eq!(
             result,
@@ -196,20 +191,32 @@ mod tests {
                                             Literal::IntLiteral(2)
                                         )),
                                         op: BinOp::Plus,
-                                        line_info: LineInfo { line: 1, offset: 9 },
+                                        line_info: LineInfo {
+                                            line: 1,
+                                            column: 10,
+                                            offset: 9,
+                                            file: 0,
+                                        },
                                     }
                                 ))
                             }
                         )),
                         rhs_expression: Box::new(Expression::Literal(IntLiteral(3))),
                         op: BinOp::DoubleEqual,
-                        line_info: LineInfo { line: 1, offset: 8 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 9,
+                            offset: 8,
+                            file: 0,
+                        },
                     }))
                 }),
 
                 line_info: LineInfo {
                     line: 1,
+                    column: 22,
                     offset: 21,
+                    file: 0,
                 },
             })
         );
@@ -217,7 +224,7 @@ mod tests {
 
     #[test]
     fn test_docatch_statement() {
-        let input = LocatedSpan::new("do {return id} catch is error_type {return error}");
+        let input = new_span("do {return id} catch is error_type {return error}");
         let (_rest, result) = parse_docatch_statement(input).expect("Error with docatch statement");
         assert_eq!(
             result,
@@ -227,7 +234,9 @@ mod tests {
                     enclosing_type: None,
                     line_info: LineInfo {
                         line: 1,
+                        column: 25,
                         offset: 24,
+                        file: 0,
                     },
                 },),
 
@@ -237,12 +246,19 @@ mod tests {
                         enclosing_type: None,
                         line_info: LineInfo {
                             line: 1,
+                            column: 12,
                             offset: 11,
+                            file: 0,
                         },
                     })),
 
                     cleanup: vec![],
-                    line_info: LineInfo { line: 1, offset: 4 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 5,
+                        offset: 4,
+                        file: 0,
+                    },
                 })],
 
                 catch_body: vec![Statement::ReturnStatement(ReturnStatement {
@@ -251,14 +267,18 @@ mod tests {
                         enclosing_type: None,
                         line_info: LineInfo {
                             line: 1,
+                            column: 44,
                             offset: 43,
+                            file: 0,
                         },
                     })),
 
                     cleanup: vec![],
                     line_info: LineInfo {
                         line: 1,
+                        column: 37,
                         offset: 36,
+                        file: 0,
                     },
                 })],
             })
@@ -267,7 +287,7 @@ mod tests {
 
     #[test]
     fn test_if_statement() {
-        let input = LocatedSpan::new("if x<5 {return x} else if x<10 {return x} else {return 0}");
+        let input = new_span("if x<5 {return x} else if x<10 {return x} else {return 0}");
         let (_rest, result) = parse_if_statement(input).expect("Error parsing if statement");
         let else_if = Statement::IfStatement(IfStatement {
             condition: Expression::BinaryExpression(BinaryExpression {
@@ -276,14 +296,18 @@ mod tests {
                     enclosing_type: None,
                     line_info: LineInfo {
                         line: 1,
+                        column: 27,
                         offset: 26,
+                        file: 0,
                     },
                 })),
                 rhs_expression: Box::new(Expression::Literal(IntLiteral(10))),
                 op: BinOp::LessThan,
                 line_info: LineInfo {
                     line: 1,
+                    column: 27,
                     offset: 26,
+                    file: 0,
                 },
             }),
             body: vec![Statement::ReturnStatement(ReturnStatement {
@@ -292,13 +316,17 @@ mod tests {
                     enclosing_type: None,
                     line_info: LineInfo {
                         line: 1,
+                        column: 40,
                         offset: 39,
+                        file: 0,
                     },
                 })),
                 cleanup: vec![],
                 line_info: LineInfo {
                     line: 1,
+                    column: 33,
                     offset: 32,
+                    file: 0,
                 },
             })],
             else_body: vec![Statement::ReturnStatement(ReturnStatement {
@@ -306,7 +334,9 @@ mod tests {
                 cleanup: vec![],
                 line_info: LineInfo {
                     line: 1,
+                    column: 49,
                     offset: 48,
+                    file: 0,
                 },
             })],
             if_body_scope_context: None,
@@ -320,11 +350,21 @@ mod tests {
                     lhs_expression: Box::new(Expression::Identifier(Identifier {
                         token: String::from("x"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 3 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 4,
+                            offset: 3,
+                            file: 0,
+                        },
                     })),
                     rhs_expression: Box::new(Expression::Literal(IntLiteral(5))),
                     op: LessThan,
-                    line_info: LineInfo { line: 1, offset: 3 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 4,
+                        offset: 3,
+                        file: 0,
+                    },
                 }),
 
                 body: vec![Statement::ReturnStatement(ReturnStatement {
@@ -333,12 +373,19 @@ mod tests {
                         enclosing_type: None,
                         line_info: LineInfo {
                             line: 1,
+                            column: 16,
                             offset: 15,
+                            file: 0,
                         },
                     })),
 
                     cleanup: vec![],
-                    line_info: LineInfo { line: 1, offset: 8 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 9,
+                        offset: 8,
+                        file: 0,
+                    },
                 })],
 
                 else_body: vec![else_if],
@@ -350,7 +397,7 @@ mod tests {
 
     #[test]
     fn test_parse_emit_statement() {
-        let input = LocatedSpan::new("emit foo()");
+        let input = new_span("emit foo()");
         let (_rest, result) = parse_emit_statement(input).expect("Error parsing emit statement");
         assert_eq!(
             result,
@@ -359,7 +406,12 @@ mod tests {
                     identifier: Identifier {
                         token: String::from("foo"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 5 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 6,
+                            offset: 5,
+                            file: 0,
+                        },
                     },
 
                     arguments: vec![],
@@ -371,7 +423,7 @@ mod tests {
 
     #[test]
     fn test_become_statement() {
-        let input = LocatedSpan::new("become Example");
+        let input = new_span("become Example");
         let (_rest, result) =
             parse_become_statement(input).expect("Error parsing become statement");
         assert_eq!(
@@ -381,18 +433,28 @@ mod tests {
                     identifier: Identifier {
                         token: String::from("Example"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 0 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 1,
+                            offset: 0,
+                            file: 0,
+                        },
                     }
                 },
 
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             })
         );
     }
 
     #[test]
     fn test_for_statement() {
-        let input = LocatedSpan::new("for let i: Int in (1...5) {5}");
+        let input = new_span("for let i: Int in (1...5) {5}");
         let (_rest, result) = parse_for_statement(input).expect("Error parsing for statement");
         assert_eq!(
             result,
@@ -402,7 +464,12 @@ mod tests {
                     identifier: Identifier {
                         token: String::from("i"),
                         enclosing_type: None,
-                        line_info: LineInfo { line: 1, offset: 8 },
+                        line_info: LineInfo {
+                            line: 1,
+                            column: 9,
+                            offset: 8,
+                            file: 0,
+                        },
                     },
 
                     variable_type: Type::Int,
@@ -423,7 +490,7 @@ mod tests {
 
     #[test]
     fn test_parse_return_statement() {
-        let input = LocatedSpan::new("return");
+        let input = new_span("return");
         let (_rest, result) =
             parse_return_statement(input).expect("Error parsing return statement");
         assert_eq!(
@@ -431,11 +498,16 @@ mod tests {
             Statement::ReturnStatement(ReturnStatement {
                 expression: None,
                 cleanup: vec![],
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             })
         );
 
-        let input = LocatedSpan::new("return id");
+        let input = new_span("return id");
         let (_rest, result) =
             parse_return_statement(input).expect("Error parsing statement returning identifier");
         assert_eq!(
@@ -444,11 +516,21 @@ mod tests {
                 expression: Some(Expression::Identifier(Identifier {
                     token: String::from("id"),
                     enclosing_type: None,
-                    line_info: LineInfo { line: 1, offset: 0 },
+                    line_info: LineInfo {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        file: 0,
+                    },
                 })),
 
                 cleanup: vec![],
-                line_info: LineInfo { line: 1, offset: 0 },
+                line_info: LineInfo {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    file: 0,
+                },
             })
         );
     }
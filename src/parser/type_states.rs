@@ -1,18 +1,17 @@
-use crate::ast::{Identifier, LineInfo, TypeState};
+use crate::ast::{Identifier, TypeState};
+use crate::parser::error::{Expected, SyntaxError};
 use crate::parser::operators::{left_parens, right_parens};
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::error::ErrorKind;
 use nom::multi::many0;
 use nom::sequence::preceded;
 use std::collections::HashSet;
 
-pub fn parse_type_states(i: Span) -> nom::IResult<Span, Vec<TypeState>> {
-    let (i, _) = left_parens(i)?;
+pub fn parse_type_states(input: Span) -> IResult<Vec<TypeState>> {
+    let (i, _) = left_parens(input)?;
     let (i, type_states) =
         nom::multi::separated_list(tag(","), preceded(whitespace, parse_type_state))(i)?;
-    let (i, _) = right_parens(i)?;
+    let (i, _) = closing(right_parens, preceded(whitespace, parse_type_state))(i)?;
 
     // Ensure no repeats
     if type_states.len()
@@ -22,17 +21,17 @@ pub fn parse_type_states(i: Span) -> nom::IResult<Span, Vec<TypeState>> {
             .collect::<HashSet<&str>>()
             .len()
     {
-        return Err(nom::Err::Failure((i, ErrorKind::SeparatedList)));
+        return Err(nom::Err::Failure(SyntaxError::expected(
+            input,
+            Expected::Syntax("distinct type states"),
+        )));
     }
     Ok((i, type_states))
 }
 
-pub fn parse_type_state(i: Span) -> nom::IResult<Span, TypeState> {
-    let line_info = LineInfo {
-        line: i.location_line(),
-        offset: i.location_offset(),
-    };
-    let (remains, head) = nom::character::complete::alpha1(i)?;
+pub fn parse_type_state(i: Span) -> IResult<TypeState> {
+    let line_info = line_info(&i);
+    let (remains, head) = expected("type state", nom::character::complete::alpha1)(i)?;
     // Must start with upper case
     if !head
         .to_string()
@@ -41,7 +40,10 @@ pub fn parse_type_state(i: Span) -> nom::IResult<Span, TypeState> {
         .unwrap()
         .is_ascii_uppercase()
     {
-        return Err(nom::Err::Failure((i, ErrorKind::Char)));
+        return Err(nom::Err::Failure(SyntaxError::expected(
+            i,
+            Expected::Syntax("type state starting with an upper case letter"),
+        )));
     }
 
     let (i, tail) = nom::combinator::recognize(many0(alt((
@@ -65,12 +67,17 @@ mod test {
     use super::parse_type_state;
     use super::parse_type_states;
     use crate::ast::{Identifier, LineInfo, TypeState};
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     fn create_states(state_names: Vec<&str>, line_info: Vec<(u32, usize)>) -> Vec<TypeState> {
         let line_info = line_info
             .into_iter()
-            .map(|(line, offset)| LineInfo { line, offset })
+            .map(|(line, offset)| LineInfo {
+                line,
+                column: offset as u32 + 1,
+                offset,
+                file: 0,
+            })
             .collect::<Vec<LineInfo>>();
 
         state_names
@@ -88,7 +95,7 @@ mod test {
 
     #[test]
     fn test_parse_type_states() {
-        let input = LocatedSpan::new("(S1, S2, S3) {");
+        let input = new_span("(S1, S2, S3) {");
         let (remains, states) =
             parse_type_states(input).unwrap_or_else(|_| panic!("Failure parsing type states"));
         assert_eq!(*remains.fragment(), " {");
@@ -97,25 +104,25 @@ mod test {
             create_states(vec!["S1", "S2", "S3"], vec![(1, 1), (1, 5), (1, 9)])
         );
 
-        let input = LocatedSpan::new("(s1, s2, s3) {");
+        let input = new_span("(s1, s2, s3) {");
         parse_type_states(input).expect_err("Should not allow lowercase starting states");
 
-        let input = LocatedSpan::new("(S1, S2, S1, S3)");
+        let input = new_span("(S1, S2, S1, S3)");
         parse_type_states(input).expect_err("Should not allow duplicate typestates");
     }
 
     #[test]
     fn test_parse_type_state() {
-        let input = LocatedSpan::new("S1");
+        let input = new_span("S1");
         let (remains, state) =
             parse_type_state(input).unwrap_or_else(|_| panic!("Could not parse type state"));
         assert!(remains.fragment().is_empty());
         assert_eq!(vec![state], create_states(vec!["S1"], vec![(1, 0)]));
 
-        let input = LocatedSpan::new("s1");
+        let input = new_span("s1");
         parse_type_state(input).expect_err("Should not allow non upper case starting state");
 
-        let input = LocatedSpan::new("_1");
+        let input = new_span("_1");
         parse_type_state(input).expect_err("Should not allow non alpha starting state");
     }
 }
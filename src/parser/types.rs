@@ -9,11 +9,10 @@ use crate::parser::literals::*;
 use crate::parser::operators::*;
 use crate::parser::utils::*;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::sequence::preceded;
 
-pub fn parse_type_annotation(i: Span) -> nom::IResult<Span, TypeAnnotation> {
+pub fn parse_type_annotation(i: Span) -> IResult<TypeAnnotation> {
     let (i, colon) = colon(i)?;
     let (i, _) = whitespace(i)?;
     let (i, type_assigned) = preceded(whitespace, parse_type)(i)?;
@@ -24,20 +23,23 @@ pub fn parse_type_annotation(i: Span) -> nom::IResult<Span, TypeAnnotation> {
     Ok((i, type_annotation))
 }
 
-pub fn parse_type(i: Span) -> nom::IResult<Span, Type> {
-    alt((
-        parse_fixed_array_type,
-        parse_array_type,
-        parse_dictionary_type,
-        parse_self_type,
-        parse_basic_type,
-        parse_inout_type,
-        parse_solidity_type,
-        parse_identifier_type,
-    ))(i)
+pub fn parse_type(i: Span) -> IResult<Type> {
+    expected(
+        "type",
+        alt((
+            parse_fixed_array_type,
+            parse_array_type,
+            parse_dictionary_type,
+            parse_self_type,
+            parse_basic_type,
+            parse_inout_type,
+            parse_solidity_type,
+            parse_identifier_type,
+        )),
+    )(i)
 }
 
-pub fn parse_identifier_type(i: Span) -> nom::IResult<Span, Type> {
+pub fn parse_identifier_type(i: Span) -> IResult<Type> {
     let (i, identifier) = parse_identifier(i)?;
     if is_basic_type(identifier.token.as_str()) {
         let basic_type = match identifier.token.as_str() {
@@ -59,12 +61,12 @@ fn is_basic_type(basic_type: &str) -> bool {
     basic_types.contains(basic_type)
 }
 
-fn parse_self_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_self_type(i: Span) -> IResult<Type> {
     let (i, _) = tag("Self")(i)?;
     Ok((i, Type::SelfType))
 }
 
-fn parse_solidity_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_solidity_type(i: Span) -> IResult<Type> {
     alt((
         parse_solidity_type_first_part,
         parse_solidity_type_second_part,
@@ -77,7 +79,7 @@ fn parse_solidity_type(i: Span) -> nom::IResult<Span, Type> {
     ))(i)
 }
 
-fn parse_solidity_type_first_part(i: Span) -> nom::IResult<Span, Type> {
+fn parse_solidity_type_first_part(i: Span) -> IResult<Type> {
     alt((
         map(tag("int8"), |_| Type::Solidity(SolidityType::INT8)),
         map(tag("int16"), |_| Type::Solidity(SolidityType::INT16)),
@@ -97,7 +99,7 @@ fn parse_solidity_type_first_part(i: Span) -> nom::IResult<Span, Type> {
     ))(i)
 }
 
-fn parse_solidity_type_second_part(i: Span) -> nom::IResult<Span, Type> {
+fn parse_solidity_type_second_part(i: Span) -> IResult<Type> {
     alt((
         map(tag("int128"), |_| Type::Solidity(SolidityType::INT128)),
         map(tag("int136"), |_| Type::Solidity(SolidityType::INT136)),
@@ -116,7 +118,7 @@ fn parse_solidity_type_second_part(i: Span) -> nom::IResult<Span, Type> {
     ))(i)
 }
 
-fn parse_solidity_type_third_part(i: Span) -> nom::IResult<Span, Type> {
+fn parse_solidity_type_third_part(i: Span) -> IResult<Type> {
     alt((
         map(tag("int240"), |_| Type::Solidity(SolidityType::INT240)),
         map(tag("int248"), |_| Type::Solidity(SolidityType::INT248)),
@@ -135,7 +137,7 @@ fn parse_solidity_type_third_part(i: Span) -> nom::IResult<Span, Type> {
     ))(i)
 }
 
-fn parse_solidity_type_fourth_part(i: Span) -> nom::IResult<Span, Type> {
+fn parse_solidity_type_fourth_part(i: Span) -> IResult<Type> {
     alt((
         map(tag("uint96"), |_| Type::Solidity(SolidityType::UINT96)),
         map(tag("uint104"), |_| Type::Solidity(SolidityType::UINT104)),
@@ -152,7 +154,7 @@ fn parse_solidity_type_fourth_part(i: Span) -> nom::IResult<Span, Type> {
     ))(i)
 }
 
-fn parse_solidity_type_fifth_part(i: Span) -> nom::IResult<Span, Type> {
+fn parse_solidity_type_fifth_part(i: Span) -> IResult<Type> {
     alt((
         map(tag("uint192"), |_| Type::Solidity(SolidityType::UINT192)),
         map(tag("uint200"), |_| Type::Solidity(SolidityType::UINT200)),
@@ -166,7 +168,7 @@ fn parse_solidity_type_fifth_part(i: Span) -> nom::IResult<Span, Type> {
     ))(i)
 }
 
-fn parse_fixed_array_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_fixed_array_type(i: Span) -> IResult<Type> {
     let (i, identifier) = parse_identifier_type(i)?;
     let (i, literal) =
         nom::sequence::delimited(left_square_bracket, integer, right_square_bracket)(i)?;
@@ -183,7 +185,7 @@ fn parse_fixed_array_type(i: Span) -> nom::IResult<Span, Type> {
     Ok((i, Type::FixedSizedArrayType(fixed_sized_array_type)))
 }
 
-fn parse_inout_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_inout_type(i: Span) -> IResult<Type> {
     let (i, _) = tag("inout")(i)?;
     let (i, _) = whitespace(i)?;
     let (i, key_type) = parse_type(i)?;
@@ -193,7 +195,7 @@ fn parse_inout_type(i: Span) -> nom::IResult<Span, Type> {
     Ok((i, Type::InoutType(inout_type)))
 }
 
-fn parse_array_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_array_type(i: Span) -> IResult<Type> {
     let (i, key_type) =
         nom::sequence::delimited(left_square_bracket, parse_type, right_square_bracket)(i)?;
     let array_type = ArrayType {
@@ -202,7 +204,7 @@ fn parse_array_type(i: Span) -> nom::IResult<Span, Type> {
     Ok((i, Type::ArrayType(array_type)))
 }
 
-fn parse_dictionary_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_dictionary_type(i: Span) -> IResult<Type> {
     let (i, _) = left_square_bracket(i)?;
     let (i, key_type) = parse_type(i)?;
     let (i, _) = colon(i)?;
@@ -216,7 +218,7 @@ fn parse_dictionary_type(i: Span) -> nom::IResult<Span, Type> {
     Ok((i, Type::DictionaryType(dictionary_type)))
 }
 
-fn parse_basic_type(i: Span) -> nom::IResult<Span, Type> {
+fn parse_basic_type(i: Span) -> IResult<Type> {
     let (i, base_type) = alt((
         map(tag("Bool"), |_| Type::Bool),
         map(tag("Int"), |_| Type::Int),
@@ -231,12 +233,12 @@ mod test {
 
     use crate::ast::*;
     use crate::parser::types::*;
-    use nom_locate::LocatedSpan;
+    use crate::parser::utils::new_span;
 
     #[test]
     fn test_parse_int_type() {
         let input = "Int";
-        let input = LocatedSpan::new(input);
+        let input = new_span(input);
         let result = parse_type(input);
         match result {
             Ok((_c, b)) => assert_eq!(b, Type::Int),
@@ -247,7 +249,7 @@ mod test {
     #[test]
     fn test_parse_address_type() {
         let input = "Address";
-        let input = LocatedSpan::new(input);
+        let input = new_span(input);
         let result = parse_type(input);
         match result {
             Ok((_c, b)) => assert_eq!(b, Type::Address),
@@ -258,7 +260,7 @@ mod test {
     #[test]
     fn test_parse_bool_type() {
         let input = "Bool";
-        let input = LocatedSpan::new(input);
+        let input = new_span(input);
         let result = parse_type(input);
         match result {
             Ok((_c, b)) => assert_eq!(b, Type::Bool),
@@ -269,7 +271,7 @@ mod test {
     #[test]
     fn test_parse_string_type() {
         let input = "String";
-        let input = LocatedSpan::new(input);
+        let input = new_span(input);
         let result = parse_type(input);
         match result {
             Ok((_c, b)) => assert_eq!(b, Type::String),
@@ -1,53 +1,132 @@
 use crate::ast::*;
+use crate::diagnostic::Diagnostic;
 use crate::environment::Environment;
+use crate::parser::error::{Expected, SyntaxError};
 use crate::parser::operators::{left_brace, parse_comment, right_brace};
 use crate::parser::statements::*;
+use nom::error::ParseError;
 use nom::{branch::alt, multi::many0};
 use nom_locate::LocatedSpan;
 
-pub type ParseResult = Result<(Module, Environment), String>;
+pub type ParseResult = Result<(Module, Environment), Vec<Diagnostic>>;
 
-pub type Span<'a> = LocatedSpan<&'a str>;
+/// The file being parsed, and its offset in the program
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FileStart {
+    pub file: FileId,
+    pub offset: usize,
+}
+
+pub type Span<'a> = LocatedSpan<&'a str, FileStart>;
+
+/// The input of a program parsed on its own, as the only file of the program
+pub fn new_span(i: &str) -> Span {
+    LocatedSpan::new_extra(i, FileStart::default())
+}
+
+/// The location of the start of the input, in its file and in the program
+pub fn line_info(i: &Span) -> LineInfo {
+    LineInfo {
+        line: i.location_line(),
+        column: i.get_utf8_column() as u32,
+        offset: i.extra.offset + i.location_offset(),
+        file: i.extra.file,
+    }
+}
+
+pub type IResult<'a, O> = nom::IResult<Span<'a>, O, SyntaxError<'a>>;
 
-pub fn parse_code_block(i: Span) -> nom::IResult<Span, Vec<Statement>> {
+pub fn parse_code_block(i: Span) -> IResult<Vec<Statement>> {
     let (i, _) = left_brace(i)?;
-    let (i, _) = multi_whitespace(i)?;
-    let (i, statements) = parse_statements(i)?;
-    let (i, _) = multi_whitespace(i)?;
-    let (i, _) = right_brace(i)?;
+    // Nothing else starts with a brace, so errors inside the block are not backtracked over
+    nom::combinator::cut(|i| {
+        let (i, _) = multi_whitespace(i)?;
+        let (i, statements) = parse_statements(i)?;
+        let (i, _) = multi_whitespace(i)?;
+        let (i, _) = closing(right_brace, parse_statement)(i)?;
+        Ok((i, statements))
+    })(i)
+}
+
+/// Matches a keyword or punctuation token, which is reported as expected if it is missing
+pub fn tag<'a>(token: &'static str) -> impl Fn(Span<'a>) -> IResult<'a, Span<'a>> {
+    move |i| {
+        nom::bytes::complete::tag(token)(i).map_err(|err: nom::Err<SyntaxError<'a>>| {
+            err.map(|_| SyntaxError::expected(i, Expected::Token(token)))
+        })
+    }
+}
 
-    Ok((i, statements))
+/// Names what the parser expected if it fails before consuming any input, instead of listing
+/// every token it could have started with
+pub fn expected<'a, O, P>(
+    syntax: &'static str,
+    mut parser: P,
+) -> impl FnMut(Span<'a>) -> IResult<'a, O>
+where
+    P: FnMut(Span<'a>) -> IResult<'a, O>,
+{
+    move |i| {
+        parser(i).map_err(|err| {
+            err.map(|err| {
+                if err.offset() == i.location_offset() {
+                    SyntaxError::expected(i, Expected::Syntax(syntax))
+                } else {
+                    err
+                }
+            })
+        })
+    }
+}
+
+/// Parses the token closing a list of items. A list ends at the first item which fails to parse,
+/// so if the token is missing, that item is reparsed to report how far it got
+pub fn closing<'a, O, C, P>(
+    mut close: C,
+    mut item: P,
+) -> impl FnMut(Span<'a>) -> IResult<'a, Span<'a>>
+where
+    C: FnMut(Span<'a>) -> IResult<'a, Span<'a>>,
+    P: FnMut(Span<'a>) -> IResult<'a, O>,
+{
+    move |i| {
+        close(i).map_err(|err| match item(i) {
+            Err(nom::Err::Error(item_err)) | Err(nom::Err::Failure(item_err)) => {
+                err.map(|err| item_err.or(err))
+            }
+            _ => err,
+        })
+    }
 }
 
-pub fn whitespace(i: Span) -> nom::IResult<Span, Span> {
+pub fn whitespace(i: Span) -> IResult<Span> {
     let (i, _) = many0(alt((
         nom::character::complete::space1,
         nom::character::complete::line_ending,
         parse_comment,
     )))(i)?;
-    Ok((i, LocatedSpan::new("")))
+    Ok((i, new_span("")))
 }
 
-pub fn multi_whitespace(i: Span) -> nom::IResult<Span, Span> {
+pub fn multi_whitespace(i: Span) -> IResult<Span> {
     let (i, _) = many0(alt((nom::character::complete::multispace1, parse_comment)))(i)?;
-    Ok((i, LocatedSpan::new("")))
+    Ok((i, new_span("")))
 }
 
 #[cfg(test)]
 mod test {
 
     use crate::parser::utils::*;
-    use nom_locate::LocatedSpan;
 
     #[test]
     fn test_parse_whitespace() {
-        let input = LocatedSpan::new("");
+        let input = new_span("");
         let (rest, result) = whitespace(input).expect("Error parsing whitespace");
-        assert_eq!(rest, LocatedSpan::new(""));
-        assert_eq!(result, LocatedSpan::new(""));
+        assert_eq!(rest, new_span(""));
+        assert_eq!(result, new_span(""));
 
-        let input = LocatedSpan::new("   ");
+        let input = new_span("   ");
         let (_rest, result) = whitespace(input).expect("Error parsing whitespace");
-        assert_eq!(result, LocatedSpan::new(""));
+        assert_eq!(result, new_span(""));
     }
 }
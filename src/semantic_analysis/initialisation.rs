@@ -0,0 +1,799 @@
+use crate::ast::*;
+use crate::context::Context;
+use crate::diagnostic::{code, Diagnostic};
+
+/// Whether a property or local has been given a value at a point in the body
+#[derive(Debug, Clone, PartialEq)]
+enum Initialisation {
+    Unassigned,
+    Assigned,
+    /// Assigned on some paths, but not on the one described
+    MaybeUnassigned(String),
+}
+
+#[derive(Debug, Clone)]
+struct TrackedValue {
+    identifier: Identifier,
+    /// Declared with `let`, so it may be assigned only once
+    constant: bool,
+    state: Initialisation,
+}
+
+#[derive(Debug, Clone)]
+struct InitialisationState {
+    properties: Vec<TrackedValue>,
+    locals: Vec<TrackedValue>,
+    reachable: bool,
+    /// Within a `do` body, the states at every point reached so far, from any of which the error
+    /// its `catch` handles may be thrown
+    caught: Option<Box<Caught>>,
+}
+
+/// The states a `catch` body may be entered from, and the path which describes them
+#[derive(Debug, Clone)]
+struct Caught {
+    state: InitialisationState,
+    path: String,
+}
+
+impl Caught {
+    fn merge(self, other: Caught) -> Caught {
+        Caught {
+            state: self.state.merge(other.state, &self.path, &self.path),
+            path: self.path,
+        }
+    }
+}
+
+impl InitialisationState {
+    fn property_mut(&mut self, name: &str) -> Option<&mut TrackedValue> {
+        self.properties
+            .iter_mut()
+            .find(|p| p.identifier.token == name)
+    }
+
+    fn local_mut(&mut self, name: &str) -> Option<&mut TrackedValue> {
+        self.locals
+            .iter_mut()
+            .rev()
+            .find(|l| l.identifier.token == name)
+    }
+
+    /// Joins the current state into those the error of the enclosing `do` may be thrown from
+    fn may_throw(&mut self) {
+        if !self.reachable {
+            return;
+        }
+        if let Some(caught) = self.caught.take() {
+            let here = Caught {
+                state: self.clone(),
+                path: caught.path.clone(),
+            };
+            self.caught = Some(Box::new(caught.merge(here)));
+        }
+    }
+
+    /// Merges the states of two branches which rejoin, ignoring branches which cannot complete.
+    /// A value assigned on only one branch is described by the path of the other. Where an error
+    /// may have been thrown from is kept from both
+    fn merge(
+        mut self,
+        mut other: InitialisationState,
+        first_path: &str,
+        second_path: &str,
+    ) -> InitialisationState {
+        let caught = match (self.caught.take(), other.caught.take()) {
+            (Some(first), Some(second)) => Some(Box::new(first.merge(*second))),
+            (first, second) => first.or(second),
+        };
+        if !self.reachable {
+            return InitialisationState { caught, ..other };
+        }
+        if !other.reachable {
+            return InitialisationState { caught, ..self };
+        }
+
+        let merge_values = |first: Vec<TrackedValue>, second: Vec<TrackedValue>| {
+            first
+                .into_iter()
+                .zip(second)
+                .map(|(first, second)| {
+                    let state = match (first.state, second.state) {
+                        (Initialisation::Assigned, Initialisation::Assigned) => {
+                            Initialisation::Assigned
+                        }
+                        (Initialisation::Unassigned, Initialisation::Unassigned) => {
+                            Initialisation::Unassigned
+                        }
+                        (Initialisation::MaybeUnassigned(path), _)
+                        | (_, Initialisation::MaybeUnassigned(path)) => {
+                            Initialisation::MaybeUnassigned(path)
+                        }
+                        (Initialisation::Unassigned, _) => {
+                            Initialisation::MaybeUnassigned(first_path.to_string())
+                        }
+                        (_, Initialisation::Unassigned) => {
+                            Initialisation::MaybeUnassigned(second_path.to_string())
+                        }
+                    };
+                    TrackedValue { state, ..first }
+                })
+                .collect()
+        };
+
+        InitialisationState {
+            properties: merge_values(self.properties, other.properties),
+            locals: merge_values(self.locals, other.locals),
+            reachable: true,
+            caught,
+        }
+    }
+}
+
+/// Checks that locals are assigned before they are read and, within an initialiser, that every
+/// property without a default value is assigned on every path through it before it is read.
+/// Constants may only be assigned where they are certain not to have a value yet, and constant
+/// properties only within initialisers
+pub(crate) fn check_initialisation(
+    body: &[Statement],
+    is_initialiser: bool,
+    ctx: &Context,
+) -> VResult {
+    let enclosing = match ctx.enclosing_type_identifier() {
+        Some(enclosing) => enclosing.token.clone(),
+        None => return Ok(()),
+    };
+
+    // Dictionaries are stored separately from the other properties, and always start empty
+    let properties = if is_initialiser {
+        ctx.environment
+            .property_declarations(&enclosing)
+            .into_iter()
+            .filter_map(|property| match property {
+                Property::VariableDeclaration(declaration, _)
+                    if declaration.expression.is_none()
+                        && !matches!(declaration.variable_type, Type::DictionaryType(_)) =>
+                {
+                    Some(TrackedValue {
+                        constant: declaration.is_constant(),
+                        identifier: declaration.identifier,
+                        state: Initialisation::Unassigned,
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let checker = InitialisationChecker {
+        ctx,
+        enclosing,
+        is_initialiser,
+    };
+    let mut state = InitialisationState {
+        properties,
+        locals: vec![],
+        reachable: true,
+        caught: None,
+    };
+
+    checker.statements(body, &mut state)?;
+
+    if state.reachable {
+        for property in &state.properties {
+            match &property.state {
+                Initialisation::Unassigned => {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::UNINITIALISED,
+                            format!(
+                                "Property `{}` is not initialised by the initialiser of `{}`",
+                                property.identifier.token, checker.enclosing
+                            ),
+                        )
+                        .with_span(&property.identifier.line_info)
+                        .with_suggestion(
+                            "assign it in every initialiser, or give it a default value",
+                        ),
+                    ));
+                }
+                Initialisation::MaybeUnassigned(path) => {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::UNINITIALISED,
+                            format!(
+                                "Property `{}` is not initialised on {}",
+                                property.identifier.token, path
+                            ),
+                        )
+                        .with_span(&property.identifier.line_info),
+                    ));
+                }
+                Initialisation::Assigned => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct InitialisationChecker<'a> {
+    ctx: &'a Context,
+    enclosing: String,
+    is_initialiser: bool,
+}
+
+impl<'a> InitialisationChecker<'a> {
+    fn statements(&self, statements: &[Statement], state: &mut InitialisationState) -> VResult {
+        for statement in statements {
+            if !state.reachable {
+                break;
+            }
+
+            match statement {
+                Statement::Expression(expression) => {
+                    self.expression_statement(expression, state)?
+                }
+                Statement::ReturnStatement(statement) => {
+                    if let Some(ref expression) = statement.expression {
+                        self.read(expression, state)?;
+                    }
+                    self.ensure_properties_assigned(state, &statement.line_info)?;
+                    state.may_throw();
+                    state.reachable = false;
+                }
+                Statement::BecomeStatement(_) => {}
+                Statement::EmitStatement(emit) => {
+                    for argument in &emit.function_call.arguments {
+                        self.read(&argument.expression, state)?;
+                    }
+                }
+                Statement::ForStatement(for_statement) => {
+                    self.read(&for_statement.iterable, state)?;
+                    self.loop_body(for_statement, state)?;
+                }
+                Statement::IfStatement(if_statement) => {
+                    self.read(&if_statement.condition, state)?;
+                    let line = path_line(&if_statement.condition, "`if`");
+                    self.branches(
+                        &if_statement.body,
+                        &if_statement.else_body,
+                        &format!("the then-path of {}", line),
+                        &format!("the else-path of {}", line),
+                        state,
+                    )?;
+                }
+                Statement::DoCatchStatement(do_catch) => {
+                    let line = path_line(&do_catch.error, "`do`");
+                    self.do_catch(do_catch, &line, state)?;
+                }
+                Statement::Assertion(assertion) => self.read(&assertion.expression, state)?,
+            }
+            state.may_throw();
+        }
+        Ok(())
+    }
+
+    fn branches(
+        &self,
+        first: &[Statement],
+        second: &[Statement],
+        first_path: &str,
+        second_path: &str,
+        state: &mut InitialisationState,
+    ) -> VResult {
+        let outer = state.locals.len();
+
+        let mut first_state = state.clone();
+        self.statements(first, &mut first_state)?;
+        first_state.locals.truncate(outer);
+
+        let mut second_state = state.clone();
+        self.statements(second, &mut second_state)?;
+        second_state.locals.truncate(outer);
+
+        *state = first_state.merge(second_state, first_path, second_path);
+        Ok(())
+    }
+
+    /// The error may be thrown anywhere in the `do` body, so the `catch` body starts from the
+    /// states at every point of it, even when the `do` body returns: values the `do` body assigns
+    /// may or may not have been assigned
+    fn do_catch(
+        &self,
+        do_catch: &DoCatchStatement,
+        line: &str,
+        state: &mut InitialisationState,
+    ) -> VResult {
+        let outer = state.locals.len();
+        let enclosing = state.caught.take();
+
+        let mut do_state = state.clone();
+        do_state.caught = Some(Box::new(Caught {
+            state: state.clone(),
+            path: format!("the catch-path of {} if the error is thrown first", line),
+        }));
+        self.statements(&do_catch.do_body, &mut do_state)?;
+        do_state.locals.truncate(outer);
+
+        let mut catch_state = do_state.caught.take().unwrap().state;
+        catch_state.locals.truncate(outer);
+        catch_state.caught = enclosing.clone();
+        self.statements(&do_catch.catch_body, &mut catch_state)?;
+        catch_state.locals.truncate(outer);
+
+        do_state.caught = enclosing;
+        *state = do_state.merge(
+            catch_state,
+            &format!("the do-path of {}", line),
+            &format!("the catch-path of {}", line),
+        );
+        Ok(())
+    }
+
+    /// The loop variable is given each element before the body runs
+    fn loop_body(&self, for_statement: &ForStatement, state: &mut InitialisationState) -> VResult {
+        let line = &for_statement.variable.identifier.line_info;
+        let outer = state.locals.len();
+        let mut body_state = state.clone();
+        body_state.locals.push(TrackedValue {
+            identifier: for_statement.variable.identifier.clone(),
+            constant: for_statement.variable.is_constant(),
+            state: Initialisation::Assigned,
+        });
+        self.statements(&for_statement.body, &mut body_state)?;
+        body_state.locals.truncate(outer);
+
+        let before = state.properties.iter().chain(&state.locals);
+        let after = body_state.properties.iter().chain(&body_state.locals);
+        for (before, after) in before.zip(after) {
+            if before.constant
+                && before.state == Initialisation::Unassigned
+                && after.state != Initialisation::Unassigned
+            {
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::CONSTANT_REASSIGNMENT,
+                        format!(
+                            "Constant `{}` is assigned inside the loop on {} and may be assigned more than once",
+                            before.identifier.token, line
+                        ),
+                    )
+                    .with_span(line)
+                    .with_secondary(&before.identifier.line_info, "declared as a constant here"),
+                ));
+            }
+        }
+
+        // The loop may run zero times, so values it assigns may still be unassigned after it
+        let path = format!("the path skipping the loop on {}", line);
+        *state = body_state.merge(state.clone(), "", &path);
+        Ok(())
+    }
+
+    /// A `return` leaves the initialiser, so every property must have been assigned before it
+    fn ensure_properties_assigned(&self, state: &InitialisationState, line: &LineInfo) -> VResult {
+        for property in &state.properties {
+            let path = match &property.state {
+                Initialisation::Assigned => continue,
+                Initialisation::Unassigned => format!("before the `return` on {}", line),
+                Initialisation::MaybeUnassigned(path) => {
+                    format!("on {} before the `return` on {}", path, line)
+                }
+            };
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::UNINITIALISED,
+                    format!(
+                        "Property `{}` is not initialised {}",
+                        property.identifier.token, path
+                    ),
+                )
+                .with_span(line)
+                .with_secondary(
+                    &property.identifier.line_info,
+                    "declared here without a default value",
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn expression_statement(
+        &self,
+        expression: &Expression,
+        state: &mut InitialisationState,
+    ) -> VResult {
+        match expression {
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Equal => {
+                self.read(&binary.rhs_expression, state)?;
+                self.assign(&binary.lhs_expression, state)
+            }
+            Expression::BinaryExpression(binary)
+                if matches!(
+                    binary.op,
+                    BinOp::PlusEqual | BinOp::MinusEqual | BinOp::TimesEqual | BinOp::DivideEqual
+                ) =>
+            {
+                self.read(&binary.rhs_expression, state)?;
+                self.read(&binary.lhs_expression, state)?;
+                self.assign(&binary.lhs_expression, state)
+            }
+            Expression::VariableDeclaration(declaration) => {
+                state.locals.push(TrackedValue {
+                    identifier: declaration.identifier.clone(),
+                    constant: declaration.is_constant(),
+                    state: Initialisation::Unassigned,
+                });
+                Ok(())
+            }
+            _ => self.read(expression, state),
+        }
+    }
+
+    fn assign(&self, target: &Expression, state: &mut InitialisationState) -> VResult {
+        match target {
+            Expression::VariableDeclaration(declaration) => {
+                state.locals.push(TrackedValue {
+                    identifier: declaration.identifier.clone(),
+                    constant: declaration.is_constant(),
+                    state: Initialisation::Assigned,
+                });
+                Ok(())
+            }
+            Expression::Identifier(identifier) if identifier.enclosing_type.is_none() => {
+                if let Some(local) = state.local_mut(&identifier.token) {
+                    if local.constant {
+                        ensure_first_assignment(identifier, local)?;
+                    }
+                    local.state = Initialisation::Assigned;
+                }
+                Ok(())
+            }
+            Expression::Identifier(identifier)
+                if identifier.enclosing_type.as_ref() == Some(&self.enclosing) =>
+            {
+                self.assign_property(identifier, state)
+            }
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                match (&*binary.lhs_expression, &*binary.rhs_expression) {
+                    (Expression::SelfExpression, Expression::Identifier(identifier)) => {
+                        self.assign_property(identifier, state)
+                    }
+                    // Assigning to a member of a value requires the value itself to be initialised
+                    _ => {
+                        self.read(target, state)?;
+                        self.ensure_mutable(target, state)
+                    }
+                }
+            }
+            Expression::BracketedExpression(bracketed) => self.assign(&bracketed.expression, state),
+            _ => {
+                self.read(target, state)?;
+                self.ensure_mutable(target, state)
+            }
+        }
+    }
+
+    fn assign_property(&self, identifier: &Identifier, state: &mut InitialisationState) -> VResult {
+        let property = self
+            .ctx
+            .environment
+            .property(&identifier.token, &self.enclosing);
+        if let Some(Property::VariableDeclaration(declaration, _)) =
+            property.as_ref().map(|property| &property.property)
+        {
+            if declaration.is_constant() {
+                if !self.is_initialiser {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::CONSTANT_REASSIGNMENT,
+                            format!(
+                                "Cannot assign to constant `{}` outside an initialiser on {}",
+                                identifier.token, identifier.line_info
+                            ),
+                        )
+                        .with_span(&identifier.line_info)
+                        .with_secondary(
+                            &declaration.identifier.line_info,
+                            "declared as a constant here",
+                        ),
+                    ));
+                }
+                if let Some(expression) = &declaration.expression {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::CONSTANT_REASSIGNMENT,
+                            format!(
+                                "Cannot reassign to constant `{}` on {}",
+                                identifier.token, identifier.line_info
+                            ),
+                        )
+                        .with_span(&identifier.line_info)
+                        .with_secondary(&expression.get_line_info(), "given a value here"),
+                    ));
+                }
+            }
+        }
+
+        if let Some(property) = state.property_mut(&identifier.token) {
+            if property.constant {
+                ensure_first_assignment(identifier, property)?;
+            }
+            property.state = Initialisation::Assigned;
+        }
+        Ok(())
+    }
+
+    /// Assigning to a member or element of a value changes the value, so neither it nor the
+    /// member may be constant. Constant properties can still be set up within initialisers
+    fn ensure_mutable(&self, target: &Expression, state: &mut InitialisationState) -> VResult {
+        match target {
+            Expression::Identifier(identifier) => {
+                self.ensure_mutable_member(identifier, true, state)
+            }
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                let member_of_self = matches!(*binary.lhs_expression, Expression::SelfExpression);
+                if !member_of_self {
+                    self.ensure_mutable(&binary.lhs_expression, state)?;
+                }
+                match &*binary.rhs_expression {
+                    Expression::Identifier(member) => {
+                        self.ensure_mutable_member(member, member_of_self, state)
+                    }
+                    Expression::SubscriptExpression(subscript) => self.ensure_mutable_member(
+                        &subscript.base_expression,
+                        member_of_self,
+                        state,
+                    ),
+                    _ => Ok(()),
+                }
+            }
+            Expression::SubscriptExpression(subscript) => {
+                self.ensure_mutable_member(&subscript.base_expression, true, state)
+            }
+            Expression::BracketedExpression(bracketed) => {
+                self.ensure_mutable(&bracketed.expression, state)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn ensure_mutable_member(
+        &self,
+        identifier: &Identifier,
+        top_level: bool,
+        state: &mut InitialisationState,
+    ) -> VResult {
+        let enclosing = match &identifier.enclosing_type {
+            None => {
+                return match state.local_mut(&identifier.token) {
+                    Some(local) if local.constant => Err(Box::from(
+                        Diagnostic::error(
+                            code::CONSTANT_REASSIGNMENT,
+                            format!(
+                                "Cannot modify constant `{}` on {}",
+                                identifier.token, identifier.line_info
+                            ),
+                        )
+                        .with_span(&identifier.line_info)
+                        .with_secondary(&local.identifier.line_info, "declared as a constant here"),
+                    )),
+                    _ => Ok(()),
+                };
+            }
+            Some(enclosing) => enclosing,
+        };
+
+        let property = match self.ctx.environment.property(&identifier.token, enclosing) {
+            Some(property) if property.is_constant() => property,
+            _ => return Ok(()),
+        };
+        if top_level && *enclosing == self.enclosing && self.is_initialiser {
+            return Ok(());
+        }
+
+        let message = if *enclosing == self.enclosing {
+            format!(
+                "Cannot assign to constant `{}` outside an initialiser on {}",
+                identifier.token, identifier.line_info
+            )
+        } else {
+            format!(
+                "Cannot assign to constant `{}` of `{}` on {}",
+                identifier.token, enclosing, identifier.line_info
+            )
+        };
+        Err(Box::from(
+            Diagnostic::error(code::CONSTANT_REASSIGNMENT, message)
+                .with_span(&identifier.line_info)
+                .with_secondary(
+                    &property.property.get_identifier().line_info,
+                    "declared as a constant here",
+                ),
+        ))
+    }
+
+    fn tracked_mut<'s>(
+        &self,
+        identifier: &Identifier,
+        state: &'s mut InitialisationState,
+    ) -> Option<&'s mut TrackedValue> {
+        match &identifier.enclosing_type {
+            None => state.local_mut(&identifier.token),
+            Some(enclosing) if *enclosing == self.enclosing => {
+                state.property_mut(&identifier.token)
+            }
+            Some(_) => None,
+        }
+    }
+
+    fn read(&self, expression: &Expression, state: &mut InitialisationState) -> VResult {
+        match expression {
+            Expression::Identifier(identifier) => self.ensure_assigned(identifier, state),
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                match &*binary.lhs_expression {
+                    Expression::SelfExpression => {
+                        self.read_member_of_self(&binary.rhs_expression, state)
+                    }
+                    lhs => {
+                        self.read(lhs, state)?;
+                        match &*binary.rhs_expression {
+                            Expression::FunctionCall(call) => {
+                                self.arguments(&call.arguments, state)
+                            }
+                            // Other members are fields of the value, not properties of the enclosing type
+                            _ => Ok(()),
+                        }
+                    }
+                }
+            }
+            Expression::BinaryExpression(binary) => {
+                self.read(&binary.lhs_expression, state)?;
+                self.read(&binary.rhs_expression, state)
+            }
+            Expression::InoutExpression(inout) => self.read(&inout.expression, state),
+            Expression::ExternalCall(external) => {
+                self.arguments(&external.arguments, state)?;
+                self.read(
+                    &Expression::BinaryExpression(external.function_call.clone()),
+                    state,
+                )
+            }
+            Expression::FunctionCall(call) => self.arguments(&call.arguments, state),
+            Expression::AttemptExpression(attempt) => {
+                self.arguments(&attempt.function_call.arguments, state)
+            }
+            Expression::BracketedExpression(bracketed) => self.read(&bracketed.expression, state),
+            Expression::ArrayLiteral(array) => {
+                for element in &array.elements {
+                    self.read(element, state)?;
+                }
+                Ok(())
+            }
+            Expression::DictionaryLiteral(dictionary) => {
+                for (key, value) in &dictionary.elements {
+                    self.read(key, state)?;
+                    self.read(value, state)?;
+                }
+                Ok(())
+            }
+            Expression::SubscriptExpression(subscript) => {
+                self.ensure_assigned(&subscript.base_expression, state)?;
+                self.read(&subscript.index_expression, state)
+            }
+            Expression::CastExpression(cast) => self.read(&cast.expression, state),
+            Expression::RangeExpression(range) => {
+                self.read(&range.start_expression, state)?;
+                self.read(&range.end_expression, state)
+            }
+            Expression::Sequence(expressions) => {
+                for expression in expressions {
+                    self.expression_statement(expression, state)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn read_member_of_self(&self, member: &Expression, state: &mut InitialisationState) -> VResult {
+        match member {
+            Expression::Identifier(identifier) => {
+                let property = Identifier {
+                    enclosing_type: Some(self.enclosing.clone()),
+                    ..identifier.clone()
+                };
+                self.ensure_assigned(&property, state)
+            }
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                self.read_member_of_self(&binary.lhs_expression, state)?;
+                if let Expression::FunctionCall(call) = &*binary.rhs_expression {
+                    self.arguments(&call.arguments, state)?;
+                }
+                Ok(())
+            }
+            Expression::SubscriptExpression(subscript) => {
+                self.read_member_of_self(
+                    &Expression::Identifier(subscript.base_expression.clone()),
+                    state,
+                )?;
+                self.read(&subscript.index_expression, state)
+            }
+            Expression::FunctionCall(call) => self.arguments(&call.arguments, state),
+            _ => Ok(()),
+        }
+    }
+
+    fn arguments(
+        &self,
+        arguments: &[FunctionArgument],
+        state: &mut InitialisationState,
+    ) -> VResult {
+        for argument in arguments {
+            self.read(&argument.expression, state)?;
+        }
+        Ok(())
+    }
+
+    fn ensure_assigned(&self, identifier: &Identifier, state: &mut InitialisationState) -> VResult {
+        let kind = if identifier.enclosing_type.is_some() {
+            ("Property", "initialised")
+        } else {
+            ("Variable", "assigned")
+        };
+        let value = match self.tracked_mut(identifier, state) {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+
+        let message = match &value.state {
+            Initialisation::Assigned => return Ok(()),
+            Initialisation::Unassigned => format!(
+                "{} `{}` is used on {} before it is {}",
+                kind.0, identifier.token, identifier.line_info, kind.1
+            ),
+            Initialisation::MaybeUnassigned(path) => format!(
+                "{} `{}` is used on {} but is not {} on {}",
+                kind.0, identifier.token, identifier.line_info, kind.1, path
+            ),
+        };
+        Err(Box::from(
+            Diagnostic::error(code::UNINITIALISED, message)
+                .with_span(&identifier.line_info)
+                .with_secondary(&value.identifier.line_info, "declared here"),
+        ))
+    }
+}
+
+/// The line a branching statement is described by, if its condition has a position
+fn path_line(condition: &Expression, statement: &str) -> String {
+    let line_info = condition.get_line_info();
+    if line_info.line == 0 {
+        format!("the {} statement", statement)
+    } else {
+        line_info.to_string()
+    }
+}
+
+/// A constant may only be assigned where it is certain not to have a value already
+fn ensure_first_assignment(identifier: &Identifier, value: &TrackedValue) -> VResult {
+    let message = match &value.state {
+        Initialisation::Unassigned => return Ok(()),
+        Initialisation::Assigned => format!(
+            "Reassignment to constant `{}` on {}",
+            identifier.token, identifier.line_info
+        ),
+        Initialisation::MaybeUnassigned(_) => format!(
+            "Constant `{}` is assigned on {} but may already have been assigned before",
+            identifier.token, identifier.line_info
+        ),
+    };
+    Err(Box::from(
+        Diagnostic::error(code::CONSTANT_REASSIGNMENT, message)
+            .with_span(&identifier.line_info)
+            .with_secondary(&value.identifier.line_info, "declared as a constant here"),
+    ))
+}
@@ -0,0 +1,549 @@
+use crate::ast::*;
+use crate::context::Context;
+use crate::diagnostic::{code, Diagnostic};
+
+/// The ownership state of a local asset (or a by-value asset parameter) at a point in the body
+#[derive(Debug, Clone, PartialEq)]
+enum AssetState {
+    Unassigned,
+    Owned,
+    Moved(LineInfo),
+    MaybeMoved(LineInfo),
+}
+
+#[derive(Debug, Clone)]
+struct TrackedAsset {
+    identifier: Identifier,
+    state: AssetState,
+}
+
+#[derive(Debug, Clone)]
+struct LinearityState {
+    assets: Vec<TrackedAsset>,
+    reachable: bool,
+    /// Within a `do` body, the states at every point reached so far, from any of which the error
+    /// its `catch` handles may be thrown
+    caught: Option<Box<LinearityState>>,
+}
+
+impl LinearityState {
+    fn asset(&self, name: &str) -> Option<&TrackedAsset> {
+        self.assets.iter().find(|a| a.identifier.token == name)
+    }
+
+    fn asset_mut(&mut self, name: &str) -> Option<&mut TrackedAsset> {
+        self.assets.iter_mut().find(|a| a.identifier.token == name)
+    }
+
+    fn is_tracked(&self, identifier: &Identifier) -> bool {
+        identifier.enclosing_type.is_none() && self.asset(&identifier.token).is_some()
+    }
+
+    /// Joins the current state into those the error of the enclosing `do` may be thrown from
+    fn may_throw(&mut self) {
+        if !self.reachable {
+            return;
+        }
+        if let Some(caught) = self.caught.take() {
+            let here = self.clone();
+            self.caught = Some(Box::new(caught.merge(here)));
+        }
+    }
+
+    /// Merges the states of two branches which rejoin, ignoring branches which cannot complete.
+    /// Where an error may have been thrown from is kept from both
+    fn merge(mut self, mut other: LinearityState) -> LinearityState {
+        let caught = match (self.caught.take(), other.caught.take()) {
+            (Some(first), Some(second)) => Some(Box::new(first.merge(*second))),
+            (first, second) => first.or(second),
+        };
+        if !self.reachable {
+            return LinearityState { caught, ..other };
+        }
+        if !other.reachable {
+            return LinearityState { caught, ..self };
+        }
+
+        let assets = self
+            .assets
+            .into_iter()
+            .map(|asset| {
+                let state = match other.asset(&asset.identifier.token).map(|o| &o.state) {
+                    Some(other_state) if *other_state == asset.state => asset.state,
+                    Some(AssetState::Moved(line)) | Some(AssetState::MaybeMoved(line)) => {
+                        AssetState::MaybeMoved(line.clone())
+                    }
+                    Some(AssetState::Owned) => match asset.state {
+                        AssetState::Moved(line) | AssetState::MaybeMoved(line) => {
+                            AssetState::MaybeMoved(line)
+                        }
+                        _ => AssetState::Owned,
+                    },
+                    _ => match asset.state {
+                        AssetState::Moved(line) | AssetState::MaybeMoved(line) => {
+                            AssetState::MaybeMoved(line)
+                        }
+                        state => state,
+                    },
+                };
+                TrackedAsset { state, ..asset }
+            })
+            .collect();
+
+        LinearityState {
+            assets,
+            reachable: true,
+            caught,
+        }
+    }
+}
+
+/// Checks that assets are treated linearly within a function or initialiser body: every asset
+/// held in a local must be transferred or stored exactly once, and can never be implicitly copied
+pub(crate) fn check_asset_linearity(
+    parameters: &[Parameter],
+    body: &[Statement],
+    ctx: &Context,
+) -> VResult {
+    let checker = LinearityChecker { ctx };
+
+    let mut state = LinearityState {
+        assets: parameters
+            .iter()
+            .filter(|p| checker.is_asset_type(&p.type_assignment))
+            .map(|p| TrackedAsset {
+                identifier: p.identifier.clone(),
+                state: AssetState::Owned,
+            })
+            .collect(),
+        reachable: true,
+        caught: None,
+    };
+
+    checker.statements(body, &mut state)?;
+
+    if state.reachable {
+        checker.ensure_consumed(&state, 0)?;
+    }
+
+    Ok(())
+}
+
+struct LinearityChecker<'a> {
+    ctx: &'a Context,
+}
+
+impl<'a> LinearityChecker<'a> {
+    fn is_asset_type(&self, asset_type: &Type) -> bool {
+        self.ctx.environment.is_asset_type(asset_type)
+    }
+
+    fn is_asset_property(&self, identifier: &Identifier) -> bool {
+        identifier
+            .enclosing_type
+            .as_ref()
+            .and_then(|enclosing| self.ctx.environment.property(&identifier.token, enclosing))
+            .map(|property| self.is_asset_type(property.get_type()))
+            .unwrap_or(false)
+    }
+
+    fn statements(&self, statements: &[Statement], state: &mut LinearityState) -> VResult {
+        for statement in statements {
+            if !state.reachable {
+                break;
+            }
+
+            match statement {
+                Statement::Expression(expression) => {
+                    self.expression_statement(expression, state)?
+                }
+                Statement::ReturnStatement(statement) => {
+                    if let Some(ref expression) = statement.expression {
+                        self.consume(expression, state)?;
+                    }
+                    self.ensure_consumed(state, 0)?;
+                    state.may_throw();
+                    state.reachable = false;
+                }
+                Statement::BecomeStatement(_) => {}
+                Statement::EmitStatement(emit) => {
+                    for argument in &emit.function_call.arguments {
+                        self.read(&argument.expression, state)?;
+                    }
+                }
+                Statement::ForStatement(for_statement) => {
+                    self.read(&for_statement.iterable, state)?;
+                    self.loop_body(&for_statement.body, state)?;
+                }
+                Statement::IfStatement(if_statement) => {
+                    self.read(&if_statement.condition, state)?;
+                    self.branches(&if_statement.body, &if_statement.else_body, state)?;
+                }
+                Statement::DoCatchStatement(do_catch) => self.do_catch(do_catch, state)?,
+                Statement::Assertion(assertion) => self.read(&assertion.expression, state)?,
+            }
+            state.may_throw();
+        }
+        Ok(())
+    }
+
+    /// The error may be thrown anywhere in the `do` body, so the `catch` body starts from the
+    /// states at every point of it: assets it transfers may or may not have been transferred
+    fn do_catch(&self, do_catch: &DoCatchStatement, state: &mut LinearityState) -> VResult {
+        let outer = state.assets.len();
+        let enclosing = state.caught.take();
+
+        let mut do_state = state.clone();
+        do_state.caught = Some(Box::new(state.clone()));
+        self.statements(&do_catch.do_body, &mut do_state)?;
+        if do_state.reachable {
+            self.ensure_consumed(&do_state, outer)?;
+        }
+        do_state.assets.truncate(outer);
+
+        let mut catch_state = *do_state.caught.take().unwrap();
+        catch_state.assets.truncate(outer);
+        catch_state.caught = enclosing.clone();
+        self.statements(&do_catch.catch_body, &mut catch_state)?;
+        if catch_state.reachable {
+            self.ensure_consumed(&catch_state, outer)?;
+        }
+        catch_state.assets.truncate(outer);
+
+        do_state.caught = enclosing;
+        *state = do_state.merge(catch_state);
+        Ok(())
+    }
+
+    fn branches(
+        &self,
+        first: &[Statement],
+        second: &[Statement],
+        state: &mut LinearityState,
+    ) -> VResult {
+        let outer = state.assets.len();
+
+        let mut first_state = state.clone();
+        self.statements(first, &mut first_state)?;
+        if first_state.reachable {
+            self.ensure_consumed(&first_state, outer)?;
+        }
+        first_state.assets.truncate(outer);
+
+        let mut second_state = state.clone();
+        self.statements(second, &mut second_state)?;
+        if second_state.reachable {
+            self.ensure_consumed(&second_state, outer)?;
+        }
+        second_state.assets.truncate(outer);
+
+        *state = first_state.merge(second_state);
+        Ok(())
+    }
+
+    fn loop_body(&self, body: &[Statement], state: &mut LinearityState) -> VResult {
+        let outer = state.assets.len();
+        let mut body_state = state.clone();
+        self.statements(body, &mut body_state)?;
+
+        if body_state.reachable {
+            self.ensure_consumed(&body_state, outer)?;
+        }
+
+        for (before, after) in state.assets.iter().zip(&body_state.assets) {
+            if let (AssetState::Owned, AssetState::Moved(line))
+            | (AssetState::Owned, AssetState::MaybeMoved(line)) = (&before.state, &after.state)
+            {
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::ASSET_LINEARITY,
+                        format!(
+                            "Asset `{}` is transferred inside a loop on {} and may be spent more than once",
+                            before.identifier.token, line
+                        ),
+                    )
+                    .with_span(line)
+                    .with_secondary(&before.identifier.line_info, "asset declared here"),
+                ));
+            }
+        }
+
+        // The loop may run zero times, so the state after it is that before it, but an error may
+        // have been thrown from within it
+        state.caught = body_state.caught;
+        Ok(())
+    }
+
+    /// Every asset declared from `from` onwards which is still held must have been transferred
+    fn ensure_consumed(&self, state: &LinearityState, from: usize) -> VResult {
+        for asset in state.assets.iter().skip(from) {
+            match asset.state {
+                AssetState::Owned => {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::ASSET_LINEARITY,
+                            format!(
+                                "Asset `{}` declared on {} is never transferred or stored",
+                                asset.identifier.token, asset.identifier.line_info
+                            ),
+                        )
+                        .with_span(&asset.identifier.line_info),
+                    ));
+                }
+                AssetState::MaybeMoved(ref line) => {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::ASSET_LINEARITY,
+                            format!(
+                                "Asset `{}` declared on {} is only transferred on some paths (see {})",
+                                asset.identifier.token, asset.identifier.line_info, line
+                            ),
+                        )
+                        .with_span(&asset.identifier.line_info)
+                        .with_secondary(line, "transferred here on some paths"),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn expression_statement(&self, expression: &Expression, state: &mut LinearityState) -> VResult {
+        match expression {
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Equal => {
+                self.consume(&binary.rhs_expression, state)?;
+                self.assign(&binary.lhs_expression, &binary.line_info, state)
+            }
+            Expression::VariableDeclaration(declaration) => {
+                if self.is_asset_type(&declaration.variable_type) {
+                    state.assets.push(TrackedAsset {
+                        identifier: declaration.identifier.clone(),
+                        state: AssetState::Unassigned,
+                    });
+                }
+                Ok(())
+            }
+            Expression::FunctionCall(call)
+                if self
+                    .ctx
+                    .environment
+                    .is_asset_declared(&call.identifier.token) =>
+            {
+                self.arguments(&call.arguments, state)?;
+                Err(Box::from(
+                    Diagnostic::error(
+                        code::ASSET_LINEARITY,
+                        format!(
+                            "Asset created on {} is never transferred or stored",
+                            call.identifier.line_info
+                        ),
+                    )
+                    .with_span(&call.identifier.line_info),
+                ))
+            }
+            _ => self.read(expression, state),
+        }
+    }
+
+    fn assign(&self, target: &Expression, line: &LineInfo, state: &mut LinearityState) -> VResult {
+        match target {
+            Expression::VariableDeclaration(declaration) => {
+                if self.is_asset_type(&declaration.variable_type) {
+                    state.assets.push(TrackedAsset {
+                        identifier: declaration.identifier.clone(),
+                        state: AssetState::Owned,
+                    });
+                }
+                Ok(())
+            }
+            Expression::Identifier(identifier) if state.is_tracked(identifier) => {
+                let asset = state.asset_mut(&identifier.token).unwrap();
+                match asset.state {
+                    AssetState::Owned | AssetState::MaybeMoved(_) => Err(Box::from(
+                        Diagnostic::error(
+                            code::ASSET_LINEARITY,
+                            format!(
+                                "Assigning to asset `{}` on {} would destroy the asset it already holds",
+                                identifier.token, line
+                            ),
+                        )
+                        .with_span(&identifier.line_info),
+                    )),
+                    _ => {
+                        asset.state = AssetState::Owned;
+                        Ok(())
+                    }
+                }
+            }
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                self.borrow(&binary.lhs_expression, state)
+            }
+            Expression::SubscriptExpression(subscript) => {
+                self.read(&subscript.index_expression, state)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The value of the expression is transferred to a new owner
+    fn consume(&self, expression: &Expression, state: &mut LinearityState) -> VResult {
+        match expression {
+            Expression::Identifier(identifier) if state.is_tracked(identifier) => {
+                self.ensure_available(identifier, state)?;
+                state.asset_mut(&identifier.token).unwrap().state =
+                    AssetState::Moved(identifier.line_info.clone());
+                Ok(())
+            }
+            Expression::BracketedExpression(bracketed) => {
+                self.consume(&bracketed.expression, state)
+            }
+            _ => self.read(expression, state),
+        }
+    }
+
+    /// The expression is passed by reference, so must still hold its asset
+    fn borrow(&self, expression: &Expression, state: &mut LinearityState) -> VResult {
+        match expression {
+            Expression::Identifier(identifier) if state.is_tracked(identifier) => {
+                self.ensure_available(identifier, state)
+            }
+            Expression::Identifier(_) | Expression::SelfExpression => Ok(()),
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                self.borrow(&binary.lhs_expression, state)
+            }
+            Expression::BracketedExpression(bracketed) => self.borrow(&bracketed.expression, state),
+            _ => self.read(expression, state),
+        }
+    }
+
+    /// The value of the expression is used without transferring it, so an asset would be copied
+    fn read(&self, expression: &Expression, state: &mut LinearityState) -> VResult {
+        match expression {
+            Expression::Identifier(identifier) if state.is_tracked(identifier) => {
+                self.ensure_available(identifier, state)?;
+                Err(Box::from(
+                    Diagnostic::error(
+                        code::ASSET_LINEARITY,
+                        format!(
+                            "Asset `{}` is implicitly copied on {}",
+                            identifier.token, identifier.line_info
+                        ),
+                    )
+                    .with_span(&identifier.line_info)
+                    .with_suggestion(format!("transfer it explicitly with `&{}`", identifier.token)),
+                ))
+            }
+            Expression::Identifier(identifier) if self.is_asset_property(identifier) => {
+                Err(Box::from(
+                    Diagnostic::error(
+                        code::ASSET_LINEARITY,
+                        format!(
+                            "Asset property `{}` is implicitly copied on {}, pass it as `&{}` instead",
+                            identifier.token, identifier.line_info, identifier.token
+                        ),
+                    )
+                    .with_span(&identifier.line_info),
+                ))
+            }
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                self.borrow(&binary.lhs_expression, state)?;
+                match &*binary.rhs_expression {
+                    Expression::FunctionCall(call) => self.arguments(&call.arguments, state),
+                    rhs => self.read(rhs, state),
+                }
+            }
+            Expression::BinaryExpression(binary) => {
+                self.read(&binary.lhs_expression, state)?;
+                self.read(&binary.rhs_expression, state)
+            }
+            Expression::InoutExpression(inout) => self.borrow(&inout.expression, state),
+            Expression::ExternalCall(external) => {
+                self.arguments(&external.arguments, state)?;
+                self.read(
+                    &Expression::BinaryExpression(external.function_call.clone()),
+                    state,
+                )
+            }
+            Expression::FunctionCall(call) => self.arguments(&call.arguments, state),
+            Expression::AttemptExpression(attempt) => {
+                self.arguments(&attempt.function_call.arguments, state)
+            }
+            Expression::BracketedExpression(bracketed) => self.read(&bracketed.expression, state),
+            Expression::ArrayLiteral(array) => {
+                for element in &array.elements {
+                    self.consume(element, state)?;
+                }
+                Ok(())
+            }
+            Expression::DictionaryLiteral(dictionary) => {
+                for (key, value) in &dictionary.elements {
+                    self.read(key, state)?;
+                    self.consume(value, state)?;
+                }
+                Ok(())
+            }
+            Expression::SubscriptExpression(subscript) => {
+                self.read(&subscript.index_expression, state)
+            }
+            Expression::CastExpression(cast) => self.read(&cast.expression, state),
+            Expression::RangeExpression(range) => {
+                self.read(&range.start_expression, state)?;
+                self.read(&range.end_expression, state)
+            }
+            Expression::Sequence(expressions) => {
+                for expression in expressions {
+                    self.expression_statement(expression, state)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Asset arguments passed by value are transferred to the callee
+    fn arguments(&self, arguments: &[FunctionArgument], state: &mut LinearityState) -> VResult {
+        for argument in arguments {
+            self.consume(&argument.expression, state)?;
+        }
+        Ok(())
+    }
+
+    fn ensure_available(&self, identifier: &Identifier, state: &LinearityState) -> VResult {
+        match &state.asset(&identifier.token).unwrap().state {
+            AssetState::Owned => Ok(()),
+            AssetState::Unassigned => Err(Box::from(
+                Diagnostic::error(
+                    code::ASSET_LINEARITY,
+                    format!(
+                        "Asset `{}` is used on {} before it is assigned",
+                        identifier.token, identifier.line_info
+                    ),
+                )
+                .with_span(&identifier.line_info),
+            )),
+            AssetState::Moved(line) => Err(Box::from(
+                Diagnostic::error(
+                    code::ASSET_LINEARITY,
+                    format!(
+                        "Asset `{}` is used on {} after it was transferred on {}",
+                        identifier.token, identifier.line_info, line
+                    ),
+                )
+                .with_span(&identifier.line_info)
+                .with_secondary(line, "transferred here"),
+            )),
+            AssetState::MaybeMoved(line) => Err(Box::from(
+                Diagnostic::error(
+                    code::ASSET_LINEARITY,
+                    format!(
+                        "Asset `{}` is used on {} after it may have been transferred on {}",
+                        identifier.token, identifier.line_info, line
+                    ),
+                )
+                .with_span(&identifier.line_info)
+                .with_secondary(line, "transferred here on some paths"),
+            )),
+        }
+    }
+}
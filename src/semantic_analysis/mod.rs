@@ -1,13 +1,19 @@
+mod initialisation;
+mod linearity;
+mod type_states;
+
 use super::ast::*;
 use super::context::*;
 use super::visitor::*;
-use crate::environment::functions::compatible_caller_protections;
-use crate::environment::FunctionCallMatchResult::{Failure, MatchedFunction, MatchedInitializer};
+use crate::diagnostic::{code, Diagnostic};
+use crate::environment::FunctionCallMatchResult::Failure;
 use crate::environment::{CallableInformation, Candidates, Environment};
 use crate::type_checker::ExpressionChecker;
 use crate::utils::unique::Unique;
 use itertools::Itertools;
 
+pub use self::type_states::{check_type_states, state_machine, StateMachine};
+
 pub struct SemanticAnalysis {}
 
 impl Visitor for SemanticAnalysis {
@@ -16,33 +22,41 @@ impl Visitor for SemanticAnalysis {
         declaration: &mut ContractDeclaration,
         context: &mut Context,
     ) -> VResult {
+        ensure_type_not_reserved(&declaration.identifier)?;
+
         if context
             .environment
             .get_public_initialiser(&declaration.identifier.token)
             .is_none()
         {
-            return Err(Box::from(format!(
-                "No public Initialiser for contract {}",
-                declaration.identifier.token
-            )));
-        }
-
-        if context.environment.is_conflicting(&declaration.identifier) {
-            return Err(Box::from(format!(
-                "Conflicting Declarations for {}",
-                declaration.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "No public Initialiser for contract {}",
+                        declaration.identifier.token
+                    ),
+                )
+                .with_span(&declaration.identifier.line_info)
+                .with_suggestion("declare a `public init()` for the contract"),
+            ));
         }
 
         if is_conformance_repeated(&declaration.conformances) {
-            return Err(Box::from("Conformances are repeated".to_owned()));
+            return Err(Box::from(
+                Diagnostic::error(code::CONFLICTING_DECLARATION, "Conformances are repeated")
+                    .with_span(&declaration.identifier.line_info),
+            ));
         }
 
         if context
             .environment
             .conflicting_trait_signatures(&declaration.identifier.token)
         {
-            return Err(Box::from("Conflicting traits".to_owned()));
+            return Err(Box::from(
+                Diagnostic::error(code::CONFLICTING_DECLARATION, "Conflicting traits")
+                    .with_span(&declaration.identifier.line_info),
+            ));
         }
 
         let non_private_dynamic_fields = declaration
@@ -58,21 +72,31 @@ impl Visitor for SemanticAnalysis {
             })
             .collect::<Vec<&VariableDeclaration>>();
 
-        if !non_private_dynamic_fields.is_empty() {
-            let error_msg = non_private_dynamic_fields
-                .iter()
-                .map(|field| {
-                    format!(
-                        "Dynamic typed variable must be private: variable {} of type {} on {}\n",
-                        field.identifier.token.as_str(),
-                        field.variable_type,
-                        field.identifier.line_info
+        if let Some((field, others)) = non_private_dynamic_fields.split_first() {
+            let diagnostic = Diagnostic::error(
+                code::INVALID_DECLARATION,
+                format!(
+                    "Dynamic typed variable must be private: variable {} of type {} on {}",
+                    field.identifier.token.as_str(),
+                    field.variable_type,
+                    field.identifier.line_info
+                ),
+            )
+            .with_span(&field.identifier.line_info)
+            .with_suggestion("remove the access modifier");
+
+            return Err(Box::from(others.iter().fold(
+                diagnostic,
+                |diagnostic, field| {
+                    diagnostic.with_secondary(
+                        &field.identifier.line_info,
+                        format!(
+                            "variable {} of type {} must also be private",
+                            field.identifier.token, field.variable_type
+                        ),
                     )
-                })
-                .collect::<Vec<String>>()
-                .concat();
-
-            return Err(Box::from(error_msg));
+                },
+            )));
         }
 
         Ok(())
@@ -84,6 +108,14 @@ impl Visitor for SemanticAnalysis {
         context: &mut Context,
     ) -> VResult {
         let enclosing = context.enclosing_type_identifier().unwrap();
+        if let ContractMember::Invariant(invariant) = member {
+            return ensure_boolean_specification(
+                invariant,
+                &enclosing.token,
+                context,
+                &Default::default(),
+            );
+        }
         if let ContractMember::VariableDeclaration(declaration, _) = member {
             if let Some(expression) = declaration.expression.as_deref() {
                 let source_type = context.environment.get_expression_type(
@@ -106,28 +138,46 @@ impl Visitor for SemanticAnalysis {
                                 if *size == elements.len() as u64 {
                                     Ok(())
                                 } else {
-                                    Err(Box::from(format!(
-                                        "LHS array has fixed size of {} but RHS literal has size {} on line {}",
-                                        size, elements.len(), declaration.identifier.line_info.line
-                                    )))
+                                    Err(Box::from(
+                                        Diagnostic::error(
+                                            code::TYPE_MISMATCH,
+                                            format!(
+                                                "LHS array has fixed size of {} but RHS literal has size {} on line {}",
+                                                size, elements.len(), declaration.identifier.line_info.line
+                                            ),
+                                        )
+                                        .with_span(&declaration.identifier.line_info),
+                                    ))
                                 }
                             } else {
                                 Ok(())
                             }
                         } else {
-                            Err(Box::from(format!(
-                                "Cannot assign array of type `{}` to an array of type `{}` on {}",
-                                rhs_type, lhs_type, &declaration.identifier.line_info
-                            )))
+                            Err(Box::from(
+                                Diagnostic::error(
+                                    code::TYPE_MISMATCH,
+                                    format!(
+                                        "Cannot assign array of type `{}` to an array of type `{}` on {}",
+                                        rhs_type, lhs_type, &declaration.identifier.line_info
+                                    ),
+                                )
+                                .with_span(&declaration.identifier.line_info),
+                            ))
                         };
                     }
                 }
 
                 if declaration.variable_type != source_type {
-                    return Err(Box::from(format!(
-                        "Cannot initialise contract property of type `{}` with type `{}` on {}",
-                        declaration.variable_type, source_type, &declaration.identifier.line_info
-                    )));
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::TYPE_MISMATCH,
+                            format!(
+                                "Cannot initialise contract property of type `{}` with type `{}` on {}",
+                                declaration.variable_type, source_type, &declaration.identifier.line_info
+                            ),
+                        )
+                        .with_span(&declaration.identifier.line_info),
+                    ));
                 }
             }
         }
@@ -143,10 +193,13 @@ impl Visitor for SemanticAnalysis {
             .environment
             .is_contract_declared(&declaration.identifier.token)
         {
-            return Err(Box::from(format!(
-                "Undeclared contract {}",
-                declaration.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::UNDECLARED_TYPE,
+                    format!("Undeclared contract {}", declaration.identifier.token),
+                )
+                .with_span(&declaration.identifier.line_info),
+            ));
         }
 
         let stateful = context
@@ -155,27 +208,44 @@ impl Visitor for SemanticAnalysis {
         let states = declaration.type_states.clone();
         if !stateful && !states.is_empty() {
             return Err(Box::from(
-                format!(
-                    "Undeclared type states {:?}",
-                    states
-                        .iter()
-                        .map(|state| state.identifier.token.clone())
-                        .collect::<Vec<String>>()
+                Diagnostic::error(
+                    code::TYPE_STATE,
+                    format!(
+                        "Undeclared type states {:?}",
+                        states
+                            .iter()
+                            .map(|state| state.identifier.token.clone())
+                            .collect::<Vec<String>>()
+                    ),
                 )
-                .as_str(),
+                .with_span(&states[0].identifier.line_info)
+                .with_note(format!(
+                    "contract {} does not declare any type states",
+                    declaration.identifier.token
+                )),
             ));
         }
 
+        if let Some(ref caller_binding) = declaration.caller_binding {
+            ensure_not_reserved(caller_binding)?;
+        }
+
         if !context.is_trait_declaration_context() {
             let members = declaration.members.clone();
             for member in members {
                 match member {
                     ContractBehaviourMember::SpecialSignatureDeclaration(_)
                     | ContractBehaviourMember::FunctionSignatureDeclaration(_) => {
-                        return Err(Box::from(format!(
-                            "Signature Declaration in Contract {}",
-                            declaration.identifier.token
-                        )));
+                        return Err(Box::from(
+                            Diagnostic::error(
+                                code::INVALID_DECLARATION,
+                                format!(
+                                    "Signature Declaration in Contract {}",
+                                    declaration.identifier.token
+                                ),
+                            )
+                            .with_span(&declaration.identifier.line_info),
+                        ));
                     }
                     _ => continue,
                 }
@@ -190,30 +260,41 @@ impl Visitor for SemanticAnalysis {
         declaration: &mut StructDeclaration,
         context: &mut Context,
     ) -> VResult {
-        if context.environment.is_conflicting(&declaration.identifier) {
-            let i = declaration.identifier.token.clone();
-            return Err(Box::from(format!("Conflicting declarations for {}", i)));
+        if declaration.identifier.token != crate::environment::FLINT_GLOBAL {
+            ensure_type_not_reserved(&declaration.identifier)?;
         }
 
         if context
             .environment
             .is_recursive_struct(&declaration.identifier.token)
         {
-            return Err(Box::from(format!(
-                "Recusive struct definition for {} on {}",
-                declaration.identifier.token, declaration.identifier.line_info
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "Recusive struct definition for {} on {}",
+                        declaration.identifier.token, declaration.identifier.line_info
+                    ),
+                )
+                .with_span(&declaration.identifier.line_info),
+            ));
         }
 
         if is_conformance_repeated(&declaration.conformances) {
-            return Err(Box::from("Conformances are repeated".to_owned()));
+            return Err(Box::from(
+                Diagnostic::error(code::CONFLICTING_DECLARATION, "Conformances are repeated")
+                    .with_span(&declaration.identifier.line_info),
+            ));
         }
 
         if context
             .environment
             .conflicting_trait_signatures(&declaration.identifier.token)
         {
-            return Err(Box::from("Conflicting traits".to_owned()));
+            return Err(Box::from(
+                Diagnostic::error(code::CONFLICTING_DECLARATION, "Conflicting traits")
+                    .with_span(&declaration.identifier.line_info),
+            ));
         }
         Ok(())
     }
@@ -224,6 +305,14 @@ impl Visitor for SemanticAnalysis {
         context: &mut Context,
     ) -> VResult {
         let enclosing = context.enclosing_type_identifier().unwrap();
+        if let StructMember::Invariant(invariant) = member {
+            return ensure_boolean_specification(
+                invariant,
+                &enclosing.token,
+                context,
+                &Default::default(),
+            );
+        }
         if let StructMember::VariableDeclaration(ref declaration, _) = member {
             if let Some(expression) = declaration.expression.as_deref() {
                 let source_type = context.environment.get_expression_type(
@@ -234,10 +323,18 @@ impl Visitor for SemanticAnalysis {
                     Default::default(),
                 );
                 if declaration.variable_type != source_type {
-                    return Err(Box::from(format!(
-                        "Cannot initialise struct field of type `{}` with type `{}` on {}",
-                        declaration.variable_type, source_type, declaration.identifier.line_info
-                    )));
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::TYPE_MISMATCH,
+                            format!(
+                                "Cannot initialise struct field of type `{}` with type `{}` on {}",
+                                declaration.variable_type,
+                                source_type,
+                                declaration.identifier.line_info
+                            ),
+                        )
+                        .with_span(&declaration.identifier.line_info),
+                    ));
                 }
             }
         }
@@ -247,15 +344,9 @@ impl Visitor for SemanticAnalysis {
     fn start_asset_declaration(
         &mut self,
         declaration: &mut AssetDeclaration,
-        context: &mut Context,
+        _context: &mut Context,
     ) -> VResult {
-        if context.environment.is_conflicting(&declaration.identifier) {
-            return Err(Box::from(format!(
-                "Conflicting declarations for {} on line {}",
-                &declaration.identifier.token, &declaration.identifier.line_info
-            )));
-        }
-
+        ensure_type_not_reserved(&declaration.identifier)?;
         Ok(())
     }
 
@@ -272,26 +363,29 @@ impl Visitor for SemanticAnalysis {
         declaration: &mut VariableDeclaration,
         ctx: &mut Context,
     ) -> VResult {
-        let type_declared = match &declaration.variable_type {
-            Type::UserDefinedType(t) => ctx.environment.is_type_declared(&t.token.clone()),
-            _ => true,
-        };
-
-        if !type_declared {
-            return Err(Box::from(format!(
-                "Type `{}` is not declared",
-                declaration.variable_type
-            )));
+        if !is_trusted_declaration_context(ctx) {
+            ensure_not_reserved(&declaration.identifier)?;
         }
 
         if ctx.in_function_or_special() {
             if let Some(ref mut scope_context) = ctx.scope_context {
                 let redeclaration = scope_context.declaration(&declaration.identifier.token);
-                if redeclaration.is_some() {
-                    return Err(Box::from(format!(
-                        "Redeclaration of identifier `{}` on {}",
-                        declaration.identifier.token, declaration.identifier.line_info,
-                    )));
+                if let Some(redeclaration) = redeclaration {
+                    let mut diagnostic = Diagnostic::error(
+                        code::CONFLICTING_DECLARATION,
+                        format!(
+                            "Redeclaration of identifier `{}` on {}",
+                            declaration.identifier.token, declaration.identifier.line_info,
+                        ),
+                    )
+                    .with_span(&declaration.identifier.line_info);
+                    if redeclaration.identifier.line_info != declaration.identifier.line_info {
+                        diagnostic = diagnostic.with_secondary(
+                            &redeclaration.identifier.line_info,
+                            "previously declared here",
+                        );
+                    }
+                    return Err(Box::from(diagnostic));
                 }
                 scope_context.local_variables.push(declaration.clone());
             }
@@ -300,10 +394,27 @@ impl Visitor for SemanticAnalysis {
                 .environment
                 .conflicting_property_declaration(&declaration.identifier, &identifier.token)
             {
-                return Err(Box::from("Conflicting property declarations".to_owned()));
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::CONFLICTING_DECLARATION,
+                        "Conflicting property declarations",
+                    )
+                    .with_span(&declaration.identifier.line_info),
+                ));
             }
         }
 
+        // The variable is still declared in scope, so that its uses are not reported as well
+        if is_undeclared_type(&declaration.variable_type, ctx) {
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::UNDECLARED_TYPE,
+                    format!("Type `{}` is not declared", declaration.variable_type),
+                )
+                .with_span(&declaration.identifier.line_info),
+            ));
+        }
+
         Ok(())
     }
 
@@ -317,15 +428,28 @@ impl Visitor for SemanticAnalysis {
                 .environment
                 .is_conflicting_function_declaration(&declaration, &identifier.token)
             {
-                return Err(Box::from(format!(
-                    "Conflicting Function Declarations for {}",
-                    declaration.head.identifier.token
-                )));
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::CONFLICTING_DECLARATION,
+                        format!(
+                            "Conflicting Function Declarations for {}",
+                            declaration.head.identifier.token
+                        ),
+                    )
+                    .with_span(&declaration.head.identifier.line_info),
+                ));
             }
 
             if identifier.token == ctx.target.currency.identifier {
                 return Ok(());
             }
+
+            if identifier.token != crate::environment::FLINT_GLOBAL {
+                ensure_not_reserved(&declaration.head.identifier)?;
+                for parameter in &declaration.head.parameters {
+                    ensure_not_reserved(&parameter.identifier)?;
+                }
+            }
         }
 
         if !declaration
@@ -335,10 +459,16 @@ impl Visitor for SemanticAnalysis {
             .map(|p| &p.identifier.token)
             .is_unique()
         {
-            return Err(Box::from(format!(
-                "Function {} has duplicate parameters",
-                declaration.head.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "Function {} has duplicate parameters",
+                        declaration.head.identifier.token
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info),
+            ));
         }
 
         let remaining_parameters = declaration
@@ -349,24 +479,44 @@ impl Visitor for SemanticAnalysis {
             .count();
         if declaration.is_payable() {
             if remaining_parameters == 0 {
-                return Err(Box::from(format!(
-                    "Payable Function {} does not have payable parameter",
-                    declaration.head.identifier.token
-                )));
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::INVALID_DECLARATION,
+                        format!(
+                            "Payable Function {} does not have payable parameter",
+                            declaration.head.identifier.token
+                        ),
+                    )
+                    .with_span(&declaration.head.identifier.line_info),
+                ));
             } else if remaining_parameters > 1 {
-                return Err(Box::from(format!(
-                    "Payable parameter is ambiguous in function {}",
-                    declaration.head.identifier.token
-                )));
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::INVALID_DECLARATION,
+                        format!(
+                            "Payable parameter is ambiguous in function {}",
+                            declaration.head.identifier.token
+                        ),
+                    )
+                    .with_span(&declaration.head.identifier.line_info),
+                ));
             }
         } else if remaining_parameters > 0 {
-            return Err(Box::from(format!(
-                "Function not marked payable but has payable parameter: {:?}",
-                remaining_parameters
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "Function not marked payable but has payable parameter: {:?}",
+                        remaining_parameters
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info)
+                .with_suggestion("mark the function `@payable`"),
+            ));
         }
 
-        if declaration.is_public() {
+        // The standard library moves assets between accounts, which its callers cannot do themselves
+        if declaration.is_public() && !is_trusted_declaration_context(ctx) {
             let parameters = declaration
                 .head
                 .parameters
@@ -374,18 +524,30 @@ impl Visitor for SemanticAnalysis {
                 .filter(|p| p.is_dynamic() && !p.is_payable(&ctx.target))
                 .count();
             if parameters > 0 {
-                return Err(Box::from(format!(
-                    "Public Function {} has dynamic parameters",
-                    declaration.head.identifier.token
-                )));
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::INVALID_DECLARATION,
+                        format!(
+                            "Public Function {} has dynamic parameters",
+                            declaration.head.identifier.token
+                        ),
+                    )
+                    .with_span(&declaration.head.identifier.line_info),
+                ));
             }
         }
 
         if let Some(Type::UserDefinedType(_)) = declaration.head.result_type {
-            return Err(Box::from(format!(
-                "Not allowed to return struct in function {}",
-                declaration.head.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "Not allowed to return struct in function {}",
+                        declaration.head.identifier.token
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info),
+            ));
         }
 
         let mut return_statements = Vec::new();
@@ -406,31 +568,55 @@ impl Visitor for SemanticAnalysis {
 
         let remaining_after_end = remaining.filter(|s| !is_return_or_become_statement(s));
         if remaining_after_end.count() > 0 {
-            return Err(Box::from(format!(
-                "Statements after `return` in {}",
-                declaration.head.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_CONTROL_FLOW,
+                    format!(
+                        "Statements after `return` in {}",
+                        declaration.head.identifier.token
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info),
+            ));
         }
 
         if declaration.head.result_type.is_some() && !code_block_returns(&declaration.body) {
-            return Err(Box::from(format!(
-                "Function {} does not necessarily return",
-                declaration.head.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_CONTROL_FLOW,
+                    format!(
+                        "Function {} does not necessarily return",
+                        declaration.head.identifier.token
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info),
+            ));
         }
 
         if return_statements.len() > 1 {
-            return Err(Box::from(format!(
-                "Multiple `return`s in function {}",
-                declaration.head.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_CONTROL_FLOW,
+                    format!(
+                        "Multiple `return`s in function {}",
+                        declaration.head.identifier.token
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info),
+            ));
         }
 
         if become_statements.len() > 1 {
-            return Err(Box::from(format!(
-                "Multiple `become` statements in {}",
-                declaration.head.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_CONTROL_FLOW,
+                    format!(
+                        "Multiple `become` statements in {}",
+                        declaration.head.identifier.token
+                    ),
+                )
+                .with_span(&declaration.head.identifier.line_info),
+            ));
         }
 
         for become_statement in &become_statements {
@@ -438,10 +624,16 @@ impl Visitor for SemanticAnalysis {
                 if return_statement.line_info.line
                     > become_statement.state.identifier.line_info.line
                 {
-                    return Err(Box::from(format!(
-                        "`return` statement after `become` in function {}",
-                        declaration.head.identifier.token
-                    )));
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::INVALID_CONTROL_FLOW,
+                            format!(
+                                "`return` statement after `become` in function {}",
+                                declaration.head.identifier.token
+                            ),
+                        )
+                        .with_span(&declaration.head.identifier.line_info),
+                    ));
                 }
             }
         }
@@ -455,7 +647,13 @@ impl Visitor for SemanticAnalysis {
         context: &mut Context,
     ) -> VResult {
         declaration.scope_context = context.scope_context.clone();
-        Ok(())
+
+        if is_trusted_declaration_context(context) {
+            return Ok(());
+        }
+
+        linearity::check_asset_linearity(&declaration.head.parameters, &declaration.body, context)?;
+        initialisation::check_initialisation(&declaration.body, false, context)
     }
 
     fn start_special_declaration(
@@ -464,17 +662,33 @@ impl Visitor for SemanticAnalysis {
         ctx: &mut Context,
     ) -> VResult {
         if declaration.is_fallback() && declaration.head.has_parameters() {
-            return Err(Box::from(format!(
-                "Fallback {} declared with arguments",
-                declaration.head.special_token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "Fallback {} declared with arguments",
+                        declaration.head.special_token
+                    ),
+                )
+                .with_span(&declaration.head.parameters[0].identifier.line_info),
+            ));
             // TODO check body only has simple statements bit long
         }
 
+        if !declaration.generated && !is_trusted_declaration_context(ctx) {
+            for parameter in &declaration.head.parameters {
+                ensure_not_reserved(&parameter.identifier)?;
+            }
+        }
+
         if let Some(context) = &ctx.contract_behaviour_declaration_context {
             if !context.type_states.is_empty() {
                 return Err(Box::from(
-                    "Initialiser cannot have type state restrictions".to_string(),
+                    Diagnostic::error(
+                        code::TYPE_STATE,
+                        "Initialiser cannot have type state restrictions",
+                    )
+                    .with_span(&context.type_states[0].identifier.line_info),
                 ));
             }
 
@@ -489,14 +703,30 @@ impl Visitor for SemanticAnalysis {
                     .any(|state| matches!(state, Statement::BecomeStatement(_)))
             {
                 return Err(Box::from(
-                    "Initialiser of a contract with typestates must have a `become` statement"
-                        .to_string(),
+                    Diagnostic::error(
+                        code::TYPE_STATE,
+                        "Initialiser of a contract with typestates must have a `become` statement",
+                    )
+                    .with_span(&context.identifier.line_info),
                 ));
             }
         }
         Ok(())
     }
 
+    fn finish_special_declaration(
+        &mut self,
+        declaration: &mut SpecialDeclaration,
+        context: &mut Context,
+    ) -> VResult {
+        if declaration.generated || is_trusted_declaration_context(context) {
+            return Ok(());
+        }
+
+        linearity::check_asset_linearity(&declaration.head.parameters, &declaration.body, context)?;
+        initialisation::check_initialisation(&declaration.body, declaration.is_init(), context)
+    }
+
     #[allow(clippy::single_match)]
     fn finish_if_statement(
         &mut self,
@@ -507,10 +737,16 @@ impl Visitor for SemanticAnalysis {
             Expression::BinaryExpression(ref b) => {
                 if let Expression::VariableDeclaration(ref v) = *b.lhs_expression {
                     if !v.is_constant() {
-                        return Err(Box::from(format!(
-                            "Invalid condition type in `if` statement on {}",
-                            if_statement.condition.get_line_info()
-                        )));
+                        return Err(Box::from(
+                            Diagnostic::error(
+                                code::TYPE_MISMATCH,
+                                format!(
+                                    "Invalid condition type in `if` statement on {}",
+                                    if_statement.condition.get_line_info()
+                                ),
+                            )
+                            .with_span(&if_statement.condition.get_line_info()),
+                        ));
                     }
                 }
             }
@@ -526,10 +762,19 @@ impl Visitor for SemanticAnalysis {
         );
 
         if !expression_type.is_bool_type() {
-            return Err(Box::from(format!(
-                "Invalid condition type in `if` statement on {}",
-                if_statement.condition.get_line_info()
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::TYPE_MISMATCH,
+                    format!(
+                        "Invalid condition type in `if` statement on {}",
+                        if_statement.condition.get_line_info()
+                    ),
+                )
+                .with_label(
+                    &if_statement.condition.get_line_info(),
+                    format!("expected `Bool`, found `{}`", expression_type),
+                ),
+            ));
         }
 
         Ok(())
@@ -541,10 +786,16 @@ impl Visitor for SemanticAnalysis {
 
         // Check for invalid @ in name
         if token.contains('@') {
-            return Err(Box::from(format!(
-                "Invalid '@' character used in Identifier at line {}",
-                identifier.line_info.line
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_DECLARATION,
+                    format!(
+                        "Invalid '@' character used in Identifier at line {}",
+                        identifier.line_info.line
+                    ),
+                )
+                .with_span(&identifier.line_info),
+            ));
         }
 
         if ctx.is_property_default_assignment
@@ -557,16 +808,28 @@ impl Visitor for SemanticAnalysis {
                     .is_property_defined(token, &enclosing_type.token)
                 {
                     // Check for property being used to define itself (I think)
-                    Err(Box::from(format!(
-                        "State property used within property initialiser at line {}",
-                        line_number
-                    )))
+                    Err(Box::from(
+                        Diagnostic::error(
+                            code::INVALID_DECLARATION,
+                            format!(
+                                "State property used within property initialiser at line {}",
+                                line_number
+                            ),
+                        )
+                        .with_span(&identifier.line_info),
+                    ))
                 } else {
                     // Check for if property is defined
-                    Err(Box::from(format!(
-                        "Use of undeclared identifier `{}` at line {}",
-                        token, line_number
-                    )))
+                    Err(Box::from(
+                        Diagnostic::error(
+                            code::UNDECLARED_IDENTIFIER,
+                            format!(
+                                "Use of undeclared identifier `{}` at line {}",
+                                token, line_number
+                            ),
+                        )
+                        .with_span(&identifier.line_info),
+                    ))
                 };
             }
         }
@@ -587,18 +850,6 @@ impl Visitor for SemanticAnalysis {
                 }
 
                 if let Some(property) = ctx.environment.property(token, enclosing_type) {
-                    // Check: Do not allow reassignment to constants: This does not work since we never know
-                    // if something is assigned to it yet
-                    if property.is_constant()
-                        && ctx.is_lvalue
-                        && property.property.get_value().is_some()
-                    {
-                        return Err(Box::from(format!(
-                            "Cannot reassign to constant `{}` on line {}",
-                            token, line_number
-                        )));
-                    }
-
                     let current_enclosing_type =
                         if let Some(declaration_context) = &ctx.function_declaration_context {
                             declaration_context
@@ -617,48 +868,58 @@ impl Visitor for SemanticAnalysis {
                         match property.get_modifier() {
                             Some(Modifier::Visible) => {
                                 if ctx.is_lvalue {
-                                    return Err(Box::from(format!(
-                                        "Cannot assign to non-public value `{}` on line {}",
-                                        token, line_number
-                                    )));
+                                    return Err(Box::from(
+                                        Diagnostic::error(
+                                            code::INACCESSIBLE_PROPERTY,
+                                            format!(
+                                                "Cannot assign to non-public value `{}` on line {}",
+                                                token, line_number
+                                            ),
+                                        )
+                                        .with_span(&identifier.line_info),
+                                    ));
                                 }
                             }
                             None => {
-                                return Err(Box::from(format!(
-                                    "Cannot access private value `{}` on line {}",
-                                    token, line_number
-                                )));
+                                return Err(Box::from(
+                                    Diagnostic::error(
+                                        code::INACCESSIBLE_PROPERTY,
+                                        format!(
+                                            "Cannot access private value `{}` on line {}",
+                                            token, line_number
+                                        ),
+                                    )
+                                    .with_span(&identifier.line_info)
+                                    .with_suggestion("declare the property `public` or `visible`"),
+                                ));
                             }
                             Some(Modifier::Public) => (),
                         }
                     } else {
-                        ensure_mutation_declared(token, ctx)?;
+                        ensure_mutation_declared(token, &identifier.line_info, ctx)?;
                     }
                 } else {
                     // Check: cannot find property definition and it has an enclosing type
-                    return Err(Box::from(format!(
-                        "Use of undeclared identifier `{}` at line {}",
-                        token, line_number
-                    )));
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::UNDECLARED_IDENTIFIER,
+                            format!(
+                                "Use of undeclared identifier `{}` at line {}",
+                                token, line_number
+                            ),
+                        )
+                        .with_span(&identifier.line_info),
+                    ));
                 }
             } else if let Some(scope) = &ctx.scope_context {
-                if let Some(declaration) = scope.declaration(token) {
-                    // Previously we had checks for ctx.in_subscript and !declaration.variable_type.is_inout_type()
-                    // TODO the is_enclosing conjunct is required for now because of naming conflicts: the analyser cannot
-                    // yet distinguish self.<var> from <var> when reassigning. So could have const self.<var> and
-                    // local <var>, try to reassign to <var> and it think you are reassigning to self.<var>
-                    if declaration.is_constant() && ctx.is_lvalue && !ctx.is_enclosing {
-                        return Err(Box::from(format!(
-                            "Reassignment to constant `{}` on line {}",
-                            token, line_number
-                        )));
-                    }
-                } else if !ctx.environment.is_enum_declared(token) {
+                // Assignments to local constants are checked with the initialisation of values
+                let undeclared = scope.declaration(token).is_none();
+                if undeclared && !ctx.environment.is_enum_declared(token) {
                     identifier.enclosing_type =
                         Option::from(ctx.enclosing_type_identifier().unwrap().token.clone());
                     if let Some(type_id) = &identifier.enclosing_type {
                         if ctx.environment.is_property_defined(token, type_id) {
-                            ensure_mutation_declared(token, ctx)?;
+                            ensure_mutation_declared(token, &identifier.line_info, ctx)?;
                         } else if let Some(scope) = &ctx.scope_context {
                             return if scope.is_declared(token) {
                                 Ok(())
@@ -671,23 +932,38 @@ impl Visitor for SemanticAnalysis {
                                     }
                                 }
 
-                                Err(Box::from(format!(
-                                    "Use of undeclared identifier `{}` at line {}",
-                                    token, line_number
-                                )))
+                                Err(Box::from(
+                                    Diagnostic::error(
+                                        code::UNDECLARED_IDENTIFIER,
+                                        format!(
+                                            "Use of undeclared identifier `{}` at line {}",
+                                            token, line_number
+                                        ),
+                                    )
+                                    .with_span(&identifier.line_info),
+                                ))
                             } else {
-                                Err(Box::from(format!(
-                                    "Use of undeclared identifier `{}` at line {}",
-                                    token, line_number
-                                )))
+                                Err(Box::from(
+                                    Diagnostic::error(
+                                        code::UNDECLARED_IDENTIFIER,
+                                        format!(
+                                            "Use of undeclared identifier `{}` at line {}",
+                                            token, line_number
+                                        ),
+                                    )
+                                    .with_span(&identifier.line_info),
+                                ))
                             };
                         }
                     }
-                } else if !ctx.is_enclosing {
-                    return Err(Box::from(format!(
-                        "Invalid reference to `{}` on line {}",
-                        token, line_number
-                    )));
+                } else if undeclared && !ctx.is_enclosing {
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::UNDECLARED_IDENTIFIER,
+                            format!("Invalid reference to `{}` on line {}", token, line_number),
+                        )
+                        .with_span(&identifier.line_info),
+                    ));
                 }
                 // Before we had another check for if there was an enum declared, but I don't think it did anything
             }
@@ -706,10 +982,14 @@ impl Visitor for SemanticAnalysis {
 
         if is_literal(start.as_ref()) && is_literal(end.as_ref()) {
         } else {
-            return Err(Box::from(format!(
-                "Invalid Range Declaration: {:?}",
-                range_expression
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::TYPE_MISMATCH,
+                    format!("Invalid Range Declaration: {:?}", range_expression),
+                )
+                .with_span(&start.get_line_info())
+                .with_note("the bounds of a range must be literals"),
+            ));
         }
 
         Ok(())
@@ -727,10 +1007,47 @@ impl Visitor for SemanticAnalysis {
                 &context.enclosing_type_identifier().unwrap().token,
             )
         {
-            return Err(Box::from(format!(
-                "Undeclared caller protection {}",
-                protection.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::CALLER_PROTECTION,
+                    format!(
+                        "Undeclared caller protection {}",
+                        protection.identifier.token
+                    ),
+                )
+                .with_span(&protection.identifier.line_info),
+            ));
+        }
+
+        // Functions used as caller protections are called whenever the functions they protect are,
+        // so they may only inspect the contract
+        if let Some(type_info) = context
+            .enclosing_type_identifier()
+            .and_then(|identifier| context.environment.types.get(&identifier.token))
+        {
+            let mutating_check = type_info
+                .functions
+                .get(&protection.identifier.token)
+                .into_iter()
+                .flatten()
+                .find(|function| {
+                    let head = &function.declaration.head;
+                    (head.is_predicate() || head.is_0_ary_function())
+                        && function.declaration.is_mutating()
+                });
+            if let Some(function) = mutating_check {
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::CALLER_PROTECTION,
+                        format!(
+                            "Caller protection {} cannot be a function which mutates the contract",
+                            protection.identifier.token
+                        ),
+                    )
+                    .with_span(&protection.identifier.line_info)
+                    .with_secondary(&function.identifier().line_info, "function declared here"),
+                ));
+            }
         }
 
         Ok(())
@@ -742,10 +1059,13 @@ impl Visitor for SemanticAnalysis {
         context: &mut Context,
     ) -> VResult {
         if !context.environment.is_trait_declared(&conformance.name()) {
-            return Err(Box::from(format!(
-                "Undeclared trait `{}` used",
-                conformance.identifier.token
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::UNDECLARED_TYPE,
+                    format!("Undeclared trait `{}` used", conformance.identifier.token),
+                )
+                .with_span(&conformance.identifier.line_info),
+            ));
         }
         Ok(())
     }
@@ -798,10 +1118,16 @@ impl Visitor for SemanticAnalysis {
                             value_type,
                         }) => *value_type,
                         _ => {
-                            return Err(Box::from(format!(
-                                "Subscript expression on non-array type: {:?}",
-                                expression.lhs_expression
-                            )))
+                            return Err(Box::from(
+                                Diagnostic::error(
+                                    code::TYPE_MISMATCH,
+                                    format!(
+                                        "Subscript expression on non-array type: {:?}",
+                                        expression.lhs_expression
+                                    ),
+                                )
+                                .with_span(&subscript.base_expression.line_info),
+                            ))
                         }
                     }
                 }
@@ -815,10 +1141,16 @@ impl Visitor for SemanticAnalysis {
                     )
                 }
                 _ => {
-                    return Err(Box::from(format!(
-                        "Assignment to non-expression {:?}",
-                        expression.lhs_expression
-                    )));
+                    return Err(Box::from(
+                        Diagnostic::error(
+                            code::TYPE_MISMATCH,
+                            format!(
+                                "Assignment to non-expression {:?}",
+                                expression.lhs_expression
+                            ),
+                        )
+                        .with_span(&expression.line_info),
+                    ));
                 }
             }
         } else {
@@ -837,18 +1169,34 @@ impl Visitor for SemanticAnalysis {
             context.caller_protections(),
             scope,
         );
-        if expression.op.accepts(&left_type, &right_type) {
+        // An error has already been reported for whatever caused the types to be unknown
+        if expression.op.accepts(&left_type, &right_type)
+            || is_undeclared_type(&left_type, context)
+            || is_undeclared_type(&right_type, context)
+        {
             Ok(())
         } else if let BinOp::Equal = expression.op {
-            Err(Box::from(format!(
-                "Attempt to assign type `{}` to type `{}` on {}",
-                right_type, left_type, &expression.line_info
-            )))
+            Err(Box::from(
+                Diagnostic::error(
+                    code::TYPE_MISMATCH,
+                    format!(
+                        "Attempt to assign type `{}` to type `{}` on {}",
+                        right_type, left_type, &expression.line_info
+                    ),
+                )
+                .with_span(&expression.line_info),
+            ))
         } else {
-            Err(Box::from(format!(
-                "Invalid types `{}`, `{}` for operator `{}` on {}",
-                left_type, right_type, expression.op, &expression.line_info
-            )))
+            Err(Box::from(
+                Diagnostic::error(
+                    code::TYPE_MISMATCH,
+                    format!(
+                        "Invalid types `{}`, `{}` for operator `{}` on {}",
+                        left_type, right_type, expression.op, &expression.line_info
+                    ),
+                )
+                .with_span(&expression.line_info),
+            ))
         }
     }
 
@@ -867,16 +1215,19 @@ impl Visitor for SemanticAnalysis {
                 if let Some(ref behaviour_context) = context.contract_behaviour_declaration_context
                 {
                     if let CallableInformation::FunctionInformation(info) = first {
-                        if !compatible_caller_protections(
+                        if !context.environment.compatible_caller_protections(
                             &behaviour_context.caller_protections,
                             &info.caller_protections,
+                            type_id,
                         ) {
-                            return Err(Box::from(format!("Insufficient caller protections to call {} from function {} on {}.", call.identifier.token, first.name(), call.identifier.line_info)));
+                            return Err(Box::from(Diagnostic::error(code::CALLER_PROTECTION, format!("Insufficient caller protections to call {} from function {} on {}.", call.identifier.token, first.name(), call.identifier.line_info)).with_span(&call.identifier.line_info).with_note("calling it with `try!` or `try?` checks its caller protections when the call is made")));
                         }
                     }
                 }
 
-                Err(Box::from(format!(
+                let mut diagnostic = Diagnostic::error(
+                    code::INVALID_CALL,
+                    format!(
                     "Could not call `{}` with ({}) on {}, did you mean to call `{}` with ({}){}",
                     &call.identifier.token,
                     &context
@@ -891,12 +1242,24 @@ impl Visitor for SemanticAnalysis {
                         .map(|line| format!(" on {}", line))
                         .as_deref()
                         .unwrap_or("")
-                )))
+                ),
+                )
+                .with_span(&call.identifier.line_info);
+                if let Some(line_info) = first.line_info() {
+                    diagnostic = diagnostic.with_secondary(line_info, "candidate declared here");
+                }
+                Err(Box::from(diagnostic))
             } else {
-                Err(Box::from(format!(
-                    "Undefined function `{}` called on {}",
-                    &call.identifier.token, &call.identifier.line_info
-                )))
+                Err(Box::from(
+                    Diagnostic::error(
+                        code::INVALID_CALL,
+                        format!(
+                            "Undefined function `{}` called on {}",
+                            &call.identifier.token, &call.identifier.line_info
+                        ),
+                    )
+                    .with_span(&call.identifier.line_info),
+                ))
             }
         };
 
@@ -909,7 +1272,6 @@ impl Visitor for SemanticAnalysis {
 
         if let Some(ref behaviour_context) = context.contract_behaviour_declaration_context {
             let contract_name = &*behaviour_context.identifier.token;
-            let contract_call = contract_name == called_on_type;
 
             let function_info = context.environment.match_function_call(
                 &call,
@@ -918,19 +1280,9 @@ impl Visitor for SemanticAnalysis {
                 context.scope_or_default(),
             );
 
+            // Whether the contract may be in the right state is checked with the other functions
+            // of the contract by `check_type_states`
             match function_info {
-                MatchedFunction(info) if contract_call => check_if_correct_type_state_possible(
-                    behaviour_context,
-                    context.environment.get_contract_state(contract_name),
-                    &info.type_states,
-                    &call.identifier,
-                ),
-                MatchedInitializer(info) if contract_call => check_if_correct_type_state_possible(
-                    behaviour_context,
-                    context.environment.get_contract_state(contract_name),
-                    &info.type_states,
-                    &call.identifier,
-                ),
                 Failure(candidates) => fail(candidates, contract_name),
                 _ => Ok(()),
             }
@@ -974,19 +1326,31 @@ impl Visitor for SemanticAnalysis {
                     context.caller_protections(),
                     scope,
                 );
-                if expression_type != *result {
-                    Err(Box::from(format!(
-                        "Cannot return value of type `{}` when `{}` expected on {}",
-                        expression_type, result, statement.line_info
-                    )))
+                if expression_type != *result && !is_undeclared_type(&expression_type, context) {
+                    Err(Box::from(
+                        Diagnostic::error(
+                            code::TYPE_MISMATCH,
+                            format!(
+                                "Cannot return value of type `{}` when `{}` expected on {}",
+                                expression_type, result, statement.line_info
+                            ),
+                        )
+                        .with_span(&statement.line_info),
+                    ))
                 } else {
                     Ok(())
                 }
             } else {
-                Err(Box::from(format!(
-                    "Must return value when `{}` expected on {}",
-                    result, statement.line_info
-                )))
+                Err(Box::from(
+                    Diagnostic::error(
+                        code::TYPE_MISMATCH,
+                        format!(
+                            "Must return value when `{}` expected on {}",
+                            result, statement.line_info
+                        ),
+                    )
+                    .with_span(&statement.line_info),
+                ))
             }
         } else if let Some(ref expression) = statement.expression {
             let expression_type = context.environment.get_expression_type(
@@ -996,10 +1360,16 @@ impl Visitor for SemanticAnalysis {
                 context.caller_protections(),
                 scope,
             );
-            Err(Box::from(format!(
-                "No return value expected but `{}` found on {}",
-                expression_type, statement.line_info
-            )))
+            Err(Box::from(
+                Diagnostic::error(
+                    code::TYPE_MISMATCH,
+                    format!(
+                        "No return value expected but `{}` found on {}",
+                        expression_type, statement.line_info
+                    ),
+                )
+                .with_span(&statement.line_info),
+            ))
         } else {
             Ok(())
         }
@@ -1026,48 +1396,175 @@ impl Visitor for SemanticAnalysis {
         ) {
             Ok(())
         } else {
-            Err(Box::from(format!(
-                "Assertion expression must evaluate to boolean on {}",
-                assertion.line_info
-            )))
+            Err(Box::from(
+                Diagnostic::error(
+                    code::TYPE_MISMATCH,
+                    format!(
+                        "Assertion expression must evaluate to boolean on {}",
+                        assertion.line_info
+                    ),
+                )
+                .with_span(&assertion.expression.get_line_info()),
+            ))
+        }
+    }
+
+    fn start_specification(
+        &mut self,
+        specification: &mut Specification,
+        context: &mut Context,
+    ) -> VResult {
+        let enclosing_type = context
+            .enclosing_type_identifier()
+            .map(|id| &*id.token)
+            .unwrap_or_default();
+        ensure_boolean_specification(
+            specification,
+            enclosing_type,
+            context,
+            context.scope_or_default(),
+        )
+    }
+
+    fn start_old_expression(&mut self, old: &mut OldExpression, context: &mut Context) -> VResult {
+        if !context.in_postcondition {
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_SPECIFICATION,
+                    format!(
+                        "`old` can only be used in a postcondition on {}",
+                        old.line_info
+                    ),
+                )
+                .with_span(&old.line_info),
+            ));
         }
+
+        // The value is kept in a local variable when the function is entered
+        let enclosing_type = context
+            .enclosing_type_identifier()
+            .map(|id| &*id.token)
+            .unwrap_or_default();
+        let old_type = context.environment.get_expression_type(
+            &old.expression,
+            enclosing_type,
+            context.type_states(),
+            context.caller_protections(),
+            context.scope_or_default(),
+        );
+        if old_type.is_dynamic_type() {
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::INVALID_SPECIFICATION,
+                    format!(
+                        "`old` can only be used on values of basic types, not `{}`, on {}",
+                        old_type, old.line_info
+                    ),
+                )
+                .with_span(&old.line_info),
+            ));
+        }
+        Ok(())
     }
 }
 
-fn check_if_correct_type_state_possible(
-    declaration_context: &crate::context::ContractBehaviourDeclarationContext,
-    current_state: Option<TypeState>,
-    allowed_states: &[TypeState],
-    function_id: &Identifier,
-) -> VResult {
-    let current_possible_states = if let Some(state) = current_state {
-        vec![state]
-    } else {
-        declaration_context.type_states.clone()
-    };
+/// Types are registered in the environment by name, so a redeclared type must be reported before
+/// the types of expressions are assigned from it
+pub fn check_conflicting_declarations(
+    module: &Module,
+    environment: &Environment,
+) -> Result<(), Vec<Diagnostic>> {
+    let diagnostics: Vec<Diagnostic> = module
+        .declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            TopLevelDeclaration::ContractDeclaration(c) => Some(&c.identifier),
+            TopLevelDeclaration::StructDeclaration(s) => Some(&s.identifier),
+            TopLevelDeclaration::AssetDeclaration(a) => Some(&a.identifier),
+            TopLevelDeclaration::EnumDeclaration(e) => Some(&e.identifier),
+            TopLevelDeclaration::TraitDeclaration(t) => Some(&t.identifier),
+            TopLevelDeclaration::ContractBehaviourDeclaration(_) => None,
+        })
+        .filter_map(|identifier| {
+            let previous = environment.conflicting_declaration(identifier)?;
+            Some(
+                Diagnostic::error(
+                    code::CONFLICTING_DECLARATION,
+                    format!("Conflicting declarations for {}", identifier.token),
+                )
+                .with_span(&identifier.line_info)
+                .with_secondary(&previous.line_info, "previously declared here"),
+            )
+        })
+        .collect();
 
-    // If any type state is allowed, or if we could be in any type state, then we must check at runtime instead
-    if allowed_states.is_empty()
-        || current_possible_states.is_empty()
-        || current_possible_states
-            .iter()
-            .all(|state| allowed_states.contains(state))
-    {
+    if diagnostics.is_empty() {
         Ok(())
     } else {
-        let err = format!(
-            "Must definitely be in one of the following states to make function call {} on {}: {:?}.",
-            function_id.token,
-            function_id.line_info,
-            allowed_states
-                .iter()
-                .map(|state| state.identifier.token.clone())
-                .collect::<Vec<String>>(),
-        );
-        Err(Box::from(err))
+        Err(diagnostics)
     }
 }
 
+/// The standard library and the target currency are trusted to handle their own internals
+fn is_trusted_declaration_context(context: &Context) -> bool {
+    context
+        .enclosing_type_identifier()
+        .map(|identifier| {
+            identifier.token == crate::environment::FLINT_GLOBAL
+                || identifier.token == context.target.currency.identifier
+        })
+        .unwrap_or(false)
+}
+
+/// Types which could not be resolved have already been reported where they were declared or used
+fn is_undeclared_type(variable_type: &Type, context: &Context) -> bool {
+    match variable_type {
+        Type::Error => true,
+        Type::UserDefinedType(t) => !context.environment.is_type_declared(&t.token),
+        _ => false,
+    }
+}
+
+/// Names with these prefixes are used by the compiler for generated code, so user declarations
+/// must not use them
+fn ensure_not_reserved(identifier: &Identifier) -> VResult {
+    if is_reserved_identifier(&identifier.token) {
+        return Err(Box::from(
+            Diagnostic::error(
+                code::RESERVED_IDENTIFIER,
+                format!(
+                    "Identifier `{}` on {} uses a prefix reserved for compiler generated names",
+                    identifier.token, identifier.line_info
+                ),
+            )
+            .with_span(&identifier.line_info),
+        ));
+    }
+    Ok(())
+}
+
+/// Types also may not have names which make the mangled names of their functions ambiguous
+fn ensure_type_not_reserved(identifier: &Identifier) -> VResult {
+    ensure_not_reserved(identifier)?;
+    if is_reserved_type_identifier(&identifier.token) {
+        return Err(Box::from(
+            Diagnostic::error(
+                code::RESERVED_IDENTIFIER,
+                format!(
+                    "Type `{}` on {} would give its functions names reserved for compiler generated names",
+                    identifier.token, identifier.line_info
+                ),
+            )
+            .with_span(&identifier.line_info)
+            .with_suggestion(format!(
+                "rename the type so that it neither contains `{}` nor ends with `_`, and is not `Flint`",
+                MANGLE_SEPARATOR
+            )),
+        ));
+    }
+    Ok(())
+}
+
 fn is_conformance_repeated<'a, T: IntoIterator<Item = &'a Conformance>>(conformances: T) -> bool {
     !conformances
         .into_iter()
@@ -1096,7 +1593,38 @@ fn code_block_returns(block: &[Statement]) -> bool {
             }))
 }
 
-fn ensure_mutation_declared(token: &str, ctx: &Context) -> VResult {
+/// Specifications are checked when the program runs, so must be conditions. Invariants are also
+/// checked where they are declared, as a type need not have a public function to check them in
+fn ensure_boolean_specification(
+    specification: &Specification,
+    enclosing_type: &str,
+    context: &Context,
+    scope: &ScopeContext,
+) -> VResult {
+    if let Type::Bool = context.environment.get_expression_type(
+        &specification.expression,
+        enclosing_type,
+        context.type_states(),
+        context.caller_protections(),
+        scope,
+    ) {
+        Ok(())
+    } else {
+        Err(Box::from(
+            Diagnostic::error(
+                code::TYPE_MISMATCH,
+                format!(
+                    "{} must evaluate to boolean on {}",
+                    specification.kind.description(),
+                    specification.line_info
+                ),
+            )
+            .with_span(&specification.expression.get_line_info()),
+        ))
+    }
+}
+
+fn ensure_mutation_declared(token: &str, line_info: &LineInfo, ctx: &Context) -> VResult {
     if let Some(function_declaration_context) = ctx.function_declaration_context.as_ref() {
         // Check: Do not allow mutation of identifier if it is not declared mutating
         if !function_declaration_context
@@ -1105,16 +1633,30 @@ fn ensure_mutation_declared(token: &str, ctx: &Context) -> VResult {
             .any(|id| id.token == token)
             && ctx.is_lvalue
         {
-            return Err(Box::from(format!(
-                "Mutating identifier `{}` which is not declared mutating at line {}",
-                token,
-                function_declaration_context
-                    .declaration
-                    .head
-                    .identifier
-                    .line_info
-                    .line
-            )));
+            return Err(Box::from(
+                Diagnostic::error(
+                    code::UNDECLARED_MUTATION,
+                    format!(
+                        "Mutating identifier `{}` which is not declared mutating at line {}",
+                        token,
+                        function_declaration_context
+                            .declaration
+                            .head
+                            .identifier
+                            .line_info
+                            .line
+                    ),
+                )
+                .with_span(line_info)
+                .with_secondary(
+                    &function_declaration_context
+                        .declaration
+                        .head
+                        .identifier
+                        .line_info,
+                    format!("add `{}` to the `mutates` clause of this function", token),
+                ),
+            ));
         }
     }
     Ok(())
@@ -0,0 +1,710 @@
+use crate::ast::*;
+use crate::diagnostic::{code, Diagnostic};
+use crate::environment::Environment;
+use std::collections::{BTreeSet, HashMap};
+
+/// Indices into the declared type states of the contract, of the states it may be in
+type States = BTreeSet<usize>;
+
+/// A function, initialiser or fallback of a stateful contract
+struct Callable<'a> {
+    /// Initialisers and fallbacks cannot be called by name
+    name: Option<&'a str>,
+    parameters: usize,
+    /// The states its block is restricted to, if it is restricted
+    allowed: Option<States>,
+    protections: &'a [CallerProtection],
+    body: &'a [Statement],
+    /// Whether it can be called at the start of a transaction
+    entry_point: bool,
+}
+
+/// Where a body is analysed from. Bodies analysed to find the states a call may leave the contract
+/// in are checked in their own right, so do not report anything
+#[derive(Clone, Copy)]
+struct Flow {
+    restricted: bool,
+    report: bool,
+}
+
+impl Flow {
+    fn silent() -> Flow {
+        Flow {
+            restricted: false,
+            report: false,
+        }
+    }
+}
+
+/// Tracks the set of states each stateful contract may be in through the bodies of its functions.
+/// A call which can never be made in one of the states the callee allows is an error, as is one
+/// from a restricted block which may be. Any other call which may be made in a state the callee
+/// does not allow is checked by the callee when it is entered instead. The states the contract may
+/// be in when a transaction starts are recorded, so that the preprocessors only check the state of
+/// external calls which may be made in a state which is not allowed. Returns the warnings about
+/// states which are never reached, or which the contract can be stuck in
+pub fn check_type_states(
+    module: &Module,
+    environment: &mut Environment,
+) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+
+    for declaration in &module.declarations {
+        let contract = match declaration {
+            TopLevelDeclaration::ContractDeclaration(contract)
+                if !contract.type_states.is_empty() =>
+            {
+                contract
+            }
+            _ => continue,
+        };
+
+        let mut analysis = TypeStateAnalysis::new(contract, module);
+        for index in 0..analysis.callables.len() {
+            if let Err(diagnostic) = analysis.check(index) {
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        let machine = analysis.machine();
+        diagnostics.extend(analysis.reachability_warnings(&machine));
+
+        let reachable_states = contract
+            .type_states
+            .iter()
+            .filter(|state| machine.reachable.contains(&state.identifier.token))
+            .cloned()
+            .collect();
+        environment.set_type_state_analysis(
+            &contract.identifier.token,
+            reachable_states,
+            analysis.checked_functions,
+        );
+    }
+
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        Err(diagnostics)
+    } else {
+        Ok(diagnostics)
+    }
+}
+
+/// The type states of a contract and the transitions between them made by transactions
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMachine {
+    pub contract: String,
+    pub states: Vec<String>,
+    /// The states the initialiser may leave the contract in
+    pub initial: Vec<String>,
+    /// The states the contract may be in when a transaction starts
+    pub reachable: Vec<String>,
+    pub transitions: Vec<Transition>,
+}
+
+/// A function which may move the contract from one state to another, or back to the same one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub function: String,
+    pub caller_protections: Vec<String>,
+}
+
+impl StateMachine {
+    pub fn unreachable_states(&self) -> Vec<&String> {
+        self.states
+            .iter()
+            .filter(|state| !self.reachable.contains(state))
+            .collect()
+    }
+
+    /// The states no transaction can move the contract out of
+    pub fn terminal_states(&self) -> Vec<&String> {
+        self.states
+            .iter()
+            .filter(|&state| {
+                !self
+                    .transitions
+                    .iter()
+                    .any(|transition| transition.from == *state && transition.to != *state)
+            })
+            .collect()
+    }
+}
+
+/// The state machine of a contract which declares type states. Functions called externally which
+/// never change the state of the contract make no transitions
+pub fn state_machine(contract: &ContractDeclaration, module: &Module) -> StateMachine {
+    TypeStateAnalysis::new(contract, module).machine()
+}
+
+struct TypeStateAnalysis<'a> {
+    contract: &'a str,
+    states: &'a [TypeState],
+    blocks: Vec<&'a ContractBehaviourDeclaration>,
+    callables: Vec<Callable<'a>>,
+    initialiser: Option<usize>,
+    /// The states each callable may exit in, by the states it was entered in
+    summaries: HashMap<(usize, States), States>,
+    in_progress: Vec<usize>,
+    checked_functions: Vec<String>,
+}
+
+impl<'a> TypeStateAnalysis<'a> {
+    fn new(contract: &'a ContractDeclaration, module: &'a Module) -> TypeStateAnalysis<'a> {
+        let states = &contract.type_states;
+        let mut blocks = vec![];
+        let mut callables = vec![];
+        let mut initialiser = None;
+
+        for declaration in &module.declarations {
+            let behaviour = match declaration {
+                TopLevelDeclaration::ContractBehaviourDeclaration(behaviour)
+                    if behaviour.identifier.token == contract.identifier.token =>
+                {
+                    behaviour
+                }
+                _ => continue,
+            };
+            blocks.push(behaviour);
+
+            let allowed = if behaviour.type_states.is_empty()
+                || behaviour.type_states.iter().any(TypeState::is_any)
+            {
+                None
+            } else {
+                Some(
+                    behaviour
+                        .type_states
+                        .iter()
+                        .filter_map(|state| states.iter().position(|s| s == state))
+                        .collect(),
+                )
+            };
+
+            for member in &behaviour.members {
+                match member {
+                    ContractBehaviourMember::FunctionDeclaration(function) => {
+                        callables.push(Callable {
+                            name: Some(&function.head.identifier.token),
+                            parameters: function.head.parameters.len(),
+                            allowed: allowed.clone(),
+                            protections: &behaviour.caller_protections,
+                            body: &function.body,
+                            entry_point: function.is_public(),
+                        })
+                    }
+                    ContractBehaviourMember::SpecialDeclaration(special) => {
+                        if special.is_init() {
+                            initialiser = Some(callables.len());
+                        }
+                        callables.push(Callable {
+                            name: None,
+                            parameters: special.head.parameters.len(),
+                            allowed: allowed.clone(),
+                            protections: &behaviour.caller_protections,
+                            body: &special.body,
+                            entry_point: special.is_fallback(),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        TypeStateAnalysis {
+            contract: &contract.identifier.token,
+            states,
+            blocks,
+            callables,
+            initialiser,
+            summaries: HashMap::new(),
+            in_progress: vec![],
+            checked_functions: vec![],
+        }
+    }
+
+    fn all_states(&self) -> States {
+        (0..self.states.len()).collect()
+    }
+
+    fn state_name(&self, state: usize) -> String {
+        self.states[state].identifier.token.clone()
+    }
+
+    fn machine(&mut self) -> StateMachine {
+        let initial = match self.initialiser {
+            Some(index) => self.summary(index, self.all_states()),
+            None => States::new(),
+        };
+        let reachable = self.reachable_states();
+
+        let mut transitions = vec![];
+        for index in 0..self.callables.len() {
+            if !self.callables[index].entry_point {
+                continue;
+            }
+
+            let sources = self.callables[index]
+                .allowed
+                .clone()
+                .unwrap_or_else(|| self.all_states());
+            let exits: Vec<(usize, States)> = sources
+                .into_iter()
+                .map(|state| {
+                    let entry = std::iter::once(state).collect();
+                    (state, self.summary(index, entry))
+                })
+                .collect();
+            if exits
+                .iter()
+                .all(|(state, exits)| exits.iter().all(|exit| exit == state))
+            {
+                continue;
+            }
+
+            let callable = &self.callables[index];
+            for (from, exits) in exits {
+                for to in exits {
+                    transitions.push(Transition {
+                        from: self.state_name(from),
+                        to: self.state_name(to),
+                        function: callable.name.unwrap_or("fallback").to_string(),
+                        caller_protections: callable
+                            .protections
+                            .iter()
+                            .map(CallerProtection::name)
+                            .collect(),
+                    });
+                }
+            }
+        }
+
+        StateMachine {
+            contract: self.contract.to_string(),
+            states: (0..self.states.len()).map(|s| self.state_name(s)).collect(),
+            initial: initial.into_iter().map(|s| self.state_name(s)).collect(),
+            reachable: reachable.into_iter().map(|s| self.state_name(s)).collect(),
+            transitions,
+        }
+    }
+
+    /// States the contract never reaches and protection blocks which are restricted to them, and
+    /// states the contract can never leave in which no function can be called. A state named by a
+    /// protection block, even an empty one, is taken to be meant as a final state
+    fn reachability_warnings(&self, machine: &StateMachine) -> Vec<Diagnostic> {
+        let unreachable = machine.unreachable_states();
+        let terminal = machine.terminal_states();
+        let mut warnings = vec![];
+
+        for (index, state) in self.states.iter().enumerate() {
+            let name = &state.identifier.token;
+            if unreachable.contains(&name) {
+                warnings.push(
+                    Diagnostic::warning(
+                        code::UNREACHABLE_TYPE_STATE,
+                        format!(
+                            "Type state {} of {} can never be reached",
+                            name, self.contract
+                        ),
+                    )
+                    .with_span(&state.identifier.line_info),
+                );
+            } else if terminal.contains(&name)
+                && !self.callables.iter().any(|callable| {
+                    callable.entry_point
+                        && callable
+                            .allowed
+                            .as_ref()
+                            .map_or(true, |allowed| allowed.contains(&index))
+                })
+                && !self.blocks.iter().any(|block| {
+                    block
+                        .type_states
+                        .iter()
+                        .any(|state| state.identifier.token == *name)
+                })
+            {
+                warnings.push(
+                    Diagnostic::warning(
+                        code::STUCK_TYPE_STATE,
+                        format!(
+                            "{} can never leave type state {}, and no function can be called in it",
+                            self.contract, name
+                        ),
+                    )
+                    .with_span(&state.identifier.line_info),
+                );
+            }
+        }
+
+        for block in &self.blocks {
+            let restricted =
+                !block.type_states.is_empty() && !block.type_states.iter().any(TypeState::is_any);
+            if restricted
+                && block
+                    .type_states
+                    .iter()
+                    .all(|state| unreachable.contains(&&state.identifier.token))
+            {
+                warnings.push(
+                    Diagnostic::warning(
+                        code::UNREACHABLE_PROTECTION_BLOCK,
+                        format!(
+                            "The functions of {} @({}) can never be called, as it is never in these states",
+                            self.contract,
+                            block
+                                .type_states
+                                .iter()
+                                .map(|state| state.identifier.token.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    )
+                    .with_span(&block.identifier.line_info),
+                );
+            }
+        }
+
+        warnings
+    }
+
+    fn check(&mut self, index: usize) -> Result<(), Diagnostic> {
+        let body = self.callables[index].body;
+        let allowed = self.callables[index].allowed.clone();
+        let flow = Flow {
+            restricted: allowed.is_some(),
+            report: true,
+        };
+        let entry = allowed.unwrap_or_else(|| self.all_states());
+        self.exits(body, entry, flow).map(|_| ())
+    }
+
+    /// The states the contract may be in when a transaction starts: those the initialiser may
+    /// leave it in, and those any function called externally may leave it in from them
+    fn reachable_states(&mut self) -> States {
+        let mut reachable = match self.initialiser {
+            Some(index) => self.summary(index, self.all_states()),
+            None => self.all_states(),
+        };
+
+        loop {
+            let mut next = reachable.clone();
+            for index in 0..self.callables.len() {
+                if !self.callables[index].entry_point {
+                    continue;
+                }
+                let entry = match &self.callables[index].allowed {
+                    Some(allowed) => reachable.intersection(allowed).cloned().collect(),
+                    None => reachable.clone(),
+                };
+                if !entry.is_empty() {
+                    next.extend(self.summary(index, entry));
+                }
+            }
+
+            if next == reachable {
+                return reachable;
+            }
+            reachable = next;
+        }
+    }
+
+    /// The states a callable may exit in when entered in the given states, if it changes state
+    fn summary(&mut self, index: usize, entry: States) -> States {
+        let key = (index, entry);
+        if let Some(exits) = self.summaries.get(&key) {
+            return exits.clone();
+        }
+        // A recursive call could leave the contract in any state
+        if self.in_progress.contains(&index) {
+            return self.all_states();
+        }
+
+        self.in_progress.push(index);
+        let body = self.callables[index].body;
+        let exits = self
+            .exits(body, key.1.clone(), Flow::silent())
+            .unwrap_or_else(|_| self.all_states());
+        self.in_progress.pop();
+
+        self.summaries.insert(key, exits.clone());
+        exits
+    }
+
+    fn exits(
+        &mut self,
+        body: &[Statement],
+        entry: States,
+        flow: Flow,
+    ) -> Result<States, Diagnostic> {
+        let mut returned = States::new();
+        let end = self.statements(body, entry, &mut returned, flow)?;
+        returned.extend(end);
+        Ok(returned)
+    }
+
+    /// The states after the statements, which is empty if they cannot complete
+    fn statements(
+        &mut self,
+        statements: &[Statement],
+        mut current: States,
+        returned: &mut States,
+        flow: Flow,
+    ) -> Result<States, Diagnostic> {
+        for statement in statements {
+            if current.is_empty() {
+                break;
+            }
+
+            current = match statement {
+                Statement::Expression(expression) => self.expression(expression, current, flow)?,
+                Statement::ReturnStatement(statement) => {
+                    if let Some(ref expression) = statement.expression {
+                        current = self.expression(expression, current, flow)?;
+                    }
+                    returned.extend(current);
+                    States::new()
+                }
+                Statement::BecomeStatement(statement) => {
+                    match self.states.iter().position(|s| *s == statement.state) {
+                        Some(state) => std::iter::once(state).collect(),
+                        None => current,
+                    }
+                }
+                Statement::EmitStatement(emit) => {
+                    self.arguments(&emit.function_call.arguments, current, flow)?
+                }
+                Statement::ForStatement(for_statement) => {
+                    current = self.expression(&for_statement.iterable, current, flow)?;
+                    self.loop_body(&for_statement.body, current, returned, flow)?
+                }
+                Statement::IfStatement(if_statement) => {
+                    current = self.expression(&if_statement.condition, current, flow)?;
+                    let mut states =
+                        self.statements(&if_statement.body, current.clone(), returned, flow)?;
+                    states.extend(self.statements(
+                        &if_statement.else_body,
+                        current,
+                        returned,
+                        flow,
+                    )?);
+                    states
+                }
+                Statement::DoCatchStatement(do_catch) => {
+                    let mut states =
+                        self.statements(&do_catch.do_body, current.clone(), returned, flow)?;
+                    // The catch body runs when a call in the do body fails, part way through it
+                    current.extend(states.iter().cloned());
+                    states.extend(self.statements(
+                        &do_catch.catch_body,
+                        current,
+                        returned,
+                        flow,
+                    )?);
+                    states
+                }
+                Statement::Assertion(assertion) => {
+                    self.expression(&assertion.expression, current, flow)?
+                }
+            };
+        }
+        Ok(current)
+    }
+
+    /// The states after any number of iterations of a loop body
+    fn loop_body(
+        &mut self,
+        body: &[Statement],
+        mut current: States,
+        returned: &mut States,
+        flow: Flow,
+    ) -> Result<States, Diagnostic> {
+        loop {
+            let states = self.statements(body, current.clone(), returned, Flow::silent())?;
+            if states.is_subset(&current) {
+                break;
+            }
+            current.extend(states);
+        }
+
+        if flow.report {
+            self.statements(body, current.clone(), returned, flow)?;
+        }
+        Ok(current)
+    }
+
+    fn expression(
+        &mut self,
+        expression: &Expression,
+        current: States,
+        flow: Flow,
+    ) -> Result<States, Diagnostic> {
+        match expression {
+            Expression::FunctionCall(call) => {
+                let current = self.arguments(&call.arguments, current, flow)?;
+                self.call(call, current, flow)
+            }
+            Expression::BinaryExpression(binary) if binary.op == BinOp::Dot => {
+                match (&*binary.lhs_expression, &*binary.rhs_expression) {
+                    (Expression::SelfExpression, rhs) => self.expression(rhs, current, flow),
+                    // Functions called on anything else are not those of the contract
+                    (lhs, Expression::FunctionCall(call)) => {
+                        let current = self.expression(lhs, current, flow)?;
+                        self.arguments(&call.arguments, current, flow)
+                    }
+                    (lhs, rhs) => {
+                        let current = self.expression(lhs, current, flow)?;
+                        self.expression(rhs, current, flow)
+                    }
+                }
+            }
+            Expression::BinaryExpression(binary) => {
+                let current = self.expression(&binary.lhs_expression, current, flow)?;
+                self.expression(&binary.rhs_expression, current, flow)
+            }
+            Expression::InoutExpression(inout) => self.expression(&inout.expression, current, flow),
+            Expression::ExternalCall(external) => {
+                let current = self.arguments(&external.arguments, current, flow)?;
+                self.expression(&external.function_call.lhs_expression, current, flow)
+            }
+            Expression::VariableDeclaration(declaration) => match declaration.expression {
+                Some(ref expression) => self.expression(expression, current, flow),
+                None => Ok(current),
+            },
+            Expression::BracketedExpression(bracketed) => {
+                self.expression(&bracketed.expression, current, flow)
+            }
+            Expression::AttemptExpression(attempt) => {
+                let current = self.arguments(&attempt.function_call.arguments, current, flow)?;
+                self.call(&attempt.function_call, current, flow)
+            }
+            Expression::ArrayLiteral(array) => {
+                array.elements.iter().try_fold(current, |current, element| {
+                    self.expression(element, current, flow)
+                })
+            }
+            Expression::DictionaryLiteral(dictionary) => {
+                dictionary
+                    .elements
+                    .iter()
+                    .try_fold(current, |current, (key, value)| {
+                        let current = self.expression(key, current, flow)?;
+                        self.expression(value, current, flow)
+                    })
+            }
+            Expression::SubscriptExpression(subscript) => {
+                self.expression(&subscript.index_expression, current, flow)
+            }
+            Expression::RangeExpression(range) => {
+                let current = self.expression(&range.start_expression, current, flow)?;
+                self.expression(&range.end_expression, current, flow)
+            }
+            Expression::CastExpression(cast) => self.expression(&cast.expression, current, flow),
+            Expression::Sequence(expressions) => {
+                expressions.iter().try_fold(current, |current, expression| {
+                    self.expression(expression, current, flow)
+                })
+            }
+            _ => Ok(current),
+        }
+    }
+
+    fn arguments(
+        &mut self,
+        arguments: &[FunctionArgument],
+        current: States,
+        flow: Flow,
+    ) -> Result<States, Diagnostic> {
+        arguments.iter().try_fold(current, |current, argument| {
+            self.expression(&argument.expression, current, flow)
+        })
+    }
+
+    fn call(
+        &mut self,
+        call: &FunctionCall,
+        current: States,
+        flow: Flow,
+    ) -> Result<States, Diagnostic> {
+        let enclosing_type = call.identifier.enclosing_type.as_deref();
+        if current.is_empty() || enclosing_type.map_or(false, |t| t != self.contract) {
+            return Ok(current);
+        }
+
+        let candidates = self.candidates(call);
+        if candidates.is_empty() {
+            return Ok(current);
+        }
+
+        let allowed = candidates
+            .iter()
+            .try_fold(States::new(), |mut allowed, &index| {
+                allowed.extend(self.callables[index].allowed.as_ref()?.iter().cloned());
+                Some(allowed)
+            });
+
+        let current = match allowed {
+            Some(ref allowed) if !current.is_subset(allowed) => {
+                let permitted: States = current.intersection(allowed).cloned().collect();
+                if flow.report {
+                    if permitted.is_empty() || flow.restricted {
+                        return Err(self.invalid_call(call, allowed));
+                    }
+                    if !self.checked_functions.contains(&call.identifier.token) {
+                        self.checked_functions.push(call.identifier.token.clone());
+                    }
+                }
+                permitted
+            }
+            _ => current,
+        };
+
+        let mut exits = States::new();
+        for index in candidates {
+            let entry: States = match &self.callables[index].allowed {
+                Some(allowed) => current.intersection(allowed).cloned().collect(),
+                None => current.clone(),
+            };
+            if !entry.is_empty() {
+                exits.extend(self.summary(index, entry));
+            }
+        }
+        Ok(exits)
+    }
+
+    /// The functions of the contract a call may be to
+    fn candidates(&self, call: &FunctionCall) -> Vec<usize> {
+        let named: Vec<usize> = (0..self.callables.len())
+            .filter(|&index| self.callables[index].name == Some(call.identifier.token.as_str()))
+            .collect();
+
+        // Parameters with default values need not be passed
+        let matching: Vec<usize> = named
+            .iter()
+            .cloned()
+            .filter(|&index| call.arguments.len() <= self.callables[index].parameters)
+            .collect();
+        if matching.is_empty() {
+            named
+        } else {
+            matching
+        }
+    }
+
+    fn invalid_call(&self, call: &FunctionCall, allowed: &States) -> Diagnostic {
+        Diagnostic::error(
+            code::TYPE_STATE,
+            format!(
+                "Must definitely be in one of the following states to make function call {} on {}: {:?}.",
+                call.identifier.token,
+                call.identifier.line_info,
+                allowed
+                    .iter()
+                    .map(|&state| self.states[state].identifier.token.clone())
+                    .collect::<Vec<String>>(),
+            ),
+        )
+        .with_span(&call.identifier.line_info)
+    }
+}
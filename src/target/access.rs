@@ -0,0 +1,329 @@
+use crate::ast::{
+    CallerProtection, ContractBehaviourDeclaration, ContractBehaviourMember, ContractDeclaration,
+    FunctionDeclaration, Module, TopLevelDeclaration, TypeState,
+};
+use crate::environment::Environment;
+use crate::target::output::{Artifact, Emit};
+
+/// Who may call each function of each contract, and in which states, as Markdown and as JSON
+pub fn generate(module: &Module, environment: &Environment) -> Vec<Artifact> {
+    module
+        .declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            TopLevelDeclaration::ContractDeclaration(contract) => {
+                Some(report(contract, module, environment))
+            }
+            _ => None,
+        })
+        .flat_map(|report| {
+            vec![
+                Artifact::new(Emit::AccessReport, &report.contract, markdown(&report)),
+                Artifact::new(Emit::AccessReport, &report.contract, json(&report))
+                    .with_extension("access.json"),
+            ]
+        })
+        .collect()
+}
+
+/// How a restriction on a function is enforced. Calls within the contract are checked when the
+/// program is compiled, and transactions when they are made
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Check {
+    None,
+    Static,
+    Dynamic,
+}
+
+impl Check {
+    fn name(self) -> &'static str {
+        match self {
+            Check::None => "none",
+            Check::Static => "static",
+            Check::Dynamic => "dynamic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionAccess {
+    pub name: String,
+    pub public: bool,
+    pub caller_binding: Option<String>,
+    pub caller_protections: Vec<String>,
+    /// Empty if the function may be called in any state
+    pub states: Vec<String>,
+    pub mutates: Vec<String>,
+    pub caller_check: Check,
+    pub state_check: Check,
+    /// The other functions of the contract whose caller protections allow them to call it
+    pub callable_from: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessReport {
+    pub contract: String,
+    pub functions: Vec<FunctionAccess>,
+}
+
+fn report(
+    contract: &ContractDeclaration,
+    module: &Module,
+    environment: &Environment,
+) -> AccessReport {
+    let contract_name = &contract.identifier.token;
+    let functions: Vec<(&ContractBehaviourDeclaration, &FunctionDeclaration)> = module
+        .declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            TopLevelDeclaration::ContractBehaviourDeclaration(behaviour)
+                if behaviour.identifier.token == *contract_name =>
+            {
+                Some(behaviour)
+            }
+            _ => None,
+        })
+        .flat_map(|behaviour| {
+            behaviour
+                .members
+                .iter()
+                .filter_map(move |member| match member {
+                    ContractBehaviourMember::FunctionDeclaration(function) => {
+                        Some((behaviour, function))
+                    }
+                    _ => None,
+                })
+        })
+        .collect();
+
+    let functions = functions
+        .iter()
+        .map(|&(behaviour, function)| {
+            let name = function.head.identifier.token.clone();
+            let public = function.is_public();
+            let protections = &behaviour.caller_protections;
+            let restricted_states = !behaviour.type_states.is_empty()
+                && !behaviour.type_states.iter().any(TypeState::is_any);
+
+            let caller_check = if protections.is_empty() || protections.iter().any(|p| p.is_any()) {
+                Check::None
+            } else if public {
+                Check::Dynamic
+            } else {
+                Check::Static
+            };
+            let state_check = if !restricted_states {
+                Check::None
+            } else if environment.checks_type_state_on_entry(contract_name, &name)
+                || (public
+                    && environment
+                        .may_call_in_disallowed_state(contract_name, &behaviour.type_states))
+            {
+                Check::Dynamic
+            } else {
+                Check::Static
+            };
+
+            let callable_from = functions
+                .iter()
+                .filter(|&&(other_behaviour, other)| {
+                    !std::ptr::eq(other, function)
+                        && environment.compatible_caller_protections(
+                            &other_behaviour.caller_protections,
+                            protections,
+                            contract_name,
+                        )
+                })
+                .map(|(_, other)| other.head.identifier.token.clone())
+                .collect();
+
+            FunctionAccess {
+                name,
+                public,
+                caller_binding: behaviour
+                    .caller_binding
+                    .as_ref()
+                    .map(|binding| binding.token.clone()),
+                caller_protections: protections.iter().map(CallerProtection::name).collect(),
+                states: if restricted_states {
+                    behaviour
+                        .type_states
+                        .iter()
+                        .map(|state| state.identifier.token.clone())
+                        .collect()
+                } else {
+                    vec![]
+                },
+                mutates: function
+                    .mutates()
+                    .into_iter()
+                    .map(|identifier| identifier.token)
+                    .collect(),
+                caller_check,
+                state_check,
+                callable_from,
+            }
+        })
+        .collect();
+
+    AccessReport {
+        contract: contract_name.clone(),
+        functions,
+    }
+}
+
+fn code_list(items: &[String], empty: &str) -> String {
+    if items.is_empty() {
+        empty.to_string()
+    } else {
+        items
+            .iter()
+            .map(|item| format!("`{}`", item))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn markdown(report: &AccessReport) -> String {
+    let mut markdown = format!("# Access report for `{}`\n\n", report.contract);
+    if report.functions.is_empty() {
+        markdown.push_str("The contract declares no functions.\n");
+        return markdown;
+    }
+
+    markdown.push_str(
+        "| Function | Visibility | Callers | States | Mutates | Caller check | State check | Callable from |\n",
+    );
+    markdown.push_str("|---|---|---|---|---|---|---|---|\n");
+    for function in &report.functions {
+        let mut callers = code_list(&function.caller_protections, "`any`");
+        if let Some(binding) = &function.caller_binding {
+            callers.push_str(&format!(" as `{}`", binding));
+        }
+        markdown.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} | {} | {} | {} |\n",
+            function.name,
+            if function.public {
+                "public"
+            } else {
+                "internal"
+            },
+            callers,
+            code_list(&function.states, "any"),
+            code_list(&function.mutates, "nothing"),
+            function.caller_check.name(),
+            function.state_check.name(),
+            code_list(&function.callable_from, "none"),
+        ));
+    }
+    markdown
+}
+
+fn json(report: &AccessReport) -> String {
+    let functions = report
+        .functions
+        .iter()
+        .map(|function| {
+            json::object! {
+                name: function.name.as_str(),
+                visibility: if function.public { "public" } else { "internal" },
+                callerBinding: function.caller_binding.clone(),
+                callerProtections: function.caller_protections.clone(),
+                states: function.states.clone(),
+                mutates: function.mutates.clone(),
+                callerCheck: function.caller_check.name(),
+                stateCheck: function.state_check.name(),
+                callableFrom: function.callable_from.clone(),
+            }
+        })
+        .collect::<json::Array>();
+
+    let description = json::object! {
+        contract: report.contract.as_str(),
+        functions: functions,
+    };
+    format!("{}\n", json::stringify_pretty(description, 2))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compile, target, Emit, Options, Source};
+
+    const BANK: &str = "contract Bank (Open, Closed) {
+  let manager: Address
+  var balance: Int = 0
+}
+
+Bank :: caller <- (any) {
+  public init() {
+    manager = caller
+    become Open
+  }
+}
+
+Bank @(Open) :: (manager) {
+  public func deposit(amount: Int) mutates (balance) {
+    balance += amount
+    audit()
+  }
+
+  public func close() {
+    become Closed
+  }
+
+  func audit() {}
+}
+
+Bank :: (any) {
+  public func total() -> Int {
+    return balance
+  }
+}
+";
+
+    fn generate() -> (String, String) {
+        let options = Options {
+            emit: vec![Emit::AccessReport],
+            ..Default::default()
+        };
+        let artifacts = compile(
+            &[Source::new("bank.flint", BANK)],
+            target("libra").unwrap(),
+            &options,
+        )
+        .unwrap()
+        .artifacts;
+
+        let file_names: Vec<String> = artifacts.iter().map(|a| a.file_name()).collect();
+        assert_eq!(file_names, vec!["Bank.access.md", "Bank.access.json"]);
+        (
+            artifacts[0].text().unwrap().to_string(),
+            artifacts[1].text().unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_markdown() {
+        let (markdown, _) = generate();
+        assert!(markdown.starts_with("# Access report for `Bank`\n\n| Function |"));
+        assert!(markdown.contains(
+            "| `deposit` | public | `manager` | `Open` | `balance` | dynamic | dynamic | `close`, `audit` |\n"
+        ));
+        assert!(markdown
+            .contains("| `total` | public | `any` | any | nothing | none | none | `deposit`, `close`, `audit` |\n"));
+    }
+
+    #[test]
+    fn test_json() {
+        let (_, description) = generate();
+        let description = json::parse(&description).unwrap();
+        assert_eq!(description["contract"], "Bank");
+        let audit = &description["functions"][2];
+        assert_eq!(audit["name"], "audit");
+        assert_eq!(audit["visibility"], "internal");
+        assert_eq!(audit["callerProtections"], json::array!["manager"]);
+        assert_eq!(audit["callerCheck"], "static");
+        assert_eq!(audit["stateCheck"], "static");
+        assert_eq!(audit["callableFrom"], json::array!["deposit", "close"]);
+    }
+}
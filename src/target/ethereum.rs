@@ -0,0 +1,27 @@
+use super::currency::Currency;
+use super::feature::Feature;
+use super::output::Emit;
+use super::Target;
+use crate::ewasm;
+use crate::ewasm::preprocessor::LLVMPreProcessor;
+
+pub(crate) fn currency() -> Currency {
+    Currency {
+        identifier: "Wei",
+        currency_types: vec!["Wei"],
+    }
+}
+
+pub(crate) fn target() -> Target {
+    Target {
+        name: "eWASM",
+        currency: currency(),
+        processor: Box::new(LLVMPreProcessor {}),
+        generate: ewasm::generate,
+        stdlib_name: "stdlib/ether/global.flint",
+        stdlib: include_str!("../../stdlib/ether/global.flint"),
+        emits: &[Emit::Llvm, Emit::Wat, Emit::Wasm, Emit::Abi],
+        default_emits: &[Emit::Wasm, Emit::Abi],
+        features: &[Feature::DictionaryLiterals, Feature::Enums],
+    }
+}
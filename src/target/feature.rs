@@ -0,0 +1,182 @@
+use crate::ast::*;
+use crate::context::Context;
+use crate::diagnostic::{code, Diagnostic};
+use crate::target::Target;
+use crate::visitor::Visitor;
+
+/// Language constructs which only some targets can generate code for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Feature {
+    ForLoops,
+    DoCatch,
+    Ranges,
+    DictionaryLiterals,
+    FloatingPoint,
+    SolidityTypes,
+    Strings,
+    Events,
+    DynamicArrays,
+    Enums,
+    ExternalCalls,
+}
+
+impl Feature {
+    pub fn description(self) -> &'static str {
+        match self {
+            Feature::ForLoops => "`for` loops",
+            Feature::DoCatch => "`do`-`catch` statements",
+            Feature::Ranges => "ranges",
+            Feature::DictionaryLiterals => "dictionary literals",
+            Feature::FloatingPoint => "floating point numbers",
+            Feature::SolidityTypes => "Solidity types",
+            Feature::Strings => "strings",
+            Feature::Events => "events",
+            Feature::DynamicArrays => "dynamic arrays",
+            Feature::Enums => "enums",
+            Feature::ExternalCalls => "external calls",
+        }
+    }
+}
+
+/// Reports the constructs a target cannot generate code for, before its preprocessor runs, so
+/// that they are errors in the program rather than crashes in the backend. Literals and types
+/// have no position of their own, so they are reported at the closest node before them which has
+pub(crate) struct FeatureCheck {
+    target: &'static str,
+    features: &'static [Feature],
+    line_info: LineInfo,
+}
+
+impl FeatureCheck {
+    pub fn new(target: &Target) -> Self {
+        FeatureCheck {
+            target: target.name,
+            features: target.features,
+            line_info: LineInfo::default(),
+        }
+    }
+
+    fn require(&self, feature: Feature, line_info: &LineInfo) -> VResult {
+        if self.features.contains(&feature) {
+            return Ok(());
+        }
+
+        let description = feature.description();
+        let line_info = if line_info.line == 0 {
+            &self.line_info
+        } else {
+            line_info
+        };
+        Err(Box::from(
+            Diagnostic::error(
+                code::UNSUPPORTED_FEATURE,
+                format!(
+                    "{}{} are not supported on target {}",
+                    description[..1].to_uppercase(),
+                    &description[1..],
+                    self.target
+                ),
+            )
+            .with_span(line_info),
+        ))
+    }
+
+    fn locate(&mut self, line_info: &LineInfo) {
+        if line_info.line != 0 {
+            self.line_info = line_info.clone();
+        }
+    }
+}
+
+fn type_feature(t: &Type, ctx: &Context) -> Option<Feature> {
+    match t {
+        Type::InoutType(inout) => type_feature(&inout.key_type, ctx),
+        Type::ArrayType(array) => {
+            type_feature(&array.key_type, ctx).or(Some(Feature::DynamicArrays))
+        }
+        Type::FixedSizedArrayType(array) => type_feature(&array.key_type, ctx),
+        Type::DictionaryType(dictionary) => type_feature(&dictionary.key_type, ctx)
+            .or_else(|| type_feature(&dictionary.value_type, ctx)),
+        Type::RangeType(_) => Some(Feature::Ranges),
+        Type::Solidity(_) => Some(Feature::SolidityTypes),
+        Type::String => Some(Feature::Strings),
+        Type::UserDefinedType(identifier)
+            if ctx.environment.is_enum_declared(&identifier.token) =>
+        {
+            Some(Feature::Enums)
+        }
+        _ => None,
+    }
+}
+
+impl Visitor for FeatureCheck {
+    fn start_variable_declaration(
+        &mut self,
+        t: &mut VariableDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.locate(&t.identifier.line_info);
+        Ok(())
+    }
+
+    fn start_function_declaration(
+        &mut self,
+        t: &mut FunctionDeclaration,
+        _ctx: &mut Context,
+    ) -> VResult {
+        self.locate(&t.head.identifier.line_info);
+        Ok(())
+    }
+
+    fn start_statement(&mut self, t: &mut Statement, _ctx: &mut Context) -> VResult {
+        match t {
+            Statement::ForStatement(for_statement) => {
+                self.locate(&for_statement.variable.identifier.line_info);
+                self.require(
+                    Feature::ForLoops,
+                    &for_statement.variable.identifier.line_info,
+                )
+            }
+            Statement::DoCatchStatement(do_catch) => {
+                self.require(Feature::DoCatch, &do_catch.error.get_line_info())
+            }
+            Statement::EmitStatement(emit) => {
+                self.require(Feature::Events, &emit.function_call.identifier.line_info)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn start_expression(&mut self, t: &mut Expression, ctx: &mut Context) -> VResult {
+        let line_info = t.get_line_info();
+        self.locate(&line_info);
+
+        match t {
+            Expression::RangeExpression(_) => self.require(Feature::Ranges, &line_info),
+            // Every target initialises properties from dictionary literals
+            Expression::DictionaryLiteral(_) if !ctx.is_property_default_assignment => {
+                self.require(Feature::DictionaryLiterals, &line_info)
+            }
+            Expression::ExternalCall(_) => self.require(Feature::ExternalCalls, &line_info),
+            Expression::Literal(Literal::FloatLiteral(_)) => {
+                self.require(Feature::FloatingPoint, &line_info)
+            }
+            Expression::Literal(Literal::StringLiteral(_)) => {
+                self.require(Feature::Strings, &line_info)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn start_parameter(&mut self, t: &mut Parameter, _ctx: &mut Context) -> VResult {
+        self.locate(&t.line_info);
+        Ok(())
+    }
+
+    fn start_type(&mut self, t: &mut Type, ctx: &mut Context) -> VResult {
+        match type_feature(t, ctx) {
+            Some(feature) => self.require(feature, &LineInfo::default()),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::moveir;
+use crate::moveir::preprocessor::MovePreProcessor;
+use crate::target::currency::Currency;
+use crate::target::feature::Feature;
+use crate::target::output::Emit;
+use crate::target::Target;
+
+pub(crate) fn currency() -> Currency {
+    Currency {
+        identifier: "Libra",
+        currency_types: vec!["Libra", "LibraCoin.T"],
+    }
+}
+
+pub(crate) fn target() -> Target {
+    Target {
+        name: "Libra",
+        currency: currency(),
+        processor: Box::new(MovePreProcessor {}),
+        generate: moveir::generate,
+        stdlib_name: "stdlib/libra/global.flint",
+        stdlib: include_str!("../../stdlib/libra/global.flint"),
+        emits: &[Emit::MoveIR],
+        default_emits: &[Emit::MoveIR],
+        features: &[
+            Feature::Strings,
+            Feature::Events,
+            Feature::DynamicArrays,
+            Feature::ExternalCalls,
+        ],
+    }
+}
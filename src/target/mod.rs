@@ -0,0 +1,36 @@
+pub mod access;
+pub mod currency;
+pub mod ethereum;
+pub mod feature;
+pub mod libra;
+pub mod output;
+pub mod states;
+
+use crate::ast::Module;
+use crate::context::Context;
+use crate::target::currency::Currency;
+use crate::target::feature::Feature;
+use crate::target::output::{Artifact, Emit, Options};
+use crate::visitor::Visitor;
+use std::error::Error;
+
+/// Integrating any future target should simply require updating this module by adding a new
+/// impl of Target, and registering it in io target(&str)
+pub struct Target {
+    pub(crate) name: &'static str,
+    pub(crate) currency: Currency,
+    pub(crate) processor: Box<dyn Visitor>,
+    pub(crate) generate:
+        fn(module: &Module, context: &mut Context, options: &Options) -> GenerationResult,
+    /// The standard library is compiled into the binary, so that it does not matter where the
+    /// compiler is run from. The name is the file it came from, which diagnostics refer to
+    pub(crate) stdlib_name: &'static str,
+    pub(crate) stdlib: &'static str,
+    /// The code the target can generate, in addition to the outputs of every target
+    pub(crate) emits: &'static [Emit],
+    pub(crate) default_emits: &'static [Emit],
+    /// The constructs the target can generate code for, of those which not every target can
+    pub(crate) features: &'static [Feature],
+}
+
+pub type GenerationResult = Result<Vec<Artifact>, Box<dyn Error>>;
@@ -0,0 +1,190 @@
+use crate::{Diagnostic, Source};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A kind of output the compiler can produce. The AST, environment and preprocessed AST describe
+/// the whole program, while the others are generated for each contract. The state machine of a
+/// contract is output both as a Graphviz graph and as JSON, and its access report both as
+/// Markdown and as JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Emit {
+    Ast,
+    Environment,
+    Preprocessed,
+    States,
+    AccessReport,
+    MoveIR,
+    Llvm,
+    Wat,
+    Wasm,
+    Abi,
+}
+
+impl Emit {
+    /// Outputs which are produced before code generation, and so are available for every target
+    pub const PROGRAM: &'static [Emit] = &[
+        Emit::Ast,
+        Emit::Environment,
+        Emit::Preprocessed,
+        Emit::States,
+        Emit::AccessReport,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Emit::Ast => "ast",
+            Emit::Environment => "env",
+            Emit::Preprocessed => "preprocessed.ast",
+            Emit::States => "states.dot",
+            Emit::AccessReport => "access.md",
+            Emit::MoveIR => "mvir",
+            Emit::Llvm => "ll",
+            Emit::Wat => "wat",
+            Emit::Wasm => "wasm",
+            Emit::Abi => "json",
+        }
+    }
+}
+
+impl FromStr for Emit {
+    type Err = String;
+
+    fn from_str(emit: &str) -> Result<Self, Self::Err> {
+        match emit {
+            "ast" => Ok(Emit::Ast),
+            "env" => Ok(Emit::Environment),
+            "preprocessed" => Ok(Emit::Preprocessed),
+            "states" => Ok(Emit::States),
+            "access-report" => Ok(Emit::AccessReport),
+            "mvir" => Ok(Emit::MoveIR),
+            "llvm" => Ok(Emit::Llvm),
+            "wat" => Ok(Emit::Wat),
+            "wasm" => Ok(Emit::Wasm),
+            "abi" => Ok(Emit::Abi),
+            _ => Err(format!("Unknown output kind `{}`", emit)),
+        }
+    }
+}
+
+impl fmt::Display for Emit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let emit = match self {
+            Emit::Ast => "ast",
+            Emit::Environment => "env",
+            Emit::Preprocessed => "preprocessed",
+            Emit::States => "states",
+            Emit::AccessReport => "access-report",
+            Emit::MoveIR => "mvir",
+            Emit::Llvm => "llvm",
+            Emit::Wat => "wat",
+            Emit::Wasm => "wasm",
+            Emit::Abi => "abi",
+        };
+        write!(f, "{}", emit)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Name of the outputs which describe the whole program rather than one of its contracts
+    pub name: String,
+    /// Only the kinds of output listed are produced
+    pub emit: Vec<Emit>,
+    /// From 0 to 3, as for llc. The Move IR target does not optimise its output
+    pub optimisation: u8,
+    /// Replaces the standard library of the target
+    pub stdlib: Option<Source>,
+    /// The flint config naming the external tools code generation runs, which is otherwise
+    /// looked for as described in `io::config`
+    pub config: Option<PathBuf>,
+    /// Preconditions, postconditions and invariants are asserted when the contract runs unless
+    /// disabled, which saves their cost in production builds
+    pub check_specifications: bool,
+}
+
+impl Options {
+    pub fn emits(&self, emit: Emit) -> bool {
+        self.emit.contains(&emit)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            name: "main".to_string(),
+            emit: vec![],
+            optimisation: 3,
+            stdlib: None,
+            config: None,
+            check_specifications: true,
+        }
+    }
+}
+
+/// The outputs of a program which compiled, and the warnings about it
+#[derive(Debug, Clone)]
+pub struct Compilation {
+    pub artifacts: Vec<Artifact>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// An output of the compiler, held in memory. Outputs describing a contract are named after it,
+/// and the others after the program
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    pub kind: Emit,
+    pub name: String,
+    pub extension: &'static str,
+    pub contents: Vec<u8>,
+}
+
+impl Artifact {
+    pub fn new<N: Into<String>, C: Into<Vec<u8>>>(kind: Emit, name: N, contents: C) -> Artifact {
+        Artifact {
+            kind,
+            name: name.into(),
+            extension: kind.extension(),
+            contents: contents.into(),
+        }
+    }
+
+    /// Outputs of the same kind for the same contract are told apart by their extensions
+    pub fn with_extension(mut self, extension: &'static str) -> Artifact {
+        self.extension = extension;
+        self
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self.name, self.extension)
+    }
+
+    /// The contents of every output but wasm are text
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.contents).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::target::output::Emit;
+
+    #[test]
+    fn test_emit_names_round_trip() {
+        for emit in &[
+            Emit::Ast,
+            Emit::Environment,
+            Emit::Preprocessed,
+            Emit::States,
+            Emit::AccessReport,
+            Emit::MoveIR,
+            Emit::Llvm,
+            Emit::Wat,
+            Emit::Wasm,
+            Emit::Abi,
+        ] {
+            assert_eq!(emit.to_string().parse::<Emit>(), Ok(*emit));
+        }
+        assert!("bytecode".parse::<Emit>().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::ast::{Module, TopLevelDeclaration};
+use crate::semantic_analysis::{state_machine, StateMachine};
+use crate::target::output::{Artifact, Emit};
+
+/// The state machine of each contract which declares type states, as a Graphviz graph and as JSON
+pub fn generate(module: &Module) -> Vec<Artifact> {
+    module
+        .declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            TopLevelDeclaration::ContractDeclaration(contract)
+                if !contract.type_states.is_empty() =>
+            {
+                Some(state_machine(contract, module))
+            }
+            _ => None,
+        })
+        .flat_map(|machine| {
+            vec![
+                Artifact::new(Emit::States, &machine.contract, dot(&machine)),
+                Artifact::new(Emit::States, &machine.contract, json(&machine))
+                    .with_extension("states.json"),
+            ]
+        })
+        .collect()
+}
+
+/// The initialiser is drawn as an edge from a point into each initial state. Unreachable states
+/// are dashed and terminal states are drawn with a double outline
+fn dot(machine: &StateMachine) -> String {
+    let unreachable = machine.unreachable_states();
+    let terminal = machine.terminal_states();
+
+    let mut dot = format!("digraph {:?} {{\n", machine.contract);
+    dot.push_str("  \"_init\" [shape=point];\n");
+    for state in &machine.states {
+        let mut attributes = vec![];
+        if terminal.contains(&state) {
+            attributes.push("shape=doublecircle");
+        }
+        if unreachable.contains(&state) {
+            attributes.push("style=dashed");
+        }
+        if attributes.is_empty() {
+            dot.push_str(&format!("  {:?};\n", state));
+        } else {
+            dot.push_str(&format!("  {:?} [{}];\n", state, attributes.join(", ")));
+        }
+    }
+    for state in &machine.initial {
+        dot.push_str(&format!("  \"_init\" -> {:?} [label=\"init\"];\n", state));
+    }
+    for transition in &machine.transitions {
+        let label = format!(
+            "{}\\n({})",
+            transition.function,
+            transition.caller_protections.join(", ")
+        );
+        dot.push_str(&format!(
+            "  {:?} -> {:?} [label=\"{}\"];\n",
+            transition.from, transition.to, label
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn json(machine: &StateMachine) -> String {
+    let transitions = machine
+        .transitions
+        .iter()
+        .map(|transition| {
+            json::object! {
+                from: transition.from.as_str(),
+                to: transition.to.as_str(),
+                function: transition.function.as_str(),
+                callerProtections: transition.caller_protections.clone(),
+            }
+        })
+        .collect::<json::Array>();
+
+    let description = json::object! {
+        contract: machine.contract.as_str(),
+        states: machine.states.clone(),
+        initial: machine.initial.clone(),
+        transitions: transitions,
+        unreachable: machine.unreachable_states().into_iter().cloned().collect::<Vec<_>>(),
+        terminal: machine.terminal_states().into_iter().cloned().collect::<Vec<_>>(),
+    };
+    format!("{}\n", json::stringify_pretty(description, 2))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compile, target, Emit, Options, Source};
+
+    const AUCTION: &str = "contract Auction (Open, Closed, Settled, Abandoned) {
+  var highest: Int = 0
+  let auctioneer: Address
+}
+
+Auction :: caller <- (any) {
+  public init() {
+    auctioneer = caller
+    become Open
+  }
+}
+
+Auction @(Open) :: (any) {
+  public func bid(amount: Int) mutates (highest) {
+    highest = amount
+  }
+
+  public func close() {
+    become Closed
+  }
+}
+
+Auction @(Closed) :: (auctioneer) {
+  public func settle() {
+    become Settled
+  }
+}
+
+Auction @(Abandoned) :: (any) {
+  public func reopen() {
+    become Open
+  }
+}
+";
+
+    fn generate() -> (String, String) {
+        let options = Options {
+            emit: vec![Emit::States],
+            ..Default::default()
+        };
+        let artifacts = compile(
+            &[Source::new("auction.flint", AUCTION)],
+            target("libra").unwrap(),
+            &options,
+        )
+        .unwrap()
+        .artifacts;
+
+        let file_names: Vec<String> = artifacts.iter().map(|a| a.file_name()).collect();
+        assert_eq!(
+            file_names,
+            vec!["Auction.states.dot", "Auction.states.json"]
+        );
+        (
+            artifacts[0].text().unwrap().to_string(),
+            artifacts[1].text().unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_dot() {
+        let (dot, _) = generate();
+        assert!(dot.starts_with("digraph \"Auction\" {\n"));
+        assert!(dot.contains("  \"_init\" -> \"Open\" [label=\"init\"];\n"));
+        assert!(dot.contains("  \"Open\" -> \"Closed\" [label=\"close\\n(any)\"];\n"));
+        assert!(dot.contains("  \"Settled\" [shape=doublecircle];\n"));
+        assert!(dot.contains("  \"Abandoned\" [style=dashed];\n"));
+        assert!(!dot.contains("bid"));
+    }
+
+    #[test]
+    fn test_json() {
+        let (_, description) = generate();
+        let description = json::parse(&description).unwrap();
+        assert_eq!(description["initial"], json::array!["Open"]);
+        assert_eq!(description["unreachable"], json::array!["Abandoned"]);
+        assert_eq!(description["terminal"], json::array!["Settled"]);
+        assert_eq!(
+            description["transitions"],
+            json::array![
+                {
+                    from: "Open",
+                    to: "Closed",
+                    function: "close",
+                    callerProtections: ["any"],
+                },
+                {
+                    from: "Closed",
+                    to: "Settled",
+                    function: "settle",
+                    callerProtections: ["auctioneer"],
+                },
+                {
+                    from: "Abandoned",
+                    to: "Open",
+                    function: "reopen",
+                    callerProtections: ["any"],
+                },
+            ]
+        );
+    }
+}
@@ -1,9 +1,7 @@
-mod type_error;
-
 use super::ast::*;
 use super::context::*;
 use super::visitor::*;
-use type_error::StateTypeError;
+use crate::diagnostic::{code, Diagnostic};
 
 pub struct TypeChecker {}
 
@@ -31,10 +29,16 @@ impl Visitor for TypeChecker {
                 .is_state_declared(&state, &declaration.identifier.token)
                 && !state.is_any()
             {
-                return Err(Box::from(StateTypeError::new(
-                    state.identifier.token,
-                    state.identifier.line_info.line,
-                )));
+                return Err(Box::from(
+                    Diagnostic::error(
+                        code::TYPE_STATE,
+                        format!(
+                            "StateTypeError: unknown state '{}' at line {}",
+                            state.identifier.token, state.identifier.line_info.line
+                        ),
+                    )
+                    .with_span(&state.identifier.line_info),
+                ));
             }
         }
 
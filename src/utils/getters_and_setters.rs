@@ -97,6 +97,7 @@ fn generate_and_add_getter(
 
     let getter = FunctionDeclaration {
         head: getter_signature,
+        specifications: vec![],
         body: vec![return_statement],
         scope_context: Some(Default::default()),
         tags: vec![],
@@ -182,6 +183,7 @@ fn generate_and_add_setter(
 
     let setter_declaration = FunctionDeclaration {
         head: setter_signature,
+        specifications: vec![],
         body: vec![assignment, return_statement],
         scope_context: Some(Default::default()),
         tags: vec![],
@@ -1,6 +1,7 @@
 use crate::ast::ContractBehaviourMember;
 
 pub(crate) mod getters_and_setters;
+pub(crate) mod specifications;
 pub(crate) mod type_states;
 pub mod unique;
 
@@ -0,0 +1,406 @@
+use crate::ast::{
+    Assertion, BinOp, BinaryExpression, Expression, FunctionDeclaration, Identifier, LineInfo,
+    ReturnStatement, SpecialDeclaration, Specification, SpecificationKind, Statement, Type,
+    VResult, VariableDeclaration, Visitable,
+};
+use crate::context::{Context, ScopeContext};
+use crate::environment::Environment;
+use crate::type_assigner::TypeAssigner;
+use crate::type_checker::ExpressionChecker;
+use crate::visitor::Visitor;
+use std::error::Error;
+
+/// Prefix of the local variables which keep the values of `old` expressions
+pub(crate) const OLD_VALUE_PREFIX: &str = "_old_";
+
+/// Prefix of the local variables which keep the values returned while the exit checks are made
+pub(crate) const RESULT_PREFIX: &str = "_result_";
+
+/// Compiles the specifications of a function into assertions in its body. Preconditions and
+/// invariants are asserted when it is entered, postconditions and invariants before it returns or
+/// changes state, and invariants again after each call to a public function of its type. The
+/// values of `old` expressions are kept in local variables when the function is entered
+pub fn insert_specification_checks(
+    declaration: &mut FunctionDeclaration,
+    ctx: &mut Context,
+) -> VResult {
+    let mut specifications = std::mem::take(&mut declaration.specifications);
+    if !ctx.check_specifications {
+        return Ok(());
+    }
+
+    let enclosing_type = ctx
+        .enclosing_type_identifier()
+        .map(|id| id.token.clone())
+        .unwrap_or_default();
+    if declaration.is_public() {
+        specifications.extend(resolved_invariants(&enclosing_type, ctx)?);
+    }
+    if specifications.is_empty() {
+        return Ok(());
+    }
+    let scope = ScopeContext {
+        parameters: declaration.head.parameters.clone(),
+        ..Default::default()
+    };
+
+    let mut entry = vec![];
+    let mut snapshots = vec![];
+    let mut exit = vec![];
+    let mut invariants = vec![];
+    for Specification {
+        kind,
+        mut expression,
+        line_info,
+    } in specifications
+    {
+        match kind {
+            SpecificationKind::Pre => entry.push(assertion(expression, line_info)),
+            SpecificationKind::Post => {
+                replace_old_expressions(&mut expression, &mut snapshots, &|old| {
+                    ctx.environment.get_expression_type(
+                        old,
+                        &enclosing_type,
+                        ctx.type_states(),
+                        ctx.caller_protections(),
+                        &scope,
+                    )
+                });
+                exit.push(assertion(expression, line_info));
+            }
+            SpecificationKind::Invariant => {
+                let invariant = assertion(expression, line_info);
+                entry.push(invariant.clone());
+                exit.push(invariant.clone());
+                invariants.push(invariant);
+            }
+        }
+    }
+
+    let mut checks = Checks {
+        exit,
+        invariants,
+        result_type: declaration.get_result_type().cloned(),
+        results: 0,
+        enclosing_type: &enclosing_type,
+        environment: &ctx.environment,
+    };
+    checks.insert_at_exits(&mut declaration.body);
+
+    declaration
+        .body
+        .splice(0..0, entry.into_iter().chain(snapshots));
+    Ok(())
+}
+
+/// Compiles the invariants of a type into assertions at every exit of one of its initialisers, by
+/// which each of its properties has a value
+pub fn insert_initialiser_checks(
+    declaration: &mut SpecialDeclaration,
+    ctx: &mut Context,
+) -> VResult {
+    let enclosing_type = match ctx.enclosing_type_identifier() {
+        Some(enclosing_type) if ctx.check_specifications && declaration.is_init() => {
+            enclosing_type.token.clone()
+        }
+        _ => return Ok(()),
+    };
+
+    let invariants = resolved_invariants(&enclosing_type, ctx)?;
+
+    let mut checks = Checks {
+        exit: invariants
+            .into_iter()
+            .map(|invariant| assertion(invariant.expression, invariant.line_info))
+            .collect(),
+        invariants: vec![],
+        result_type: None,
+        results: 0,
+        enclosing_type: &enclosing_type,
+        environment: &ctx.environment,
+    };
+    if !checks.exit.is_empty() {
+        checks.insert_at_exits(&mut declaration.body);
+    }
+    Ok(())
+}
+
+/// Compiles specifications into assertions without otherwise changing the program, for tools which
+/// run it rather than generate code for it
+pub struct SpecificationChecks;
+
+impl Visitor for SpecificationChecks {
+    fn start_function_declaration(
+        &mut self,
+        declaration: &mut FunctionDeclaration,
+        ctx: &mut Context,
+    ) -> VResult {
+        insert_specification_checks(declaration, ctx)
+    }
+
+    fn start_special_declaration(
+        &mut self,
+        declaration: &mut SpecialDeclaration,
+        ctx: &mut Context,
+    ) -> VResult {
+        insert_initialiser_checks(declaration, ctx)
+    }
+}
+
+/// The invariants of a type, ready to be checked within one of its functions or initialisers.
+/// They are declared outside any function, so neither semantic analysis nor type assignment has
+/// seen them in its scope
+fn resolved_invariants(
+    enclosing_type: &str,
+    ctx: &mut Context,
+) -> Result<Vec<Specification>, Box<dyn Error>> {
+    let mut invariants = ctx.environment.invariants(enclosing_type).to_vec();
+    for invariant in &mut invariants {
+        invariant.visit(&mut PropertyResolver, ctx)?;
+        invariant.visit(&mut TypeAssigner {}, ctx)?;
+    }
+    Ok(invariants)
+}
+
+/// Resolves the identifiers of an invariant as the properties of its type, which the parameters of
+/// the function or initialiser checking it do not shadow
+struct PropertyResolver;
+
+impl Visitor for PropertyResolver {
+    fn start_identifier(&mut self, identifier: &mut Identifier, ctx: &mut Context) -> VResult {
+        if let Some(enclosing_type) = ctx.enclosing_type_identifier() {
+            if identifier.enclosing_type.is_none()
+                && ctx
+                    .environment
+                    .is_property_defined(&identifier.token, &enclosing_type.token)
+            {
+                identifier.enclosing_type = Some(enclosing_type.token.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn assertion(expression: Expression, line_info: LineInfo) -> Statement {
+    Statement::Assertion(Assertion {
+        expression,
+        line_info,
+    })
+}
+
+/// Declares a local constant as `let identifier: T = value`
+fn constant(
+    identifier: Identifier,
+    variable_type: Type,
+    value: Expression,
+    line_info: LineInfo,
+) -> Statement {
+    Statement::Expression(Expression::BinaryExpression(BinaryExpression {
+        lhs_expression: Box::new(Expression::VariableDeclaration(VariableDeclaration {
+            declaration_token: Some("let".to_string()),
+            identifier,
+            variable_type,
+            expression: None,
+        })),
+        rhs_expression: Box::new(value),
+        op: BinOp::Equal,
+        line_info,
+    }))
+}
+
+/// Replaces each `old(e)` with a local variable declared as `let _old_N: T = e`
+fn replace_old_expressions(
+    expression: &mut Expression,
+    snapshots: &mut Vec<Statement>,
+    type_of: &dyn Fn(&Expression) -> Type,
+) {
+    match expression {
+        Expression::OldExpression(old) => {
+            let identifier =
+                Identifier::generated(&format!("{}{}", OLD_VALUE_PREFIX, snapshots.len()));
+            let value = std::mem::replace(&mut *old.expression, Expression::SelfExpression);
+            snapshots.push(constant(
+                identifier.clone(),
+                type_of(&value),
+                value,
+                old.line_info.clone(),
+            ));
+            *expression = Expression::Identifier(identifier);
+        }
+        Expression::BinaryExpression(binary) => {
+            replace_old_expressions(&mut binary.lhs_expression, snapshots, type_of);
+            replace_old_expressions(&mut binary.rhs_expression, snapshots, type_of);
+        }
+        Expression::BracketedExpression(bracketed) => {
+            replace_old_expressions(&mut bracketed.expression, snapshots, type_of)
+        }
+        Expression::CastExpression(cast) => {
+            replace_old_expressions(&mut cast.expression, snapshots, type_of)
+        }
+        Expression::SubscriptExpression(subscript) => {
+            replace_old_expressions(&mut subscript.index_expression, snapshots, type_of)
+        }
+        Expression::FunctionCall(call) => {
+            for argument in &mut call.arguments {
+                replace_old_expressions(&mut argument.expression, snapshots, type_of);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Checks<'a> {
+    exit: Vec<Statement>,
+    invariants: Vec<Statement>,
+    /// The type of the values returned, which are kept in locals while the exit checks are made
+    result_type: Option<Type>,
+    results: usize,
+    enclosing_type: &'a str,
+    environment: &'a Environment,
+}
+
+impl<'a> Checks<'a> {
+    /// Inserts the checks, and makes the exit checks at the end of the body if it can run off it
+    fn insert_at_exits(&mut self, body: &mut Vec<Statement>) {
+        self.insert(body);
+        if !matches!(
+            body.last(),
+            Some(Statement::ReturnStatement(_)) | Some(Statement::BecomeStatement(_))
+        ) {
+            body.extend(self.exit.iter().cloned());
+        }
+    }
+
+    fn insert(&mut self, statements: &mut Vec<Statement>) {
+        for statement in std::mem::take(statements) {
+            match statement {
+                // The returned value is found before the checks, which may depend on its effects
+                Statement::ReturnStatement(ReturnStatement {
+                    expression: Some(expression),
+                    cleanup,
+                    line_info,
+                }) if self.result_type.is_some() => {
+                    let result =
+                        Identifier::generated(&format!("{}{}", RESULT_PREFIX, self.results));
+                    self.results += 1;
+                    statements.push(constant(
+                        result.clone(),
+                        self.result_type.clone().unwrap(),
+                        expression,
+                        line_info.clone(),
+                    ));
+                    statements.extend(self.exit.iter().cloned());
+                    statements.push(Statement::ReturnStatement(ReturnStatement {
+                        expression: Some(Expression::Identifier(result)),
+                        cleanup,
+                        line_info,
+                    }));
+                }
+                Statement::ReturnStatement(_) | Statement::BecomeStatement(_) => {
+                    statements.extend(self.exit.iter().cloned());
+                    statements.push(statement);
+                }
+                Statement::IfStatement(mut if_statement) => {
+                    self.insert(&mut if_statement.body);
+                    self.insert(&mut if_statement.else_body);
+                    statements.push(Statement::IfStatement(if_statement));
+                }
+                Statement::ForStatement(mut for_statement) => {
+                    self.insert(&mut for_statement.body);
+                    statements.push(Statement::ForStatement(for_statement));
+                }
+                Statement::DoCatchStatement(mut do_catch) => {
+                    self.insert(&mut do_catch.do_body);
+                    self.insert(&mut do_catch.catch_body);
+                    statements.push(Statement::DoCatchStatement(do_catch));
+                }
+                Statement::Expression(ref expression) if self.calls_public_function(expression) => {
+                    statements.push(statement);
+                    statements.extend(self.invariants.iter().cloned());
+                }
+                statement => statements.push(statement),
+            }
+        }
+    }
+
+    fn calls_public_function(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::FunctionCall(call) => {
+                let identifier = &call.identifier;
+                let within_type = match &identifier.enclosing_type {
+                    Some(enclosing_type) => enclosing_type == self.enclosing_type,
+                    None => true,
+                };
+                (within_type
+                    && self
+                        .environment
+                        .is_public_function(&identifier.token, self.enclosing_type))
+                    || call
+                        .arguments
+                        .iter()
+                        .any(|argument| self.calls_public_function(&argument.expression))
+            }
+            Expression::AttemptExpression(attempt) => {
+                self.calls_public_function(&Expression::FunctionCall(attempt.function_call.clone()))
+            }
+            Expression::BinaryExpression(binary) => match binary.op {
+                // Only a function called on `self` is of this type
+                BinOp::Dot if !matches!(*binary.lhs_expression, Expression::SelfExpression) => {
+                    self.calls_public_function(&binary.lhs_expression)
+                }
+                _ => {
+                    self.calls_public_function(&binary.lhs_expression)
+                        || self.calls_public_function(&binary.rhs_expression)
+                }
+            },
+            Expression::BracketedExpression(bracketed) => {
+                self.calls_public_function(&bracketed.expression)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compile, target, Emit, Options, Source};
+
+    const COUNTER: &str = "contract Counter {
+  var count: Int = 0
+  invariant(count >= 0)
+}
+
+Counter :: (any) {
+  public init() {}
+
+  public func add(amount: Int) mutates (count)
+    pre(amount > 0)
+    post(count == old(count) + amount) {
+    count += amount
+  }
+}
+";
+
+    fn assertions(check_specifications: bool) -> usize {
+        let options = Options {
+            emit: vec![Emit::MoveIR],
+            check_specifications,
+            ..Default::default()
+        };
+        let artifacts = compile(
+            &[Source::new("counter.flint", COUNTER)],
+            target("libra").unwrap(),
+            &options,
+        )
+        .unwrap()
+        .artifacts;
+        artifacts[0].text().unwrap().matches("assert(").count()
+    }
+
+    #[test]
+    fn test_specification_checks() {
+        // The invariant at the end of the initialiser, the precondition and invariant on entry, and
+        // the postcondition and invariant on exit, besides the assertions of the runtime functions
+        assert_eq!(assertions(true) - assertions(false), 5);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::ast::{BinOp, BinaryExpression, Expression, Identifier, Literal, TypeState};
+use crate::ast::{Assertion, BinOp, BinaryExpression, Expression, Literal, Statement, TypeState};
+use crate::context::Context;
 use itertools::Itertools;
 
 pub fn extract_allowed_states<'a>(
@@ -14,13 +15,13 @@ pub fn extract_allowed_states<'a>(
     })
 }
 
-pub fn generate_type_state_condition(id: Identifier, allowed: &[u8]) -> BinaryExpression {
+pub fn generate_type_state_condition(state: Expression, allowed: &[u8]) -> BinaryExpression {
     assert!(!allowed.is_empty());
     allowed
         .iter()
-        .map(|state| BinaryExpression {
-            lhs_expression: Box::from(Expression::Identifier(id.clone())),
-            rhs_expression: Box::from(Expression::Literal(Literal::U8Literal(*state))),
+        .map(|allowed_state| BinaryExpression {
+            lhs_expression: Box::from(state.clone()),
+            rhs_expression: Box::from(Expression::Literal(Literal::U8Literal(*allowed_state))),
             op: BinOp::DoubleEqual,
             line_info: Default::default(),
         })
@@ -32,3 +33,32 @@ pub fn generate_type_state_condition(id: Identifier, allowed: &[u8]) -> BinaryEx
         })
         .unwrap()
 }
+
+/// Functions which may be called within the contract in a state they do not allow check the state
+/// when they are entered, where `state` is the expression for the state of the contract
+pub fn generate_entry_type_state_assertion(
+    function_name: &str,
+    state: Expression,
+    ctx: &Context,
+) -> Option<Statement> {
+    let contract = ctx.contract_behaviour_declaration_context.as_ref()?;
+    let contract_name = &contract.identifier.token;
+    if contract.type_states.is_empty()
+        || contract.type_states.iter().any(TypeState::is_any)
+        || !ctx
+            .environment
+            .checks_type_state_on_entry(contract_name, function_name)
+    {
+        return None;
+    }
+
+    let allowed = extract_allowed_states(
+        &contract.type_states,
+        &ctx.environment.get_contract_type_states(contract_name),
+    )
+    .collect::<Vec<u8>>();
+    Some(Statement::Assertion(Assertion {
+        expression: Expression::BinaryExpression(generate_type_state_condition(state, &allowed)),
+        line_info: contract.identifier.line_info.clone(),
+    }))
+}
@@ -186,6 +186,14 @@ pub trait Visitor {
         Ok(())
     }
 
+    fn start_specification(&mut self, _t: &mut Specification, _ctx: &mut Context) -> VResult {
+        Ok(())
+    }
+
+    fn finish_specification(&mut self, _t: &mut Specification, _ctx: &mut Context) -> VResult {
+        Ok(())
+    }
+
     fn start_function_signature_declaration(
         &mut self,
         _t: &mut FunctionSignatureDeclaration,
@@ -386,6 +394,14 @@ pub trait Visitor {
         Ok(())
     }
 
+    fn start_old_expression(&mut self, _t: &mut OldExpression, _ctx: &mut Context) -> VResult {
+        Ok(())
+    }
+
+    fn finish_old_expression(&mut self, _t: &mut OldExpression, _ctx: &mut Context) -> VResult {
+        Ok(())
+    }
+
     fn start_inout_expression(&mut self, _t: &mut InoutExpression, _ctx: &mut Context) -> VResult {
         Ok(())
     }
@@ -0,0 +1,364 @@
+//! Compiles the test programs for Libra, replacing `run_move_tests.py`.
+//!
+//! A compilation test annotated `//! compile fail <message>` must fail with an error containing
+//! the message on the line after the annotation, or on line N if the message starts with `line N`,
+//! which is the whole message for a test of the line alone. A test annotated
+//! `//! compile warn <message>` must compile with a warning matched in the same way. Every
+//! annotation of a test must be matched, and every other compilation test must compile, with no
+//! warnings but those its annotations expect. The Move IR of each behaviour test must match the file of the same name in
+//! `tests/behaviour_tests/move_expected`, which is rewritten instead when `FLINT_UPDATE_EXPECTED`
+//! is set. When the flint config sets `libraPath`, the behaviour tests are also run on Libra along
+//! with their scripts in `tests/behaviour_tests/move_tests`, which may expect the transaction to
+//! abort on a line with `//! expect fail N`
+
+use flint::io::config;
+use flint::{compile_with_source_map, target, Diagnostic, Emit, Options, Source, SourceMap};
+use regex::Regex;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const COMPILATION_TESTS: &str = "tests/compilation_tests";
+const BEHAVIOUR_TESTS: &str = "tests/behaviour_tests";
+const MOVE_TESTS: &str = "tests/behaviour_tests/move_tests";
+const EXPECTED_MOVE_IR: &str = "tests/behaviour_tests/move_expected";
+const UPDATE_EXPECTED_ENV_VAR: &str = "FLINT_UPDATE_EXPECTED";
+const LIBRA_TEST_DIRECTORY: &str = "language/ir-testsuite/tests/flint2tests";
+
+/// An error or warning, with the line of the file it points at if it has one
+type Error = (Option<usize>, String);
+
+/// What a `//! compile fail` or `//! compile warn` annotation expects: whether compilation fails,
+/// the line of the error or warning, and the message it contains if the annotation gives one
+struct Expectation {
+    fails: bool,
+    line: usize,
+    message: Option<String>,
+}
+
+impl Expectation {
+    fn matches(&self, errors: &[Error]) -> bool {
+        errors.iter().any(|(line, message)| {
+            *line == Some(self.line)
+                && self
+                    .message
+                    .as_ref()
+                    .map_or(true, |expected| message.contains(expected.as_str()))
+        })
+    }
+
+    fn describe(&self) -> String {
+        match &self.message {
+            Some(message) => format!("`{}` on line {}", message, self.line),
+            None => format!("line {}", self.line),
+        }
+    }
+}
+
+/// The Move IR of a program which compiled, and its warnings
+type Compiled = (String, Vec<Error>);
+
+fn flint_files(directory: &str) -> Vec<PathBuf> {
+    let mut files: Vec<_> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "flint")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn name(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().into_owned()
+}
+
+fn compile(path: &Path) -> Result<Compiled, Vec<Error>> {
+    let source = Source::new(path.to_string_lossy(), fs::read_to_string(path).unwrap());
+    let options = Options {
+        emit: vec![Emit::MoveIR],
+        ..Default::default()
+    };
+    let mut source_map = SourceMap::default();
+
+    let result = compile_with_source_map(
+        &[source],
+        target("libra").unwrap(),
+        &options,
+        &mut source_map,
+    );
+    let locate = |diagnostic: Diagnostic| {
+        let line = diagnostic
+            .primary
+            .as_ref()
+            .and_then(|label| source_map.locate(&label.line_info))
+            .map(|location| location.line);
+        (line, diagnostic.message)
+    };
+
+    match result {
+        Ok(compilation) => Ok((
+            compilation
+                .artifacts
+                .iter()
+                .filter_map(|artifact| artifact.text())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            compilation.warnings.into_iter().map(locate).collect(),
+        )),
+        Err(diagnostics) => Err(diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(locate)
+            .collect()),
+    }
+}
+
+fn describe(errors: &[Error]) -> String {
+    errors
+        .iter()
+        .map(|(line, message)| match line {
+            Some(line) => format!("\n\tline {}: {}", line, message),
+            None => format!("\n\t{}", message),
+        })
+        .collect()
+}
+
+fn assert_passed(failures: Vec<String>) {
+    assert!(
+        failures.is_empty(),
+        "{} failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+fn expectations(text: &str) -> Vec<Expectation> {
+    let annotation = Regex::new(r"//! compile (fail|warn) (.+)").unwrap();
+    let line_annotation = Regex::new(r"^line (\d+)(?: (.+))?$").unwrap();
+
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let captures = annotation.captures(line)?;
+            let message = captures[2].trim().to_string();
+            let expectation = match line_annotation.captures(&message) {
+                Some(line) => Expectation {
+                    fails: &captures[1] == "fail",
+                    line: line[1].parse().unwrap(),
+                    message: line.get(2).map(|message| message.as_str().to_string()),
+                },
+                None => Expectation {
+                    fails: &captures[1] == "fail",
+                    line: index + 2,
+                    message: Some(message),
+                },
+            };
+            Some(expectation)
+        })
+        .collect()
+}
+
+#[test]
+fn compilation_tests() {
+    let mut failures = vec![];
+    for path in flint_files(COMPILATION_TESTS) {
+        let expectations = expectations(&fs::read_to_string(&path).unwrap());
+        let fails = expectations.iter().any(|expectation| expectation.fails);
+
+        let (errors, kind) = match compile(&path) {
+            Ok(_) if fails => {
+                failures.push(format!("{}: compiled, but should fail", path.display()));
+                continue;
+            }
+            Ok((_, warnings)) => (warnings, "warn"),
+            Err(errors) if !fails => {
+                failures.push(format!(
+                    "{}: failed to compile:{}",
+                    path.display(),
+                    describe(&errors)
+                ));
+                continue;
+            }
+            Err(errors) => (errors, "fail"),
+        };
+
+        if !fails {
+            for warning in &errors {
+                if !expectations
+                    .iter()
+                    .any(|expectation| expectation.matches(std::slice::from_ref(warning)))
+                {
+                    failures.push(format!(
+                        "{}: gave an unexpected warning:{}",
+                        path.display(),
+                        describe(std::slice::from_ref(warning))
+                    ));
+                }
+            }
+        }
+
+        for expectation in expectations {
+            if expectation.fails == fails && !expectation.matches(&errors) {
+                failures.push(format!(
+                    "{}: should {} with {}, but gave:{}",
+                    path.display(),
+                    kind,
+                    expectation.describe(),
+                    describe(&errors)
+                ));
+            }
+        }
+    }
+
+    assert_passed(failures);
+}
+
+#[test]
+fn move_ir_is_as_expected() {
+    let update = env::var_os(UPDATE_EXPECTED_ENV_VAR).is_some();
+
+    let mut failures = vec![];
+    for path in flint_files(BEHAVIOUR_TESTS) {
+        let move_ir = match compile(&path) {
+            Ok((move_ir, _)) => move_ir,
+            Err(errors) => {
+                failures.push(format!(
+                    "{}: failed to compile:{}",
+                    path.display(),
+                    describe(&errors)
+                ));
+                continue;
+            }
+        };
+
+        let expected_path = Path::new(EXPECTED_MOVE_IR).join(format!("{}.mvir", name(&path)));
+        if update {
+            fs::create_dir_all(EXPECTED_MOVE_IR).unwrap();
+            fs::write(&expected_path, move_ir).unwrap();
+            continue;
+        }
+
+        let expected = match fs::read_to_string(&expected_path) {
+            Ok(expected) => expected,
+            Err(_) => {
+                failures.push(format!("{}: does not exist", expected_path.display()));
+                continue;
+            }
+        };
+        if expected != move_ir {
+            let (line, (expected, generated)) = expected
+                .lines()
+                .chain(std::iter::repeat(""))
+                .zip(move_ir.lines().chain(std::iter::repeat("")))
+                .enumerate()
+                .find(|(_, (expected, generated))| expected != generated)
+                .unwrap();
+            failures.push(format!(
+                "{}: line {} is\n\t{}\nbut was expected to be\n\t{}",
+                path.display(),
+                line + 1,
+                generated,
+                expected
+            ));
+        }
+    }
+
+    if !failures.is_empty() {
+        failures.push(format!(
+            "If the changes are intended, set {} to update the expected Move IR",
+            UPDATE_EXPECTED_ENV_VAR
+        ));
+    }
+    assert_passed(failures);
+}
+
+#[test]
+fn behaviour_tests_on_libra() {
+    let libra = match config::libra_path(None).unwrap() {
+        Some(libra) => libra,
+        None => {
+            eprintln!("Set `libraPath` in the flint config to run the behaviour tests on Libra");
+            return;
+        }
+    };
+    let test_directory = libra.join(LIBRA_TEST_DIRECTORY);
+    fs::create_dir_all(&test_directory).unwrap();
+
+    let expect_fail = Regex::new(r"(?i)//! expect fail (\d+)").unwrap();
+    let aborted = Regex::new(r"ABORTED \{ code: (\d+)").unwrap();
+    let has_failures = Regex::new(r"[^0\s]\s+failed").unwrap();
+    let has_passes = Regex::new(r"[1-9]\s+passed").unwrap();
+
+    let mut failures = vec![];
+    for path in flint_files(BEHAVIOUR_TESTS) {
+        let name = name(&path);
+        let move_ir = match compile(&path) {
+            Ok((move_ir, _)) => move_ir,
+            // Reported by `move_ir_is_as_expected`
+            Err(_) => continue,
+        };
+
+        // The modules a script provides come before the contract, and the script after it, each
+        // in its own transaction
+        let script = fs::read_to_string(Path::new(MOVE_TESTS).join(format!("{}.mvir", name)))
+            .unwrap_or_default();
+        let mut parts = script.split("//! provide module");
+        let transactions = parts.next().unwrap_or_default();
+        let mut test = String::new();
+        for module in parts {
+            test.push_str(&format!("\n{}\n//! new-transaction\n", module));
+        }
+        test.push_str(&format!(
+            "{}\n//! new-transaction\n{}\n",
+            move_ir.replace("import 0x0.", "import Transaction."),
+            transactions
+        ));
+
+        let test_path = test_directory.join(format!("{}.mvir", name));
+        fs::write(&test_path, test).unwrap();
+        let output = Command::new("cargo")
+            .args(
+                [
+                    "test",
+                    "-p",
+                    "ir-testsuite",
+                    &format!("flint2tests/{}.mvir", name),
+                ]
+                .iter(),
+            )
+            .current_dir(&libra)
+            .output()
+            .unwrap();
+        fs::remove_file(&test_path).unwrap();
+
+        let output = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let failed = has_failures.is_match(&output) || !has_passes.is_match(&output);
+        let failed_line = aborted
+            .captures(&output)
+            .map(|captures| captures[1].parse::<usize>().unwrap());
+        let expected_line = expect_fail
+            .captures(&script)
+            .map(|captures| captures[1].parse::<usize>().unwrap());
+
+        match (failed, expected_line) {
+            (false, None) => {}
+            (true, Some(expected)) if failed_line == Some(expected) => {}
+            (false, Some(expected)) => failures.push(format!(
+                "{}: should abort on line {}, but did not",
+                path.display(),
+                expected
+            )),
+            (true, _) => failures.push(format!("{}: failed on Libra:\n{}", path.display(), output)),
+        }
+    }
+
+    assert_passed(failures);
+}